@@ -0,0 +1,468 @@
+//! Reconstitution du stock dans le temps (série quotidienne ou hebdomadaire).
+//!
+//! Un seul passage sur les tickets alimente les tableaux d'arrivées et de
+//! départs (global et par dimension), puis un balayage des périodes en déduit
+//! le stock, les flux cumulés (diagramme de flux cumulé) et les percentiles
+//! d'âge du stock. Les âges sont tenus dans un arbre de Fenwick indexé par
+//! jour d'ouverture : chaque percentile coûte O(log n).
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::temporal::period_key;
+
+/// Modalités conservées par dimension ; les suivantes sont regroupées.
+pub const MAX_MODALITES: usize = 15;
+const AUTRES: &str = "Autres";
+const NON_ASSIGNE: &str = "(non assigné)";
+const NON_RENSEIGNE: &str = "(non renseigné)";
+
+/// Ticket réduit à ses dates d'entrée et de sortie du stock.
+#[derive(Debug, Clone)]
+pub struct TicketFlux {
+    pub ouverture: NaiveDate,
+    /// None = ticket toujours vivant.
+    pub sortie: Option<NaiveDate>,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub categorie: Option<String>,
+    pub type_ticket: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogSerie {
+    pub granularite: String,
+    pub date_debut: Option<String>,
+    pub date_fin: Option<String>,
+    pub points: Vec<BacklogPoint>,
+    pub par_technicien: Vec<BacklogDimension>,
+    pub par_groupe: Vec<BacklogDimension>,
+    pub par_categorie: Vec<BacklogDimension>,
+    pub par_type: Vec<BacklogDimension>,
+}
+
+/// Stock mesuré en fin de période.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogPoint {
+    pub periode: String,
+    pub date_debut: String,
+    pub date_fin: String,
+    pub arrivees: i64,
+    pub departs: i64,
+    pub stock: i64,
+    pub arrivees_cumulees: i64,
+    pub departs_cumulees: i64,
+    pub age_median_jours: f64,
+    pub age_p90_jours: f64,
+    pub age_max_jours: i64,
+}
+
+/// Séries d'une modalité, alignées sur `BacklogSerie::points`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogDimension {
+    pub label: String,
+    pub arrivees: Vec<i64>,
+    pub departs: Vec<i64>,
+    pub stock: Vec<i64>,
+}
+
+/// Arbre de Fenwick des tickets en stock, indexé par jour d'ouverture.
+struct Fenwick {
+    arbre: Vec<i64>,
+    total: i64,
+}
+
+impl Fenwick {
+    fn new(taille: usize) -> Self {
+        Self {
+            arbre: vec![0; taille + 1],
+            total: 0,
+        }
+    }
+
+    fn ajouter(&mut self, index: usize, delta: i64) {
+        self.total += delta;
+        let mut i = index + 1;
+        while i < self.arbre.len() {
+            self.arbre[i] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Index du k-ième élément (k à partir de 1) dans l'ordre croissant.
+    fn kieme(&self, mut k: i64) -> usize {
+        let mut pos = 0;
+        let mut pas = (self.arbre.len() - 1).next_power_of_two();
+        while pas > 0 {
+            if pos + pas < self.arbre.len() && self.arbre[pos + pas] < k {
+                pos += pas;
+                k -= self.arbre[pos];
+            }
+            pas >>= 1;
+        }
+        pos
+    }
+}
+
+/// Percentile des âges (interpolation linéaire, comme `stats::percentile`).
+/// Les âges croissants correspondent aux jours d'ouverture décroissants.
+fn percentile_age(fenwick: &Fenwick, jour_fin: usize, p: f64) -> f64 {
+    let n = fenwick.total;
+    if n <= 0 {
+        return 0.0;
+    }
+    let age = |rang: i64| (jour_fin - fenwick.kieme(n - rang)) as f64;
+    let rang = p / 100.0 * (n - 1) as f64;
+    let (bas, haut) = (rang.floor() as i64, rang.ceil() as i64);
+    let frac = rang - bas as f64;
+    let v = age(bas) * (1.0 - frac) + age(haut) * frac;
+    (v * 10.0).round() / 10.0
+}
+
+struct Decoupage {
+    debut: NaiveDate,
+    /// Premier jour (index) de chaque période.
+    bornes: Vec<usize>,
+    nb_jours: usize,
+}
+
+impl Decoupage {
+    fn new(debut: NaiveDate, fin: NaiveDate, granularite: &str) -> Self {
+        let nb_jours = (fin - debut).num_days() as usize + 1;
+        let bornes = if granularite == "week" {
+            let decalage = debut.weekday().num_days_from_monday() as usize;
+            std::iter::once(0)
+                .chain((7 - decalage..nb_jours).step_by(7))
+                .filter(|&j| j < nb_jours)
+                .collect()
+        } else {
+            (0..nb_jours).collect()
+        };
+        Self {
+            debut,
+            bornes,
+            nb_jours,
+        }
+    }
+
+    fn jour(&self, date: NaiveDate) -> usize {
+        (date - self.debut).num_days() as usize
+    }
+
+    fn periode(&self, jour: usize) -> usize {
+        self.bornes.partition_point(|&b| b <= jour) - 1
+    }
+
+    fn fin_periode(&self, p: usize) -> usize {
+        self.bornes.get(p + 1).map_or(self.nb_jours, |&b| b) - 1
+    }
+
+    fn date(&self, jour: usize) -> NaiveDate {
+        self.debut + Duration::days(jour as i64)
+    }
+}
+
+struct Flux {
+    arrivees: Vec<i64>,
+    departs: Vec<i64>,
+}
+
+impl Flux {
+    fn new(nb_periodes: usize) -> Self {
+        Self {
+            arrivees: vec![0; nb_periodes],
+            departs: vec![0; nb_periodes],
+        }
+    }
+}
+
+/// Accumulateur d'une dimension : flux par modalité.
+struct Ventilation {
+    modalites: HashMap<String, Flux>,
+    nb_periodes: usize,
+}
+
+impl Ventilation {
+    fn new(nb_periodes: usize) -> Self {
+        Self {
+            modalites: HashMap::new(),
+            nb_periodes,
+        }
+    }
+
+    fn flux(&mut self, label: &Option<String>, defaut: &str) -> &mut Flux {
+        let label = label.as_deref().map(str::trim).filter(|l| !l.is_empty());
+        let nb = self.nb_periodes;
+        self.modalites
+            .entry(label.unwrap_or(defaut).to_string())
+            .or_insert_with(|| Flux::new(nb))
+    }
+
+    /// Modalités triées par volume d'arrivées ; au-delà de `MAX_MODALITES`,
+    /// regroupement sous "Autres".
+    fn finaliser(self) -> Vec<BacklogDimension> {
+        let nb = self.nb_periodes;
+        let mut liste: Vec<(String, Flux)> = self.modalites.into_iter().collect();
+        liste.sort_by(|a, b| {
+            let total = |f: &Flux| f.arrivees.iter().sum::<i64>();
+            total(&b.1).cmp(&total(&a.1)).then(a.0.cmp(&b.0))
+        });
+        let mut autres: Option<Flux> = None;
+        if liste.len() > MAX_MODALITES {
+            let mut cumul = Flux::new(nb);
+            for (_, f) in liste.drain(MAX_MODALITES..) {
+                for p in 0..nb {
+                    cumul.arrivees[p] += f.arrivees[p];
+                    cumul.departs[p] += f.departs[p];
+                }
+            }
+            autres = Some(cumul);
+        }
+        liste
+            .into_iter()
+            .chain(autres.map(|f| (AUTRES.to_string(), f)))
+            .map(|(label, f)| {
+                let mut stock = Vec::with_capacity(nb);
+                let mut courant = 0;
+                for p in 0..nb {
+                    courant += f.arrivees[p] - f.departs[p];
+                    stock.push(courant);
+                }
+                BacklogDimension {
+                    label,
+                    arrivees: f.arrivees,
+                    departs: f.departs,
+                    stock,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Série du stock de la première ouverture jusqu'à `reference` (date de
+/// l'import) ; `granularite` = "day" ou "week" (semaines ISO). Un ticket
+/// sorti le jour même de son ouverture n'apparaît jamais en stock.
+pub fn compute_backlog_series(
+    tickets: &[TicketFlux],
+    reference: Option<NaiveDate>,
+    granularite: &str,
+) -> BacklogSerie {
+    let granularite = if granularite == "week" { "week" } else { "day" };
+    let Some(debut) = tickets.iter().map(|t| t.ouverture).min() else {
+        return BacklogSerie {
+            granularite: granularite.to_string(),
+            ..Default::default()
+        };
+    };
+    let fin = tickets
+        .iter()
+        .flat_map(|t| [Some(t.ouverture), t.sortie])
+        .flatten()
+        .chain(reference)
+        .max()
+        .unwrap_or(debut)
+        .max(debut);
+
+    let decoupage = Decoupage::new(debut, fin, granularite);
+    let nb_periodes = decoupage.bornes.len();
+    let mut global = Flux::new(nb_periodes);
+    let mut par_technicien = Ventilation::new(nb_periodes);
+    let mut par_groupe = Ventilation::new(nb_periodes);
+    let mut par_categorie = Ventilation::new(nb_periodes);
+    let mut par_type = Ventilation::new(nb_periodes);
+    // Jours d'ouverture des tickets entrés / sortis, par période
+    let mut entrees: Vec<Vec<usize>> = vec![Vec::new(); nb_periodes];
+    let mut sorties: Vec<Vec<usize>> = vec![Vec::new(); nb_periodes];
+
+    for t in tickets {
+        let jour_ouverture = decoupage.jour(t.ouverture);
+        let arrivee = decoupage.periode(jour_ouverture);
+        let depart = t
+            .sortie
+            .map(|s| decoupage.periode(decoupage.jour(s.max(t.ouverture))));
+
+        entrees[arrivee].push(jour_ouverture);
+        if let Some(d) = depart {
+            sorties[d].push(jour_ouverture);
+        }
+        for flux in [
+            &mut global,
+            par_technicien.flux(&t.technicien, NON_ASSIGNE),
+            par_groupe.flux(&t.groupe, NON_RENSEIGNE),
+            par_categorie.flux(&t.categorie, NON_RENSEIGNE),
+            par_type.flux(&t.type_ticket, NON_RENSEIGNE),
+        ] {
+            flux.arrivees[arrivee] += 1;
+            if let Some(d) = depart {
+                flux.departs[d] += 1;
+            }
+        }
+    }
+
+    let mut fenwick = Fenwick::new(decoupage.nb_jours);
+    let mut points = Vec::with_capacity(nb_periodes);
+    let (mut arrivees_cumulees, mut departs_cumulees) = (0, 0);
+    for p in 0..nb_periodes {
+        for &j in &entrees[p] {
+            fenwick.ajouter(j, 1);
+        }
+        for &j in &sorties[p] {
+            fenwick.ajouter(j, -1);
+        }
+        arrivees_cumulees += global.arrivees[p];
+        departs_cumulees += global.departs[p];
+
+        let (jour_debut, jour_fin) = (decoupage.bornes[p], decoupage.fin_periode(p));
+        let age_max_jours = if fenwick.total > 0 {
+            (jour_fin - fenwick.kieme(1)) as i64
+        } else {
+            0
+        };
+        let date_debut = decoupage.date(jour_debut);
+        points.push(BacklogPoint {
+            periode: period_key(date_debut.and_hms_opt(0, 0, 0).unwrap(), granularite),
+            date_debut: date_debut.format("%Y-%m-%d").to_string(),
+            date_fin: decoupage.date(jour_fin).format("%Y-%m-%d").to_string(),
+            arrivees: global.arrivees[p],
+            departs: global.departs[p],
+            stock: fenwick.total,
+            arrivees_cumulees,
+            departs_cumulees,
+            age_median_jours: percentile_age(&fenwick, jour_fin, 50.0),
+            age_p90_jours: percentile_age(&fenwick, jour_fin, 90.0),
+            age_max_jours,
+        });
+    }
+
+    BacklogSerie {
+        granularite: granularite.to_string(),
+        date_debut: Some(debut.format("%Y-%m-%d").to_string()),
+        date_fin: Some(fin.format("%Y-%m-%d").to_string()),
+        points,
+        par_technicien: par_technicien.finaliser(),
+        par_groupe: par_groupe.finaliser(),
+        par_categorie: par_categorie.finaliser(),
+        par_type: par_type.finaliser(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::stats::percentile;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn ticket(ouverture: &str, sortie: Option<&str>, technicien: &str) -> TicketFlux {
+        TicketFlux {
+            ouverture: d(ouverture),
+            sortie: sortie.map(d),
+            technicien: Some(technicien.to_string()),
+            groupe: Some("Support".to_string()),
+            categorie: None,
+            type_ticket: Some("Incident".to_string()),
+        }
+    }
+
+    /// Stock naïf à la fin d'un jour donné.
+    fn stock_naif(tickets: &[TicketFlux], jour: NaiveDate) -> Vec<f64> {
+        tickets
+            .iter()
+            .filter(|t| t.ouverture <= jour && t.sortie.is_none_or(|s| s > jour))
+            .map(|t| (jour - t.ouverture).num_days() as f64)
+            .collect()
+    }
+
+    #[test]
+    fn test_daily_series() {
+        let tickets = vec![
+            ticket("2025-03-03", Some("2025-03-05"), "Alice"),
+            ticket("2025-03-03", None, "Bob"),
+            ticket("2025-03-04", Some("2025-03-04"), "Alice"),
+            ticket("2025-03-06", None, ""),
+        ];
+        let serie = compute_backlog_series(&tickets, Some(d("2025-03-08")), "day");
+        assert_eq!(serie.points.len(), 6);
+        let stocks: Vec<i64> = serie.points.iter().map(|p| p.stock).collect();
+        assert_eq!(stocks, vec![2, 2, 1, 2, 2, 2]);
+        assert_eq!(serie.points[1].arrivees, 1);
+        assert_eq!(serie.points[1].departs, 1);
+
+        // Flux cumulés : stock = arrivées cumulées - départs cumulés
+        let dernier = serie.points.last().unwrap();
+        assert_eq!(dernier.arrivees_cumulees, 4);
+        assert_eq!(dernier.departs_cumulees, 2);
+        assert_eq!(dernier.age_max_jours, 5);
+        assert_eq!(dernier.age_median_jours, 3.5);
+
+        let alice = serie
+            .par_technicien
+            .iter()
+            .find(|t| t.label == "Alice")
+            .unwrap();
+        assert_eq!(alice.stock, vec![1, 1, 0, 0, 0, 0]);
+        assert!(serie.par_technicien.iter().any(|t| t.label == NON_ASSIGNE));
+        assert_eq!(serie.par_categorie[0].label, NON_RENSEIGNE);
+        assert_eq!(serie.par_type[0].stock, stocks);
+    }
+
+    #[test]
+    fn test_age_percentiles_match_naive() {
+        let mut tickets = Vec::new();
+        for i in 0..60i64 {
+            let ouverture = d("2025-01-01") + Duration::days(i * 3 % 47);
+            let sortie = (i % 3 != 0).then(|| ouverture + Duration::days(i % 11));
+            tickets.push(TicketFlux {
+                ouverture,
+                sortie,
+                technicien: None,
+                groupe: None,
+                categorie: None,
+                type_ticket: None,
+            });
+        }
+        let serie = compute_backlog_series(&tickets, Some(d("2025-03-01")), "day");
+        for p in &serie.points {
+            let ages = stock_naif(&tickets, d(&p.date_fin));
+            assert_eq!(p.stock, ages.len() as i64, "{}", p.date_fin);
+            let attendu = (percentile(&ages, 90.0) * 10.0).round() / 10.0;
+            assert_eq!(p.age_p90_jours, attendu, "{}", p.date_fin);
+        }
+    }
+
+    #[test]
+    fn test_weekly_series_and_overflow() {
+        let tickets: Vec<TicketFlux> = (0..20)
+            .map(|i| ticket("2025-03-05", None, &format!("Tech {i:02}")))
+            .chain([ticket("2025-03-12", Some("2025-03-17"), "Tech 00")])
+            .collect();
+        // 2025-03-05 est un mercredi : semaines W10, W11, W12
+        let serie = compute_backlog_series(&tickets, Some(d("2025-03-18")), "week");
+        let periodes: Vec<&str> = serie.points.iter().map(|p| p.periode.as_str()).collect();
+        assert_eq!(periodes, vec!["2025-W10", "2025-W11", "2025-W12"]);
+        assert_eq!(serie.points[0].date_fin, "2025-03-09");
+        assert_eq!(serie.points[2].date_fin, "2025-03-18");
+        let stocks: Vec<i64> = serie.points.iter().map(|p| p.stock).collect();
+        assert_eq!(stocks, vec![20, 21, 20]);
+
+        assert_eq!(serie.par_technicien.len(), MAX_MODALITES + 1);
+        assert_eq!(serie.par_technicien[0].label, "Tech 00");
+        let autres = serie.par_technicien.last().unwrap();
+        assert_eq!(autres.label, AUTRES);
+        assert_eq!(autres.stock, vec![5, 5, 5]);
+    }
+
+    #[test]
+    fn test_empty() {
+        let serie = compute_backlog_series(&[], None, "week");
+        assert_eq!(serie.granularite, "week");
+        assert!(serie.points.is_empty());
+    }
+}
@@ -75,6 +75,7 @@ pub fn compute_bilan(
         },
         ventilation: None,
         resolution: None,
+        comparaison: None,
     }
 }
 
@@ -0,0 +1,258 @@
+//! Jeux de tranches nommés pour les distributions d'ancienneté, de délai de
+//! prise en charge et de délai de résolution.
+//!
+//! Un jeu de N bornes croissantes définit N + 1 tranches : [0, b1[, [b1, b2[,
+//! …, [bN, +∞[. Les bornes s'expriment en heures ou en jours ; les valeurs
+//! réparties sont toujours en jours.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+/// Jeu de tranches des délais (prise en charge, résolution) par défaut.
+pub const JEU_DELAIS: &str = "delais";
+/// Jeu de tranches de l'ancienneté du stock par défaut.
+pub const JEU_ANCIENNETE: &str = "anciennete";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UniteTranches {
+    Heures,
+    Jours,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JeuTranches {
+    pub nom: String,
+    pub unite: UniteTranches,
+    pub bornes: Vec<f64>,
+    /// Un libellé par tranche (bornes + 1) ; générés si vide.
+    #[serde(default)]
+    pub libelles: Vec<String>,
+}
+
+/// Jeux disponibles et jeu retenu pour chaque distribution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranchesDistribution {
+    pub jeux: Vec<JeuTranches>,
+    pub anciennete: String,
+    pub prise_en_charge: String,
+    pub resolution: String,
+}
+
+impl Default for TranchesDistribution {
+    fn default() -> Self {
+        Self {
+            jeux: vec![jeu_anciennete_defaut(), jeu_delais_defaut()],
+            anciennete: JEU_ANCIENNETE.to_string(),
+            prise_en_charge: JEU_DELAIS.to_string(),
+            resolution: JEU_DELAIS.to_string(),
+        }
+    }
+}
+
+fn jeu_defaut(nom: &str, libelles: [&str; 5]) -> JeuTranches {
+    JeuTranches {
+        nom: nom.to_string(),
+        unite: UniteTranches::Jours,
+        bornes: vec![1.0, 2.0, 7.0, 30.0],
+        libelles: libelles.iter().map(|l| l.to_string()).collect(),
+    }
+}
+
+fn jeu_anciennete_defaut() -> JeuTranches {
+    jeu_defaut(
+        JEU_ANCIENNETE,
+        ["< 24h", "24h - 48h", "48h - 7j", "7j - 30j", "> 30j"],
+    )
+}
+
+fn jeu_delais_defaut() -> JeuTranches {
+    jeu_defaut(
+        JEU_DELAIS,
+        ["< 24h", "24h - 48h", "2j - 7j", "7j - 30j", "> 30j"],
+    )
+}
+
+impl TranchesDistribution {
+    fn jeu(&self, nom: &str, defaut: fn() -> JeuTranches) -> JeuTranches {
+        self.jeux
+            .iter()
+            .find(|j| j.nom == nom)
+            .cloned()
+            .unwrap_or_else(defaut)
+    }
+
+    /// Jeu de l'ancienneté du stock (défaut si le nom est inconnu).
+    pub fn anciennete(&self) -> JeuTranches {
+        self.jeu(&self.anciennete, jeu_anciennete_defaut)
+    }
+
+    /// Jeu du délai de prise en charge (défaut si le nom est inconnu).
+    pub fn prise_en_charge(&self) -> JeuTranches {
+        self.jeu(&self.prise_en_charge, jeu_delais_defaut)
+    }
+
+    /// Jeu du délai de résolution (défaut si le nom est inconnu).
+    pub fn resolution(&self) -> JeuTranches {
+        self.jeu(&self.resolution, jeu_delais_defaut)
+    }
+
+    /// Vérifie les jeux avant enregistrement : noms uniques, bornes
+    /// strictement croissantes et positives, un libellé par tranche, jeux
+    /// retenus existants.
+    pub fn valider(&self) -> Result<(), String> {
+        let mut noms = HashSet::new();
+        for j in &self.jeux {
+            if j.nom.trim().is_empty() {
+                return Err("Jeu de tranches sans nom".to_string());
+            }
+            if !noms.insert(j.nom.as_str()) {
+                return Err(format!("Jeu de tranches en double : {}", j.nom));
+            }
+            j.valider().map_err(|e| format!("{} : {e}", j.nom))?;
+        }
+        for (usage, nom) in [
+            ("ancienneté", &self.anciennete),
+            ("prise en charge", &self.prise_en_charge),
+            ("résolution", &self.resolution),
+        ] {
+            if !noms.contains(nom.as_str()) {
+                return Err(format!("Jeu de tranches inconnu pour {usage} : {nom}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl JeuTranches {
+    fn valider(&self) -> Result<(), String> {
+        if self.bornes.is_empty() {
+            return Err("aucune borne".to_string());
+        }
+        if self.bornes.iter().any(|b| !b.is_finite() || *b <= 0.0) {
+            return Err("bornes strictement positives attendues".to_string());
+        }
+        if self.bornes.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("bornes non strictement croissantes".to_string());
+        }
+        if !self.libelles.is_empty() && self.libelles.len() != self.bornes.len() + 1 {
+            return Err(format!(
+                "{} libellés pour {} tranches",
+                self.libelles.len(),
+                self.bornes.len() + 1
+            ));
+        }
+        Ok(())
+    }
+
+    fn en_jours(&self, borne: f64) -> f64 {
+        match self.unite {
+            UniteTranches::Heures => borne / 24.0,
+            UniteTranches::Jours => borne,
+        }
+    }
+
+    /// Borne basse de chaque tranche, en jours (0 pour la première).
+    pub fn bornes_basses_jours(&self) -> Vec<f64> {
+        std::iter::once(0.0)
+            .chain(self.bornes.iter().map(|b| self.en_jours(*b)))
+            .collect()
+    }
+
+    /// Libellé de chaque tranche, configuré ou généré (« < 4h », « 4h - 8h »,
+    /// « > 8h »).
+    pub fn libelles(&self) -> Vec<String> {
+        if !self.libelles.is_empty() {
+            return self.libelles.clone();
+        }
+        let suffixe = match self.unite {
+            UniteTranches::Heures => "h",
+            UniteTranches::Jours => "j",
+        };
+        let n = self.bornes.len();
+        (0..=n)
+            .map(|i| match i {
+                0 => format!("< {}{suffixe}", self.bornes[0]),
+                i if i == n => format!("> {}{suffixe}", self.bornes[n - 1]),
+                i => format!(
+                    "{}{suffixe} - {}{suffixe}",
+                    self.bornes[i - 1],
+                    self.bornes[i]
+                ),
+            })
+            .collect()
+    }
+
+    /// Nombre de valeurs (en jours) par tranche, borne basse incluse.
+    pub fn compter(&self, valeurs_jours: impl IntoIterator<Item = f64>) -> Vec<usize> {
+        let bornes: Vec<f64> = self.bornes.iter().map(|b| self.en_jours(*b)).collect();
+        let mut counts = vec![0; bornes.len() + 1];
+        for v in valeurs_jours {
+            let i = bornes.iter().position(|b| v < *b).unwrap_or(bornes.len());
+            counts[i] += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jeu_heures() -> JeuTranches {
+        JeuTranches {
+            nom: "sla".to_string(),
+            unite: UniteTranches::Heures,
+            bornes: vec![4.0, 8.0, 24.0],
+            libelles: vec![],
+        }
+    }
+
+    #[test]
+    fn test_compter_en_heures() {
+        let jeu = jeu_heures();
+        // 2h, 4h (borne basse incluse), 12h, 2 jours
+        let counts = jeu.compter([2.0 / 24.0, 4.0 / 24.0, 0.5, 2.0]);
+        assert_eq!(counts, vec![1, 1, 1, 1]);
+        assert_eq!(jeu.libelles(), vec!["< 4h", "4h - 8h", "8h - 24h", "> 24h"]);
+        assert_eq!(jeu.bornes_basses_jours()[1], 4.0 / 24.0);
+    }
+
+    #[test]
+    fn test_defaut_valide_et_historique() {
+        let t = TranchesDistribution::default();
+        assert!(t.valider().is_ok());
+        let jeu = t.resolution();
+        assert_eq!(jeu.libelles()[2], "2j - 7j");
+        assert_eq!(jeu.compter([0.5, 1.0, 6.9, 7.0, 30.0]), vec![1, 1, 1, 1, 1]);
+        assert_eq!(t.anciennete().libelles()[2], "48h - 7j");
+    }
+
+    #[test]
+    fn test_valider_rejette_bornes_non_croissantes() {
+        let mut t = TranchesDistribution::default();
+        let mut jeu = jeu_heures();
+        jeu.bornes = vec![4.0, 4.0, 24.0];
+        t.jeux.push(jeu);
+        assert!(t.valider().is_err());
+
+        let mut t = TranchesDistribution::default();
+        let mut jeu = jeu_heures();
+        jeu.libelles = vec!["a".to_string()];
+        t.jeux.push(jeu);
+        assert!(t.valider().is_err());
+
+        let mut t = TranchesDistribution::default();
+        t.resolution = "inconnu".to_string();
+        assert!(t.valider().is_err());
+
+        let mut t = TranchesDistribution::default();
+        t.jeux.push(jeu_heures());
+        t.resolution = "sla".to_string();
+        assert!(t.valider().is_ok());
+        assert_eq!(t.resolution().unite, UniteTranches::Heures);
+    }
+}
@@ -0,0 +1,577 @@
+//! Calendrier ouvré : plages d'ouverture hebdomadaires, jours fériés français
+//! (fixes et mobiles liés à Pâques) et fermetures exceptionnelles.
+//!
+//! Sert à exprimer chaque délai en temps ouvré en plus du temps calendaire.
+//! Un "jour ouvré" vaut la durée moyenne d'une journée d'ouverture : un ticket
+//! ouvert le vendredi à 18h et résolu le lundi à 9h compte pour quelques
+//! minutes, pas pour 3 jours.
+
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+/// Jours fériés à date fixe (mois, jour).
+const FERIES_FIXES: [(u32, u32, &str); 8] = [
+    (1, 1, "Jour de l'an"),
+    (5, 1, "Fête du Travail"),
+    (5, 8, "Victoire 1945"),
+    (7, 14, "Fête nationale"),
+    (8, 15, "Assomption"),
+    (11, 1, "Toussaint"),
+    (11, 11, "Armistice 1918"),
+    (12, 25, "Noël"),
+];
+/// Jours fériés mobiles, en jours après le dimanche de Pâques.
+const FERIES_PAQUES: [(i64, &str); 3] = [
+    (1, "Lundi de Pâques"),
+    (39, "Ascension"),
+    (50, "Lundi de Pentecôte"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CalendrierOuvre {
+    /// Plages d'ouverture ; un jour sans plage est fermé.
+    pub plages: Vec<PlageOuverture>,
+    /// Jours fériés français chômés.
+    pub jours_feries: bool,
+    /// Fermetures du site (ponts, congés collectifs…), bornes incluses.
+    pub fermetures: Vec<Fermeture>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlageOuverture {
+    /// 1 = lundi … 7 = dimanche
+    pub jour: u32,
+    /// "HH:MM"
+    pub debut: String,
+    pub fin: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fermeture {
+    pub debut: NaiveDate,
+    pub fin: NaiveDate,
+    #[serde(default)]
+    pub libelle: String,
+}
+
+impl Default for CalendrierOuvre {
+    /// Du lundi au vendredi, 8h30-12h30 et 13h30-17h30, fériés chômés.
+    fn default() -> Self {
+        let plages = (1..=5)
+            .flat_map(|jour| {
+                [("08:30", "12:30"), ("13:30", "17:30")].map(|(debut, fin)| PlageOuverture {
+                    jour,
+                    debut: debut.to_string(),
+                    fin: fin.to_string(),
+                })
+            })
+            .collect();
+        Self {
+            plages,
+            jours_feries: true,
+            fermetures: Vec::new(),
+        }
+    }
+}
+
+/// Minutes depuis minuit ; "24:00" est accepté comme fin de journée.
+pub(crate) fn parse_heure(s: &str) -> Result<u32, String> {
+    if s.trim() == "24:00" {
+        return Ok(24 * 60);
+    }
+    let t = NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .map_err(|_| format!("Heure invalide: {s} (attendu HH:MM)"))?;
+    Ok(t.hour() * 60 + t.minute())
+}
+
+impl CalendrierOuvre {
+    /// Valide et précalcule le calendrier.
+    pub fn compile(&self) -> Result<CalendrierCompile, String> {
+        let mut plages: [Vec<(u32, u32)>; 7] = Default::default();
+        for p in &self.plages {
+            if !(1..=7).contains(&p.jour) {
+                return Err(format!(
+                    "Jour invalide: {} (1 = lundi … 7 = dimanche)",
+                    p.jour
+                ));
+            }
+            let (debut, fin) = (parse_heure(&p.debut)?, parse_heure(&p.fin)?);
+            if debut >= fin {
+                return Err(format!("Plage vide ou inversée: {}-{}", p.debut, p.fin));
+            }
+            plages[(p.jour - 1) as usize].push((debut, fin));
+        }
+        for jour in plages.iter_mut() {
+            jour.sort_unstable();
+            if jour.windows(2).any(|w| w[0].1 > w[1].0) {
+                return Err("Plages d'ouverture qui se chevauchent".to_string());
+            }
+        }
+        let jours_ouverts = plages.iter().filter(|p| !p.is_empty()).count();
+        if jours_ouverts == 0 {
+            return Err("Le calendrier doit comporter au moins une plage d'ouverture".to_string());
+        }
+        if let Some(f) = self.fermetures.iter().find(|f| f.debut > f.fin) {
+            return Err(format!("Fermeture inversée: {} > {}", f.debut, f.fin));
+        }
+
+        let minutes_jour = plages
+            .each_ref()
+            .map(|jour| jour.iter().map(|(d, f)| (f - d) as i64).sum::<i64>());
+        let minutes_semaine: i64 = minutes_jour.iter().sum();
+
+        Ok(CalendrierCompile {
+            plages,
+            jours_feries: self.jours_feries,
+            fermetures: self.fermetures.iter().map(|f| (f.debut, f.fin)).collect(),
+            minutes_par_jour: minutes_semaine as f64 / jours_ouverts as f64,
+            minutes_jour,
+            minutes_semaine,
+        })
+    }
+}
+
+/// Délai exprimé en jours calendaires et en jours ouvrés.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Delai {
+    pub jours: f64,
+    pub jours_ouvres: f64,
+}
+
+/// Calendrier prêt au calcul (plages en minutes depuis minuit).
+#[derive(Debug, Clone)]
+pub struct CalendrierCompile {
+    plages: [Vec<(u32, u32)>; 7],
+    jours_feries: bool,
+    fermetures: Vec<(NaiveDate, NaiveDate)>,
+    minutes_par_jour: f64,
+    /// Minutes d'ouverture de chaque jour de semaine (lundi = 0), hors fermetures.
+    minutes_jour: [i64; 7],
+    minutes_semaine: i64,
+}
+
+impl CalendrierCompile {
+    pub fn est_ferme(&self, date: NaiveDate) -> bool {
+        (self.jours_feries && est_ferie_france(date))
+            || self
+                .fermetures
+                .iter()
+                .any(|(debut, fin)| (*debut..=*fin).contains(&date))
+    }
+
+    /// Jour comportant au moins une plage d'ouverture.
+    pub fn est_ouvre(&self, date: NaiveDate) -> bool {
+        !self.plages_du_jour(date).is_empty()
+    }
+
+    fn plages_du_jour(&self, date: NaiveDate) -> &[(u32, u32)] {
+        if self.est_ferme(date) {
+            &[]
+        } else {
+            &self.plages[date.weekday().num_days_from_monday() as usize]
+        }
+    }
+
+    /// Minutes ouvrées entre deux instants (0 si `fin` <= `debut`).
+    pub fn minutes_ouvrees(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> i64 {
+        if fin <= debut {
+            return 0;
+        }
+        let minute_du_jour = |dt: NaiveDateTime| dt.hour() * 60 + dt.minute();
+        let (premier, dernier) = (debut.date(), fin.date());
+        if premier == dernier {
+            return self.minutes_plages(premier, minute_du_jour(debut), minute_du_jour(fin));
+        }
+        self.minutes_plages(premier, minute_du_jour(debut), 24 * 60)
+            + self.minutes_jours_pleins(premier + Duration::days(1), dernier - Duration::days(1))
+            + self.minutes_plages(dernier, 0, minute_du_jour(fin))
+    }
+
+    /// Minutes ouvrées d'une journée entre deux heures (minutes depuis minuit).
+    fn minutes_plages(&self, jour: NaiveDate, borne_basse: u32, borne_haute: u32) -> i64 {
+        self.plages_du_jour(jour)
+            .iter()
+            .map(|&(d, f)| (f.min(borne_haute) as i64 - d.max(borne_basse) as i64).max(0))
+            .sum()
+    }
+
+    /// Minutes ouvrées des journées entières de `debut` à `fin` incluses :
+    /// semaines complètes au volume hebdomadaire, jours restants un à un, puis
+    /// retrait des jours fermés tombant sur un jour d'ouverture.
+    fn minutes_jours_pleins(&self, debut: NaiveDate, fin: NaiveDate) -> i64 {
+        if fin < debut {
+            return 0;
+        }
+        let minutes_jour =
+            |jour: NaiveDate| self.minutes_jour[jour.weekday().num_days_from_monday() as usize];
+        let semaines = ((fin - debut).num_days() + 1) / 7;
+        let mut total = semaines * self.minutes_semaine;
+        let mut jour = debut + Duration::days(semaines * 7);
+        while jour <= fin {
+            total += minutes_jour(jour);
+            jour += Duration::days(1);
+        }
+        total
+            - self
+                .jours_fermes(debut, fin)
+                .into_iter()
+                .map(minutes_jour)
+                .sum::<i64>()
+    }
+
+    /// Jours fériés et de fermeture entre deux dates incluses, sans doublon.
+    fn jours_fermes(&self, debut: NaiveDate, fin: NaiveDate) -> BTreeSet<NaiveDate> {
+        let mut jours = BTreeSet::new();
+        if self.jours_feries {
+            for annee in debut.year()..=fin.year() {
+                jours.extend(
+                    jours_feries_france(annee)
+                        .into_iter()
+                        .map(|(date, _)| date)
+                        .filter(|date| (debut..=fin).contains(date)),
+                );
+            }
+        }
+        for &(d, f) in &self.fermetures {
+            let (mut jour, f) = (d.max(debut), f.min(fin));
+            while jour <= f {
+                jours.insert(jour);
+                jour += Duration::days(1);
+            }
+        }
+        jours
+    }
+
+    /// Instant atteint après `minutes` ouvrées depuis `debut` : le décompte
+    /// démarre à la première plage d'ouverture qui suit.
+    pub fn ajouter_minutes_ouvrees(&self, debut: NaiveDateTime, minutes: i64) -> NaiveDateTime {
+        if minutes <= 0 {
+            return debut;
+        }
+        let mut reste = minutes;
+        let mut jour = debut.date();
+        let mut borne_basse = debut.hour() * 60 + debut.minute();
+        // Termine : au moins une plage par semaine, fermetures en nombre fini
+        loop {
+            for &(d, f) in self.plages_du_jour(jour) {
+                let d = d.max(borne_basse);
+                if f <= d {
+                    continue;
+                }
+                let disponible = (f - d) as i64;
+                if reste <= disponible {
+                    return jour.and_time(NaiveTime::MIN) + Duration::minutes(d as i64 + reste);
+                }
+                reste -= disponible;
+            }
+            jour += Duration::days(1);
+            borne_basse = 0;
+        }
+    }
+
+    /// Heures ouvrées de `debut` à `fin`, négatives si `fin` précède `debut`.
+    pub fn heures_ouvrees_signees(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> f64 {
+        if fin >= debut {
+            self.minutes_ouvrees(debut, fin) as f64 / 60.0
+        } else {
+            -(self.minutes_ouvrees(fin, debut) as f64 / 60.0)
+        }
+    }
+
+    /// Délai en jours ouvrés (journée d'ouverture moyenne).
+    pub fn jours_ouvres(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> f64 {
+        self.minutes_ouvrees(debut, fin) as f64 / self.minutes_par_jour
+    }
+
+    /// Délai en jours ouvrés entre deux dates texte (format GLPI ou ISO) ;
+    /// None si l'une est illisible ou si `fin` précède `debut`.
+    pub fn jours_ouvres_str(&self, debut: &str, fin: &str) -> Option<f64> {
+        self.delai_str(debut, fin).map(|d| d.jours_ouvres)
+    }
+
+    /// Délai calendaire et ouvré entre deux dates texte.
+    pub fn delai_str(&self, debut: &str, fin: &str) -> Option<Delai> {
+        use crate::parser::deserializers::parse_french_datetime;
+        let (d, f) = (parse_french_datetime(debut)?, parse_french_datetime(fin)?);
+        (f >= d).then(|| Delai {
+            jours: (f - d).num_seconds() as f64 / 86_400.0,
+            jours_ouvres: self.jours_ouvres(d, f),
+        })
+    }
+}
+
+/// Dimanche de Pâques (algorithme de Meeus/Jones/Butcher, calendrier grégorien).
+pub fn paques(annee: i32) -> NaiveDate {
+    let a = annee % 19;
+    let b = annee / 100;
+    let c = annee % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let mois = (h + l - 7 * m + 114) / 31;
+    let jour = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(annee, mois as u32, jour as u32).expect("date de Pâques valide")
+}
+
+/// Les 11 jours fériés légaux de France métropolitaine, par date croissante.
+pub fn jours_feries_france(annee: i32) -> Vec<(NaiveDate, &'static str)> {
+    let p = paques(annee);
+    let mut jours: Vec<(NaiveDate, &'static str)> = FERIES_FIXES
+        .iter()
+        .map(|&(m, j, nom)| {
+            let date = NaiveDate::from_ymd_opt(annee, m, j).expect("date fixe valide");
+            (date, nom)
+        })
+        .chain(
+            FERIES_PAQUES
+                .iter()
+                .map(|&(decalage, nom)| (p + Duration::days(decalage), nom)),
+        )
+        .collect();
+    jours.sort_by_key(|(d, _)| *d);
+    jours
+}
+
+/// Jour férié légal, calculé pour l'année de la date (sans précalcul).
+pub fn est_ferie_france(date: NaiveDate) -> bool {
+    if FERIES_FIXES
+        .iter()
+        .any(|&(m, j, _)| date.month() == m && date.day() == j)
+    {
+        return true;
+    }
+    // Pâques tombe entre le 22 mars et le 25 avril : mobiles de mars à juin
+    if !(3..=6).contains(&date.month()) {
+        return false;
+    }
+    let p = paques(date.year());
+    FERIES_PAQUES
+        .iter()
+        .any(|&(decalage, _)| date == p + Duration::days(decalage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_paques() {
+        assert_eq!(paques(2024), NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+        assert_eq!(paques(2025), NaiveDate::from_ymd_opt(2025, 4, 20).unwrap());
+        assert_eq!(paques(2026), NaiveDate::from_ymd_opt(2026, 4, 5).unwrap());
+    }
+
+    #[test]
+    fn test_jours_feries_2025() {
+        let feries = jours_feries_france(2025);
+        assert_eq!(feries.len(), 11);
+        let date = |m, j| NaiveDate::from_ymd_opt(2025, m, j).unwrap();
+        assert!(feries.contains(&(date(4, 21), "Lundi de Pâques")));
+        assert!(feries.contains(&(date(5, 29), "Ascension")));
+        assert!(feries.contains(&(date(6, 9), "Lundi de Pentecôte")));
+    }
+
+    #[test]
+    fn test_est_ferie_matches_list() {
+        for annee in [1900, 2025, 2026, 2150] {
+            let feries = jours_feries_france(annee);
+            let mut jour = NaiveDate::from_ymd_opt(annee, 1, 1).unwrap();
+            while jour.year() == annee {
+                assert_eq!(
+                    est_ferie_france(jour),
+                    feries.iter().any(|(d, _)| *d == jour),
+                    "{jour}"
+                );
+                jour += Duration::days(1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_weekend_is_not_counted() {
+        let cal = CalendrierOuvre::default().compile().unwrap();
+        // Vendredi 17h → lundi 9h30 : 30 min vendredi + 1h lundi
+        let m = cal.minutes_ouvrees(dt("2025-01-10 17:00"), dt("2025-01-13 09:30"));
+        assert_eq!(m, 90);
+        // Une semaine pleine = 5 jours ouvrés
+        let j = cal.jours_ouvres(dt("2025-01-06 08:30"), dt("2025-01-13 08:30"));
+        assert_eq!(j, 5.0);
+        // Pause déjeuner exclue
+        assert_eq!(
+            cal.minutes_ouvrees(dt("2025-01-06 12:00"), dt("2025-01-06 14:00")),
+            60
+        );
+        assert_eq!(
+            cal.minutes_ouvrees(dt("2025-01-06 14:00"), dt("2025-01-06 12:00")),
+            0
+        );
+        // Journée de 8h = 1 jour ouvré
+        assert_eq!(
+            cal.jours_ouvres(dt("2025-01-06 12:30"), dt("2025-01-07 12:30")),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_add_business_minutes() {
+        let cal = CalendrierOuvre::default().compile().unwrap();
+        // Vendredi 17h + 2h ouvrées : 30 min vendredi, 1h30 lundi
+        assert_eq!(
+            cal.ajouter_minutes_ouvrees(dt("2025-01-10 17:00"), 120),
+            dt("2025-01-13 10:00")
+        );
+        // Ouvert pendant la pause : décompte à la reprise
+        assert_eq!(
+            cal.ajouter_minutes_ouvrees(dt("2025-01-06 12:45"), 60),
+            dt("2025-01-06 14:30")
+        );
+        assert_eq!(
+            cal.heures_ouvrees_signees(dt("2025-01-13 10:00"), dt("2025-01-10 17:00")),
+            -2.0
+        );
+
+        let continu = CalendrierOuvre {
+            plages: (1..=7)
+                .map(|jour| PlageOuverture {
+                    jour,
+                    debut: "00:00".into(),
+                    fin: "24:00".into(),
+                })
+                .collect(),
+            jours_feries: false,
+            fermetures: Vec::new(),
+        }
+        .compile()
+        .unwrap();
+        assert_eq!(
+            continu.ajouter_minutes_ouvrees(dt("2025-01-10 17:00"), 72 * 60),
+            dt("2025-01-13 17:00")
+        );
+    }
+
+    #[test]
+    fn test_holidays_and_closures() {
+        let mut cal = CalendrierOuvre::default();
+        // Semaine du lundi de Pâques 2025 : 4 jours ouvrés au lieu de 5
+        let semaine = (dt("2025-04-21 00:00"), dt("2025-04-28 00:00"));
+        assert_eq!(
+            cal.compile().unwrap().jours_ouvres(semaine.0, semaine.1),
+            4.0
+        );
+
+        cal.fermetures.push(Fermeture {
+            debut: NaiveDate::from_ymd_opt(2025, 4, 24).unwrap(),
+            fin: NaiveDate::from_ymd_opt(2025, 4, 25).unwrap(),
+            libelle: "Pont".into(),
+        });
+        assert_eq!(
+            cal.compile().unwrap().jours_ouvres(semaine.0, semaine.1),
+            2.0
+        );
+
+        cal.jours_feries = false;
+        assert_eq!(
+            cal.compile().unwrap().jours_ouvres(semaine.0, semaine.1),
+            3.0
+        );
+    }
+
+    #[test]
+    fn test_semaines_entieres_egales_au_jour_par_jour() {
+        let mut cal = CalendrierOuvre::default();
+        cal.plages.push(PlageOuverture {
+            jour: 6,
+            debut: "09:00".into(),
+            fin: "12:00".into(),
+        });
+        // Fermeture chevauchant l'Ascension et le 8 mai 2025
+        cal.fermetures.push(Fermeture {
+            debut: NaiveDate::from_ymd_opt(2025, 5, 5).unwrap(),
+            fin: NaiveDate::from_ymd_opt(2025, 5, 30).unwrap(),
+            libelle: "Travaux".into(),
+        });
+        let cal = cal.compile().unwrap();
+        let jour_par_jour = |debut: NaiveDateTime, fin: NaiveDateTime| {
+            let mut total = 0;
+            let mut jour = debut.date();
+            while jour <= fin.date() {
+                let bas = if jour == debut.date() {
+                    debut.hour() * 60 + debut.minute()
+                } else {
+                    0
+                };
+                let haut = if jour == fin.date() {
+                    fin.hour() * 60 + fin.minute()
+                } else {
+                    24 * 60
+                };
+                total += cal.minutes_plages(jour, bas, haut);
+                jour += Duration::days(1);
+            }
+            total
+        };
+        for (debut, fin) in [
+            ("2024-12-20 10:15", "2025-01-06 09:00"),
+            ("2025-04-18 16:00", "2025-06-10 11:30"),
+            ("2025-05-01 00:00", "2025-05-31 23:59"),
+            ("2023-07-01 08:00", "2025-07-01 08:00"),
+            ("2025-03-03 12:00", "2025-03-07 12:00"),
+        ] {
+            let (debut, fin) = (dt(debut), dt(fin));
+            assert_eq!(
+                cal.minutes_ouvrees(debut, fin),
+                jour_par_jour(debut, fin),
+                "{debut} → {fin}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_compile_validation() {
+        let mut cal = CalendrierOuvre::default();
+        cal.plages[0].fin = "07:00".into();
+        assert!(cal.compile().is_err());
+
+        let mut cal = CalendrierOuvre::default();
+        cal.plages[0].jour = 8;
+        assert!(cal.compile().is_err());
+
+        let mut cal = CalendrierOuvre::default();
+        cal.plages[1].debut = "12:00".into(); // chevauche 8h30-12h30
+        assert!(cal.compile().is_err());
+
+        let cal = CalendrierOuvre {
+            plages: vec![],
+            ..Default::default()
+        };
+        assert!(cal.compile().is_err());
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let cal = CalendrierOuvre::default();
+        let json = serde_json::to_string(&cal).unwrap();
+        assert!(json.contains("\"joursFeries\":true"));
+        let back: CalendrierOuvre = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, cal);
+        // Champs absents : valeurs par défaut
+        let partial: CalendrierOuvre = serde_json::from_str(r#"{"joursFeries":false}"#).unwrap();
+        assert_eq!(partial.plages.len(), 10);
+        assert!(!partial.jours_feries);
+    }
+}
@@ -0,0 +1,297 @@
+//! Capacité : taux d'arrivée, débit et délai de traversée (loi de Little)
+//! par équipe, avec estimation du temps de résorption du stock courant.
+//!
+//! - taux d'arrivée λ et débit μ en tickets par jour sur la fenêtre ;
+//! - délai de traversée W = L / μ (L = stock vivant actuel), valable en
+//!   régime à peu près stationnaire ;
+//! - résorption au débit récent (dernières périodes) : L / (μ récent − λ
+//!   récent), `None` si le stock ne décroît pas ;
+//! - déficit persistant : arrivées > sorties sur les dernières périodes
+//!   consécutives.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::stats::{round1, round2};
+
+pub const PERIODES_RECENTES_DEFAUT: usize = 3;
+pub const SEUIL_PERSISTANCE_DEFAUT: usize = 3;
+
+/// Clés de période issues de `temporal::generate_period_keys`.
+pub type PeriodKey = (String, String, NaiveDateTime, NaiveDateTime);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapaciteAnalyse {
+    pub date_debut: String,
+    pub date_fin: String,
+    pub granularite: String,
+    pub periodes_recentes: usize,
+    pub seuil_persistance: usize,
+    pub global: CapaciteEquipe,
+    pub par_groupe: Vec<CapaciteEquipe>,
+    pub par_technicien: Vec<CapaciteEquipe>,
+    /// Groupes en déficit persistant.
+    pub groupes_en_deficit: Vec<String>,
+    /// Techniciens en déficit persistant.
+    pub techniciens_en_deficit: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapaciteEquipe {
+    pub label: String,
+    pub stock_actuel: usize,
+    pub arrivees: usize,
+    pub sorties: usize,
+    pub taux_arrivee_jour: f64,
+    pub debit_jour: f64,
+    pub arrivees_recentes_jour: f64,
+    pub debit_recent_jour: f64,
+    /// Arrivées / sorties sur la fenêtre (> 1 : le stock croît).
+    pub ratio_arrivees_sorties: Option<f64>,
+    /// Loi de Little : stock actuel / débit (jours).
+    pub delai_traversee_jours: Option<f64>,
+    /// Jours pour écouler le stock au débit récent, sans nouvelles arrivées.
+    pub jours_ecoulement: Option<f64>,
+    /// Jours pour résorber le stock au débit récent net des arrivées
+    /// récentes ; `None` si les arrivées égalent ou dépassent le débit.
+    pub jours_resorption: Option<f64>,
+    pub periodes_en_deficit: usize,
+    /// Périodes consécutives en déficit, en remontant depuis la dernière.
+    pub deficit_consecutif: usize,
+    pub deficit_persistant: bool,
+    pub series: Vec<CapacitePeriode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapacitePeriode {
+    pub period_key: String,
+    pub period_label: String,
+    pub arrivees: usize,
+    pub sorties: usize,
+}
+
+fn jours_periode(k: &PeriodKey) -> f64 {
+    ((k.3.date() - k.2.date()).num_days() + 1).max(1) as f64
+}
+
+fn par_jour(n: usize, jours: f64) -> f64 {
+    if jours > 0.0 {
+        n as f64 / jours
+    } else {
+        0.0
+    }
+}
+
+/// Indicateurs d'une équipe à partir de ses flux par période.
+pub fn compute_equipe(
+    label: &str,
+    entrees: &HashMap<&str, usize>,
+    sorties: &HashMap<&str, usize>,
+    stock_actuel: usize,
+    period_keys: &[PeriodKey],
+    periodes_recentes: usize,
+    seuil_persistance: usize,
+) -> CapaciteEquipe {
+    let series: Vec<CapacitePeriode> = period_keys
+        .iter()
+        .map(|k| CapacitePeriode {
+            period_key: k.0.clone(),
+            period_label: k.1.clone(),
+            arrivees: entrees.get(k.0.as_str()).copied().unwrap_or(0),
+            sorties: sorties.get(k.0.as_str()).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let jours: f64 = period_keys.iter().map(jours_periode).sum();
+    let arrivees: usize = series.iter().map(|p| p.arrivees).sum();
+    let total_sorties: usize = series.iter().map(|p| p.sorties).sum();
+
+    let debut_recent = series.len().saturating_sub(periodes_recentes.max(1));
+    let jours_recents: f64 = period_keys[debut_recent..].iter().map(jours_periode).sum();
+    let recentes = &series[debut_recent..];
+    let arrivees_recentes = par_jour(recentes.iter().map(|p| p.arrivees).sum(), jours_recents);
+    let debit_recent = par_jour(recentes.iter().map(|p| p.sorties).sum(), jours_recents);
+    let debit = par_jour(total_sorties, jours);
+
+    let en_deficit = |p: &CapacitePeriode| p.arrivees > p.sorties;
+    let deficit_consecutif = series.iter().rev().take_while(|p| en_deficit(p)).count();
+
+    let stock = stock_actuel as f64;
+    let net = debit_recent - arrivees_recentes;
+    CapaciteEquipe {
+        label: label.to_string(),
+        stock_actuel,
+        arrivees,
+        sorties: total_sorties,
+        taux_arrivee_jour: round2(par_jour(arrivees, jours)),
+        debit_jour: round2(debit),
+        arrivees_recentes_jour: round2(arrivees_recentes),
+        debit_recent_jour: round2(debit_recent),
+        ratio_arrivees_sorties: (total_sorties > 0)
+            .then(|| round2(arrivees as f64 / total_sorties as f64)),
+        delai_traversee_jours: (debit > 0.0).then(|| round1(stock / debit)),
+        jours_ecoulement: (debit_recent > 0.0).then(|| round1(stock / debit_recent)),
+        jours_resorption: if stock_actuel == 0 {
+            Some(0.0)
+        } else {
+            (net > 0.0).then(|| round1(stock / net))
+        },
+        periodes_en_deficit: series.iter().filter(|p| en_deficit(p)).count(),
+        deficit_consecutif,
+        deficit_persistant: seuil_persistance > 0 && deficit_consecutif >= seuil_persistance,
+        series,
+    }
+}
+
+/// (période, valeur, n) → valeur → période → n.
+fn indexer(rows: &[(String, String, usize)]) -> HashMap<&str, HashMap<&str, usize>> {
+    let mut map: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+    for (periode, label, n) in rows {
+        *map.entry(label.as_str())
+            .or_default()
+            .entry(periode.as_str())
+            .or_default() += n;
+    }
+    map
+}
+
+/// Indicateurs par valeur d'une dimension, triés par stock puis arrivées.
+pub fn compute_par_dimension(
+    entrees: &[(String, String, usize)],
+    sorties: &[(String, String, usize)],
+    stocks: &[(String, usize)],
+    period_keys: &[PeriodKey],
+    periodes_recentes: usize,
+    seuil_persistance: usize,
+) -> Vec<CapaciteEquipe> {
+    let entrees = indexer(entrees);
+    let sorties = indexer(sorties);
+    let stocks: HashMap<&str, usize> = stocks.iter().map(|(l, n)| (l.as_str(), *n)).collect();
+
+    let mut labels: Vec<&str> = entrees
+        .keys()
+        .chain(sorties.keys())
+        .chain(stocks.keys())
+        .copied()
+        .collect();
+    labels.sort_unstable();
+    labels.dedup();
+
+    let vide = HashMap::new();
+    let mut result: Vec<CapaciteEquipe> = labels
+        .into_iter()
+        .map(|label| {
+            compute_equipe(
+                label,
+                entrees.get(label).unwrap_or(&vide),
+                sorties.get(label).unwrap_or(&vide),
+                stocks.get(label).copied().unwrap_or(0),
+                period_keys,
+                periodes_recentes,
+                seuil_persistance,
+            )
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.stock_actuel
+            .cmp(&a.stock_actuel)
+            .then(b.arrivees.cmp(&a.arrivees))
+            .then(a.label.cmp(&b.label))
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::temporal::generate_period_keys;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn keys() -> Vec<PeriodKey> {
+        // 4 périodes de 7 jours (28 jours)
+        generate_period_keys(dt("2026-01-01 00:00:00"), dt("2026-01-28 23:59:59"), "day")
+            .chunks(7)
+            .map(|c| (c[0].0.clone(), c[0].1.clone(), c[0].2, c[6].3))
+            .collect()
+    }
+
+    fn flux<'a>(keys: &'a [PeriodKey], valeurs: &[usize]) -> HashMap<&'a str, usize> {
+        keys.iter()
+            .map(|k| k.0.as_str())
+            .zip(valeurs.iter().copied())
+            .collect()
+    }
+
+    #[test]
+    fn test_little_and_clearance() {
+        let k = keys();
+        let e = compute_equipe(
+            "Support",
+            &flux(&k, &[7, 7, 7, 7]),
+            &flux(&k, &[14, 14, 14, 14]),
+            28,
+            &k,
+            2,
+            3,
+        );
+        assert_eq!(e.taux_arrivee_jour, 1.0);
+        assert_eq!(e.debit_jour, 2.0);
+        assert_eq!(e.ratio_arrivees_sorties, Some(0.5));
+        // W = L / μ = 28 / 2
+        assert_eq!(e.delai_traversee_jours, Some(14.0));
+        assert_eq!(e.jours_ecoulement, Some(14.0));
+        // Net récent : 2 − 1 = 1 ticket/jour
+        assert_eq!(e.jours_resorption, Some(28.0));
+        assert!(!e.deficit_persistant);
+    }
+
+    #[test]
+    fn test_persistent_deficit() {
+        let k = keys();
+        let e = compute_equipe(
+            "Infra",
+            &flux(&k, &[7, 14, 14, 21]),
+            &flux(&k, &[14, 7, 7, 7]),
+            40,
+            &k,
+            2,
+            3,
+        );
+        assert_eq!(e.periodes_en_deficit, 3);
+        assert_eq!(e.deficit_consecutif, 3);
+        assert!(e.deficit_persistant);
+        assert_eq!(e.jours_resorption, None);
+        assert_eq!(e.arrivees_recentes_jour, 2.5);
+        assert_eq!(e.debit_recent_jour, 1.0);
+    }
+
+    #[test]
+    fn test_by_dimension() {
+        let k = keys();
+        let p = |i: usize| k[i].0.clone();
+        let entrees = vec![
+            (p(0), "A".to_string(), 5),
+            (p(3), "A".to_string(), 2),
+            (p(1), "B".to_string(), 3),
+        ];
+        let sorties = vec![(p(0), "A".to_string(), 4)];
+        let stocks = vec![("B".to_string(), 3), ("C".to_string(), 9)];
+        let r = compute_par_dimension(&entrees, &sorties, &stocks, &k, 2, 3);
+        assert_eq!(
+            r.iter().map(|e| e.label.as_str()).collect::<Vec<_>>(),
+            vec!["C", "B", "A"]
+        );
+        let a = &r[2];
+        assert_eq!((a.arrivees, a.sorties, a.stock_actuel), (7, 4, 0));
+        assert_eq!(a.jours_resorption, Some(0.0));
+        assert_eq!(r[0].delai_traversee_jours, None);
+    }
+}
@@ -0,0 +1,384 @@
+//! Hiérarchie des catégories ITIL (profondeur quelconque) avec indicateurs
+//! par nœud.
+//!
+//! Chaque nœud porte ses indicateurs propres (tickets rattachés directement)
+//! et cumulés (nœud + descendants, agrégés de la feuille vers la racine) :
+//! stock vivant, entrants par période, MTTR médian / P90, taux N1 et
+//! répartition par ancienneté (jeu de tranches configuré). Les nœuds sans
+//! ticket sont omis.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::buckets::JeuTranches;
+use super::stats::{pct, percentile, round1};
+
+#[derive(Debug, Clone)]
+pub struct NoeudCategorie {
+    pub id: i64,
+    pub parent_id: Option<i64>,
+    pub nom: String,
+    pub chemin: String,
+    pub niveau: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct TicketCategorie {
+    pub categorie_id: Option<i64>,
+    pub ouverture: NaiveDateTime,
+    /// Clôture (terminés uniquement).
+    pub sortie: Option<NaiveDateTime>,
+    pub est_vivant: bool,
+    pub age_jours: Option<f64>,
+    pub delai_resolution_jours: Option<f64>,
+    /// Résolu au niveau 1 (un seul technicien, au plus un suivi).
+    pub n1: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorieHierarchie {
+    pub granularite: String,
+    pub periodes: Vec<String>,
+    pub profondeur_max: usize,
+    pub total_tickets: usize,
+    pub sans_categorie: usize,
+    pub noeuds: Vec<NoeudKpi>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoeudKpi {
+    pub id: i64,
+    pub nom: String,
+    pub chemin: String,
+    pub niveau: usize,
+    /// Tickets rattachés directement au nœud.
+    pub propres: KpiCategorie,
+    /// Nœud et tous ses descendants.
+    pub cumules: KpiCategorie,
+    pub children: Vec<NoeudKpi>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KpiCategorie {
+    pub tickets: usize,
+    pub stock_vivant: usize,
+    /// Entrants par période, alignés sur `CategorieHierarchie::periodes`.
+    pub entrants: Vec<usize>,
+    pub total_entrants: usize,
+    /// Terminés dans la fenêtre (base du MTTR et du taux N1).
+    pub termines: usize,
+    pub mttr_mediane_jours: Option<f64>,
+    pub mttr_p90_jours: Option<f64>,
+    pub taux_n1: Option<f64>,
+    pub distribution_age: Vec<TrancheAge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrancheAge {
+    pub label: String,
+    pub count: usize,
+}
+
+/// Fenêtre d'analyse : clés de `temporal::generate_period_keys`.
+pub struct Fenetre<'a> {
+    pub granularite: &'a str,
+    pub periodes: &'a [(String, String, NaiveDateTime, NaiveDateTime)],
+}
+
+impl Fenetre<'_> {
+    fn periode(&self, dt: NaiveDateTime) -> Option<usize> {
+        self.periodes
+            .iter()
+            .position(|(_, _, debut, fin)| dt >= *debut && dt <= *fin)
+    }
+
+    fn contient(&self, dt: NaiveDateTime) -> bool {
+        match (self.periodes.first(), self.periodes.last()) {
+            (Some(premiere), Some(derniere)) => dt >= premiere.2 && dt <= derniere.3,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Default, Clone)]
+struct Accumulateur {
+    tickets: usize,
+    stock: usize,
+    entrants: Vec<usize>,
+    delais: Vec<f64>,
+    termines: usize,
+    n1: usize,
+    /// Ancienneté (jours) des tickets vivants.
+    ages: Vec<f64>,
+}
+
+impl Accumulateur {
+    fn ajouter(&mut self, t: &TicketCategorie, periode: Option<usize>, termine_fenetre: bool) {
+        self.tickets += 1;
+        if let Some(p) = periode {
+            self.entrants[p] += 1;
+        }
+        if t.est_vivant {
+            self.stock += 1;
+            self.ages.extend(t.age_jours);
+        }
+        if termine_fenetre {
+            self.termines += 1;
+            if t.n1 {
+                self.n1 += 1;
+            }
+            self.delais.extend(t.delai_resolution_jours);
+        }
+    }
+
+    fn kpi(&self, jeu: &JeuTranches) -> KpiCategorie {
+        KpiCategorie {
+            tickets: self.tickets,
+            stock_vivant: self.stock,
+            total_entrants: self.entrants.iter().sum(),
+            entrants: self.entrants.clone(),
+            termines: self.termines,
+            mttr_mediane_jours: (!self.delais.is_empty())
+                .then(|| round1(percentile(&self.delais, 50.0))),
+            mttr_p90_jours: (!self.delais.is_empty())
+                .then(|| round1(percentile(&self.delais, 90.0))),
+            taux_n1: (self.termines > 0).then(|| pct(self.n1, self.termines)),
+            distribution_age: jeu
+                .libelles()
+                .into_iter()
+                .zip(jeu.compter(self.ages.iter().copied()))
+                .map(|(label, count)| TrancheAge { label, count })
+                .collect(),
+        }
+    }
+}
+
+pub fn compute_hierarchie(
+    noeuds: &[NoeudCategorie],
+    tickets: &[TicketCategorie],
+    fenetre: &Fenetre,
+    jeu: &JeuTranches,
+) -> CategorieHierarchie {
+    let index: HashMap<i64, usize> = noeuds.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
+    let vide = Accumulateur {
+        entrants: vec![0; fenetre.periodes.len()],
+        ..Default::default()
+    };
+    let mut propres = vec![vide.clone(); noeuds.len()];
+    let mut cumules = vec![vide; noeuds.len()];
+
+    let mut sans_categorie = 0;
+    for t in tickets {
+        let Some(&i) = t.categorie_id.and_then(|id| index.get(&id)) else {
+            sans_categorie += 1;
+            continue;
+        };
+        let periode = fenetre.periode(t.ouverture);
+        let termine_fenetre = !t.est_vivant && t.sortie.is_some_and(|s| fenetre.contient(s));
+        propres[i].ajouter(t, periode, termine_fenetre);
+
+        // Agrégation vers la racine (garde-fou contre un cycle de parents)
+        let mut courant = Some(i);
+        let mut profondeur = 0;
+        while let Some(j) = courant {
+            cumules[j].ajouter(t, periode, termine_fenetre);
+            profondeur += 1;
+            if profondeur > noeuds.len() {
+                break;
+            }
+            courant = noeuds[j].parent_id.and_then(|p| index.get(&p).copied());
+        }
+    }
+
+    let mut enfants: HashMap<Option<i64>, Vec<usize>> = HashMap::new();
+    for (i, n) in noeuds.iter().enumerate() {
+        let parent = n.parent_id.filter(|p| index.contains_key(p));
+        enfants.entry(parent).or_default().push(i);
+    }
+
+    fn construire(
+        i: usize,
+        noeuds: &[NoeudCategorie],
+        enfants: &HashMap<Option<i64>, Vec<usize>>,
+        propres: &[Accumulateur],
+        cumules: &[Accumulateur],
+        jeu: &JeuTranches,
+        profondeur_max: &mut usize,
+    ) -> Option<NoeudKpi> {
+        if cumules[i].tickets == 0 {
+            return None;
+        }
+        let n = &noeuds[i];
+        *profondeur_max = (*profondeur_max).max(n.niveau);
+        let mut children: Vec<NoeudKpi> = enfants
+            .get(&Some(n.id))
+            .into_iter()
+            .flatten()
+            .filter(|&&c| c != i)
+            .filter_map(|&c| construire(c, noeuds, enfants, propres, cumules, jeu, profondeur_max))
+            .collect();
+        trier(&mut children);
+        Some(NoeudKpi {
+            id: n.id,
+            nom: n.nom.clone(),
+            chemin: n.chemin.clone(),
+            niveau: n.niveau,
+            propres: propres[i].kpi(jeu),
+            cumules: cumules[i].kpi(jeu),
+            children,
+        })
+    }
+
+    fn trier(noeuds: &mut [NoeudKpi]) {
+        noeuds.sort_by(|a, b| {
+            b.cumules
+                .tickets
+                .cmp(&a.cumules.tickets)
+                .then(a.nom.cmp(&b.nom))
+        });
+    }
+
+    let mut profondeur_max = 0;
+    let mut racines: Vec<NoeudKpi> = enfants
+        .get(&None)
+        .into_iter()
+        .flatten()
+        .filter_map(|&i| {
+            construire(
+                i,
+                noeuds,
+                &enfants,
+                &propres,
+                &cumules,
+                jeu,
+                &mut profondeur_max,
+            )
+        })
+        .collect();
+    trier(&mut racines);
+
+    CategorieHierarchie {
+        granularite: fenetre.granularite.to_string(),
+        periodes: fenetre.periodes.iter().map(|p| p.0.clone()).collect(),
+        profondeur_max,
+        total_tickets: tickets.len(),
+        sans_categorie,
+        noeuds: racines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::buckets::TranchesDistribution;
+    use crate::analyzer::temporal::generate_period_keys;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn noeud(id: i64, parent_id: Option<i64>, chemin: &str) -> NoeudCategorie {
+        let parts: Vec<&str> = chemin.split(" > ").collect();
+        NoeudCategorie {
+            id,
+            parent_id,
+            nom: parts.last().unwrap().to_string(),
+            chemin: chemin.to_string(),
+            niveau: parts.len(),
+        }
+    }
+
+    fn vivant(categorie_id: i64, ouverture: &str, age: f64) -> TicketCategorie {
+        TicketCategorie {
+            categorie_id: Some(categorie_id),
+            ouverture: dt(ouverture),
+            sortie: None,
+            est_vivant: true,
+            age_jours: Some(age),
+            delai_resolution_jours: None,
+            n1: false,
+        }
+    }
+
+    fn termine(categorie_id: i64, ouverture: &str, delai: f64, n1: bool) -> TicketCategorie {
+        let ouverture = dt(ouverture);
+        TicketCategorie {
+            categorie_id: Some(categorie_id),
+            ouverture,
+            sortie: Some(ouverture + chrono::Duration::days(delai as i64)),
+            est_vivant: false,
+            age_jours: None,
+            delai_resolution_jours: Some(delai),
+            n1,
+        }
+    }
+
+    #[test]
+    fn test_rollup_four_levels() {
+        let noeuds = vec![
+            noeud(1, None, "Matériel"),
+            noeud(2, Some(1), "Matériel > Poste"),
+            noeud(3, Some(2), "Matériel > Poste > Écran"),
+            noeud(4, Some(3), "Matériel > Poste > Écran > Dalle"),
+            noeud(5, Some(1), "Matériel > Imprimante"),
+            noeud(6, None, "Logiciel"),
+        ];
+        let tickets = vec![
+            vivant(4, "2026-01-10 09:00:00", 40.0),
+            vivant(3, "2026-02-10 09:00:00", 0.5),
+            termine(4, "2026-01-05 09:00:00", 2.0, true),
+            termine(2, "2026-02-01 09:00:00", 10.0, false),
+            termine(5, "2026-01-03 09:00:00", 4.0, true),
+            TicketCategorie {
+                categorie_id: None,
+                ..vivant(0, "2026-01-01 09:00:00", 1.0)
+            },
+        ];
+        let keys = generate_period_keys(
+            dt("2026-01-01 00:00:00"),
+            dt("2026-02-28 00:00:00"),
+            "month",
+        );
+        let fenetre = Fenetre {
+            granularite: "month",
+            periodes: &keys,
+        };
+        let h = compute_hierarchie(
+            &noeuds,
+            &tickets,
+            &fenetre,
+            &TranchesDistribution::default().anciennete(),
+        );
+
+        assert_eq!(h.profondeur_max, 4);
+        assert_eq!(h.sans_categorie, 1);
+        assert_eq!(h.periodes, vec!["2026-01", "2026-02"]);
+        // "Logiciel" n'a aucun ticket
+        assert_eq!(h.noeuds.len(), 1);
+
+        let materiel = &h.noeuds[0];
+        assert_eq!(materiel.cumules.tickets, 5);
+        assert_eq!(materiel.propres.tickets, 0);
+        assert_eq!(materiel.cumules.stock_vivant, 2);
+        assert_eq!(materiel.cumules.entrants, vec![3, 2]);
+        assert_eq!(materiel.cumules.mttr_mediane_jours, Some(4.0));
+        assert_eq!(materiel.cumules.taux_n1, Some(66.7));
+        assert_eq!(materiel.children[0].nom, "Poste");
+
+        let dalle = &materiel.children[0].children[0].children[0];
+        assert_eq!(dalle.chemin, "Matériel > Poste > Écran > Dalle");
+        assert_eq!(dalle.cumules.tickets, 2);
+        assert_eq!(dalle.cumules.distribution_age[4].count, 1);
+
+        let ecran = &materiel.children[0].children[0];
+        assert_eq!(ecran.propres.tickets, 1);
+        assert_eq!(ecran.propres.distribution_age[0].count, 1);
+        assert_eq!(ecran.cumules.tickets, 3);
+    }
+}
@@ -1,13 +1,17 @@
+use crate::analyzer::thresholds::SeuilsEffectifs;
 use crate::config::AppConfig;
+use crate::nlp::patterns::{PatternEvidence, SignalKind};
 use crate::parser::types::GlpiTicketNormalized;
 
 /// Classifie un ticket vivant et affecte action_recommandee + motif_classification.
 /// Ordre de priorité : Zombie > Ancien > Inactif > Normal.
 /// Les tickets terminés (est_vivant = false) ne sont pas modifiés.
+/// Les seuils sont ceux du groupe / technicien du ticket (surcharges de config).
 pub fn classify_ticket(ticket: &mut GlpiTicketNormalized, config: &AppConfig) {
     if !ticket.est_vivant {
         return;
     }
+    let seuils = seuils_ticket(ticket, config);
 
     // Zombie : vivant + 0 suivis (None ou 0)
     if ticket.nombre_suivis.unwrap_or(0) == 0 {
@@ -18,17 +22,17 @@ pub fn classify_ticket(ticket: &mut GlpiTicketNormalized, config: &AppConfig) {
 
     // Ancien : ancienneté dépasse le seuil de clôture
     if let Some(anciennete) = ticket.anciennete_jours {
-        if anciennete > config.seuil_anciennete_cloturer as i64 {
+        if anciennete > seuils.seuil_anciennete_cloturer as i64 {
             ticket.action_recommandee = Some("clôturer".to_string());
             ticket.motif_classification =
-                Some(format!("Ancienneté > {}j", config.seuil_anciennete_cloturer));
+                Some(format!("Ancienneté > {}j", seuils.seuil_anciennete_cloturer));
             return;
         }
     }
 
     // Inactif : inactivité dépasse le seuil de relance
     if let Some(inactivite) = ticket.inactivite_jours {
-        if inactivite > config.seuil_inactivite_relancer as i64 {
+        if inactivite > seuils.seuil_inactivite_relancer as i64 {
             ticket.action_recommandee = Some("relancer".to_string());
             ticket.motif_classification = Some(format!("Inactif depuis {}j", inactivite));
             return;
@@ -40,6 +44,50 @@ pub fn classify_ticket(ticket: &mut GlpiTicketNormalized, config: &AppConfig) {
     ticket.motif_classification = Some("En cours normal".to_string());
 }
 
+/// Affine la classification à partir des signaux détectés dans les suivis
+/// (dictionnaires de mots-clés). À appeler après `classify_ticket`.
+/// - doublon/annulé ou résolu non clos → "clôturer", le motif cite la preuve ;
+/// - relancé sans réponse → "clôturer" si l'inactivité dépasse le seuil de relance.
+///
+/// Les zombies (aucun suivi) ne sont pas concernés.
+pub fn apply_pattern_signals(
+    ticket: &mut GlpiTicketNormalized,
+    signals: &[PatternEvidence],
+    config: &AppConfig,
+) {
+    if !ticket.est_vivant || ticket.action_recommandee.as_deref() == Some("qualifier") {
+        return;
+    }
+    let seuil_relance = seuils_ticket(ticket, config).seuil_inactivite_relancer as i64;
+    // Signaux triés par priorité : le premier retenu l'emporte
+    for ev in signals {
+        let motif = match ev.kind {
+            SignalKind::DoublonAnnule | SignalKind::ResoluNonClos => format!(
+                "{} : « {} » ({})",
+                ev.kind.label(),
+                ev.keyword,
+                ev.source
+            ),
+            SignalKind::RelanceSansReponse => match ticket.inactivite_jours {
+                Some(j) if j > seuil_relance => {
+                    format!("{} depuis {}j : « {} »", ev.kind.label(), j, ev.keyword)
+                }
+                _ => continue,
+            },
+        };
+        ticket.action_recommandee = Some("clôturer".to_string());
+        ticket.motif_classification = Some(motif);
+        return;
+    }
+}
+
+fn seuils_ticket(ticket: &GlpiTicketNormalized, config: &AppConfig) -> SeuilsEffectifs {
+    config.seuils_pour(
+        ticket.groupe_principal.as_deref(),
+        ticket.technicien_principal.as_deref(),
+    )
+}
+
 /// Retourne le poids de pondération pour une priorité GLPI (libellé français).
 /// Inclut "Majeure" (non standard GLPI vanilla, présent dans l'export CPAM 92).
 #[allow(dead_code)]
@@ -62,17 +110,9 @@ mod tests {
 
     fn default_config() -> AppConfig {
         AppConfig {
-            seuil_tickets_technicien: 20,
-            seuil_anciennete_cloturer: 90,
-            seuil_inactivite_cloturer: 60,
-            seuil_anciennete_relancer: 30,
-            seuil_inactivite_relancer: 14,
-            seuil_couleur_vert: 10,
-            seuil_couleur_jaune: 20,
-            seuil_couleur_orange: 40,
-            seuil_similarite_doublons: 0.92,
             statuts_vivants: vec![],
             statuts_termines: vec![],
+            ..Default::default()
         }
     }
 
@@ -85,6 +125,7 @@ mod tests {
             priorite: None,
             priorite_label: None,
             urgence: None,
+            impact: None,
             demandeur: String::new(),
             date_ouverture: String::new(),
             derniere_modification: None,
@@ -103,6 +144,7 @@ mod tests {
             categorie: None,
             categorie_niveau1: None,
             categorie_niveau2: None,
+            categorie_chemin: vec![],
             date_resolution: None,
             est_vivant: true,
             anciennete_jours: Some(5),
@@ -165,6 +207,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_classify_seuils_surcharges() {
+        use crate::analyzer::thresholds::{PorteeSeuils, SurchargeSeuils};
+        let mut config = default_config();
+        config.surcharges_seuils = vec![SurchargeSeuils {
+            portee: PorteeSeuils::Groupe,
+            valeur: "DSI > Projets".to_string(),
+            seuil_tickets_technicien: None,
+            seuil_anciennete_cloturer: Some(180),
+            seuil_inactivite_cloturer: None,
+            seuil_anciennete_relancer: None,
+            seuil_inactivite_relancer: Some(30),
+            seuil_couleur_vert: None,
+            seuil_couleur_jaune: None,
+            seuil_couleur_orange: None,
+        }];
+        let mut ticket = base_ticket();
+        ticket.anciennete_jours = Some(120);
+        ticket.inactivite_jours = Some(20);
+
+        // Hors du groupe : seuils globaux (ancienneté > 90j)
+        classify_ticket(&mut ticket, &config);
+        assert_eq!(ticket.action_recommandee.as_deref(), Some("clôturer"));
+
+        // Dans un sous-groupe : 120j < 180j, 20j d'inactivité < 30j
+        ticket.groupe_principal = Some("DSI > Projets > ERP".to_string());
+        classify_ticket(&mut ticket, &config);
+        assert_eq!(ticket.action_recommandee.as_deref(), Some("suivre"));
+    }
+
     #[test]
     fn test_classify_normal() {
         let config = default_config();
@@ -201,6 +273,67 @@ mod tests {
         assert_eq!(ticket.action_recommandee.as_deref(), Some("qualifier")); // zombie gagne
     }
 
+    fn evidence(kind: SignalKind, keyword: &str) -> PatternEvidence {
+        PatternEvidence {
+            kind,
+            keyword: keyword.to_string(),
+            source: "suivi".to_string(),
+            extrait: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_signal_resolu_non_clos_cloturer() {
+        let config = default_config();
+        let mut ticket = base_ticket();
+        classify_ticket(&mut ticket, &config);
+        apply_pattern_signals(
+            &mut ticket,
+            &[evidence(SignalKind::ResoluNonClos, "réglé")],
+            &config,
+        );
+        assert_eq!(ticket.action_recommandee.as_deref(), Some("clôturer"));
+        assert_eq!(
+            ticket.motif_classification.as_deref(),
+            Some("Apparemment résolu mais non clos : « réglé » (suivi)")
+        );
+    }
+
+    #[test]
+    fn test_signal_relance_selon_inactivite() {
+        let config = default_config();
+        let signals = [evidence(SignalKind::RelanceSansReponse, "sans nouvelles")];
+
+        let mut recent = base_ticket();
+        recent.inactivite_jours = Some(3);
+        classify_ticket(&mut recent, &config);
+        apply_pattern_signals(&mut recent, &signals, &config);
+        assert_eq!(recent.action_recommandee.as_deref(), Some("suivre"));
+
+        let mut inactif = base_ticket();
+        inactif.inactivite_jours = Some(20);
+        classify_ticket(&mut inactif, &config);
+        apply_pattern_signals(&mut inactif, &signals, &config);
+        assert_eq!(inactif.action_recommandee.as_deref(), Some("clôturer"));
+    }
+
+    #[test]
+    fn test_signal_ignore_zombie_et_termine() {
+        let config = default_config();
+        let signals = [evidence(SignalKind::DoublonAnnule, "doublon")];
+
+        let mut zombie = base_ticket();
+        zombie.nombre_suivis = Some(0);
+        classify_ticket(&mut zombie, &config);
+        apply_pattern_signals(&mut zombie, &signals, &config);
+        assert_eq!(zombie.action_recommandee.as_deref(), Some("qualifier"));
+
+        let mut termine = base_ticket();
+        termine.est_vivant = false;
+        apply_pattern_signals(&mut termine, &signals, &config);
+        assert!(termine.action_recommandee.is_none());
+    }
+
     #[test]
     fn test_poids_priorite() {
         assert_eq!(poids_priorite("Très haute"), 5);
@@ -0,0 +1,283 @@
+//! Analyse de cohortes : tickets regroupés par période d'ouverture, part
+//! résolue après 1, 7, 30 et 90 jours et part encore ouverte à la date de
+//! l'import.
+//!
+//! Une cohorte trop récente n'a pas encore atteint tous les horizons : le
+//! taux à un horizon n'est calculé que sur les tickets ouverts depuis au
+//! moins ce nombre de jours (`observables`), `None` si aucun ne l'est.
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::stats::pct;
+use super::temporal::{generate_period_keys, period_key};
+
+pub const HORIZONS_JOURS: [i64; 4] = [1, 7, 30, 90];
+/// Modalités détaillées par dimension ; les suivantes sont regroupées.
+const MAX_MODALITES: usize = 10;
+const AUTRES: &str = "Autres";
+const NON_RENSEIGNE: &str = "(non renseigné)";
+
+#[derive(Debug, Clone)]
+pub struct TicketCohorte {
+    pub ouverture: NaiveDateTime,
+    /// Résolution (à défaut clôture) ; `None` si le ticket est vivant.
+    pub sortie: Option<NaiveDateTime>,
+    pub dimension: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohorteAnalyse {
+    pub granularite: String,
+    pub dimension: Option<String>,
+    pub date_reference: String,
+    pub horizons: Vec<i64>,
+    pub cohortes: Vec<CohorteLigne>,
+    pub par_dimension: Vec<CohorteDimension>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohorteDimension {
+    pub label: String,
+    pub effectif: usize,
+    pub cohortes: Vec<CohorteLigne>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohorteLigne {
+    pub period_key: String,
+    pub period_label: String,
+    pub effectif: usize,
+    pub horizons: Vec<CohorteHorizon>,
+    pub encore_ouverts: usize,
+    pub taux_encore_ouverts: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohorteHorizon {
+    pub jours: i64,
+    /// Tickets ouverts depuis au moins `jours` à la date de référence.
+    pub observables: usize,
+    pub resolus: usize,
+    pub taux_resolus: Option<f64>,
+}
+
+fn ligne(
+    key: &str,
+    label: &str,
+    tickets: &[&TicketCohorte],
+    reference: NaiveDateTime,
+) -> CohorteLigne {
+    let horizons = HORIZONS_JOURS
+        .iter()
+        .map(|&jours| {
+            let horizon = Duration::days(jours);
+            let observables: Vec<&&TicketCohorte> = tickets
+                .iter()
+                .filter(|t| reference - t.ouverture >= horizon)
+                .collect();
+            let resolus = observables
+                .iter()
+                .filter(|t| t.sortie.is_some_and(|s| s - t.ouverture <= horizon))
+                .count();
+            CohorteHorizon {
+                jours,
+                observables: observables.len(),
+                resolus,
+                taux_resolus: (!observables.is_empty()).then(|| pct(resolus, observables.len())),
+            }
+        })
+        .collect();
+    let encore_ouverts = tickets
+        .iter()
+        .filter(|t| t.sortie.is_none_or(|s| s > reference))
+        .count();
+    CohorteLigne {
+        period_key: key.to_string(),
+        period_label: label.to_string(),
+        effectif: tickets.len(),
+        horizons,
+        encore_ouverts,
+        taux_encore_ouverts: pct(encore_ouverts, tickets.len()),
+    }
+}
+
+fn lignes(
+    tickets: &[&TicketCohorte],
+    keys: &[(String, String, NaiveDateTime, NaiveDateTime)],
+    granularity: &str,
+    reference: NaiveDateTime,
+) -> Vec<CohorteLigne> {
+    let mut par_periode: HashMap<String, Vec<&TicketCohorte>> = HashMap::new();
+    for t in tickets {
+        par_periode
+            .entry(period_key(t.ouverture, granularity))
+            .or_default()
+            .push(t);
+    }
+    keys.iter()
+        .map(|(key, label, _, _)| {
+            let groupe = par_periode.get(key).map(Vec::as_slice).unwrap_or_default();
+            ligne(key, label, groupe, reference)
+        })
+        .collect()
+}
+
+/// Cohortes des tickets ouverts entre `date_from` et `date_to`, observées
+/// à la date `reference`.
+pub fn compute_cohortes(
+    tickets: &[TicketCohorte],
+    date_from: NaiveDateTime,
+    date_to: NaiveDateTime,
+    granularity: &str,
+    dimension: Option<&str>,
+    reference: NaiveDateTime,
+) -> CohorteAnalyse {
+    let keys = generate_period_keys(date_from, date_to, granularity);
+    let dans_fenetre: Vec<&TicketCohorte> = tickets
+        .iter()
+        .filter(|t| t.ouverture.date() >= date_from.date() && t.ouverture.date() <= date_to.date())
+        .collect();
+
+    let mut par_dimension = Vec::new();
+    if dimension.is_some() {
+        let mut groupes: HashMap<&str, Vec<&TicketCohorte>> = HashMap::new();
+        for t in &dans_fenetre {
+            let label = t
+                .dimension
+                .as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .unwrap_or(NON_RENSEIGNE);
+            groupes.entry(label).or_default().push(t);
+        }
+        let mut groupes: Vec<(&str, Vec<&TicketCohorte>)> = groupes.into_iter().collect();
+        groupes.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then(a.0.cmp(b.0)));
+        if groupes.len() > MAX_MODALITES {
+            let reste: Vec<&TicketCohorte> = groupes
+                .drain(MAX_MODALITES - 1..)
+                .flat_map(|(_, g)| g)
+                .collect();
+            groupes.push((AUTRES, reste));
+        }
+        par_dimension = groupes
+            .into_iter()
+            .map(|(label, groupe)| CohorteDimension {
+                label: label.to_string(),
+                effectif: groupe.len(),
+                cohortes: lignes(&groupe, &keys, granularity, reference),
+            })
+            .collect();
+    }
+
+    CohorteAnalyse {
+        granularite: granularity.to_string(),
+        dimension: dimension.map(str::to_string),
+        date_reference: reference.format("%Y-%m-%d").to_string(),
+        horizons: HORIZONS_JOURS.to_vec(),
+        cohortes: lignes(&dans_fenetre, &keys, granularity, reference),
+        par_dimension,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn ticket(ouverture: &str, sortie: Option<&str>, dimension: &str) -> TicketCohorte {
+        TicketCohorte {
+            ouverture: dt(ouverture),
+            sortie: sortie.map(dt),
+            dimension: Some(dimension.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_monthly_cohorts() {
+        let tickets = vec![
+            ticket(
+                "2025-01-05 09:00:00",
+                Some("2025-01-05 15:00:00"),
+                "Incident",
+            ),
+            ticket(
+                "2025-01-10 09:00:00",
+                Some("2025-01-15 09:00:00"),
+                "Incident",
+            ),
+            ticket(
+                "2025-01-20 09:00:00",
+                Some("2025-03-01 09:00:00"),
+                "Demande",
+            ),
+            ticket("2025-01-25 09:00:00", None, "Demande"),
+            ticket(
+                "2025-03-25 09:00:00",
+                Some("2025-03-25 10:00:00"),
+                "Incident",
+            ),
+            // Hors fenêtre
+            ticket("2024-12-31 09:00:00", None, "Incident"),
+        ];
+        let a = compute_cohortes(
+            &tickets,
+            dt("2025-01-01 00:00:00"),
+            dt("2025-03-31 00:00:00"),
+            "month",
+            Some("type"),
+            dt("2025-04-15 00:00:00"),
+        );
+        assert_eq!(a.cohortes.len(), 3);
+        let jan = &a.cohortes[0];
+        assert_eq!(jan.period_key, "2025-01");
+        assert_eq!(jan.effectif, 4);
+        let taux: Vec<Option<f64>> = jan.horizons.iter().map(|h| h.taux_resolus).collect();
+        assert_eq!(taux, vec![Some(25.0), Some(50.0), Some(50.0), Some(100.0)]);
+        // À 90 jours, seuls les tickets ouverts avant le 15/01 sont observables
+        assert_eq!(jan.horizons[3].observables, 2);
+        assert_eq!(jan.encore_ouverts, 1);
+        assert_eq!(jan.taux_encore_ouverts, 25.0);
+
+        // Février vide, mars trop récent pour 30 jours
+        assert_eq!(a.cohortes[1].effectif, 0);
+        assert_eq!(a.cohortes[1].horizons[0].taux_resolus, None);
+        assert_eq!(a.cohortes[2].horizons[2].taux_resolus, None);
+        assert_eq!(a.cohortes[2].horizons[1].taux_resolus, Some(100.0));
+
+        let incidents = a
+            .par_dimension
+            .iter()
+            .find(|d| d.label == "Incident")
+            .unwrap();
+        assert_eq!(incidents.effectif, 3);
+        assert_eq!(incidents.cohortes[0].horizons[1].taux_resolus, Some(100.0));
+    }
+
+    #[test]
+    fn test_overflow_grouped() {
+        let tickets: Vec<TicketCohorte> = (0..12)
+            .map(|i| ticket("2025-01-05 09:00:00", None, &format!("G{i:02}")))
+            .collect();
+        let a = compute_cohortes(
+            &tickets,
+            dt("2025-01-01 00:00:00"),
+            dt("2025-01-31 00:00:00"),
+            "month",
+            Some("groupe"),
+            dt("2025-02-01 00:00:00"),
+        );
+        assert_eq!(a.par_dimension.len(), MAX_MODALITES);
+        assert_eq!(a.par_dimension.last().unwrap().label, AUTRES);
+        assert_eq!(a.par_dimension.last().unwrap().effectif, 3);
+    }
+}
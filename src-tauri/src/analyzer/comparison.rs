@@ -0,0 +1,572 @@
+//! Comparaison de deux fenêtres de dates (revue mensuelle, N vs N-1).
+//!
+//! Chaque indicateur est restitué avec ses écarts absolu et relatif et une
+//! indication de significativité : test de Poisson pour les volumes, test de
+//! Welch pour les moyennes, test z à deux proportions pour les taux. Ce n'est
+//! qu'un repère de lecture : les tickets d'une même fenêtre ne sont pas
+//! indépendants et les fenêtres personnalisées peuvent différer en durée.
+
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::dashboard::DashboardKpi;
+use super::stats::{round1, round2};
+
+const Z_SIGNIFICATIF: f64 = 1.96;
+const Z_FORT: f64 = 2.58;
+/// Écart relatif (en %) sous lequel la tendance est dite stable.
+const SEUIL_STABLE_PCT: f64 = 1.0;
+
+/// Choix de la fenêtre de référence : "periode_precedente" (même durée,
+/// juste avant), "annee_precedente" (mêmes dates, un an plus tôt) ou
+/// "personnalisee" (bornes `date_debut` / `date_fin`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparaisonRequest {
+    pub reference: String,
+    pub date_debut: Option<String>,
+    pub date_fin: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fenetre {
+    pub date_debut: String,
+    pub date_fin: String,
+}
+
+impl Fenetre {
+    fn new(debut: NaiveDate, fin: NaiveDate) -> Self {
+        Self {
+            date_debut: debut.format("%Y-%m-%d").to_string(),
+            date_fin: fin.format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KpiDelta {
+    pub cle: String,
+    pub libelle: String,
+    pub courant: Option<f64>,
+    pub reference: Option<f64>,
+    pub delta_absolu: Option<f64>,
+    /// En % de la valeur de référence ; None si la référence est nulle.
+    pub delta_relatif_pct: Option<f64>,
+    /// "hausse", "baisse" ou "stable".
+    pub tendance: Option<String>,
+    /// None pour un indicateur sans sens favorable (volumes entrants…).
+    pub favorable: Option<bool>,
+    pub z_score: Option<f64>,
+    /// "forte", "significative", "non_significative" ou "indeterminee".
+    pub significativite: String,
+}
+
+/// Information de dispersion disponible pour le test de significativité.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dispersion {
+    /// Volume d'événements (loi de Poisson).
+    Comptage,
+    Moyenne {
+        ecart_type: f64,
+        n: i64,
+    },
+    /// Taux en pourcentage sur `n` tickets.
+    Proportion {
+        n: i64,
+    },
+    Aucune,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mesure {
+    pub valeur: Option<f64>,
+    pub dispersion: Dispersion,
+}
+
+impl Mesure {
+    pub fn comptage(valeur: f64) -> Self {
+        Self {
+            valeur: Some(valeur),
+            dispersion: Dispersion::Comptage,
+        }
+    }
+
+    pub fn moyenne(valeur: f64, ecart_type: f64, n: i64) -> Self {
+        Self {
+            valeur: Some(valeur),
+            dispersion: Dispersion::Moyenne { ecart_type, n },
+        }
+    }
+
+    pub fn proportion(pct: f64, n: i64) -> Self {
+        Self {
+            valeur: Some(pct),
+            dispersion: Dispersion::Proportion { n },
+        }
+    }
+
+    pub fn simple(valeur: Option<f64>) -> Self {
+        Self {
+            valeur,
+            dispersion: Dispersion::Aucune,
+        }
+    }
+}
+
+/// Sens d'évolution souhaité d'un indicateur.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sens {
+    Hausse,
+    Baisse,
+    Neutre,
+}
+
+/// Fenêtre de référence associée à la fenêtre courante [debut, fin].
+pub fn fenetre_reference(
+    debut: NaiveDate,
+    fin: NaiveDate,
+    request: &ComparaisonRequest,
+) -> Result<(NaiveDate, NaiveDate), String> {
+    if fin < debut {
+        return Err("La date de fin précède la date de début".to_string());
+    }
+    match request.reference.as_str() {
+        "periode_precedente" => {
+            let duree = fin - debut;
+            let ref_fin = debut.pred_opt().ok_or("Date hors limites")?;
+            Ok((ref_fin - duree, ref_fin))
+        }
+        "annee_precedente" => {
+            // 29 février ramené au 28
+            let recul = |d: NaiveDate| {
+                d.checked_sub_months(Months::new(12))
+                    .ok_or("Date hors limites")
+            };
+            Ok((recul(debut)?, recul(fin)?))
+        }
+        "personnalisee" => {
+            let parse = |s: &Option<String>, champ: &str| {
+                s.as_deref()
+                    .and_then(|s| {
+                        NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d").ok()
+                    })
+                    .ok_or_else(|| format!("Date de référence invalide ({champ})"))
+            };
+            let (d, f) = (
+                parse(&request.date_debut, "début")?,
+                parse(&request.date_fin, "fin")?,
+            );
+            if f < d {
+                return Err("La date de fin de référence précède la date de début".to_string());
+            }
+            Ok((d, f))
+        }
+        autre => Err(format!("Mode de comparaison inconnu: {autre}")),
+    }
+}
+
+/// Fenêtres courante et de référence au format de l'IPC.
+pub fn fenetres(
+    debut: NaiveDate,
+    fin: NaiveDate,
+    request: &ComparaisonRequest,
+) -> Result<(Fenetre, Fenetre), String> {
+    let (ref_debut, ref_fin) = fenetre_reference(debut, fin, request)?;
+    Ok((Fenetre::new(debut, fin), Fenetre::new(ref_debut, ref_fin)))
+}
+
+fn z_score(courant: f64, reference: f64, c: Dispersion, r: Dispersion) -> Option<f64> {
+    let z = match (c, r) {
+        (Dispersion::Comptage, Dispersion::Comptage) => {
+            let somme = courant + reference;
+            if somme <= 0.0 {
+                return None;
+            }
+            (courant - reference) / somme.sqrt()
+        }
+        (
+            Dispersion::Moyenne {
+                ecart_type: s1,
+                n: n1,
+            },
+            Dispersion::Moyenne {
+                ecart_type: s2,
+                n: n2,
+            },
+        ) => {
+            if n1 < 2 || n2 < 2 {
+                return None;
+            }
+            let se = (s1 * s1 / n1 as f64 + s2 * s2 / n2 as f64).sqrt();
+            if se <= 0.0 {
+                return None;
+            }
+            (courant - reference) / se
+        }
+        (Dispersion::Proportion { n: n1 }, Dispersion::Proportion { n: n2 }) => {
+            if n1 == 0 || n2 == 0 {
+                return None;
+            }
+            let (p1, p2) = (courant / 100.0, reference / 100.0);
+            let p = (p1 * n1 as f64 + p2 * n2 as f64) / (n1 + n2) as f64;
+            let se = (p * (1.0 - p) * (1.0 / n1 as f64 + 1.0 / n2 as f64)).sqrt();
+            if se <= 0.0 {
+                return None;
+            }
+            (p1 - p2) / se
+        }
+        _ => return None,
+    };
+    Some(z)
+}
+
+/// Écarts et significativité d'un indicateur entre les deux fenêtres.
+pub fn comparer(
+    cle: &str,
+    libelle: &str,
+    courant: Mesure,
+    reference: Mesure,
+    sens: Sens,
+) -> KpiDelta {
+    let mut delta = KpiDelta {
+        cle: cle.to_string(),
+        libelle: libelle.to_string(),
+        courant: courant.valeur,
+        reference: reference.valeur,
+        delta_absolu: None,
+        delta_relatif_pct: None,
+        tendance: None,
+        favorable: None,
+        z_score: None,
+        significativite: "indeterminee".to_string(),
+    };
+    let (Some(c), Some(r)) = (courant.valeur, reference.valeur) else {
+        return delta;
+    };
+
+    let ecart = c - r;
+    delta.delta_absolu = Some(round2(ecart));
+    delta.delta_relatif_pct = (r != 0.0).then(|| round1(ecart / r.abs() * 100.0));
+    let stable = match delta.delta_relatif_pct {
+        Some(pct) => pct.abs() < SEUIL_STABLE_PCT,
+        None => ecart == 0.0,
+    };
+    let tendance = if stable {
+        "stable"
+    } else if ecart > 0.0 {
+        "hausse"
+    } else {
+        "baisse"
+    };
+    delta.tendance = Some(tendance.to_string());
+    delta.favorable = match (sens, tendance) {
+        (Sens::Neutre, _) | (_, "stable") => None,
+        (Sens::Hausse, t) => Some(t == "hausse"),
+        (Sens::Baisse, t) => Some(t == "baisse"),
+    };
+
+    if let Some(z) = z_score(c, r, courant.dispersion, reference.dispersion) {
+        delta.z_score = Some(round2(z));
+        delta.significativite = if z.abs() >= Z_FORT {
+            "forte"
+        } else if z.abs() >= Z_SIGNIFICATIF {
+            "significative"
+        } else {
+            "non_significative"
+        }
+        .to_string();
+    }
+    delta
+}
+
+/// Écarts de tous les indicateurs scalaires du tableau de bord.
+pub fn deltas_dashboard(c: &DashboardKpi, r: &DashboardKpi) -> Vec<KpiDelta> {
+    use Sens::*;
+    let mut deltas = Vec::new();
+    let mut ajouter =
+        |cle: &str, libelle: &str, f: &dyn Fn(&DashboardKpi) -> Mesure, sens: Sens| {
+            deltas.push(comparer(cle, libelle, f(c), f(r), sens));
+        };
+
+    ajouter(
+        "totalTickets",
+        "Tickets ouverts sur la période",
+        &|k| Mesure::comptage(k.meta.total_tickets as f64),
+        Neutre,
+    );
+    ajouter(
+        "totalVivants",
+        "Tickets encore vivants",
+        &|k| Mesure::comptage(k.meta.total_vivants as f64),
+        Baisse,
+    );
+    ajouter(
+        "totalTermines",
+        "Tickets terminés",
+        &|k| Mesure::comptage(k.meta.total_termines as f64),
+        Hausse,
+    );
+    ajouter(
+        "totalCrees",
+        "Tickets créés",
+        &|k| Mesure::comptage(k.volumes.total_crees as f64),
+        Neutre,
+    );
+    ajouter(
+        "totalResolus",
+        "Tickets résolus",
+        &|k| Mesure::comptage(k.volumes.total_resolus as f64),
+        Hausse,
+    );
+    ajouter(
+        "ratioSortieEntree",
+        "Ratio sorties / entrées",
+        &|k| Mesure::simple(Some(k.volumes.ratio_sortie_entree)),
+        Hausse,
+    );
+    ajouter(
+        "stockDebut",
+        "Stock en début de période",
+        &|k| Mesure::simple(Some(k.volumes.stock_debut as f64)),
+        Baisse,
+    );
+    ajouter(
+        "priseEnChargeMoyenne",
+        "Délai moyen de prise en charge (j)",
+        &|k| Mesure::simple(k.prise_en_charge.delai_moyen_jours),
+        Baisse,
+    );
+    ajouter(
+        "priseEnChargeMediane",
+        "Délai médian de prise en charge (j)",
+        &|k| Mesure::simple(k.prise_en_charge.mediane_jours),
+        Baisse,
+    );
+    ajouter(
+        "priseEnChargeP90",
+        "P90 de prise en charge (j)",
+        &|k| Mesure::simple(k.prise_en_charge.p90_jours),
+        Baisse,
+    );
+    ajouter(
+        "mttr",
+        "MTTR (j)",
+        &|k| {
+            Mesure::moyenne(
+                k.resolution.mttr_global_jours,
+                k.resolution.ecart_type_jours,
+                k.resolution.echantillon,
+            )
+        },
+        Baisse,
+    );
+    ajouter(
+        "mttrMediane",
+        "Délai médian de résolution (j)",
+        &|k| Mesure::simple(Some(k.resolution.mediane_jours)),
+        Baisse,
+    );
+    ajouter(
+        "mttrP90",
+        "P90 de résolution (j)",
+        &|k| Mesure::simple(Some(k.resolution.p90_jours)),
+        Baisse,
+    );
+    ajouter(
+        "mttrOuvres",
+        "MTTR (j ouvrés)",
+        &|k| Mesure::simple(Some(k.resolution.mttr_global_jours_ouvres)),
+        Baisse,
+    );
+    ajouter(
+        "mttrMedianeOuvres",
+        "Délai médian de résolution (j ouvrés)",
+        &|k| Mesure::simple(Some(k.resolution.mediane_jours_ouvres)),
+        Baisse,
+    );
+    ajouter(
+        "survieMediane",
+        "Médiane de survie Kaplan–Meier (j)",
+        &|k| Mesure::simple(k.survie.mediane.jours),
+        Baisse,
+    );
+    ajouter(
+        "tauxN1Strict",
+        "Taux N1 strict (%)",
+        &|k| Mesure::proportion(k.taux_n1.n1_strict.pourcentage, k.taux_n1.total_termines),
+        Hausse,
+    );
+    ajouter(
+        "tauxN1Elargi",
+        "Taux N1 élargi (%)",
+        &|k| Mesure::proportion(k.taux_n1.n1_elargi.pourcentage, k.taux_n1.total_termines),
+        Hausse,
+    );
+    ajouter(
+        "tauxMultiNiveaux",
+        "Taux multi-niveaux (%)",
+        &|k| {
+            Mesure::proportion(
+                k.taux_n1.multi_niveaux.pourcentage,
+                k.taux_n1.total_termines,
+            )
+        },
+        Baisse,
+    );
+    ajouter(
+        "slaRespect",
+        "Respect SLA résolution (%)",
+        &|k| {
+            Mesure::proportion(
+                k.sla.resolution.taux_respect,
+                k.sla.resolution.evalues as i64,
+            )
+        },
+        Hausse,
+    );
+    deltas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn req(reference: &str) -> ComparaisonRequest {
+        ComparaisonRequest {
+            reference: reference.to_string(),
+            date_debut: None,
+            date_fin: None,
+        }
+    }
+
+    #[test]
+    fn test_reference_windows() {
+        let (debut, fin) = (d("2025-03-01"), d("2025-03-31"));
+        assert_eq!(
+            fenetre_reference(debut, fin, &req("periode_precedente")).unwrap(),
+            (d("2025-01-29"), d("2025-02-28"))
+        );
+        assert_eq!(
+            fenetre_reference(debut, fin, &req("annee_precedente")).unwrap(),
+            (d("2024-03-01"), d("2024-03-31"))
+        );
+        assert_eq!(
+            fenetre_reference(d("2024-02-01"), d("2024-02-29"), &req("annee_precedente")).unwrap(),
+            (d("2023-02-01"), d("2023-02-28"))
+        );
+
+        let mut custom = req("personnalisee");
+        assert!(fenetre_reference(debut, fin, &custom).is_err());
+        custom.date_debut = Some("2024-11-01".into());
+        custom.date_fin = Some("2024-11-30T00:00:00".into());
+        assert_eq!(
+            fenetre_reference(debut, fin, &custom).unwrap(),
+            (d("2024-11-01"), d("2024-11-30"))
+        );
+
+        assert!(fenetre_reference(debut, fin, &req("inconnu")).is_err());
+        assert!(fenetre_reference(fin, debut, &req("periode_precedente")).is_err());
+    }
+
+    #[test]
+    fn test_count_delta_significance() {
+        let delta = comparer(
+            "x",
+            "X",
+            Mesure::comptage(150.0),
+            Mesure::comptage(100.0),
+            Sens::Baisse,
+        );
+        assert_eq!(delta.delta_absolu, Some(50.0));
+        assert_eq!(delta.delta_relatif_pct, Some(50.0));
+        assert_eq!(delta.tendance.as_deref(), Some("hausse"));
+        assert_eq!(delta.favorable, Some(false));
+        // z = 50 / √250 ≈ 3.16
+        assert_eq!(delta.z_score, Some(3.16));
+        assert_eq!(delta.significativite, "forte");
+
+        let faible = comparer(
+            "x",
+            "X",
+            Mesure::comptage(105.0),
+            Mesure::comptage(100.0),
+            Sens::Neutre,
+        );
+        assert_eq!(faible.significativite, "non_significative");
+        assert_eq!(faible.favorable, None);
+    }
+
+    #[test]
+    fn test_mean_and_proportion_tests() {
+        // Welch : (8 - 10) / √(4²/100 + 4²/100) ≈ -3.54
+        let mttr = comparer(
+            "m",
+            "M",
+            Mesure::moyenne(8.0, 4.0, 100),
+            Mesure::moyenne(10.0, 4.0, 100),
+            Sens::Baisse,
+        );
+        assert_eq!(mttr.favorable, Some(true));
+        assert_eq!(mttr.significativite, "forte");
+
+        // 60 % vs 50 % sur 200 tickets chacun : z ≈ 2.01
+        let taux = comparer(
+            "t",
+            "T",
+            Mesure::proportion(60.0, 200),
+            Mesure::proportion(50.0, 200),
+            Sens::Hausse,
+        );
+        assert_eq!(taux.significativite, "significative");
+        assert_eq!(taux.favorable, Some(true));
+
+        let petit = comparer(
+            "m",
+            "M",
+            Mesure::moyenne(8.0, 4.0, 1),
+            Mesure::moyenne(10.0, 4.0, 50),
+            Sens::Baisse,
+        );
+        assert_eq!(petit.significativite, "indeterminee");
+    }
+
+    #[test]
+    fn test_missing_and_zero_reference() {
+        let absent = comparer(
+            "p",
+            "P",
+            Mesure::simple(Some(2.0)),
+            Mesure::simple(None),
+            Sens::Baisse,
+        );
+        assert_eq!(absent.delta_absolu, None);
+        assert_eq!(absent.tendance, None);
+
+        let zero = comparer(
+            "c",
+            "C",
+            Mesure::comptage(3.0),
+            Mesure::comptage(0.0),
+            Sens::Neutre,
+        );
+        assert_eq!(zero.delta_relatif_pct, None);
+        assert_eq!(zero.tendance.as_deref(), Some("hausse"));
+
+        let stable = comparer(
+            "c",
+            "C",
+            Mesure::simple(Some(100.5)),
+            Mesure::simple(Some(100.0)),
+            Sens::Hausse,
+        );
+        assert_eq!(stable.tendance.as_deref(), Some("stable"));
+        assert_eq!(stable.favorable, None);
+        assert_eq!(stable.significativite, "indeterminee");
+    }
+}
@@ -3,27 +3,41 @@ use std::collections::BTreeMap;
 use std::time::Instant;
 
 use rusqlite::Connection;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[cfg(test)]
 use rusqlite::params;
 
-use super::stats::{ecart_type, moyenne, percentile};
+use super::buckets::JeuTranches;
+use super::calendar::CalendrierCompile;
+use super::priority::{load_priorite_kpi, PrioriteKpi};
+use super::reopen::{load_qualite_kpi, QualiteResolutionKpi};
+use super::spc::{carte_individuelle, carte_p, SpcKpi};
+use super::sla::{load_sla_kpi, SlaKpi, HORIZON_DEFAUT_JOURS, MAX_TICKETS_LISTE};
+use super::stats::{ecart_type, moyenne, pct, percentile, round1};
+use super::survival::{compute_survie, Observation, SurvieKpi, TicketSurvie};
 
 // ─── Data Structures ─────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DashboardKpi {
     pub meta: DashboardMeta,
     pub prise_en_charge: PriseEnChargeKpi,
     pub resolution: ResolutionKpi,
+    /// Kaplan–Meier : tickets vivants censurés à leur âge courant.
+    pub survie: SurvieKpi,
     pub taux_n1: TauxN1Kpi,
+    /// Réouvertures et FCR, sur la date de résolution.
+    pub qualite: QualiteResolutionKpi,
     pub volumes: VolumetrieKpi,
     pub typologie: TypologieKpi,
+    pub sla: SlaKpi,
+    /// Matrice urgence × impact, cohérence et dérive des priorités.
+    pub priorites: PrioriteKpi,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DashboardMeta {
     pub total_tickets: i64,
@@ -36,7 +50,7 @@ pub struct DashboardMeta {
     pub calcul_duration_ms: u64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PriseEnChargeKpi {
     pub methode: String,
@@ -44,17 +58,23 @@ pub struct PriseEnChargeKpi {
     pub delai_moyen_jours: Option<f64>,
     pub mediane_jours: Option<f64>,
     pub p90_jours: Option<f64>,
+    pub delai_moyen_jours_ouvres: Option<f64>,
+    pub mediane_jours_ouvres: Option<f64>,
+    pub p90_jours_ouvres: Option<f64>,
     pub distribution: Vec<TrancheDelai>,
     pub avertissement: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResolutionKpi {
     pub mttr_global_jours: f64,
     pub mediane_jours: f64,
     pub p90_jours: f64,
     pub ecart_type_jours: f64,
+    pub mttr_global_jours_ouvres: f64,
+    pub mediane_jours_ouvres: f64,
+    pub p90_jours_ouvres: f64,
     pub par_type: Vec<MttrParDimension>,
     pub par_priorite: Vec<MttrParDimension>,
     pub par_groupe: Vec<MttrParDimension>,
@@ -65,7 +85,7 @@ pub struct ResolutionKpi {
     pub echantillon: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResolutionSpeedTrend {
     pub periode: String,
@@ -74,7 +94,7 @@ pub struct ResolutionSpeedTrend {
     pub total_resolus: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TauxN1Kpi {
     pub total_termines: i64,
@@ -87,7 +107,7 @@ pub struct TauxN1Kpi {
     pub objectif_itil: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VolumetrieKpi {
     pub par_mois: Vec<VolumePeriode>,
@@ -98,7 +118,7 @@ pub struct VolumetrieKpi {
     pub stock_debut: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TypologieKpi {
     pub par_type: Vec<VentilationItem>,
@@ -110,7 +130,7 @@ pub struct TypologieKpi {
 
 // ─── Sub-types ───────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TrancheDelai {
     pub label: String,
@@ -118,33 +138,37 @@ pub struct TrancheDelai {
     pub pourcentage: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MttrParDimension {
     pub label: String,
     pub mttr_jours: f64,
     pub mediane_jours: f64,
+    pub mttr_jours_ouvres: f64,
+    pub mediane_jours_ouvres: f64,
     pub count: i64,
     pub pourcentage_total: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MttrTrend {
     pub periode: String,
     pub mttr_jours: f64,
     pub mediane_jours: f64,
+    pub mttr_jours_ouvres: f64,
+    pub mediane_jours_ouvres: f64,
     pub nb_resolus: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TauxDetail {
     pub count: i64,
     pub pourcentage: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TauxN1ParGroupe {
     pub groupe: String,
@@ -155,16 +179,17 @@ pub struct TauxN1ParGroupe {
     pub n1_elargi_pct: f64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TauxN1Trend {
     pub periode: String,
+    pub n1_strict_count: i64,
     pub n1_strict_pct: f64,
     pub n1_elargi_pct: f64,
     pub total_resolus: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VolumePeriode {
     pub periode: String,
@@ -173,7 +198,7 @@ pub struct VolumePeriode {
     pub delta: i64,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VentilationItem {
     pub label: String,
@@ -185,24 +210,12 @@ pub struct VentilationItem {
 
 // ─── Helpers ─────────────────────────────────────────────────────────────────
 
-fn round1(v: f64) -> f64 {
-    (v * 10.0).round() / 10.0
-}
-
-fn pct(count: i64, total: i64) -> f64 {
-    if total == 0 {
-        0.0
-    } else {
-        round1(count as f64 / total as f64 * 100.0)
-    }
-}
-
 /// Returns a SQL expression that buckets a date column into period keys
 /// matching the granularity (day, week, month, quarter).
 fn period_expr(granularity: &str, date_col: &str) -> String {
     match granularity {
         "day" => format!("strftime('%Y-%m-%d', {date_col})"),
-        "week" => format!("strftime('%Y-W%W', {date_col})"),
+        "week" => format!("strftime('%G-W%V', {date_col})"),
         "quarter" => format!(
             "(strftime('%Y', {date_col}) || '-Q' || ((CAST(strftime('%m', {date_col}) AS INTEGER) - 1) / 3 + 1))"
         ),
@@ -247,6 +260,11 @@ fn date_filter_clause(
     date_filter_clause_on("date_ouverture", date_debut, date_fin)
 }
 
+/// Délai en jours ouvrés entre deux colonnes date lues en texte.
+fn jours_ouvres(cal: &CalendrierCompile, debut: Option<String>, fin: Option<String>) -> Option<f64> {
+    cal.jours_ouvres_str(debut.as_deref()?, fin.as_deref()?)
+}
+
 /// Safe json_array_length expression that handles empty string, '[]', and NULL.
 const SAFE_JSON_LEN: &str =
     "CASE WHEN techniciens = '' OR techniciens = '[]' OR techniciens IS NULL THEN 0 ELSE json_array_length(techniciens) END";
@@ -369,13 +387,16 @@ fn build_meta(
 
 fn build_prise_en_charge(
     conn: &Connection,
+    cal: &CalendrierCompile,
     import_id: i64,
     date_clause: &str,
     date_params: &[String],
+    jeu: &JeuTranches,
 ) -> Result<PriseEnChargeKpi, rusqlite::Error> {
     // Collect proxy delays for terminated tickets
     let sql = format!(
-        "SELECT julianday(derniere_modification) - julianday(date_ouverture)
+        "SELECT julianday(derniere_modification) - julianday(date_ouverture),
+                date_ouverture, derniere_modification
          FROM tickets
          WHERE import_id = ? AND est_vivant = 0
            AND derniere_modification IS NOT NULL AND date_ouverture IS NOT NULL{}",
@@ -388,14 +409,16 @@ fn build_prise_en_charge(
     let mut stmt = conn.prepare(&sql)?;
     let rows = stmt.query_map(
         rusqlite::params_from_iter(all_params.iter().map(|b| b.as_ref())),
-        |row| row.get::<_, f64>(0),
+        |row| Ok((row.get::<_, f64>(0)?, jours_ouvres(cal, row.get(1)?, row.get(2)?))),
     )?;
 
     let mut delays: Vec<f64> = Vec::new();
+    let mut delays_ouvres: Vec<f64> = Vec::new();
     for row in rows {
-        let v = row?;
+        let (v, ouvre) = row?;
         if v >= 0.0 {
             delays.push(v);
+            delays_ouvres.extend(ouvre);
         }
     }
 
@@ -406,7 +429,10 @@ fn build_prise_en_charge(
             delai_moyen_jours: None,
             mediane_jours: None,
             p90_jours: None,
-            distribution: build_pec_distribution(&[], 0),
+            delai_moyen_jours_ouvres: None,
+            mediane_jours_ouvres: None,
+            p90_jours_ouvres: None,
+            distribution: build_pec_distribution(&[], 0, jeu),
             avertissement: Some(
                 "Aucun ticket terminé avec dates valides pour calculer le délai de prise en charge."
                     .to_string(),
@@ -419,7 +445,7 @@ fn build_prise_en_charge(
     let p90 = round1(percentile(&delays, 90.0));
 
     // Distribution by tranches
-    let distribution = build_pec_distribution(&delays, delays.len() as i64);
+    let distribution = build_pec_distribution(&delays, delays.len() as i64, jeu);
 
     Ok(PriseEnChargeKpi {
         methode: "proxy_derniere_modification".to_string(),
@@ -427,6 +453,10 @@ fn build_prise_en_charge(
         delai_moyen_jours: Some(mean),
         mediane_jours: Some(med),
         p90_jours: Some(p90),
+        delai_moyen_jours_ouvres: (!delays_ouvres.is_empty()).then(|| round1(moyenne(&delays_ouvres))),
+        mediane_jours_ouvres: (!delays_ouvres.is_empty())
+            .then(|| round1(percentile(&delays_ouvres, 50.0))),
+        p90_jours_ouvres: (!delays_ouvres.is_empty()).then(|| round1(percentile(&delays_ouvres, 90.0))),
         distribution,
         avertissement: Some(
             "Approximation : le délai est calculé entre date_ouverture et derniere_modification. \
@@ -436,46 +466,32 @@ fn build_prise_en_charge(
     })
 }
 
-fn build_pec_distribution(delays: &[f64], total: i64) -> Vec<TrancheDelai> {
-    let mut lt24h = 0i64;
-    let mut lt48h = 0i64;
-    let mut lt7j = 0i64;
-    let mut lt30j = 0i64;
-    let mut ge30j = 0i64;
-
-    for &d in delays {
-        if d < 1.0 {
-            lt24h += 1;
-        } else if d < 2.0 {
-            lt48h += 1;
-        } else if d < 7.0 {
-            lt7j += 1;
-        } else if d < 30.0 {
-            lt30j += 1;
-        } else {
-            ge30j += 1;
-        }
-    }
-
-    vec![
-        TrancheDelai { label: "< 24h".to_string(), count: lt24h, pourcentage: pct(lt24h, total) },
-        TrancheDelai { label: "24h - 48h".to_string(), count: lt48h, pourcentage: pct(lt48h, total) },
-        TrancheDelai { label: "2j - 7j".to_string(), count: lt7j, pourcentage: pct(lt7j, total) },
-        TrancheDelai { label: "7j - 30j".to_string(), count: lt30j, pourcentage: pct(lt30j, total) },
-        TrancheDelai { label: "> 30j".to_string(), count: ge30j, pourcentage: pct(ge30j, total) },
-    ]
+/// Répartit des délais (jours) selon le jeu de tranches configuré.
+fn build_pec_distribution(delays: &[f64], total: i64, jeu: &JeuTranches) -> Vec<TrancheDelai> {
+    jeu.libelles()
+        .into_iter()
+        .zip(jeu.compter(delays.iter().copied()))
+        .map(|(label, count)| TrancheDelai {
+            label,
+            count: count as i64,
+            pourcentage: pct(count, total as usize),
+        })
+        .collect()
 }
 
 fn build_resolution(
     conn: &Connection,
+    cal: &CalendrierCompile,
     import_id: i64,
     date_clause: &str,
     date_params: &[String],
     gran: &str,
+    jeu: &JeuTranches,
 ) -> Result<ResolutionKpi, rusqlite::Error> {
     // Collect resolution durations
     let sql = format!(
-        "SELECT julianday(date_cloture_approx) - julianday(date_ouverture)
+        "SELECT julianday(date_cloture_approx) - julianday(date_ouverture),
+                date_ouverture, date_cloture_approx
          FROM tickets
          WHERE import_id = ? AND est_vivant = 0
            AND date_cloture_approx IS NOT NULL AND date_ouverture IS NOT NULL{}",
@@ -488,14 +504,16 @@ fn build_resolution(
     let mut stmt = conn.prepare(&sql)?;
     let rows = stmt.query_map(
         rusqlite::params_from_iter(all_params.iter().map(|b| b.as_ref())),
-        |row| row.get::<_, f64>(0),
+        |row| Ok((row.get::<_, f64>(0)?, jours_ouvres(cal, row.get(1)?, row.get(2)?))),
     )?;
 
     let mut durations: Vec<f64> = Vec::new();
+    let mut durations_ouvres: Vec<f64> = Vec::new();
     for row in rows {
-        let v = row?;
+        let (v, ouvre) = row?;
         if v >= 0.0 {
             durations.push(v);
+            durations_ouvres.extend(ouvre);
         }
     }
 
@@ -504,24 +522,27 @@ fn build_resolution(
     let mediane = round1(percentile(&durations, 50.0));
     let p90 = round1(percentile(&durations, 90.0));
     let et = round1(ecart_type(&durations));
+    let mttr_global_ouvres = round1(moyenne(&durations_ouvres));
+    let mediane_ouvres = round1(percentile(&durations_ouvres, 50.0));
+    let p90_ouvres = round1(percentile(&durations_ouvres, 90.0));
 
     // MTTR by type
-    let par_type = build_mttr_by_dimension(conn, import_id, "type_ticket", date_clause, date_params, echantillon)?;
+    let par_type = build_mttr_by_dimension(conn, cal, import_id, "type_ticket", date_clause, date_params, echantillon)?;
 
     // MTTR by priority (use priorite_label for text display)
-    let par_priorite = build_mttr_by_dimension(conn, import_id, "priorite_label", date_clause, date_params, echantillon)?;
+    let par_priorite = build_mttr_by_dimension(conn, cal, import_id, "priorite_label", date_clause, date_params, echantillon)?;
 
     // MTTR by groupe
-    let par_groupe = build_mttr_by_dimension(conn, import_id, "groupe_principal", date_clause, date_params, echantillon)?;
+    let par_groupe = build_mttr_by_dimension(conn, cal, import_id, "groupe_principal", date_clause, date_params, echantillon)?;
 
     // MTTR by technicien
-    let par_technicien = build_mttr_by_dimension(conn, import_id, "technicien_principal", date_clause, date_params, echantillon)?;
+    let par_technicien = build_mttr_by_dimension(conn, cal, import_id, "technicien_principal", date_clause, date_params, echantillon)?;
 
     // Distribution by tranches
-    let distribution_tranches = build_pec_distribution(&durations, echantillon);
+    let distribution_tranches = build_pec_distribution(&durations, echantillon, jeu);
 
     // Monthly trend
-    let trend_mensuel = build_resolution_trend(conn, import_id, date_clause, date_params, gran)?;
+    let trend_mensuel = build_resolution_trend(conn, cal, import_id, date_clause, date_params, gran)?;
 
     // Resolution speed trend (% < 24h and % < 48h per period)
     let resolution_speed_trend = build_resolution_speed_trend(conn, import_id, date_clause, date_params, gran)?;
@@ -531,6 +552,9 @@ fn build_resolution(
         mediane_jours: mediane,
         p90_jours: p90,
         ecart_type_jours: et,
+        mttr_global_jours_ouvres: mttr_global_ouvres,
+        mediane_jours_ouvres: mediane_ouvres,
+        p90_jours_ouvres: p90_ouvres,
         par_type,
         par_priorite,
         par_groupe,
@@ -544,6 +568,7 @@ fn build_resolution(
 
 fn build_mttr_by_dimension(
     conn: &Connection,
+    cal: &CalendrierCompile,
     import_id: i64,
     column: &str,
     date_clause: &str,
@@ -582,7 +607,8 @@ fn build_mttr_by_dimension(
     // Also need median per dimension — collect durations per group
     let sql_details = format!(
         "SELECT {col},
-                julianday(date_cloture_approx) - julianday(date_ouverture) AS dur
+                julianday(date_cloture_approx) - julianday(date_ouverture) AS dur,
+                date_ouverture, date_cloture_approx
          FROM tickets
          WHERE import_id = ? AND est_vivant = 0
            AND date_cloture_approx IS NOT NULL AND date_ouverture IS NOT NULL
@@ -602,18 +628,21 @@ fn build_mttr_by_dimension(
             Ok((
                 row.get::<_, rusqlite::types::Value>(0)?,
                 row.get::<_, f64>(1)?,
+                jours_ouvres(cal, row.get(2)?, row.get(3)?),
             ))
         },
     )?;
 
     // Group durations by dimension value for median computation
     let mut durations_by_key: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    let mut ouvres_by_key: BTreeMap<String, Vec<f64>> = BTreeMap::new();
     for row in detail_rows {
-        let (val, dur) = row?;
+        let (val, dur, ouvre) = row?;
         if dur < 0.0 {
             continue;
         }
         let key = value_to_label(&val);
+        ouvres_by_key.entry(key.clone()).or_default().extend(ouvre);
         durations_by_key.entry(key).or_default().push(dur);
     }
 
@@ -625,12 +654,15 @@ fn build_mttr_by_dimension(
             .get(&label)
             .map(|v| round1(percentile(v, 50.0)))
             .unwrap_or(0.0);
+        let ouvres = ouvres_by_key.get(&label).map(Vec::as_slice).unwrap_or_default();
         results.push(MttrParDimension {
+            mttr_jours_ouvres: round1(moyenne(ouvres)),
+            mediane_jours_ouvres: round1(percentile(ouvres, 50.0)),
             label,
             mttr_jours: round1(avg_dur),
             mediane_jours: med,
             count: cnt,
-            pourcentage_total: pct(cnt, total_echantillon),
+            pourcentage_total: pct(cnt as usize, total_echantillon as usize),
         });
     }
 
@@ -648,6 +680,7 @@ fn value_to_label(val: &rusqlite::types::Value) -> String {
 
 fn build_resolution_trend(
     conn: &Connection,
+    cal: &CalendrierCompile,
     import_id: i64,
     date_clause: &str,
     date_params: &[String],
@@ -656,7 +689,8 @@ fn build_resolution_trend(
     let pe = period_expr(gran, "date_cloture_approx");
     let sql = format!(
         "SELECT {pe} AS periode,
-                julianday(date_cloture_approx) - julianday(date_ouverture) AS dur
+                julianday(date_cloture_approx) - julianday(date_ouverture) AS dur,
+                date_ouverture, date_cloture_approx
          FROM tickets
          WHERE import_id = ? AND est_vivant = 0
            AND date_cloture_approx IS NOT NULL AND date_ouverture IS NOT NULL{}
@@ -671,24 +705,33 @@ fn build_resolution_trend(
     let rows = stmt.query_map(
         rusqlite::params_from_iter(all_params.iter().map(|b| b.as_ref())),
         |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, f64>(1)?,
+                jours_ouvres(cal, row.get(2)?, row.get(3)?),
+            ))
         },
     )?;
 
-    let mut by_period: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    // (durées calendaires, durées ouvrées) par période
+    let mut by_period: BTreeMap<String, (Vec<f64>, Vec<f64>)> = BTreeMap::new();
     for row in rows {
-        let (p, dur) = row?;
+        let (p, dur, ouvre) = row?;
         if dur >= 0.0 {
-            by_period.entry(p).or_default().push(dur);
+            let entry = by_period.entry(p).or_default();
+            entry.0.push(dur);
+            entry.1.extend(ouvre);
         }
     }
 
     let mut trends = Vec::new();
-    for (p, durs) in &by_period {
+    for (p, (durs, ouvres)) in &by_period {
         trends.push(MttrTrend {
             periode: p.clone(),
             mttr_jours: round1(moyenne(durs)),
             mediane_jours: round1(percentile(durs, 50.0)),
+            mttr_jours_ouvres: round1(moyenne(ouvres)),
+            mediane_jours_ouvres: round1(percentile(ouvres, 50.0)),
             nb_resolus: durs.len() as i64,
         });
     }
@@ -860,19 +903,19 @@ fn build_taux_n1(
         total_termines,
         n1_strict: TauxDetail {
             count: n1_strict_count,
-            pourcentage: pct(n1_strict_count, total_termines),
+            pourcentage: pct(n1_strict_count as usize, total_termines as usize),
         },
         n1_elargi: TauxDetail {
             count: n1_elargi_count,
-            pourcentage: pct(n1_elargi_count, total_termines),
+            pourcentage: pct(n1_elargi_count as usize, total_termines as usize),
         },
         multi_niveaux: TauxDetail {
             count: multi_count,
-            pourcentage: pct(multi_count, total_termines),
+            pourcentage: pct(multi_count as usize, total_termines as usize),
         },
         sans_technicien: TauxDetail {
             count: sans_tech_count,
-            pourcentage: pct(sans_tech_count, total_termines),
+            pourcentage: pct(sans_tech_count as usize, total_termines as usize),
         },
         par_groupe,
         trend_mensuel,
@@ -920,9 +963,9 @@ fn build_taux_n1_par_groupe(
                 groupe,
                 total_resolus,
                 n1_strict_count,
-                n1_strict_pct: pct(n1_strict_count, total_resolus),
+                n1_strict_pct: pct(n1_strict_count as usize, total_resolus as usize),
                 n1_elargi_count,
-                n1_elargi_pct: pct(n1_elargi_count, total_resolus),
+                n1_elargi_pct: pct(n1_elargi_count as usize, total_resolus as usize),
             })
         },
     )?;
@@ -970,8 +1013,9 @@ fn build_taux_n1_trend(
             let n1_elargi_count: i64 = row.get(3)?;
             Ok(TauxN1Trend {
                 periode,
-                n1_strict_pct: pct(n1_strict_count, total_resolus),
-                n1_elargi_pct: pct(n1_elargi_count, total_resolus),
+                n1_strict_count,
+                n1_strict_pct: pct(n1_strict_count as usize, total_resolus as usize),
+                n1_elargi_pct: pct(n1_elargi_count as usize, total_resolus as usize),
                 total_resolus,
             })
         },
@@ -1154,7 +1198,7 @@ fn build_ventilation(
                 total,
                 vivants,
                 termines,
-                pourcentage_total: pct(total, grand_total),
+                pourcentage_total: pct(total as usize, grand_total as usize),
             })
         },
     )?;
@@ -1223,7 +1267,7 @@ fn build_ventilation_priorite(
                 total,
                 vivants,
                 termines,
-                pourcentage_total: pct(total, grand_total),
+                pourcentage_total: pct(total as usize, grand_total as usize),
             })
         },
     )?;
@@ -1231,6 +1275,65 @@ fn build_ventilation_priorite(
     rows.collect()
 }
 
+// ─── Survie (Kaplan–Meier) ──────────────────────────────────────────────────
+
+/// Tickets ouverts sur la période : terminés = résolution observée, vivants =
+/// censurés à leur âge à la date de l'import (à défaut, `anciennete_jours`).
+fn build_survie(
+    conn: &Connection,
+    import_id: i64,
+    date_clause: &str,
+    date_params: &[String],
+) -> Result<SurvieKpi, rusqlite::Error> {
+    let reference = crate::db::queries::get_import_reference_date(conn, import_id)?
+        .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string());
+    let sql = format!(
+        "SELECT CASE WHEN est_vivant = 1
+                    THEN COALESCE(julianday(?) - julianday(date_ouverture), anciennete_jours)
+                    ELSE julianday(date_cloture_approx) - julianday(date_ouverture) END,
+                est_vivant, type_ticket, priorite_label, groupe_principal, categorie
+         FROM tickets
+         WHERE import_id = ? AND date_ouverture IS NOT NULL
+           AND (est_vivant = 1 OR date_cloture_approx IS NOT NULL){}",
+        date_clause
+    );
+    let mut all_params: Vec<Box<dyn rusqlite::types::ToSql>> =
+        vec![Box::new(reference), Box::new(import_id)];
+    for p in date_params {
+        all_params.push(Box::new(p.clone()));
+    }
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        rusqlite::params_from_iter(all_params.iter().map(|b| b.as_ref())),
+        |row| {
+            Ok((
+                row.get::<_, Option<f64>>(0)?,
+                row.get::<_, bool>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        },
+    )?;
+
+    let mut tickets = Vec::new();
+    for row in rows {
+        let (jours, vivant, type_ticket, priorite, groupe, categorie) = row?;
+        let Some(jours) = jours.filter(|j| *j >= 0.0) else {
+            continue;
+        };
+        tickets.push(TicketSurvie {
+            observation: Observation { jours, resolu: !vivant },
+            type_ticket,
+            priorite,
+            groupe,
+            categorie,
+        });
+    }
+    Ok(compute_survie(&tickets))
+}
+
 // ─── Main Entry Point ────────────────────────────────────────────────────────
 
 /// Builds the complete Dashboard KPI structure from the database.
@@ -1265,15 +1368,29 @@ pub fn build_dashboard_kpi(
         0
     };
 
+    let cal = crate::config::get_calendrier_from_db(conn)?;
+    let tranches = crate::config::get_config_from_db(conn)?.tranches;
+
     let mut meta = build_meta(conn, import_id, &date_clause, &date_params)?;
-    let prise_en_charge = build_prise_en_charge(conn, import_id, &date_clause, &date_params)?;
-    let resolution = build_resolution(conn, import_id, &date_clause, &date_params, gran)?;
+    let prise_en_charge = build_prise_en_charge(
+        conn, &cal, import_id, &date_clause, &date_params, &tranches.prise_en_charge(),
+    )?;
+    let resolution = build_resolution(
+        conn, &cal, import_id, &date_clause, &date_params, gran, &tranches.resolution(),
+    )?;
+    let survie = build_survie(conn, import_id, &date_clause, &date_params)?;
     let taux_n1 = build_taux_n1(conn, import_id, &date_clause, &date_params, gran)?;
+    let qualite = load_qualite_kpi(conn, import_id, date_debut.as_deref(), date_fin.as_deref(), gran)?;
     let volumes = build_volumetrie(
         conn, import_id, &date_clause, &date_params,
         &date_clause_res, &date_params_res, gran, stock_debut,
     )?;
     let typologie = build_typologie(conn, import_id, meta.has_categorie, &date_clause, &date_params)?;
+    let sla = load_sla_kpi(
+        conn, import_id, date_debut.as_deref(), date_fin.as_deref(), gran,
+        HORIZON_DEFAUT_JOURS, Some(MAX_TICKETS_LISTE),
+    )?;
+    let priorites = load_priorite_kpi(conn, import_id, date_debut.as_deref(), date_fin.as_deref(), gran)?;
 
     meta.calcul_duration_ms = start.elapsed().as_millis() as u64;
 
@@ -1281,9 +1398,59 @@ pub fn build_dashboard_kpi(
         meta,
         prise_en_charge,
         resolution,
+        survie,
         taux_n1,
+        qualite,
         volumes,
         typologie,
+        sla,
+        priorites,
+    })
+}
+
+/// Cartes de contrôle hebdomadaires ou mensuelles des arrivées, du MTTR et
+/// du taux N1 strict, à partir des séries par période du tableau de bord.
+pub fn build_spc_kpi(
+    conn: &Connection,
+    import_id: i64,
+    date_debut: &Option<String>,
+    date_fin: &Option<String>,
+    granularity: &str,
+) -> Result<SpcKpi, rusqlite::Error> {
+    let gran = if granularity == "week" { "week" } else { "month" };
+    let (date_clause, date_params) = date_filter_clause(date_debut, date_fin);
+    let (date_clause_res, date_params_res) =
+        date_filter_clause_on("date_cloture_approx", date_debut, date_fin);
+    let cal = crate::config::get_calendrier_from_db(conn)?;
+
+    // Les périodes sans création ni résolution sont absentes de la série
+    let volumes = build_volumetrie(
+        conn, import_id, &date_clause, &date_params,
+        &date_clause_res, &date_params_res, gran, 0,
+    )?;
+    let arrivees: Vec<(String, f64)> = volumes
+        .par_mois
+        .iter()
+        .map(|v| (v.periode.clone(), v.crees as f64))
+        .collect();
+
+    let mttr: Vec<(String, f64)> =
+        build_resolution_trend(conn, &cal, import_id, &date_clause_res, &date_params_res, gran)?
+            .into_iter()
+            .map(|t| (t.periode, t.mttr_jours))
+            .collect();
+
+    let taux_n1: Vec<(String, i64, i64)> =
+        build_taux_n1_trend(conn, import_id, &date_clause_res, &date_params_res, gran)?
+            .into_iter()
+            .map(|t| (t.periode, t.n1_strict_count, t.total_resolus))
+            .collect();
+
+    Ok(SpcKpi {
+        granularite: gran.to_string(),
+        arrivees: carte_individuelle("arrivees", &arrivees),
+        mttr: carte_individuelle("mttr", &mttr),
+        taux_n1: carte_p("taux_n1", &taux_n1),
     })
 }
 
@@ -1296,10 +1463,7 @@ mod tests {
 
     fn setup_test_db() -> Connection {
         let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(include_str!("../db/sql/001_initial.sql"))
-            .unwrap();
-        conn.execute_batch(include_str!("../db/sql/003_date_resolution.sql"))
-            .unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
 
         // Insert test import
         conn.execute(
@@ -1385,7 +1549,7 @@ mod tests {
     fn test_taux_n1_mono() {
         // Create a DB where all terminated tickets are mono-tech
         let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(include_str!("../db/sql/001_initial.sql")).unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
         conn.execute(
             "INSERT INTO imports (id, filename, total_rows, parsed_rows, skipped_rows,
                 vivants_count, termines_count, detected_columns, unique_statuts, unique_types, is_active)
@@ -1469,10 +1633,30 @@ mod tests {
         assert_eq!(sum_resolus, kpi.volumes.total_resolus);
     }
 
+    #[test]
+    fn test_spc_charts() {
+        let conn = setup_test_db();
+        let spc = build_spc_kpi(&conn, 1, &None, &None, "day").unwrap();
+        assert_eq!(spc.granularite, "month");
+
+        // Arrivées 3 / 3 / 4 : MR̄ = 0,5
+        let valeurs: Vec<f64> = spc.arrivees.points.iter().map(|p| p.valeur).collect();
+        assert_eq!(valeurs, vec![3.0, 3.0, 4.0]);
+        assert_eq!(spc.arrivees.centre, Some(3.33));
+        assert_eq!(spc.arrivees.moyenne_etendue_mobile, Some(0.5));
+        assert_eq!(spc.mttr.points.len(), 3);
+
+        // N1 strict : 2/2, 1/2, 1/3
+        let effectifs: Vec<Option<i64>> = spc.taux_n1.points.iter().map(|p| p.effectif).collect();
+        assert_eq!(effectifs, vec![Some(2), Some(2), Some(3)]);
+        assert_eq!(spc.taux_n1.centre, Some(57.14));
+        assert_eq!(spc.taux_n1.type_carte, "p");
+    }
+
     #[test]
     fn test_empty_dataset() {
         let conn = Connection::open_in_memory().unwrap();
-        conn.execute_batch(include_str!("../db/sql/001_initial.sql")).unwrap();
+        crate::db::migrations::run_migrations(&conn).unwrap();
         conn.execute(
             "INSERT INTO imports (id, filename, total_rows, parsed_rows, skipped_rows,
                 vivants_count, termines_count, detected_columns, unique_statuts, unique_types, is_active)
@@ -1557,4 +1741,138 @@ mod tests {
         let dist_sum: i64 = kpi.prise_en_charge.distribution.iter().map(|t| t.count).sum();
         assert!(dist_sum > 0);
     }
+
+    #[test]
+    fn test_priorites() {
+        let conn = setup_test_db();
+        conn.execute("UPDATE tickets SET urgence = 5, impact = 5 WHERE id = 1", []).unwrap();
+        conn.execute("UPDATE tickets SET urgence = 3, impact = 3 WHERE id = 4", []).unwrap();
+        let kpi = build_dashboard_kpi(&conn, 1, &None, &None, "month").unwrap();
+        let p = &kpi.priorites;
+
+        assert_eq!(p.total_tickets, 10);
+        assert_eq!(p.sans_urgence, 8);
+        assert!(p.impact_disponible);
+        assert_eq!(p.coherence.evalues, 2);
+        assert_eq!(p.coherence.sousclasses, 1);
+        assert_eq!(p.coherence.tickets[0].id, 1);
+        // Jan–mars 2025
+        assert_eq!(p.derive.len(), 3);
+        assert_eq!(p.derive.iter().map(|d| d.total).sum::<usize>(), 10);
+    }
+
+    #[test]
+    fn test_sla() {
+        let conn = setup_test_db();
+        let kpi = build_dashboard_kpi(&conn, 1, &None, &None, "month").unwrap();
+
+        assert_eq!(kpi.sla.nb_definitions, 7);
+        // Tous les terminés ont une date de résolution et un SLA par priorité
+        assert_eq!(kpi.sla.resolution.evalues + kpi.sla.resolution.sans_sla, kpi.meta.total_termines as usize);
+        assert_eq!(kpi.sla.vivants.evalues, kpi.meta.total_vivants as usize);
+        // Import de test daté d'aujourd'hui : les vivants de 2025 sont hors délai
+        assert_eq!(kpi.sla.vivants.ttr_depasses, 3);
+        assert!(kpi.sla.depasses.iter().all(|e| e.heures_restantes < 0.0));
+
+        let filtered = build_dashboard_kpi(
+            &conn, 1, &Some("2025-01-01".to_string()), &Some("2025-01-31".to_string()), "month",
+        )
+        .unwrap();
+        assert!(filtered.sla.resolution.evalues < kpi.sla.resolution.evalues);
+        assert_eq!(filtered.sla.vivants.evalues, 3);
+    }
+
+    #[test]
+    fn test_survival() {
+        let conn = setup_test_db();
+        let kpi = build_dashboard_kpi(&conn, 1, &None, &None, "month").unwrap();
+
+        let s = &kpi.survie;
+        assert_eq!(s.effectif, 10);
+        assert_eq!(s.resolus, 7);
+        assert_eq!(s.censures, 3);
+        // Résolus en 1, 2, 3, 5, 7, 9, 10 j ; vivants censurés bien au-delà :
+        // S(7) = 5/10 alors que la médiane des seuls résolus vaut 5 j
+        assert_eq!(s.mediane.jours, Some(7.0));
+        assert_eq!(kpi.resolution.mediane_jours, 5.0);
+        assert_eq!(s.horizons[2].jours, 7);
+        assert_eq!(s.horizons[2].probabilite_ouvert, 0.5);
+        assert!(s.courbe.windows(2).all(|w| w[1].probabilite_ouvert <= w[0].probabilite_ouvert));
+
+        let filtered = build_dashboard_kpi(
+            &conn, 1, &Some("2025-01-01".to_string()), &Some("2025-01-31".to_string()), "month",
+        )
+        .unwrap();
+        assert_eq!(filtered.survie.effectif, 3);
+        assert_eq!(filtered.survie.censures, 1);
+    }
+
+    #[test]
+    fn test_reopen_and_fcr() {
+        let conn = setup_test_db();
+        let kpi = build_dashboard_kpi(&conn, 1, &None, &None, "month").unwrap();
+        assert!(!kpi.qualite.historique_disponible);
+        assert_eq!(kpi.qualite.reouvertes, 0);
+        // Terminés avec au plus 1 suivi : #4, 5, 7, 8, 9, 10
+        assert_eq!(kpi.qualite.total_termines, 7);
+        assert_eq!(kpi.qualite.fcr_count, 6);
+
+        // Import antérieur où le ticket vivant #1 était clos, et marqueur dans les suivis de #5
+        conn.execute(
+            "INSERT INTO imports (id, filename, total_rows, parsed_rows, skipped_rows,
+                vivants_count, termines_count, detected_columns, unique_statuts, unique_types, is_active)
+             VALUES (0, 'old.csv', 1, 1, 0, 0, 1, '[]', '[]', '[]', 0)",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO tickets (id, import_id, statut, date_ouverture, est_vivant, date_cloture_approx, technicien_principal)
+             VALUES (1, 0, 'Clos', '2025-01-10T10:00:00', 0, '2025-01-12T10:00:00', 'Alice')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "UPDATE tickets SET suivis_description = 'Ticket réouvert à la demande de l''utilisateur'
+             WHERE id = 5 AND import_id = 1",
+            [],
+        )
+        .unwrap();
+
+        let kpi = build_dashboard_kpi(&conn, 1, &None, &None, "month").unwrap();
+        let q = &kpi.qualite;
+        assert!(q.historique_disponible);
+        assert_eq!(q.total_resolutions, 8);
+        assert_eq!(q.reouvertes, 2);
+        assert_eq!(q.reouvertes_historique, 1);
+        assert_eq!(q.reouvertes_suivis, 1);
+        assert_eq!(q.taux_reouverture, 25.0);
+        assert_eq!(q.fcr_count, 5);
+        let alice = q.par_technicien.iter().find(|d| d.label == "Alice").unwrap();
+        assert_eq!(alice.reouvertes, 1);
+        let janvier = q.trend.iter().find(|t| t.periode == "2025-01").unwrap();
+        assert_eq!(janvier.reouvertes, 2);
+
+        // Fenêtre de février : aucune des deux réouvertures
+        let fevrier = build_dashboard_kpi(
+            &conn, 1, &Some("2025-02-01".to_string()), &Some("2025-02-28".to_string()), "month",
+        )
+        .unwrap();
+        assert_eq!(fevrier.qualite.reouvertes, 0);
+        assert_eq!(fevrier.qualite.total_resolutions, 2);
+    }
+
+    #[test]
+    fn test_business_time_metrics() {
+        let conn = setup_test_db();
+        let kpi = build_dashboard_kpi(&conn, 1, &None, &None, "month").unwrap();
+
+        // Nuits, week-ends et fériés exclus : le temps ouvré est plus court
+        let r = &kpi.resolution;
+        assert!(r.mttr_global_jours_ouvres > 0.0);
+        assert!(r.mttr_global_jours_ouvres < r.mttr_global_jours);
+        assert!(r.par_type.iter().all(|d| d.mttr_jours_ouvres <= d.mttr_jours));
+        assert!(r.trend_mensuel.iter().all(|t| t.mediane_jours_ouvres <= t.mediane_jours));
+        let pec = &kpi.prise_en_charge;
+        assert!(pec.delai_moyen_jours_ouvres.unwrap() < pec.delai_moyen_jours.unwrap());
+    }
 }
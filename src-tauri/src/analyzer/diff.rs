@@ -0,0 +1,402 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+const NON_ASSIGNE: &str = "(non assigné)";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDiff {
+    pub import_from: i64,
+    pub import_to: i64,
+    pub resume: DiffResume,
+    pub nouveaux: Vec<DiffTicket>,
+    pub disparus: Vec<DiffTicket>,
+    pub clotures: Vec<DiffTicket>,
+    pub reouverts: Vec<DiffTicket>,
+    pub changements_technicien: Vec<DiffChange>,
+    pub changements_groupe: Vec<DiffChange>,
+    pub changements_priorite: Vec<DiffChange>,
+    pub changements_categorie: Vec<DiffChange>,
+    pub par_technicien: Vec<DiffAggregate>,
+    pub par_groupe: Vec<DiffAggregate>,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffResume {
+    pub nouveaux: usize,
+    pub disparus: usize,
+    pub clotures: usize,
+    pub reouverts: usize,
+    pub changements_technicien: usize,
+    pub changements_groupe: usize,
+    pub changements_priorite: usize,
+    pub changements_categorie: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffTicket {
+    pub id: u64,
+    pub titre: String,
+    pub statut: String,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffChange {
+    pub id: u64,
+    pub titre: String,
+    pub avant: Option<String>,
+    pub apres: Option<String>,
+}
+
+/// Mouvements agrégés pour un technicien ou un groupe.
+/// `transferts_entrants`/`transferts_sortants` : réaffectations vers/depuis ce libellé.
+#[derive(Serialize, Default, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffAggregate {
+    pub label: String,
+    pub nouveaux: usize,
+    pub disparus: usize,
+    pub clotures: usize,
+    pub reouverts: usize,
+    pub transferts_entrants: usize,
+    pub transferts_sortants: usize,
+}
+
+/// État d'un ticket dans un import, limité aux champs suivis par le diff.
+#[derive(Debug, Clone)]
+pub struct TicketSnapshot {
+    pub id: u64,
+    pub titre: String,
+    pub statut: String,
+    pub est_vivant: bool,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub priorite: Option<String>,
+    pub categorie: Option<String>,
+}
+
+impl TicketSnapshot {
+    fn to_diff_ticket(&self) -> DiffTicket {
+        DiffTicket {
+            id: self.id,
+            titre: self.titre.clone(),
+            statut: self.statut.clone(),
+            technicien: self.technicien.clone(),
+            groupe: self.groupe.clone(),
+        }
+    }
+}
+
+/// Compare deux instantanés d'import (ticket par ticket, clé = id GLPI).
+///
+/// - nouveau : présent uniquement dans `after`
+/// - disparu : présent uniquement dans `before`
+/// - clôturé / réouvert : bascule de `est_vivant`
+/// - changements : technicien principal, groupe principal, priorité, catégorie
+///
+/// Les agrégats sont attribués au technicien/groupe de l'import le plus récent
+/// (sauf disparus : import de référence).
+pub fn compute_import_diff(
+    import_from: i64,
+    import_to: i64,
+    before: &[TicketSnapshot],
+    after: &[TicketSnapshot],
+) -> ImportDiff {
+    let before_map: HashMap<u64, &TicketSnapshot> = before.iter().map(|t| (t.id, t)).collect();
+    let after_map: HashMap<u64, &TicketSnapshot> = after.iter().map(|t| (t.id, t)).collect();
+
+    let mut nouveaux = Vec::new();
+    let mut disparus = Vec::new();
+    let mut clotures = Vec::new();
+    let mut reouverts = Vec::new();
+    let mut chg_tech = Vec::new();
+    let mut chg_groupe = Vec::new();
+    let mut chg_prio = Vec::new();
+    let mut chg_cat = Vec::new();
+
+    let mut par_tech: BTreeMap<String, DiffAggregate> = BTreeMap::new();
+    let mut par_groupe: BTreeMap<String, DiffAggregate> = BTreeMap::new();
+
+    fn agg<'a>(
+        map: &'a mut BTreeMap<String, DiffAggregate>,
+        key: &Option<String>,
+    ) -> &'a mut DiffAggregate {
+        let label = key
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(NON_ASSIGNE)
+            .to_string();
+        map.entry(label.clone()).or_insert_with(|| DiffAggregate {
+            label,
+            ..Default::default()
+        })
+    }
+
+    for new in after {
+        let Some(old) = before_map.get(&new.id) else {
+            nouveaux.push(new.to_diff_ticket());
+            agg(&mut par_tech, &new.technicien).nouveaux += 1;
+            agg(&mut par_groupe, &new.groupe).nouveaux += 1;
+            continue;
+        };
+
+        if old.est_vivant && !new.est_vivant {
+            clotures.push(new.to_diff_ticket());
+            agg(&mut par_tech, &new.technicien).clotures += 1;
+            agg(&mut par_groupe, &new.groupe).clotures += 1;
+        } else if !old.est_vivant && new.est_vivant {
+            reouverts.push(new.to_diff_ticket());
+            agg(&mut par_tech, &new.technicien).reouverts += 1;
+            agg(&mut par_groupe, &new.groupe).reouverts += 1;
+        }
+
+        if let Some(c) = field_change(new, &old.technicien, &new.technicien) {
+            agg(&mut par_tech, &old.technicien).transferts_sortants += 1;
+            agg(&mut par_tech, &new.technicien).transferts_entrants += 1;
+            chg_tech.push(c);
+        }
+        if let Some(c) = field_change(new, &old.groupe, &new.groupe) {
+            agg(&mut par_groupe, &old.groupe).transferts_sortants += 1;
+            agg(&mut par_groupe, &new.groupe).transferts_entrants += 1;
+            chg_groupe.push(c);
+        }
+        if let Some(c) = field_change(new, &old.priorite, &new.priorite) {
+            chg_prio.push(c);
+        }
+        if let Some(c) = field_change(new, &old.categorie, &new.categorie) {
+            chg_cat.push(c);
+        }
+    }
+
+    for old in before {
+        if !after_map.contains_key(&old.id) {
+            disparus.push(old.to_diff_ticket());
+            agg(&mut par_tech, &old.technicien).disparus += 1;
+            agg(&mut par_groupe, &old.groupe).disparus += 1;
+        }
+    }
+
+    for list in [&mut nouveaux, &mut disparus, &mut clotures, &mut reouverts] {
+        list.sort_by_key(|t| t.id);
+    }
+    for list in [&mut chg_tech, &mut chg_groupe, &mut chg_prio, &mut chg_cat] {
+        list.sort_by_key(|c| c.id);
+    }
+
+    let resume = DiffResume {
+        nouveaux: nouveaux.len(),
+        disparus: disparus.len(),
+        clotures: clotures.len(),
+        reouverts: reouverts.len(),
+        changements_technicien: chg_tech.len(),
+        changements_groupe: chg_groupe.len(),
+        changements_priorite: chg_prio.len(),
+        changements_categorie: chg_cat.len(),
+    };
+
+    ImportDiff {
+        import_from,
+        import_to,
+        resume,
+        nouveaux,
+        disparus,
+        clotures,
+        reouverts,
+        changements_technicien: chg_tech,
+        changements_groupe: chg_groupe,
+        changements_priorite: chg_prio,
+        changements_categorie: chg_cat,
+        par_technicien: sort_aggregates(par_tech),
+        par_groupe: sort_aggregates(par_groupe),
+    }
+}
+
+fn field_change(
+    new: &TicketSnapshot,
+    avant: &Option<String>,
+    apres: &Option<String>,
+) -> Option<DiffChange> {
+    let norm = |v: &Option<String>| {
+        v.as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+    };
+    let (avant, apres) = (norm(avant), norm(apres));
+    if avant == apres {
+        return None;
+    }
+    Some(DiffChange {
+        id: new.id,
+        titre: new.titre.clone(),
+        avant,
+        apres,
+    })
+}
+
+/// Tri par volume de mouvements décroissant, puis libellé.
+fn sort_aggregates(map: BTreeMap<String, DiffAggregate>) -> Vec<DiffAggregate> {
+    let mut v: Vec<DiffAggregate> = map.into_values().collect();
+    v.sort_by(|a, b| {
+        total_mouvements(b)
+            .cmp(&total_mouvements(a))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    v
+}
+
+fn total_mouvements(a: &DiffAggregate) -> usize {
+    a.nouveaux
+        + a.disparus
+        + a.clotures
+        + a.reouverts
+        + a.transferts_entrants
+        + a.transferts_sortants
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snap(id: u64, vivant: bool, tech: Option<&str>, groupe: Option<&str>) -> TicketSnapshot {
+        TicketSnapshot {
+            id,
+            titre: format!("Ticket {id}"),
+            statut: if vivant {
+                "En cours (Attribué)"
+            } else {
+                "Clos"
+            }
+            .to_string(),
+            est_vivant: vivant,
+            technicien: tech.map(String::from),
+            groupe: groupe.map(String::from),
+            priorite: Some("Moyenne".to_string()),
+            categorie: Some("Réseau".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_nouveaux_et_disparus() {
+        let before = vec![
+            snap(1, true, Some("Alice"), Some("N1")),
+            snap(2, true, Some("Bob"), Some("N1")),
+        ];
+        let after = vec![
+            snap(2, true, Some("Bob"), Some("N1")),
+            snap(3, true, Some("Alice"), Some("N2")),
+        ];
+
+        let diff = compute_import_diff(1, 2, &before, &after);
+
+        assert_eq!(diff.nouveaux.len(), 1);
+        assert_eq!(diff.nouveaux[0].id, 3);
+        assert_eq!(diff.disparus.len(), 1);
+        assert_eq!(diff.disparus[0].id, 1);
+        assert_eq!(diff.resume.clotures, 0);
+    }
+
+    #[test]
+    fn test_clotures_et_reouvertures() {
+        let before = vec![
+            snap(1, true, Some("Alice"), None),
+            snap(2, false, Some("Bob"), None),
+        ];
+        let after = vec![
+            snap(1, false, Some("Alice"), None),
+            snap(2, true, Some("Bob"), None),
+        ];
+
+        let diff = compute_import_diff(1, 2, &before, &after);
+
+        assert_eq!(
+            diff.clotures.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(
+            diff.reouverts.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn test_changements_de_champs() {
+        let before = vec![snap(1, true, Some("Alice"), Some("N1"))];
+        let mut t = snap(1, true, Some("Bob"), Some("N2"));
+        t.priorite = Some("Haute".to_string());
+        t.categorie = Some("Poste de travail".to_string());
+        let after = vec![t];
+
+        let diff = compute_import_diff(1, 2, &before, &after);
+
+        assert_eq!(diff.changements_technicien.len(), 1);
+        assert_eq!(
+            diff.changements_technicien[0].avant.as_deref(),
+            Some("Alice")
+        );
+        assert_eq!(diff.changements_technicien[0].apres.as_deref(), Some("Bob"));
+        assert_eq!(diff.resume.changements_groupe, 1);
+        assert_eq!(diff.resume.changements_priorite, 1);
+        assert_eq!(diff.resume.changements_categorie, 1);
+    }
+
+    #[test]
+    fn test_vide_et_none_equivalents() {
+        // "" et None ne doivent pas être vus comme un changement
+        let before = vec![snap(1, true, Some(""), None)];
+        let after = vec![snap(1, true, None, Some(" "))];
+
+        let diff = compute_import_diff(1, 2, &before, &after);
+
+        assert_eq!(diff.resume, DiffResume::default());
+    }
+
+    #[test]
+    fn test_agregats_par_technicien() {
+        let before = vec![
+            snap(1, true, Some("Alice"), Some("N1")),
+            snap(2, true, Some("Alice"), Some("N1")),
+        ];
+        let after = vec![
+            snap(1, false, Some("Alice"), Some("N1")),
+            snap(2, true, Some("Bob"), Some("N1")),
+            snap(3, true, None, Some("N1")),
+        ];
+
+        let diff = compute_import_diff(1, 2, &before, &after);
+
+        let alice = diff
+            .par_technicien
+            .iter()
+            .find(|a| a.label == "Alice")
+            .unwrap();
+        assert_eq!(alice.clotures, 1);
+        assert_eq!(alice.transferts_sortants, 1);
+        let bob = diff
+            .par_technicien
+            .iter()
+            .find(|a| a.label == "Bob")
+            .unwrap();
+        assert_eq!(bob.transferts_entrants, 1);
+        let non_assigne = diff
+            .par_technicien
+            .iter()
+            .find(|a| a.label == NON_ASSIGNE)
+            .unwrap();
+        assert_eq!(non_assigne.nouveaux, 1);
+
+        let n1 = &diff.par_groupe[0];
+        assert_eq!(n1.label, "N1");
+        assert_eq!(n1.nouveaux, 1);
+        assert_eq!(n1.clotures, 1);
+        // Alice a le plus de mouvements (1 clôture + 1 transfert sortant)
+        assert_eq!(diff.par_technicien[0].label, "Alice");
+    }
+}
@@ -0,0 +1,414 @@
+//! Parcours d'escalade à partir des listes ordonnées de groupes et de
+//! techniciens (`tickets.groupes` / `tickets.techniciens`).
+//!
+//! - transitions entre groupes (N1 → N2 → N3…), au format Sankey : les nœuds
+//!   sont indexés par étape pour garantir un graphe acyclique ;
+//! - ping-pong : retour dans un groupe déjà visité (A → B → A) ;
+//! - réaffectations par ticket et impact de chaque saut sur le MTTR.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::stats::{moyenne, pct, pente, percentile, round1};
+
+pub const LIMITE_DEFAUT: usize = 20;
+/// Étapes représentées dans le Sankey.
+const MAX_ETAPES: usize = 5;
+/// Tranche « N sauts et plus » de l'impact MTTR.
+const MAX_SAUTS_TRANCHE: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct TicketParcours {
+    pub id: u64,
+    pub titre: String,
+    pub groupes: Vec<String>,
+    pub techniciens: Vec<String>,
+    pub est_vivant: bool,
+    pub delai_resolution_jours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EscaladeKpi {
+    pub total_tickets: usize,
+    /// Tickets passés par au moins deux groupes.
+    pub escalades: usize,
+    pub taux_escalade: f64,
+    pub ping_pong: usize,
+    pub taux_ping_pong: f64,
+    pub sauts_moyens: f64,
+    pub reaffectations_moyennes: f64,
+    pub transitions: Vec<Transition>,
+    pub sankey: Sankey,
+    pub distribution_reaffectations: Vec<TrancheReaffectation>,
+    pub impact_mttr: Vec<ImpactSaut>,
+    /// Pente de la régression MTTR ~ sauts (jours par saut supplémentaire).
+    pub jours_par_saut: Option<f64>,
+    pub pires_rebonds: Vec<TicketRebond>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transition {
+    pub source: String,
+    pub cible: String,
+    pub count: usize,
+    /// Part des tickets sortant de `source` qui partent vers `cible`.
+    pub pourcentage: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sankey {
+    pub nodes: Vec<SankeyNode>,
+    pub links: Vec<SankeyLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SankeyNode {
+    pub id: usize,
+    pub label: String,
+    /// Étape dans le parcours (1 = premier groupe).
+    pub etape: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SankeyLink {
+    pub source: usize,
+    pub target: usize,
+    pub value: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrancheReaffectation {
+    pub reaffectations: usize,
+    pub label: String,
+    pub count: usize,
+    pub pourcentage: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpactSaut {
+    pub sauts: usize,
+    pub label: String,
+    pub count: usize,
+    pub mttr_jours: f64,
+    pub mediane_jours: f64,
+    /// Écart de MTTR avec les tickets traités sans escalade.
+    pub delta_vs_direct_jours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketRebond {
+    pub id: u64,
+    pub titre: String,
+    pub parcours: Vec<String>,
+    pub sauts: usize,
+    pub rebonds: usize,
+    pub reaffectations: usize,
+    pub est_vivant: bool,
+    pub delai_resolution_jours: Option<f64>,
+}
+
+/// Parcours nettoyé : libellés vides retirés, répétitions consécutives
+/// fusionnées (A, A, B → A, B).
+pub fn parcours(groupes: &[String]) -> Vec<&str> {
+    let mut result: Vec<&str> = Vec::with_capacity(groupes.len());
+    for g in groupes.iter().map(|g| g.trim()).filter(|g| !g.is_empty()) {
+        if result.last() != Some(&g) {
+            result.push(g);
+        }
+    }
+    result
+}
+
+/// Retours dans un groupe déjà visité.
+pub fn rebonds(parcours: &[&str]) -> usize {
+    let mut vus: HashSet<&str> = HashSet::new();
+    parcours.iter().filter(|g| !vus.insert(g)).count()
+}
+
+/// Changements de technicien (hors doublons consécutifs).
+fn reaffectations(techniciens: &[String]) -> usize {
+    parcours(techniciens).len().saturating_sub(1)
+}
+
+fn label_sauts(n: usize, max: usize, unite: &str) -> String {
+    match n {
+        _ if n >= max => format!("{max}+ {unite}s"),
+        0 | 1 => format!("{n} {unite}"),
+        _ => format!("{n} {unite}s"),
+    }
+}
+
+fn noeud_sankey<'a>(
+    index: &mut HashMap<(usize, &'a str), usize>,
+    nodes: &mut Vec<SankeyNode>,
+    etape: usize,
+    label: &'a str,
+) -> usize {
+    *index.entry((etape, label)).or_insert_with(|| {
+        nodes.push(SankeyNode {
+            id: nodes.len(),
+            label: label.to_string(),
+            etape,
+        });
+        nodes.len() - 1
+    })
+}
+
+fn build_sankey(chemins: &[Vec<&str>]) -> Sankey {
+    let mut index: HashMap<(usize, &str), usize> = HashMap::new();
+    let mut nodes: Vec<SankeyNode> = Vec::new();
+    let mut liens: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+    // Au-delà de MAX_ETAPES, les sauts restants sont ignorés : le graphe
+    // reste acyclique et lisible.
+    for chemin in chemins {
+        for (i, pair) in chemin.windows(2).enumerate().take(MAX_ETAPES - 1) {
+            let source = noeud_sankey(&mut index, &mut nodes, i + 1, pair[0]);
+            let target = noeud_sankey(&mut index, &mut nodes, i + 2, pair[1]);
+            *liens.entry((source, target)).or_default() += 1;
+        }
+    }
+    Sankey {
+        nodes,
+        links: liens
+            .into_iter()
+            .map(|((source, target), value)| SankeyLink {
+                source,
+                target,
+                value,
+            })
+            .collect(),
+    }
+}
+
+pub fn compute_escalade(tickets: &[TicketParcours], limite: usize) -> EscaladeKpi {
+    let chemins: Vec<Vec<&str>> = tickets.iter().map(|t| parcours(&t.groupes)).collect();
+    let total = tickets.len();
+
+    let mut transitions: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut sorties: HashMap<&str, usize> = HashMap::new();
+    let mut par_sauts: BTreeMap<usize, Vec<f64>> = BTreeMap::new();
+    let mut par_reaffectations: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    let mut rebondissants: Vec<TicketRebond> = Vec::new();
+    let (mut escalades, mut ping_pong, mut total_sauts, mut total_reaff) = (0, 0, 0, 0);
+
+    for (t, chemin) in tickets.iter().zip(&chemins) {
+        let sauts = chemin.len().saturating_sub(1);
+        let nb_rebonds = rebonds(chemin);
+        let reaff = reaffectations(&t.techniciens);
+        total_sauts += sauts;
+        total_reaff += reaff;
+        if sauts > 0 {
+            escalades += 1;
+        }
+        *par_reaffectations
+            .entry(reaff.min(MAX_SAUTS_TRANCHE))
+            .or_default() += 1;
+
+        for pair in chemin.windows(2) {
+            *transitions.entry((pair[0], pair[1])).or_default() += 1;
+            *sorties.entry(pair[0]).or_default() += 1;
+        }
+
+        if let Some(delai) = t.delai_resolution_jours.filter(|_| !t.est_vivant) {
+            par_sauts
+                .entry(sauts.min(MAX_SAUTS_TRANCHE))
+                .or_default()
+                .push(delai);
+            points.push((sauts as f64, delai));
+        }
+
+        if nb_rebonds > 0 {
+            ping_pong += 1;
+            rebondissants.push(TicketRebond {
+                id: t.id,
+                titre: t.titre.clone(),
+                parcours: chemin.iter().map(|g| g.to_string()).collect(),
+                sauts,
+                rebonds: nb_rebonds,
+                reaffectations: reaff,
+                est_vivant: t.est_vivant,
+                delai_resolution_jours: t.delai_resolution_jours.map(round1),
+            });
+        }
+    }
+
+    let mut transitions: Vec<Transition> = transitions
+        .into_iter()
+        .map(|((source, cible), count)| Transition {
+            source: source.to_string(),
+            cible: cible.to_string(),
+            count,
+            pourcentage: pct(count, sorties[source]),
+        })
+        .collect();
+    transitions.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then(a.source.cmp(&b.source))
+            .then(a.cible.cmp(&b.cible))
+    });
+
+    let mttr_direct = par_sauts.get(&0).map(|d| moyenne(d));
+    let impact_mttr = par_sauts
+        .iter()
+        .map(|(&sauts, delais)| {
+            let mttr = moyenne(delais);
+            ImpactSaut {
+                sauts,
+                label: label_sauts(sauts, MAX_SAUTS_TRANCHE, "saut"),
+                count: delais.len(),
+                mttr_jours: round1(mttr),
+                mediane_jours: round1(percentile(delais, 50.0)),
+                delta_vs_direct_jours: mttr_direct.map(|d| round1(mttr - d)),
+            }
+        })
+        .collect();
+
+    let distribution_reaffectations = par_reaffectations
+        .into_iter()
+        .map(|(n, count)| TrancheReaffectation {
+            reaffectations: n,
+            label: label_sauts(n, MAX_SAUTS_TRANCHE, "réaffectation"),
+            count,
+            pourcentage: pct(count, total),
+        })
+        .collect();
+
+    rebondissants.sort_by(|a, b| {
+        b.rebonds
+            .cmp(&a.rebonds)
+            .then(b.sauts.cmp(&a.sauts))
+            .then(b.reaffectations.cmp(&a.reaffectations))
+            .then(a.id.cmp(&b.id))
+    });
+    rebondissants.truncate(limite);
+
+    let moyenne_sur = |n: usize| {
+        if total == 0 {
+            0.0
+        } else {
+            (n as f64 / total as f64 * 100.0).round() / 100.0
+        }
+    };
+    EscaladeKpi {
+        total_tickets: total,
+        escalades,
+        taux_escalade: pct(escalades, total),
+        ping_pong,
+        taux_ping_pong: pct(ping_pong, total),
+        sauts_moyens: moyenne_sur(total_sauts),
+        reaffectations_moyennes: moyenne_sur(total_reaff),
+        transitions,
+        sankey: build_sankey(&chemins),
+        distribution_reaffectations,
+        impact_mttr,
+        jours_par_saut: pente(&points).map(|p| (p * 100.0).round() / 100.0),
+        pires_rebonds: rebondissants,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(
+        id: u64,
+        groupes: &[&str],
+        techniciens: &[&str],
+        delai: Option<f64>,
+    ) -> TicketParcours {
+        TicketParcours {
+            id,
+            titre: format!("Ticket {id}"),
+            groupes: groupes.iter().map(|s| s.to_string()).collect(),
+            techniciens: techniciens.iter().map(|s| s.to_string()).collect(),
+            est_vivant: delai.is_none(),
+            delai_resolution_jours: delai,
+        }
+    }
+
+    #[test]
+    fn test_path_cleanup_and_bounces() {
+        let g: Vec<String> = ["N1", " N1 ", "", "N2", "N1", "N2", "N3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let p = parcours(&g);
+        assert_eq!(p, vec!["N1", "N2", "N1", "N2", "N3"]);
+        assert_eq!(rebonds(&p), 2);
+        assert_eq!(rebonds(&["N1", "N2", "N3"]), 0);
+    }
+
+    #[test]
+    fn test_compute_escalation() {
+        let tickets = vec![
+            ticket(1, &["N1"], &["Alice"], Some(1.0)),
+            ticket(2, &["N1"], &["Alice"], Some(3.0)),
+            ticket(3, &["N1", "N2"], &["Alice", "Bob"], Some(5.0)),
+            ticket(4, &["N1", "N2", "N3"], &["Alice", "Bob", "Carl"], Some(9.0)),
+            ticket(5, &["N1", "N2", "N1"], &["Alice", "Bob", "Alice"], None),
+        ];
+        let kpi = compute_escalade(&tickets, 10);
+        assert_eq!(kpi.total_tickets, 5);
+        assert_eq!(kpi.escalades, 3);
+        assert_eq!(kpi.taux_escalade, 60.0);
+        assert_eq!(kpi.ping_pong, 1);
+        assert_eq!(kpi.pires_rebonds[0].id, 5);
+        assert_eq!(kpi.sauts_moyens, 1.0);
+        assert_eq!(kpi.reaffectations_moyennes, 1.0);
+
+        let n1_n2 = &kpi.transitions[0];
+        assert_eq!(
+            (n1_n2.source.as_str(), n1_n2.cible.as_str(), n1_n2.count),
+            ("N1", "N2", 3)
+        );
+        assert_eq!(n1_n2.pourcentage, 100.0);
+        let n2_sorties: Vec<f64> = kpi
+            .transitions
+            .iter()
+            .filter(|t| t.source == "N2")
+            .map(|t| t.pourcentage)
+            .collect();
+        assert_eq!(n2_sorties, vec![50.0, 50.0]);
+
+        // Sankey : N1 réapparaît à l'étape 3, nœud distinct de l'étape 1
+        let n1: Vec<usize> = kpi
+            .sankey
+            .nodes
+            .iter()
+            .filter(|n| n.label == "N1")
+            .map(|n| n.etape)
+            .collect();
+        assert_eq!(n1, vec![1, 3]);
+        assert!(kpi
+            .sankey
+            .links
+            .iter()
+            .all(|l| { kpi.sankey.nodes[l.source].etape + 1 == kpi.sankey.nodes[l.target].etape }));
+        assert_eq!(kpi.sankey.links.iter().map(|l| l.value).sum::<usize>(), 5);
+
+        // MTTR : direct 2 j, 1 saut 5 j, 2 sauts 9 j
+        assert_eq!(kpi.impact_mttr.len(), 3);
+        assert_eq!(kpi.impact_mttr[0].mttr_jours, 2.0);
+        assert_eq!(kpi.impact_mttr[2].delta_vs_direct_jours, Some(7.0));
+        assert_eq!(kpi.jours_par_saut, Some(3.45));
+    }
+
+    #[test]
+    fn test_empty() {
+        let kpi = compute_escalade(&[], 10);
+        assert_eq!(kpi.taux_escalade, 0.0);
+        assert!(kpi.sankey.nodes.is_empty());
+        assert_eq!(kpi.jours_par_saut, None);
+    }
+}
@@ -0,0 +1,470 @@
+//! Cartes de chaleur jour de semaine × heure : arrivées, résolutions et
+//! premières réponses, puis confrontation des arrivées au planning de
+//! présence configuré par groupe.
+//!
+//! Chaque cellule donne le nombre d'événements et la moyenne par occurrence
+//! du jour dans la fenêtre (un lundi sur 13 lundis, etc.). La première réponse
+//! n'est pas exportée par GLPI : comme pour la prise en charge du tableau de
+//! bord, la dernière modification des tickets terminés sert d'approximation.
+
+use std::collections::HashSet;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use super::calendar::parse_heure;
+use super::stats::round2;
+use crate::config::PlanningGroupe;
+
+const JOURS: usize = 7;
+const HEURES: usize = 24;
+/// Cellules sous-dotées remontées par groupe.
+const MAX_SOUS_DOTEES: usize = 5;
+
+pub const METHODE_PREMIERE_REPONSE: &str = "proxy_derniere_modification";
+
+/// Effectif présent heure par heure (lundi = 0).
+type Grille = [[f64; HEURES]; JOURS];
+
+impl PlanningGroupe {
+    /// Effectif moyen de chaque heure, au prorata des minutes couvertes.
+    fn grille(&self) -> Result<Grille, String> {
+        let mut grille = [[0.0; HEURES]; JOURS];
+        for p in &self.plages {
+            if !(1..=7).contains(&p.jour) {
+                return Err(format!(
+                    "{} : jour invalide {} (1 = lundi … 7 = dimanche)",
+                    self.groupe, p.jour
+                ));
+            }
+            if !p.effectif.is_finite() || p.effectif < 0.0 {
+                return Err(format!(
+                    "{} : effectif invalide {}",
+                    self.groupe, p.effectif
+                ));
+            }
+            let (debut, fin) = (parse_heure(&p.debut)?, parse_heure(&p.fin)?);
+            if debut >= fin {
+                return Err(format!(
+                    "{} : plage vide ou inversée {}-{}",
+                    self.groupe, p.debut, p.fin
+                ));
+            }
+            for (h, cellule) in grille[(p.jour - 1) as usize].iter_mut().enumerate() {
+                let (h_debut, h_fin) = (h as u32 * 60, h as u32 * 60 + 60);
+                let recouvrement = fin.min(h_fin).saturating_sub(debut.max(h_debut));
+                *cellule += p.effectif * recouvrement as f64 / 60.0;
+            }
+        }
+        Ok(grille)
+    }
+}
+
+/// Vérifie les plannings avant enregistrement.
+pub fn valider_plannings(plannings: &[PlanningGroupe]) -> Result<(), String> {
+    let mut vus = HashSet::new();
+    for p in plannings {
+        if p.groupe.trim().is_empty() {
+            return Err("Planning sans groupe".to_string());
+        }
+        if !vus.insert(p.groupe.as_str()) {
+            return Err(format!("Planning en double pour le groupe {}", p.groupe));
+        }
+        p.grille()?;
+    }
+    Ok(())
+}
+
+/// Filtres communs aux cartes de chaleur.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FiltreHoraire {
+    pub groupe: Option<String>,
+    pub type_ticket: Option<String>,
+    /// Préfixe : "Réseau" couvre "Réseau > Wifi".
+    pub categorie: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TicketHoraire {
+    pub groupe: Option<String>,
+    pub ouverture: NaiveDateTime,
+    pub resolution: Option<NaiveDateTime>,
+    pub premiere_reponse: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CelluleHoraire {
+    /// 1 = lundi … 7 = dimanche
+    pub jour: u32,
+    pub heure: u32,
+    pub nombre: i64,
+    /// Moyenne par occurrence du jour dans la fenêtre.
+    pub moyenne: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrilleHoraire {
+    pub total: i64,
+    /// 168 cellules, lundi 0h → dimanche 23h.
+    pub cellules: Vec<CelluleHoraire>,
+    pub pic: Option<CelluleHoraire>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CelluleCouverture {
+    pub jour: u32,
+    pub heure: u32,
+    pub arrivees_moyennes: f64,
+    pub effectif: f64,
+    pub arrivees_par_agent: Option<f64>,
+    pub part_arrivees_pct: f64,
+    pub part_effectif_pct: f64,
+    /// Part d'effectif − part d'arrivées : négatif = sous-doté.
+    pub ecart_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CouvertureGroupe {
+    pub groupe: String,
+    pub arrivees: i64,
+    /// Arrivées sur des heures sans personne planifiée.
+    pub arrivees_hors_couverture: i64,
+    pub pct_hors_couverture: f64,
+    pub heures_agent_semaine: f64,
+    pub cellules: Vec<CelluleCouverture>,
+    /// Heures les plus sous-dotées, de la pire à la moins pire.
+    pub sous_dotees: Vec<CelluleCouverture>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapKpi {
+    pub date_debut: Option<String>,
+    pub date_fin: Option<String>,
+    pub arrivees: GrilleHoraire,
+    pub resolutions: GrilleHoraire,
+    pub premieres_reponses: GrilleHoraire,
+    pub methode_premiere_reponse: String,
+    pub couverture: Vec<CouvertureGroupe>,
+}
+
+/// Part de `part` dans `total`, en pourcentage à deux décimales.
+fn part_pct(part: f64, total: f64) -> f64 {
+    if total > 0.0 {
+        round2(part / total * 100.0)
+    } else {
+        0.0
+    }
+}
+
+/// Nombre de lundis, mardis… entre deux dates incluses.
+fn occurrences_jours(debut: NaiveDate, fin: NaiveDate) -> [i64; JOURS] {
+    let mut occ = [0; JOURS];
+    let jours = (fin - debut).num_days() + 1;
+    for j in 0..jours.max(0) {
+        let d = debut + Duration::days(j);
+        occ[d.weekday().num_days_from_monday() as usize] += 1;
+    }
+    occ
+}
+
+fn compter<'a>(
+    dates: impl Iterator<Item = &'a NaiveDateTime>,
+    debut: NaiveDate,
+    fin: NaiveDate,
+) -> [[i64; HEURES]; JOURS] {
+    let mut comptes = [[0; HEURES]; JOURS];
+    for d in dates.filter(|d| (debut..=fin).contains(&d.date())) {
+        comptes[d.weekday().num_days_from_monday() as usize][d.hour() as usize] += 1;
+    }
+    comptes
+}
+
+fn grille_horaire(comptes: &[[i64; HEURES]; JOURS], occ: &[i64; JOURS]) -> GrilleHoraire {
+    let cellules: Vec<CelluleHoraire> = (0..JOURS)
+        .flat_map(|j| {
+            (0..HEURES).map(move |h| CelluleHoraire {
+                jour: j as u32 + 1,
+                heure: h as u32,
+                nombre: comptes[j][h],
+                moyenne: if occ[j] > 0 {
+                    round2(comptes[j][h] as f64 / occ[j] as f64)
+                } else {
+                    0.0
+                },
+            })
+        })
+        .collect();
+    let pic = cellules
+        .iter()
+        .filter(|c| c.nombre > 0)
+        .max_by(|a, b| a.moyenne.total_cmp(&b.moyenne))
+        .cloned();
+    GrilleHoraire {
+        total: comptes.iter().flatten().sum(),
+        cellules,
+        pic,
+    }
+}
+
+fn couverture_groupe(
+    planning: &PlanningGroupe,
+    effectifs: &Grille,
+    tickets: &[TicketHoraire],
+    debut: NaiveDate,
+    fin: NaiveDate,
+    occ: &[i64; JOURS],
+) -> CouvertureGroupe {
+    let arrivees = compter(
+        tickets
+            .iter()
+            .filter(|t| t.groupe.as_deref() == Some(planning.groupe.as_str()))
+            .map(|t| &t.ouverture),
+        debut,
+        fin,
+    );
+    let moyenne = |j: usize, h: usize| {
+        if occ[j] > 0 {
+            arrivees[j][h] as f64 / occ[j] as f64
+        } else {
+            0.0
+        }
+    };
+    let total_moyen: f64 = (0..JOURS)
+        .flat_map(|j| (0..HEURES).map(move |h| (j, h)))
+        .map(|(j, h)| moyenne(j, h))
+        .sum();
+    let heures_agent: f64 = effectifs.iter().flatten().sum();
+
+    let mut total = 0;
+    let mut hors_couverture = 0;
+    let mut cellules = Vec::with_capacity(JOURS * HEURES);
+    for j in 0..JOURS {
+        for h in 0..HEURES {
+            let (n, effectif) = (arrivees[j][h], effectifs[j][h]);
+            total += n;
+            if effectif <= 0.0 {
+                hors_couverture += n;
+            }
+            let (part_arrivees, part_effectif) = (
+                part_pct(moyenne(j, h), total_moyen),
+                part_pct(effectif, heures_agent),
+            );
+            cellules.push(CelluleCouverture {
+                jour: j as u32 + 1,
+                heure: h as u32,
+                arrivees_moyennes: round2(moyenne(j, h)),
+                effectif: round2(effectif),
+                arrivees_par_agent: (effectif > 0.0).then(|| round2(moyenne(j, h) / effectif)),
+                part_arrivees_pct: part_arrivees,
+                part_effectif_pct: part_effectif,
+                ecart_pct: round2(part_effectif - part_arrivees),
+            });
+        }
+    }
+
+    let mut sous_dotees: Vec<CelluleCouverture> = cellules
+        .iter()
+        .filter(|c| c.ecart_pct < 0.0)
+        .cloned()
+        .collect();
+    sous_dotees.sort_by(|a, b| a.ecart_pct.total_cmp(&b.ecart_pct));
+    sous_dotees.truncate(MAX_SOUS_DOTEES);
+
+    CouvertureGroupe {
+        groupe: planning.groupe.clone(),
+        arrivees: total,
+        arrivees_hors_couverture: hors_couverture,
+        pct_hors_couverture: part_pct(hors_couverture as f64, total as f64),
+        heures_agent_semaine: round2(heures_agent),
+        cellules,
+        sous_dotees,
+    }
+}
+
+/// Cartes de chaleur sur [debut, fin] et couverture des groupes planifiés.
+pub fn compute_heatmaps(
+    tickets: &[TicketHoraire],
+    debut: NaiveDate,
+    fin: NaiveDate,
+    plannings: &[PlanningGroupe],
+) -> HeatmapKpi {
+    let occ = occurrences_jours(debut, fin);
+    let arrivees = compter(tickets.iter().map(|t| &t.ouverture), debut, fin);
+    let resolutions = compter(
+        tickets.iter().filter_map(|t| t.resolution.as_ref()),
+        debut,
+        fin,
+    );
+    let reponses = compter(
+        tickets.iter().filter_map(|t| t.premiere_reponse.as_ref()),
+        debut,
+        fin,
+    );
+
+    let couverture = plannings
+        .iter()
+        .filter_map(|p| match p.grille() {
+            Ok(g) => Some(couverture_groupe(p, &g, tickets, debut, fin, &occ)),
+            Err(e) => {
+                log::warn!("Planning ignoré: {e}");
+                None
+            }
+        })
+        .collect();
+
+    HeatmapKpi {
+        date_debut: Some(debut.format("%Y-%m-%d").to_string()),
+        date_fin: Some(fin.format("%Y-%m-%d").to_string()),
+        arrivees: grille_horaire(&arrivees, &occ),
+        resolutions: grille_horaire(&resolutions, &occ),
+        premieres_reponses: grille_horaire(&reponses, &occ),
+        methode_premiere_reponse: METHODE_PREMIERE_REPONSE.to_string(),
+        couverture,
+    }
+}
+
+/// Cartes de chaleur de l'import, fenêtre par défaut = étendue des ouvertures.
+pub fn load_heatmap_kpi(
+    conn: &rusqlite::Connection,
+    import_id: i64,
+    filtre: &FiltreHoraire,
+    date_debut: Option<&str>,
+    date_fin: Option<&str>,
+) -> Result<HeatmapKpi, rusqlite::Error> {
+    use crate::db::queries;
+    let tickets = queries::get_tickets_horaires(conn, import_id, filtre)?;
+    let borne = |s: Option<&str>| {
+        s.and_then(|s| NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d").ok())
+    };
+    let ouvertures = tickets.iter().map(|t| t.ouverture.date());
+    let (Some(debut), Some(fin)) = (
+        borne(date_debut).or_else(|| ouvertures.clone().min()),
+        borne(date_fin).or_else(|| ouvertures.max()),
+    ) else {
+        return Ok(HeatmapKpi {
+            methode_premiere_reponse: METHODE_PREMIERE_REPONSE.to_string(),
+            ..Default::default()
+        });
+    };
+
+    let plannings: Vec<PlanningGroupe> = crate::config::get_config_from_db(conn)?
+        .plannings_groupes
+        .into_iter()
+        .filter(|p| filtre.groupe.as_ref().is_none_or(|g| *g == p.groupe))
+        .collect();
+    Ok(compute_heatmaps(&tickets, debut, fin, &plannings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PlageEffectif;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn ticket(groupe: &str, ouverture: &str, resolution: Option<&str>) -> TicketHoraire {
+        TicketHoraire {
+            groupe: Some(groupe.to_string()),
+            ouverture: dt(ouverture),
+            resolution: resolution.map(dt),
+            premiere_reponse: resolution.map(dt),
+        }
+    }
+
+    fn plage(jour: u32, debut: &str, fin: &str, effectif: f64) -> PlageEffectif {
+        PlageEffectif {
+            jour,
+            debut: debut.into(),
+            fin: fin.into(),
+            effectif,
+        }
+    }
+
+    #[test]
+    fn test_heatmap_cells_and_weekday_average() {
+        // 2025-01-06 et 2025-01-13 sont des lundis : 2 occurrences sur la fenêtre
+        let tickets = vec![
+            ticket("Hotline", "2025-01-06 09:15", Some("2025-01-07 14:00")),
+            ticket("Hotline", "2025-01-13 09:50", None),
+            ticket("Hotline", "2025-01-13 18:05", None),
+            ticket("Hotline", "2025-02-03 09:00", None), // hors fenêtre
+        ];
+        let kpi = compute_heatmaps(&tickets, d("2025-01-06"), d("2025-01-19"), &[]);
+        assert_eq!(kpi.arrivees.cellules.len(), 168);
+        assert_eq!(kpi.arrivees.total, 3);
+        let lundi_9h = &kpi.arrivees.cellules[9];
+        assert_eq!((lundi_9h.jour, lundi_9h.heure, lundi_9h.nombre), (1, 9, 2));
+        assert_eq!(lundi_9h.moyenne, 1.0);
+        assert_eq!(kpi.arrivees.pic.as_ref().map(|c| c.heure), Some(9));
+        // Mardi 14h
+        assert_eq!(kpi.resolutions.cellules[24 + 14].nombre, 1);
+        assert_eq!(kpi.methode_premiere_reponse, METHODE_PREMIERE_REPONSE);
+    }
+
+    #[test]
+    fn test_staffing_coverage() {
+        let planning = PlanningGroupe {
+            groupe: "Hotline".into(),
+            plages: vec![
+                plage(1, "08:30", "10:00", 2.0),
+                plage(1, "10:00", "12:00", 1.0),
+            ],
+        };
+        let grille = planning.grille().unwrap();
+        assert_eq!(grille[0][8], 1.0); // 30 min à 2 agents
+        assert_eq!(grille[0][9], 2.0);
+        assert_eq!(grille[0][11], 1.0);
+
+        let tickets = vec![
+            ticket("Hotline", "2025-01-06 09:15", None),
+            ticket("Hotline", "2025-01-06 09:40", None),
+            ticket("Hotline", "2025-01-06 09:55", None),
+            ticket("Hotline", "2025-01-06 13:10", None),
+            ticket("Réseau", "2025-01-06 09:10", None),
+        ];
+        let kpi = compute_heatmaps(&tickets, d("2025-01-06"), d("2025-01-12"), &[planning]);
+        let c = &kpi.couverture[0];
+        assert_eq!(c.arrivees, 4);
+        assert_eq!(c.arrivees_hors_couverture, 1);
+        assert_eq!(c.pct_hors_couverture, 25.0);
+        assert_eq!(c.heures_agent_semaine, 5.0);
+        let h9 = &c.cellules[9];
+        assert_eq!(h9.arrivees_par_agent, Some(1.5));
+        assert_eq!((h9.part_arrivees_pct, h9.part_effectif_pct), (75.0, 40.0));
+        // 13h : 25 % des arrivées sans personne, la plus sous-dotée après 9h
+        assert_eq!(c.sous_dotees[0].heure, 9);
+        assert_eq!(c.sous_dotees[1].heure, 13);
+    }
+
+    #[test]
+    fn test_validate_plannings() {
+        let ok = PlanningGroupe {
+            groupe: "Hotline".into(),
+            plages: vec![plage(1, "08:00", "12:00", 2.0)],
+        };
+        assert!(valider_plannings(std::slice::from_ref(&ok)).is_ok());
+        assert!(valider_plannings(&[ok.clone(), ok.clone()]).is_err());
+        let inverse = PlanningGroupe {
+            plages: vec![plage(1, "12:00", "08:00", 2.0)],
+            ..ok.clone()
+        };
+        assert!(valider_plannings(&[inverse]).is_err());
+        let jour_invalide = PlanningGroupe {
+            plages: vec![plage(8, "08:00", "12:00", 1.0)],
+            ..ok
+        };
+        assert!(valider_plannings(&[jour_invalide]).is_err());
+    }
+}
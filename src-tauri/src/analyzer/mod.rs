@@ -1,6 +1,26 @@
+pub mod backlog;
 pub mod bilan;
+pub mod buckets;
+pub mod calendar;
+pub mod category_tree;
+pub mod capacity;
 pub mod classifier;
+pub mod cohort;
+pub mod comparison;
 pub mod dashboard;
+pub mod diff;
+pub mod escalation;
+pub mod heatmap;
+pub mod priority;
+pub mod reopen;
+pub mod requesters;
+pub mod rules;
+pub mod sla;
+pub mod spc;
 pub mod stats;
 pub mod stock;
+pub mod survival;
 pub mod temporal;
+pub mod thresholds;
+pub mod vendor;
+pub mod workload;
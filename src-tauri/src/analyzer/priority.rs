@@ -0,0 +1,390 @@
+//! Matrice urgence × impact × priorité et cohérence des priorités.
+//!
+//! GLPI calcule la priorité à partir de l'urgence et de l'impact
+//! (`MATRICE_GLPI`, valeurs par défaut). Un ticket est incohérent quand sa
+//! priorité diffère de celle de la matrice. Sans impact exporté, la priorité
+//! doit seulement rester dans la plage atteignable pour son urgence. La
+//! priorité « Majeure » (6), posée à la main, n'est pas évaluée.
+//!
+//! La dérive suit, par période d'ouverture, la répartition des priorités,
+//! la priorité moyenne et la part de priorités hautes (≥ 4).
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::stats::{moyenne, pct, pente, percentile, round1, round2};
+use super::temporal::{generate_period_keys, period_key};
+
+/// Priorité par défaut de GLPI : `MATRICE_GLPI[urgence - 1][impact - 1]`.
+pub const MATRICE_GLPI: [[i32; 5]; 5] = [
+    [1, 1, 2, 2, 2],
+    [1, 2, 2, 3, 3],
+    [2, 2, 3, 4, 4],
+    [2, 3, 4, 4, 5],
+    [2, 3, 4, 5, 5],
+];
+pub const PRIORITE_MAJEURE: i32 = 6;
+/// Priorité à partir de laquelle un ticket compte comme « haute ».
+const SEUIL_PRIORITE_HAUTE: i32 = 4;
+const MAX_INCOHERENCES: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct TicketPriorite {
+    pub id: u64,
+    pub titre: String,
+    pub priorite: Option<i32>,
+    pub urgence: Option<i32>,
+    pub impact: Option<i32>,
+    pub ouverture: NaiveDateTime,
+    pub est_vivant: bool,
+    pub delai_resolution_jours: Option<f64>,
+    pub technicien: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrioriteKpi {
+    /// Au moins un ticket porte un impact : cohérence évaluée sur la matrice
+    /// exacte plutôt que sur la plage par urgence.
+    pub impact_disponible: bool,
+    pub total_tickets: usize,
+    pub sans_priorite: usize,
+    pub sans_urgence: usize,
+    /// Lignes = urgence, colonnes = impact.
+    pub urgence_impact: Vec<CelluleMatrice>,
+    /// Lignes = priorité, colonnes = urgence.
+    pub priorite_urgence: Vec<CelluleMatrice>,
+    pub coherence: CoherencePriorite,
+    pub derive: Vec<DerivePriorite>,
+    /// Évolution de la priorité moyenne par période (moindres carrés).
+    pub pente_priorite_moyenne: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CelluleMatrice {
+    pub ligne: i32,
+    pub colonne: i32,
+    pub tickets: usize,
+    pub vivants: usize,
+    pub termines: usize,
+    pub mttr_moyen_jours: Option<f64>,
+    pub mttr_median_jours: Option<f64>,
+    /// Priorité GLPI attendue (matrice urgence × impact uniquement).
+    pub priorite_attendue: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoherencePriorite {
+    pub evalues: usize,
+    pub coherents: usize,
+    pub incoherents: usize,
+    pub taux_incoherence: f64,
+    /// Priorité au-dessus de la matrice.
+    pub surclasses: usize,
+    /// Priorité en dessous de la matrice.
+    pub sousclasses: usize,
+    pub majeures: usize,
+    /// Incohérents, les plus gros écarts d'abord (tronqué).
+    pub tickets: Vec<TicketIncoherent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketIncoherent {
+    pub id: u64,
+    pub titre: String,
+    pub est_vivant: bool,
+    pub technicien: Option<String>,
+    pub priorite: i32,
+    pub urgence: i32,
+    pub impact: Option<i32>,
+    pub priorite_attendue_min: i32,
+    pub priorite_attendue_max: i32,
+    /// Positif : surclassé ; négatif : sous-classé.
+    pub ecart: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivePriorite {
+    pub period_key: String,
+    pub period_label: String,
+    pub total: usize,
+    /// Effectifs des priorités 1 à 6.
+    pub par_priorite: Vec<usize>,
+    pub priorite_moyenne: Option<f64>,
+    pub part_haute: f64,
+    pub taux_incoherence: Option<f64>,
+}
+
+/// Plage de priorités admise pour une urgence (et un impact s'il est connu).
+pub fn priorites_attendues(urgence: i32, impact: Option<i32>) -> Option<(i32, i32)> {
+    let ligne = MATRICE_GLPI.get(usize::try_from(urgence - 1).ok()?)?;
+    match impact.and_then(|i| ligne.get(usize::try_from(i - 1).ok()?)) {
+        Some(&p) => Some((p, p)),
+        None => Some((*ligne.iter().min()?, *ligne.iter().max()?)),
+    }
+}
+
+/// Écart à la plage attendue ; `None` si non évaluable, `Some(0)` si cohérent.
+fn ecart(t: &TicketPriorite) -> Option<(i32, (i32, i32))> {
+    let priorite = t.priorite.filter(|p| *p != PRIORITE_MAJEURE)?;
+    let (min, max) = priorites_attendues(t.urgence?, t.impact)?;
+    let e = if priorite > max {
+        priorite - max
+    } else if priorite < min {
+        priorite - min
+    } else {
+        0
+    };
+    Some((e, (min, max)))
+}
+
+fn cellules<F>(tickets: &[TicketPriorite], axes: F, attendue: bool) -> Vec<CelluleMatrice>
+where
+    F: Fn(&TicketPriorite) -> Option<(i32, i32)>,
+{
+    let mut groupes: BTreeMap<(i32, i32), Vec<&TicketPriorite>> = BTreeMap::new();
+    for t in tickets {
+        if let Some(cle) = axes(t) {
+            groupes.entry(cle).or_default().push(t);
+        }
+    }
+    groupes
+        .into_iter()
+        .map(|((ligne, colonne), groupe)| {
+            let delais: Vec<f64> = groupe
+                .iter()
+                .filter(|t| !t.est_vivant)
+                .filter_map(|t| t.delai_resolution_jours)
+                .collect();
+            let vivants = groupe.iter().filter(|t| t.est_vivant).count();
+            CelluleMatrice {
+                ligne,
+                colonne,
+                tickets: groupe.len(),
+                vivants,
+                termines: groupe.len() - vivants,
+                mttr_moyen_jours: (!delais.is_empty()).then(|| round1(moyenne(&delais))),
+                mttr_median_jours: (!delais.is_empty()).then(|| round1(percentile(&delais, 50.0))),
+                priorite_attendue: if attendue {
+                    priorites_attendues(ligne, Some(colonne)).map(|p| p.0)
+                } else {
+                    None
+                },
+            }
+        })
+        .collect()
+}
+
+fn coherence(tickets: &[TicketPriorite]) -> CoherencePriorite {
+    let mut c = CoherencePriorite {
+        majeures: tickets
+            .iter()
+            .filter(|t| t.priorite == Some(PRIORITE_MAJEURE))
+            .count(),
+        ..Default::default()
+    };
+    for t in tickets {
+        let Some((e, (min, max))) = ecart(t) else {
+            continue;
+        };
+        c.evalues += 1;
+        match e.signum() {
+            0 => c.coherents += 1,
+            1 => c.surclasses += 1,
+            _ => c.sousclasses += 1,
+        }
+        if e != 0 {
+            c.tickets.push(TicketIncoherent {
+                id: t.id,
+                titre: t.titre.clone(),
+                est_vivant: t.est_vivant,
+                technicien: t.technicien.clone(),
+                priorite: t.priorite.unwrap_or_default(),
+                urgence: t.urgence.unwrap_or_default(),
+                impact: t.impact,
+                priorite_attendue_min: min,
+                priorite_attendue_max: max,
+                ecart: e,
+            });
+        }
+    }
+    c.incoherents = c.surclasses + c.sousclasses;
+    c.taux_incoherence = pct(c.incoherents, c.evalues);
+    c.tickets.sort_by(|a, b| {
+        b.ecart
+            .abs()
+            .cmp(&a.ecart.abs())
+            .then(b.est_vivant.cmp(&a.est_vivant))
+            .then(a.id.cmp(&b.id))
+    });
+    c.tickets.truncate(MAX_INCOHERENCES);
+    c
+}
+
+fn derive(tickets: &[TicketPriorite], granularity: &str) -> Vec<DerivePriorite> {
+    let (Some(debut), Some(fin)) = (
+        tickets.iter().map(|t| t.ouverture).min(),
+        tickets.iter().map(|t| t.ouverture).max(),
+    ) else {
+        return Vec::new();
+    };
+    let mut par_periode: BTreeMap<String, Vec<&TicketPriorite>> = BTreeMap::new();
+    for t in tickets {
+        par_periode
+            .entry(period_key(t.ouverture, granularity))
+            .or_default()
+            .push(t);
+    }
+    generate_period_keys(debut, fin, granularity)
+        .into_iter()
+        .map(|(key, label, _, _)| {
+            let groupe = par_periode.get(&key).map(Vec::as_slice).unwrap_or_default();
+            let mut par_priorite = vec![0; PRIORITE_MAJEURE as usize];
+            let mut valeurs = Vec::new();
+            for p in groupe.iter().filter_map(|t| t.priorite) {
+                if let Some(n) = usize::try_from(p - 1)
+                    .ok()
+                    .and_then(|i| par_priorite.get_mut(i))
+                {
+                    *n += 1;
+                    valeurs.push(p as f64);
+                }
+            }
+            let hautes = valeurs
+                .iter()
+                .filter(|p| **p >= SEUIL_PRIORITE_HAUTE as f64)
+                .count();
+            let ecarts: Vec<i32> = groupe
+                .iter()
+                .filter_map(|t| ecart(t))
+                .map(|e| e.0)
+                .collect();
+            DerivePriorite {
+                period_key: key,
+                period_label: label,
+                total: groupe.len(),
+                par_priorite,
+                priorite_moyenne: (!valeurs.is_empty()).then(|| round2(moyenne(&valeurs))),
+                part_haute: pct(hautes, valeurs.len()),
+                taux_incoherence: (!ecarts.is_empty())
+                    .then(|| pct(ecarts.iter().filter(|e| **e != 0).count(), ecarts.len())),
+            }
+        })
+        .collect()
+}
+
+pub fn compute_priorite_kpi(tickets: &[TicketPriorite], granularity: &str) -> PrioriteKpi {
+    let derive = derive(tickets, granularity);
+    let points: Vec<(f64, f64)> = derive
+        .iter()
+        .enumerate()
+        .filter_map(|(i, d)| d.priorite_moyenne.map(|m| (i as f64, m)))
+        .collect();
+    PrioriteKpi {
+        impact_disponible: tickets.iter().any(|t| t.impact.is_some()),
+        total_tickets: tickets.len(),
+        sans_priorite: tickets.iter().filter(|t| t.priorite.is_none()).count(),
+        sans_urgence: tickets.iter().filter(|t| t.urgence.is_none()).count(),
+        urgence_impact: cellules(tickets, |t| Some((t.urgence?, t.impact?)), true),
+        priorite_urgence: cellules(tickets, |t| Some((t.priorite?, t.urgence?)), false),
+        coherence: coherence(tickets),
+        pente_priorite_moyenne: pente(&points).map(|p| (p * 1000.0).round() / 1000.0),
+        derive,
+    }
+}
+
+/// Indicateurs de priorité des tickets ouverts sur la période.
+pub fn load_priorite_kpi(
+    conn: &rusqlite::Connection,
+    import_id: i64,
+    date_debut: Option<&str>,
+    date_fin: Option<&str>,
+    granularity: &str,
+) -> Result<PrioriteKpi, rusqlite::Error> {
+    let tickets =
+        crate::db::queries::get_tickets_priorite(conn, import_id, date_debut, date_fin, false)?;
+    Ok(compute_priorite_kpi(&tickets, granularity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(
+        id: u64,
+        ouverture: &str,
+        priorite: i32,
+        urgence: i32,
+        impact: Option<i32>,
+        delai: Option<f64>,
+    ) -> TicketPriorite {
+        TicketPriorite {
+            id,
+            titre: format!("T{id}"),
+            priorite: Some(priorite),
+            urgence: Some(urgence),
+            impact,
+            ouverture: NaiveDateTime::parse_from_str(ouverture, "%Y-%m-%d %H:%M:%S").unwrap(),
+            est_vivant: delai.is_none(),
+            delai_resolution_jours: delai,
+            technicien: None,
+        }
+    }
+
+    #[test]
+    fn test_expected_priority() {
+        assert_eq!(priorites_attendues(4, Some(5)), Some((5, 5)));
+        assert_eq!(priorites_attendues(1, Some(1)), Some((1, 1)));
+        // Sans impact : plage de la ligne
+        assert_eq!(priorites_attendues(3, None), Some((2, 4)));
+        assert_eq!(priorites_attendues(0, None), None);
+        assert_eq!(priorites_attendues(6, Some(3)), None);
+    }
+
+    #[test]
+    fn test_matrix_and_consistency() {
+        let tickets = vec![
+            ticket(1, "2026-01-05 09:00:00", 3, 3, Some(3), Some(2.0)),
+            ticket(2, "2026-01-06 09:00:00", 3, 3, Some(3), Some(4.0)),
+            ticket(3, "2026-01-07 09:00:00", 5, 3, Some(3), None),
+            ticket(4, "2026-02-01 09:00:00", 1, 5, Some(5), Some(1.0)),
+            ticket(5, "2026-02-02 09:00:00", PRIORITE_MAJEURE, 2, Some(1), None),
+            // Sans impact : 4 admis pour l'urgence 4, 1 ne l'est pas
+            ticket(6, "2026-02-03 09:00:00", 4, 4, None, Some(3.0)),
+            ticket(7, "2026-02-04 09:00:00", 1, 4, None, None),
+        ];
+        let kpi = compute_priorite_kpi(&tickets, "month");
+        assert!(kpi.impact_disponible);
+
+        let cellule = kpi
+            .urgence_impact
+            .iter()
+            .find(|c| (c.ligne, c.colonne) == (3, 3))
+            .unwrap();
+        assert_eq!((cellule.tickets, cellule.vivants), (3, 1));
+        assert_eq!(cellule.mttr_median_jours, Some(3.0));
+        assert_eq!(cellule.priorite_attendue, Some(3));
+
+        let c = &kpi.coherence;
+        assert_eq!(c.evalues, 6);
+        assert_eq!(c.majeures, 1);
+        assert_eq!((c.surclasses, c.sousclasses), (1, 2));
+        assert_eq!(c.taux_incoherence, 50.0);
+        // Plus gros écart d'abord : ticket 4 (1 au lieu de 5)
+        assert_eq!(c.tickets[0].id, 4);
+        assert_eq!(c.tickets[0].ecart, -4);
+        assert_eq!(c.tickets[1].id, 3);
+
+        assert_eq!(kpi.derive.len(), 2);
+        assert_eq!(kpi.derive[0].priorite_moyenne, Some(3.67));
+        assert_eq!(kpi.derive[1].par_priorite, vec![2, 0, 0, 1, 0, 1]);
+        assert_eq!(kpi.derive[1].part_haute, 50.0);
+        assert!(kpi.pente_priorite_moyenne.unwrap() < 0.0);
+    }
+}
@@ -0,0 +1,358 @@
+//! Réouvertures et résolution au premier contact (FCR).
+//!
+//! Une résolution est comptée comme réouverte quand :
+//! - l'historique des imports montre le ticket terminé puis de nouveau vivant
+//!   dans un import ultérieur (jusqu'à l'import analysé) ;
+//! - ou un suivi mentionne explicitement une réouverture effective (« ticket
+//!   réouvert », « réouverture ») : les formes niées et les invitations des
+//!   notifications (« vous pouvez réouvrir le ticket ») ne comptent pas.
+//!
+//! Le FCR porte sur les tickets terminés : résolus en un tour de suivi au
+//! plus, sans réouverture. Les tours sont comptés sur le texte des suivis
+//! (cf. `parser::suivis`), à défaut sur `nombre_suivis`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
+
+use chrono::NaiveDateTime;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::stats::pct;
+use super::temporal::period_key;
+use crate::nlp::preprocessing::strip_html;
+use crate::parser::suivis::decouper_suivis;
+
+const NON_ASSIGNE: &str = "(non assigné)";
+const NON_RENSEIGNE: &str = "(non renseigné)";
+/// Tours de suivi maximum pour une résolution au premier contact.
+const MAX_TOURS_FCR: usize = 1;
+
+/// Réouverture effective : participe ou nom, pas l'infinitif des invitations.
+static REOUVERTURE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(r[ée]ouv(ert|erte|erture)|rouvert|rouverte|reopened)\b")
+        .expect("regex réouverture invalide")
+});
+
+/// Négation ou formule de notification dans la même proposition.
+static EXCLUSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(\bne\b|\bn['’]|\bpas\b|\bsans\b|\bjamais\b|\bnot\b|\bpouvez\b|\bpourrez\b|\bpossibilit[ée]\b|\bpossible\b|h[ée]sitez)",
+    )
+    .expect("regex exclusion réouverture invalide")
+});
+
+/// Origine de la détection d'une réouverture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceReouverture {
+    Historique,
+    Suivis,
+}
+
+/// Une résolution observée (ticket terminé dans l'import analysé, ou terminé
+/// dans un import antérieur puis rouvert).
+#[derive(Debug, Clone)]
+pub struct ResolutionObservee {
+    pub date: NaiveDateTime,
+    /// Terminé dans l'import analysé (éligible au FCR).
+    pub termine: bool,
+    pub reouverture: Option<SourceReouverture>,
+    pub tours_suivi: usize,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub categorie: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualiteResolutionKpi {
+    /// Plusieurs imports disponibles : détection par l'historique possible.
+    pub historique_disponible: bool,
+    pub total_resolutions: usize,
+    pub reouvertes: usize,
+    pub taux_reouverture: f64,
+    pub reouvertes_historique: usize,
+    pub reouvertes_suivis: usize,
+    pub total_termines: usize,
+    pub fcr_count: usize,
+    pub taux_fcr: f64,
+    pub par_technicien: Vec<QualiteDimension>,
+    pub par_groupe: Vec<QualiteDimension>,
+    pub par_categorie: Vec<QualiteDimension>,
+    pub trend: Vec<QualiteTrend>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualiteDimension {
+    pub label: String,
+    pub total_resolutions: usize,
+    pub reouvertes: usize,
+    pub taux_reouverture: f64,
+    pub total_termines: usize,
+    pub fcr_count: usize,
+    pub taux_fcr: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QualiteTrend {
+    pub periode: String,
+    pub total_resolutions: usize,
+    pub reouvertes: usize,
+    pub taux_reouverture: f64,
+    pub total_termines: usize,
+    pub fcr_count: usize,
+    pub taux_fcr: f64,
+}
+
+/// Un suivi mentionne-t-il une réouverture ? Chaque suivi est examiné
+/// proposition par proposition.
+pub fn mentionne_reouverture(suivis: &str) -> bool {
+    decouper_suivis(suivis).into_iter().any(|suivi| {
+        strip_html(suivi)
+            .split(['.', ',', ';', ':', '!', '?', '\n'])
+            .any(|p| REOUVERTURE_REGEX.is_match(p) && !EXCLUSION_REGEX.is_match(p))
+    })
+}
+
+/// Tours de suivi effectifs (segments non vides) ; `nombre_suivis` si le
+/// texte des suivis n'a pas été exporté.
+pub fn tours_de_suivi(suivis: &str, nombre_suivis: Option<i64>) -> usize {
+    let tours = decouper_suivis(suivis)
+        .into_iter()
+        .filter(|s| !strip_html(s).trim().is_empty())
+        .count();
+    if tours == 0 {
+        nombre_suivis.unwrap_or(0).max(0) as usize
+    } else {
+        tours
+    }
+}
+
+#[derive(Default)]
+struct Compteur {
+    resolutions: usize,
+    reouvertes: usize,
+    termines: usize,
+    fcr: usize,
+}
+
+impl Compteur {
+    fn ajouter(&mut self, r: &ResolutionObservee) {
+        self.resolutions += 1;
+        if r.reouverture.is_some() {
+            self.reouvertes += 1;
+        }
+        if r.termine {
+            self.termines += 1;
+            if est_fcr(r) {
+                self.fcr += 1;
+            }
+        }
+    }
+}
+
+fn est_fcr(r: &ResolutionObservee) -> bool {
+    r.termine && r.reouverture.is_none() && r.tours_suivi <= MAX_TOURS_FCR
+}
+
+fn par_dimension(
+    resolutions: &[ResolutionObservee],
+    cle: impl Fn(&ResolutionObservee) -> &Option<String>,
+    defaut: &str,
+) -> Vec<QualiteDimension> {
+    let mut map: HashMap<String, Compteur> = HashMap::new();
+    for r in resolutions {
+        let label = cle(r)
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .unwrap_or(defaut);
+        map.entry(label.to_string()).or_default().ajouter(r);
+    }
+    let mut result: Vec<QualiteDimension> = map
+        .into_iter()
+        .map(|(label, c)| QualiteDimension {
+            label,
+            total_resolutions: c.resolutions,
+            reouvertes: c.reouvertes,
+            taux_reouverture: pct(c.reouvertes, c.resolutions),
+            total_termines: c.termines,
+            fcr_count: c.fcr,
+            taux_fcr: pct(c.fcr, c.termines),
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.total_resolutions
+            .cmp(&a.total_resolutions)
+            .then(a.label.cmp(&b.label))
+    });
+    result
+}
+
+pub fn compute_qualite_kpi(
+    resolutions: &[ResolutionObservee],
+    historique_disponible: bool,
+    granularity: &str,
+) -> QualiteResolutionKpi {
+    let mut global = Compteur::default();
+    let mut trend: BTreeMap<String, Compteur> = BTreeMap::new();
+    let (mut historique, mut suivis) = (0, 0);
+    for r in resolutions {
+        global.ajouter(r);
+        trend
+            .entry(period_key(r.date, granularity))
+            .or_default()
+            .ajouter(r);
+        match r.reouverture {
+            Some(SourceReouverture::Historique) => historique += 1,
+            Some(SourceReouverture::Suivis) => suivis += 1,
+            None => {}
+        }
+    }
+
+    QualiteResolutionKpi {
+        historique_disponible,
+        total_resolutions: global.resolutions,
+        reouvertes: global.reouvertes,
+        taux_reouverture: pct(global.reouvertes, global.resolutions),
+        reouvertes_historique: historique,
+        reouvertes_suivis: suivis,
+        total_termines: global.termines,
+        fcr_count: global.fcr,
+        taux_fcr: pct(global.fcr, global.termines),
+        par_technicien: par_dimension(resolutions, |r| &r.technicien, NON_ASSIGNE),
+        par_groupe: par_dimension(resolutions, |r| &r.groupe, NON_RENSEIGNE),
+        par_categorie: par_dimension(resolutions, |r| &r.categorie, NON_RENSEIGNE),
+        trend: trend
+            .into_iter()
+            .map(|(periode, c)| QualiteTrend {
+                periode,
+                total_resolutions: c.resolutions,
+                reouvertes: c.reouvertes,
+                taux_reouverture: pct(c.reouvertes, c.resolutions),
+                total_termines: c.termines,
+                fcr_count: c.fcr,
+                taux_fcr: pct(c.fcr, c.termines),
+            })
+            .collect(),
+    }
+}
+
+/// Charge les résolutions d'un import (fenêtre sur la date de résolution,
+/// bornes incluses) puis calcule réouvertures et FCR.
+pub fn load_qualite_kpi(
+    conn: &rusqlite::Connection,
+    import_id: i64,
+    date_debut: Option<&str>,
+    date_fin: Option<&str>,
+    granularity: &str,
+) -> Result<QualiteResolutionKpi, rusqlite::Error> {
+    use crate::db::queries;
+    let resolutions = queries::get_resolutions_observees(conn, import_id, date_debut, date_fin)?;
+    let historique = queries::count_imports_before(conn, import_id)? > 0;
+    Ok(compute_qualite_kpi(&resolutions, historique, granularity))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn resolution(
+        date: &str,
+        termine: bool,
+        reouverture: Option<SourceReouverture>,
+        tours: usize,
+        technicien: &str,
+    ) -> ResolutionObservee {
+        ResolutionObservee {
+            date: dt(date),
+            termine,
+            reouverture,
+            tours_suivi: tours,
+            technicien: Some(technicien.to_string()),
+            groupe: Some("Support".to_string()),
+            categorie: None,
+        }
+    }
+
+    #[test]
+    fn test_reopen_markers() {
+        assert!(mentionne_reouverture("Ticket réouvert suite à rappel"));
+        assert!(mentionne_reouverture("<p>Reouverture demandée</p>"));
+        assert!(mentionne_reouverture("L'utilisateur a rouvert le ticket"));
+        assert!(mentionne_reouverture("Ticket reopened by requester"));
+        assert!(!mentionne_reouverture("Bureau ouvert le lundi"));
+        assert!(!mentionne_reouverture(""));
+    }
+
+    #[test]
+    fn test_reopen_false_positives() {
+        assert!(!mentionne_reouverture("Merci de ne pas réouvrir ce ticket"));
+        assert!(!mentionne_reouverture("Le ticket n'a pas été réouvert"));
+        assert!(!mentionne_reouverture(
+            "Votre ticket est résolu. Vous pouvez réouvrir le ticket sous 7 jours."
+        ));
+        assert!(!mentionne_reouverture(
+            "Réouverture possible depuis le portail<hr>Solution validée"
+        ));
+        // Une vraie réouverture dans un autre suivi reste détectée
+        assert!(mentionne_reouverture(
+            "Vous pouvez réouvrir le ticket<hr>Ticket réouvert, pas de retour"
+        ));
+    }
+
+    #[test]
+    fn test_followup_turns() {
+        assert_eq!(tours_de_suivi("Premier\n\n<p></p>\nSecond", Some(7)), 2);
+        assert_eq!(
+            tours_de_suivi("Premier\nsur deux lignes<hr>Second", None),
+            2
+        );
+        assert_eq!(tours_de_suivi("", Some(3)), 3);
+        assert_eq!(tours_de_suivi("", None), 0);
+    }
+
+    #[test]
+    fn test_compute_rates_and_trend() {
+        use SourceReouverture::*;
+        let data = vec![
+            resolution("2025-01-10 10:00:00", true, None, 1, "Alice"),
+            resolution("2025-01-12 10:00:00", true, None, 3, "Alice"),
+            resolution("2025-01-20 10:00:00", true, Some(Suivis), 1, "Bob"),
+            resolution("2025-02-05 10:00:00", false, Some(Historique), 2, "Bob"),
+            resolution("2025-02-10 10:00:00", true, None, 0, ""),
+        ];
+        let kpi = compute_qualite_kpi(&data, true, "month");
+        assert_eq!(kpi.total_resolutions, 5);
+        assert_eq!(kpi.reouvertes, 2);
+        assert_eq!(kpi.taux_reouverture, 40.0);
+        assert_eq!(kpi.reouvertes_historique, 1);
+        assert_eq!(kpi.reouvertes_suivis, 1);
+        // FCR : terminés seulement, réouverts exclus
+        assert_eq!(kpi.total_termines, 4);
+        assert_eq!(kpi.fcr_count, 2);
+        assert_eq!(kpi.taux_fcr, 50.0);
+
+        let bob = kpi
+            .par_technicien
+            .iter()
+            .find(|d| d.label == "Bob")
+            .unwrap();
+        assert_eq!(bob.taux_reouverture, 100.0);
+        assert_eq!(bob.total_termines, 1);
+        assert_eq!(bob.taux_fcr, 0.0);
+        assert!(kpi.par_technicien.iter().any(|d| d.label == NON_ASSIGNE));
+        assert_eq!(kpi.par_categorie[0].label, NON_RENSEIGNE);
+
+        assert_eq!(kpi.trend.len(), 2);
+        assert_eq!(kpi.trend[0].periode, "2025-01");
+        assert_eq!(kpi.trend[0].taux_reouverture, 33.3);
+        assert_eq!(kpi.trend[1].taux_fcr, 100.0);
+    }
+}
@@ -0,0 +1,450 @@
+//! Indicateurs par demandeur, ou par service via l'annuaire optionnel.
+//!
+//! - volumes, tickets vivants et MTTR par demandeur (ou service) ;
+//! - demandes répétées : tickets d'un même demandeur (ou service) ouverts à
+//!   moins de N jours d'intervalle sur un sujet proche (similarité de Jaccard
+//!   des radicaux du titre), regroupés par composantes connexes.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::stats::{moyenne, percentile, round1};
+
+pub const FENETRE_DEFAUT_JOURS: u32 = 7;
+pub const SEUIL_SIMILARITE_DEFAUT: f64 = 0.5;
+pub const LIMITE_DEFAUT: usize = 20;
+const HORS_ANNUAIRE: &str = "(hors annuaire)";
+
+/// Niveau d'agrégation : demandeur ou service (annuaire).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NiveauDemandeur {
+    Demandeur,
+    Service,
+}
+
+impl NiveauDemandeur {
+    pub fn parse(s: Option<&str>) -> Result<Self, String> {
+        match s {
+            None | Some("demandeur") => Ok(Self::Demandeur),
+            Some("service") => Ok(Self::Service),
+            Some(autre) => Err(format!("Niveau d'agrégation inconnu: {autre}")),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Demandeur => "demandeur",
+            Self::Service => "service",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TicketDemandeur {
+    pub id: u64,
+    pub titre: String,
+    /// Radicaux du titre (cf. `nlp::preprocessing::preprocess_text`).
+    pub radicaux: Vec<String>,
+    pub demandeur: String,
+    pub service: Option<String>,
+    pub date_ouverture: NaiveDateTime,
+    pub est_vivant: bool,
+    pub delai_resolution_jours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandeurKpi {
+    pub niveau: String,
+    pub total_tickets: usize,
+    /// Tickets sans demandeur renseigné, exclus de l'analyse.
+    pub sans_demandeur: usize,
+    pub nb_demandeurs: usize,
+    pub annuaire_entrees: usize,
+    /// Part des tickets dont le demandeur figure dans l'annuaire.
+    pub couverture_annuaire_pct: f64,
+    pub top_volume: Vec<DemandeurStats>,
+    pub top_vivants: Vec<DemandeurStats>,
+    pub top_mttr: Vec<DemandeurStats>,
+    pub fenetre_jours: u32,
+    pub seuil_similarite: f64,
+    pub nb_tickets_repetes: usize,
+    pub demandes_repetees: Vec<DemandeRepetee>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandeurStats {
+    pub label: String,
+    /// Service du demandeur (niveau demandeur, si présent dans l'annuaire).
+    pub service: Option<String>,
+    /// Demandeurs distincts (niveau service).
+    pub nb_demandeurs: usize,
+    pub total: usize,
+    pub vivants: usize,
+    pub termines: usize,
+    pub mttr_jours: Option<f64>,
+    pub mediane_jours: Option<f64>,
+    pub tickets_repetes: usize,
+}
+
+/// Groupe de tickets similaires rapprochés dans le temps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DemandeRepetee {
+    pub label: String,
+    pub nb_tickets: usize,
+    pub premiere_ouverture: String,
+    pub derniere_ouverture: String,
+    pub termes_communs: Vec<String>,
+    pub tickets: Vec<TicketRepete>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketRepete {
+    pub id: u64,
+    pub titre: String,
+    pub demandeur: String,
+    pub date_ouverture: String,
+    pub est_vivant: bool,
+}
+
+/// Similarité de Jaccard de deux ensembles de radicaux (0 si l'un est vide).
+pub fn jaccard(a: &HashSet<&str>, b: &HashSet<&str>) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let inter = a.intersection(b).count();
+    inter as f64 / (a.len() + b.len() - inter) as f64
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Groupes (indices dans `tickets`, triés par date) de demandes répétées
+/// d'une même clé.
+fn groupes_repetes(
+    tickets: &[&TicketDemandeur],
+    fenetre_jours: u32,
+    seuil: f64,
+) -> Vec<Vec<usize>> {
+    let ensembles: Vec<HashSet<&str>> = tickets
+        .iter()
+        .map(|t| t.radicaux.iter().map(String::as_str).collect())
+        .collect();
+    let fenetre = chrono::Duration::days(fenetre_jours as i64);
+    let mut parent: Vec<usize> = (0..tickets.len()).collect();
+
+    for i in 0..tickets.len() {
+        for j in i + 1..tickets.len() {
+            if tickets[j].date_ouverture - tickets[i].date_ouverture > fenetre {
+                break;
+            }
+            if jaccard(&ensembles[i], &ensembles[j]) >= seuil {
+                let (a, b) = (find(&mut parent, i), find(&mut parent, j));
+                parent[b] = a;
+            }
+        }
+    }
+
+    let mut composantes: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..tickets.len() {
+        let racine = find(&mut parent, i);
+        composantes.entry(racine).or_default().push(i);
+    }
+    composantes.into_values().filter(|c| c.len() >= 2).collect()
+}
+
+fn termes_communs(tickets: &[&TicketDemandeur], groupe: &[usize]) -> Vec<String> {
+    let mut compte: HashMap<&str, usize> = HashMap::new();
+    for &i in groupe {
+        let uniques: HashSet<&str> = tickets[i].radicaux.iter().map(String::as_str).collect();
+        for r in uniques {
+            *compte.entry(r).or_default() += 1;
+        }
+    }
+    let mut termes: Vec<(&str, usize)> = compte
+        .into_iter()
+        .filter(|(_, n)| *n * 2 > groupe.len())
+        .collect();
+    termes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    termes
+        .into_iter()
+        .take(5)
+        .map(|(t, _)| t.to_string())
+        .collect()
+}
+
+pub fn compute_demandeur_kpi(
+    tickets: &[TicketDemandeur],
+    sans_demandeur: usize,
+    annuaire_entrees: usize,
+    niveau: NiveauDemandeur,
+    fenetre_jours: u32,
+    seuil_similarite: f64,
+    limite: usize,
+) -> DemandeurKpi {
+    let cle = |t: &TicketDemandeur| -> String {
+        match niveau {
+            NiveauDemandeur::Demandeur => t.demandeur.clone(),
+            NiveauDemandeur::Service => t.service.clone().unwrap_or_else(|| HORS_ANNUAIRE.into()),
+        }
+    };
+
+    let mut par_cle: HashMap<String, Vec<&TicketDemandeur>> = HashMap::new();
+    for t in tickets {
+        par_cle.entry(cle(t)).or_default().push(t);
+    }
+
+    let mut stats: Vec<DemandeurStats> = Vec::with_capacity(par_cle.len());
+    let mut demandes_repetees = Vec::new();
+    let mut nb_tickets_repetes = 0;
+    for (label, mut groupe) in par_cle {
+        groupe.sort_by_key(|t| (t.date_ouverture, t.id));
+
+        let repetes = if label == HORS_ANNUAIRE {
+            Vec::new()
+        } else {
+            groupes_repetes(&groupe, fenetre_jours, seuil_similarite)
+        };
+        let tickets_repetes: usize = repetes.iter().map(Vec::len).sum();
+        nb_tickets_repetes += tickets_repetes;
+        for r in &repetes {
+            let premier = groupe[r[0]];
+            let dernier = groupe[*r.last().unwrap_or(&r[0])];
+            demandes_repetees.push(DemandeRepetee {
+                label: label.clone(),
+                nb_tickets: r.len(),
+                premiere_ouverture: premier
+                    .date_ouverture
+                    .format("%Y-%m-%dT%H:%M:%S")
+                    .to_string(),
+                derniere_ouverture: dernier
+                    .date_ouverture
+                    .format("%Y-%m-%dT%H:%M:%S")
+                    .to_string(),
+                termes_communs: termes_communs(&groupe, r),
+                tickets: r
+                    .iter()
+                    .map(|&i| TicketRepete {
+                        id: groupe[i].id,
+                        titre: groupe[i].titre.clone(),
+                        demandeur: groupe[i].demandeur.clone(),
+                        date_ouverture: groupe[i]
+                            .date_ouverture
+                            .format("%Y-%m-%dT%H:%M:%S")
+                            .to_string(),
+                        est_vivant: groupe[i].est_vivant,
+                    })
+                    .collect(),
+            });
+        }
+
+        let delais: Vec<f64> = groupe
+            .iter()
+            .filter_map(|t| t.delai_resolution_jours)
+            .collect();
+        let vivants = groupe.iter().filter(|t| t.est_vivant).count();
+        let demandeurs: HashSet<&str> = groupe.iter().map(|t| t.demandeur.as_str()).collect();
+        let service = match niveau {
+            NiveauDemandeur::Demandeur => groupe[0].service.clone(),
+            NiveauDemandeur::Service => None,
+        };
+        stats.push(DemandeurStats {
+            label,
+            service,
+            nb_demandeurs: demandeurs.len(),
+            total: groupe.len(),
+            vivants,
+            termines: groupe.len() - vivants,
+            mttr_jours: (!delais.is_empty()).then(|| round1(moyenne(&delais))),
+            mediane_jours: (!delais.is_empty()).then(|| round1(percentile(&delais, 50.0))),
+            tickets_repetes,
+        });
+    }
+
+    let classement = |tri: fn(&DemandeurStats, &DemandeurStats) -> std::cmp::Ordering,
+                      garder: fn(&DemandeurStats) -> bool| {
+        let mut liste: Vec<DemandeurStats> = stats.iter().filter(|s| garder(s)).cloned().collect();
+        liste.sort_by(|a, b| tri(a, b).then(a.label.cmp(&b.label)));
+        liste.truncate(limite);
+        liste
+    };
+    let top_volume = classement(|a, b| b.total.cmp(&a.total), |_| true);
+    let top_vivants = classement(|a, b| b.vivants.cmp(&a.vivants), |s| s.vivants > 0);
+    let top_mttr = classement(
+        |a, b| {
+            b.mttr_jours
+                .unwrap_or(0.0)
+                .total_cmp(&a.mttr_jours.unwrap_or(0.0))
+        },
+        |s| s.mttr_jours.is_some(),
+    );
+
+    demandes_repetees.sort_by(|a, b| {
+        b.nb_tickets
+            .cmp(&a.nb_tickets)
+            .then(b.derniere_ouverture.cmp(&a.derniere_ouverture))
+    });
+    demandes_repetees.truncate(limite);
+
+    let dans_annuaire = tickets.iter().filter(|t| t.service.is_some()).count();
+    DemandeurKpi {
+        niveau: niveau.as_str().to_string(),
+        total_tickets: tickets.len() + sans_demandeur,
+        sans_demandeur,
+        nb_demandeurs: tickets
+            .iter()
+            .map(|t| t.demandeur.as_str())
+            .collect::<HashSet<_>>()
+            .len(),
+        annuaire_entrees,
+        couverture_annuaire_pct: if tickets.is_empty() {
+            0.0
+        } else {
+            round1(dans_annuaire as f64 / tickets.len() as f64 * 100.0)
+        },
+        top_volume,
+        top_vivants,
+        top_mttr,
+        fenetre_jours,
+        seuil_similarite,
+        nb_tickets_repetes,
+        demandes_repetees,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(
+        id: u64,
+        titre: &str,
+        demandeur: &str,
+        service: Option<&str>,
+        date: &str,
+        delai: Option<f64>,
+    ) -> TicketDemandeur {
+        TicketDemandeur {
+            id,
+            titre: titre.to_string(),
+            radicaux: titre.split_whitespace().map(str::to_lowercase).collect(),
+            demandeur: demandeur.to_string(),
+            service: service.map(str::to_string),
+            date_ouverture: NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S").unwrap(),
+            est_vivant: delai.is_none(),
+            delai_resolution_jours: delai,
+        }
+    }
+
+    fn jeu() -> Vec<TicketDemandeur> {
+        vec![
+            ticket(
+                1,
+                "imprimante bloquee bureau",
+                "Dupont",
+                Some("Compta"),
+                "2025-03-01 09:00:00",
+                Some(2.0),
+            ),
+            ticket(
+                2,
+                "imprimante bloquee encore",
+                "Dupont",
+                Some("Compta"),
+                "2025-03-04 09:00:00",
+                Some(4.0),
+            ),
+            ticket(
+                3,
+                "imprimante bloquee toujours",
+                "Dupont",
+                Some("Compta"),
+                "2025-03-20 09:00:00",
+                None,
+            ),
+            ticket(
+                4,
+                "acces messagerie",
+                "Dupont",
+                Some("Compta"),
+                "2025-03-05 09:00:00",
+                Some(1.0),
+            ),
+            ticket(
+                5,
+                "imprimante bloquee bureau",
+                "Martin",
+                Some("Compta"),
+                "2025-03-02 09:00:00",
+                None,
+            ),
+            ticket(
+                6,
+                "ecran noir",
+                "Durand",
+                None,
+                "2025-03-02 09:00:00",
+                Some(10.0),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_jaccard() {
+        let a: HashSet<&str> = ["imprimante", "bloquee", "bureau"].into();
+        let b: HashSet<&str> = ["imprimante", "bloquee", "encore"].into();
+        assert_eq!(jaccard(&a, &b), 0.5);
+        assert_eq!(jaccard(&a, &HashSet::new()), 0.0);
+    }
+
+    #[test]
+    fn test_requester_level() {
+        let kpi = compute_demandeur_kpi(&jeu(), 2, 2, NiveauDemandeur::Demandeur, 7, 0.5, 10);
+        assert_eq!(kpi.total_tickets, 8);
+        assert_eq!(kpi.nb_demandeurs, 3);
+        assert_eq!(kpi.couverture_annuaire_pct, 83.3);
+
+        let dupont = &kpi.top_volume[0];
+        assert_eq!(dupont.label, "Dupont");
+        assert_eq!(dupont.total, 4);
+        assert_eq!(dupont.service.as_deref(), Some("Compta"));
+        assert_eq!(dupont.mttr_jours, Some(2.3));
+        // #3 hors fenêtre de 7 jours : seul le couple #1/#2 est répété
+        assert_eq!(dupont.tickets_repetes, 2);
+        assert_eq!(kpi.demandes_repetees.len(), 1);
+        let repet = &kpi.demandes_repetees[0];
+        assert_eq!(
+            repet.tickets.iter().map(|t| t.id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(repet.termes_communs, vec!["bloquee", "imprimante"]);
+
+        assert_eq!(kpi.top_mttr[0].label, "Durand");
+        assert_eq!(kpi.top_vivants.len(), 2);
+    }
+
+    #[test]
+    fn test_service_level() {
+        let kpi = compute_demandeur_kpi(&jeu(), 0, 2, NiveauDemandeur::Service, 7, 0.5, 10);
+        let compta = kpi.top_volume.iter().find(|s| s.label == "Compta").unwrap();
+        assert_eq!(compta.total, 5);
+        assert_eq!(compta.nb_demandeurs, 2);
+        assert_eq!(compta.vivants, 2);
+        // Au niveau service, #5 (Martin) rejoint le groupe #1/#2
+        assert_eq!(compta.tickets_repetes, 3);
+        assert!(kpi.top_volume.iter().any(|s| s.label == HORS_ANNUAIRE));
+
+        let large = compute_demandeur_kpi(&jeu(), 0, 2, NiveauDemandeur::Demandeur, 30, 0.5, 10);
+        assert_eq!(large.demandes_repetees[0].nb_tickets, 3);
+    }
+}
@@ -0,0 +1,653 @@
+//! Moteur de règles de classification (table `classification_rules`).
+//!
+//! Les règles actives sont compilées une fois (seuils de config vérifiés, regex
+//! compilées) puis évaluées dans l'ordre : la première satisfaite fixe
+//! `action_recommandee` et `motif_classification`. Les seuils de config sont
+//! résolus pour chaque ticket, avec les surcharges de son groupe et de son
+//! technicien.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::AppConfig;
+use crate::parser::types::GlpiTicketNormalized;
+
+/// Actions qu'une règle peut produire.
+pub const ACTIONS: [&str; 4] = ["qualifier", "clôturer", "relancer", "suivre"];
+
+/// Seuil d'une condition : valeur fixe ou nom d'un paramètre de config
+/// (ex. "seuilAncienneteCloturer").
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Threshold {
+    Valeur(i64),
+    Config(String),
+}
+
+/// Conditions d'une règle, toutes cumulatives. Bornes strictes (`Sup` : >,
+/// `Inf` : <). Listes vides = pas de filtre ; groupes et catégories couvrent
+/// les sous-niveaux. `texte` : regex insensible à la casse sur titre, suivis
+/// et solution.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuleConditions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anciennete_sup: Option<Threshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anciennete_inf: Option<Threshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inactivite_sup: Option<Threshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inactivite_inf: Option<Threshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suivis_sup: Option<Threshold>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suivis_inf: Option<Threshold>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub statuts: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub priorites: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groupes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub texte: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassificationRule {
+    /// Absent pour une règle pas encore enregistrée.
+    pub id: Option<i64>,
+    pub position: i64,
+    pub name: String,
+    pub is_active: bool,
+    pub conditions: RuleConditions,
+    pub action: String,
+    pub motif: String,
+}
+
+/// Vue d'un ticket pour l'évaluation des règles (ticket parsé ou ligne en base).
+pub struct RuleTicket<'a> {
+    pub titre: &'a str,
+    pub statut: &'a str,
+    pub type_ticket: &'a str,
+    pub priorite: Option<&'a str>,
+    pub groupe: Option<&'a str>,
+    pub technicien: Option<&'a str>,
+    pub categorie: Option<&'a str>,
+    pub anciennete: Option<i64>,
+    pub inactivite: Option<i64>,
+    pub nombre_suivis: i64,
+    pub suivis: &'a str,
+    pub solution: &'a str,
+}
+
+impl<'a> From<&'a GlpiTicketNormalized> for RuleTicket<'a> {
+    fn from(t: &'a GlpiTicketNormalized) -> Self {
+        Self {
+            titre: &t.titre,
+            statut: &t.statut,
+            type_ticket: &t.type_ticket,
+            priorite: t.priorite_label.as_deref(),
+            groupe: t.groupe_principal.as_deref(),
+            technicien: t.technicien_principal.as_deref(),
+            categorie: t.categorie.as_deref(),
+            anciennete: t.anciennete_jours,
+            inactivite: t.inactivite_jours,
+            nombre_suivis: t.nombre_suivis.unwrap_or(0) as i64,
+            suivis: &t.suivis_description,
+            solution: &t.solution,
+        }
+    }
+}
+
+/// Paramètres de config vus par un ticket : seuils surchargés pour son groupe
+/// et son technicien, valeurs globales pour le reste.
+pub struct ConfigTicket<'a> {
+    seuils: Value,
+    globale: &'a Value,
+}
+
+impl ConfigTicket<'_> {
+    fn get(&self, key: &str) -> Option<i64> {
+        self.seuils
+            .get(key)
+            .or_else(|| self.globale.get(key))
+            .and_then(Value::as_i64)
+    }
+
+    fn seuil(&self, threshold: &Option<Threshold>) -> Option<i64> {
+        match threshold {
+            None => None,
+            Some(Threshold::Valeur(v)) => Some(*v),
+            Some(Threshold::Config(key)) => self.get(key),
+        }
+    }
+}
+
+/// Ticket vivant chargé depuis la base pour la simulation.
+pub struct RuleTicketRow {
+    pub id: u64,
+    pub titre: String,
+    pub statut: String,
+    pub type_ticket: String,
+    pub priorite: Option<String>,
+    pub groupe: Option<String>,
+    pub technicien: Option<String>,
+    pub categorie: Option<String>,
+    pub anciennete: Option<i64>,
+    pub inactivite: Option<i64>,
+    pub nombre_suivis: i64,
+    pub suivis: String,
+    pub solution: String,
+}
+
+impl RuleTicketRow {
+    pub fn as_rule_ticket(&self) -> RuleTicket<'_> {
+        RuleTicket {
+            titre: &self.titre,
+            statut: &self.statut,
+            type_ticket: &self.type_ticket,
+            priorite: self.priorite.as_deref(),
+            groupe: self.groupe.as_deref(),
+            technicien: self.technicien.as_deref(),
+            categorie: self.categorie.as_deref(),
+            anciennete: self.anciennete,
+            inactivite: self.inactivite,
+            nombre_suivis: self.nombre_suivis,
+            suivis: &self.suivis,
+            solution: &self.solution,
+        }
+    }
+}
+
+/// Bornes strictes : `sup` < valeur < `inf`.
+#[derive(Debug, Default)]
+struct Bornes {
+    sup: Option<Threshold>,
+    inf: Option<Threshold>,
+}
+
+impl Bornes {
+    fn is_empty(&self) -> bool {
+        self.sup.is_none() && self.inf.is_none()
+    }
+
+    fn accepte(&self, v: i64, config: &ConfigTicket) -> bool {
+        config.seuil(&self.sup).is_none_or(|s| v > s)
+            && config.seuil(&self.inf).is_none_or(|i| v < i)
+    }
+}
+
+#[derive(Debug)]
+pub struct CompiledRule {
+    pub id: Option<i64>,
+    pub name: String,
+    pub action: String,
+    motif: String,
+    anciennete: Bornes,
+    inactivite: Bornes,
+    suivis: Bornes,
+    statuts: Vec<String>,
+    types: Vec<String>,
+    priorites: Vec<String>,
+    groupes: Vec<String>,
+    categories: Vec<String>,
+    texte: Option<Regex>,
+}
+
+impl CompiledRule {
+    pub fn matches(&self, t: &RuleTicket, config: &ConfigTicket) -> bool {
+        if !self.anciennete.is_empty()
+            && !t
+                .anciennete
+                .is_some_and(|v| self.anciennete.accepte(v, config))
+        {
+            return false;
+        }
+        if !self.inactivite.is_empty()
+            && !t
+                .inactivite
+                .is_some_and(|v| self.inactivite.accepte(v, config))
+        {
+            return false;
+        }
+        if !self.suivis.accepte(t.nombre_suivis, config) {
+            return false;
+        }
+        if !in_list(&self.statuts, Some(t.statut))
+            || !in_list(&self.types, Some(t.type_ticket))
+            || !in_list(&self.priorites, t.priorite)
+            || !in_tree(&self.groupes, t.groupe)
+            || !in_tree(&self.categories, t.categorie)
+        {
+            return false;
+        }
+        match &self.texte {
+            Some(re) => re.is_match(t.titre) || re.is_match(t.suivis) || re.is_match(t.solution),
+            None => true,
+        }
+    }
+
+    /// Motif avec {anciennete}, {inactivite}, {suivis} et seuils de config remplacés.
+    fn render_motif(&self, t: &RuleTicket, config: &ConfigTicket) -> String {
+        let mut motif = self.motif.clone();
+        if !motif.contains('{') {
+            return motif;
+        }
+        let opt = |v: Option<i64>| v.map(|v| v.to_string()).unwrap_or_else(|| "?".into());
+        motif = motif
+            .replace("{anciennete}", &opt(t.anciennete))
+            .replace("{inactivite}", &opt(t.inactivite))
+            .replace("{suivis}", &t.nombre_suivis.to_string());
+        if let Some(obj) = config.globale.as_object() {
+            for (k, v) in obj {
+                if !v.is_number() {
+                    continue;
+                }
+                let valeur = config
+                    .get(k)
+                    .map_or_else(|| v.to_string(), |s| s.to_string());
+                motif = motif.replace(&format!("{{{k}}}"), &valeur);
+            }
+        }
+        motif
+    }
+}
+
+fn normalize_list(values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+fn in_list(list: &[String], value: Option<&str>) -> bool {
+    list.is_empty() || value.is_some_and(|v| list.contains(&v.trim().to_lowercase()))
+}
+
+/// Correspondance sur un nœud ou ses descendants ("DSI > Support" couvre
+/// "DSI > Support > N1").
+fn in_tree(list: &[String], value: Option<&str>) -> bool {
+    if list.is_empty() {
+        return true;
+    }
+    let Some(v) = value.map(|v| v.trim().to_lowercase()) else {
+        return false;
+    };
+    list.iter()
+        .any(|p| v == *p || v.starts_with(&format!("{p} >")))
+}
+
+/// Vérifie qu'un seuil de config existe et est entier ; sa valeur est résolue
+/// ticket par ticket.
+fn valider(threshold: &Option<Threshold>, config: &Value) -> Result<Option<Threshold>, String> {
+    if let Some(Threshold::Config(key)) = threshold {
+        if config.get(key).and_then(Value::as_i64).is_none() {
+            return Err(format!("Paramètre de config inconnu ou non entier: {key}"));
+        }
+    }
+    Ok(threshold.clone())
+}
+
+/// Compile une règle ; erreur si action, seuil ou regex invalide.
+pub fn compile_rule(rule: &ClassificationRule, config: &Value) -> Result<CompiledRule, String> {
+    if rule.name.trim().is_empty() {
+        return Err("Nom de règle vide".to_string());
+    }
+    if !ACTIONS.contains(&rule.action.as_str()) {
+        return Err(format!(
+            "Action inconnue: {} (attendu : {})",
+            rule.action,
+            ACTIONS.join(", ")
+        ));
+    }
+    let c: &RuleConditions = &rule.conditions;
+    let texte = match c.texte.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(p) => Some(
+            Regex::new(&format!("(?i){p}"))
+                .map_err(|e| format!("Expression régulière invalide: {e}"))?,
+        ),
+        None => None,
+    };
+    Ok(CompiledRule {
+        id: rule.id,
+        name: rule.name.clone(),
+        action: rule.action.clone(),
+        motif: rule.motif.clone(),
+        anciennete: Bornes {
+            sup: valider(&c.anciennete_sup, config)?,
+            inf: valider(&c.anciennete_inf, config)?,
+        },
+        inactivite: Bornes {
+            sup: valider(&c.inactivite_sup, config)?,
+            inf: valider(&c.inactivite_inf, config)?,
+        },
+        suivis: Bornes {
+            sup: valider(&c.suivis_sup, config)?,
+            inf: valider(&c.suivis_inf, config)?,
+        },
+        statuts: normalize_list(&c.statuts),
+        types: normalize_list(&c.types),
+        priorites: normalize_list(&c.priorites),
+        groupes: normalize_list(&c.groupes),
+        categories: normalize_list(&c.categories),
+        texte,
+    })
+}
+
+/// Règles actives compilées, dans l'ordre d'évaluation.
+pub struct RuleSet<'a> {
+    pub rules: Vec<CompiledRule>,
+    config: &'a AppConfig,
+    valeurs: Value,
+}
+
+impl<'a> RuleSet<'a> {
+    /// Compile les règles actives triées par position. Une règle invalide
+    /// (ex. paramètre de config supprimé) est ignorée et journalisée.
+    pub fn compile(rules: &[ClassificationRule], config: &'a AppConfig) -> Self {
+        let valeurs = serde_json::to_value(config).unwrap_or_default();
+        let mut actives: Vec<&ClassificationRule> = rules.iter().filter(|r| r.is_active).collect();
+        actives.sort_by_key(|r| r.position);
+
+        let rules = actives
+            .into_iter()
+            .filter_map(|r| match compile_rule(r, &valeurs) {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    log::warn!("Règle '{}' ignorée: {e}", r.name);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            rules,
+            config,
+            valeurs,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Paramètres de config du ticket, selon son groupe et son technicien.
+    pub fn config_ticket(&self, t: &RuleTicket) -> ConfigTicket<'_> {
+        let seuils = if self.config.surcharges_seuils.is_empty() {
+            Value::Null
+        } else {
+            serde_json::to_value(self.config.seuils_pour(t.groupe, t.technicien))
+                .unwrap_or_default()
+        };
+        ConfigTicket {
+            seuils,
+            globale: &self.valeurs,
+        }
+    }
+
+    /// Index de la première règle satisfaite et motif rendu.
+    pub fn evaluate(&self, t: &RuleTicket) -> Option<(usize, String)> {
+        let config = self.config_ticket(t);
+        self.rules
+            .iter()
+            .position(|r| r.matches(t, &config))
+            .map(|i| (i, self.rules[i].render_motif(t, &config)))
+    }
+}
+
+/// Classifie un ticket vivant selon le jeu de règles. Sans règle active, la
+/// cascade historique (`classify_ticket`) s'applique.
+pub fn classify_ticket_with_rules(
+    ticket: &mut GlpiTicketNormalized,
+    rules: &RuleSet,
+    config: &AppConfig,
+) {
+    if !ticket.est_vivant {
+        return;
+    }
+    if rules.is_empty() {
+        super::classifier::classify_ticket(ticket, config);
+        return;
+    }
+    let matched = rules.evaluate(&RuleTicket::from(&*ticket));
+    match matched {
+        Some((i, motif)) => {
+            ticket.action_recommandee = Some(rules.rules[i].action.clone());
+            ticket.motif_classification = Some(motif);
+        }
+        None => {
+            ticket.action_recommandee = None;
+            ticket.motif_classification = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::classifier::classify_ticket;
+    use rusqlite::Connection;
+
+    fn config() -> AppConfig {
+        AppConfig {
+            statuts_vivants: vec![],
+            statuts_termines: vec![],
+            ..Default::default()
+        }
+    }
+
+    fn default_rules() -> Vec<ClassificationRule> {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("../db/sql/007_classification_rules.sql"))
+            .unwrap();
+        crate::db::queries::get_classification_rules(&conn).unwrap()
+    }
+
+    fn ticket(suivis: i32, anciennete: i64, inactivite: i64) -> GlpiTicketNormalized {
+        GlpiTicketNormalized {
+            id: 1,
+            titre: "Imprimante HS".to_string(),
+            statut: "En cours (Attribué)".to_string(),
+            type_ticket: "Incident".to_string(),
+            priorite: None,
+            priorite_label: Some("Haute".to_string()),
+            urgence: None,
+            impact: None,
+            demandeur: String::new(),
+            date_ouverture: String::new(),
+            derniere_modification: None,
+            nombre_suivis: Some(suivis),
+            suivis_description: String::new(),
+            solution: String::new(),
+            taches_description: String::new(),
+            intervention_fournisseur: String::new(),
+            techniciens: vec![],
+            groupes: vec![],
+            technicien_principal: None,
+            groupe_principal: Some("DSI > Support > N1".to_string()),
+            groupe_niveau1: None,
+            groupe_niveau2: None,
+            groupe_niveau3: None,
+            categorie: None,
+            categorie_niveau1: None,
+            categorie_niveau2: None,
+            categorie_chemin: vec![],
+            date_resolution: None,
+            est_vivant: true,
+            anciennete_jours: Some(anciennete),
+            inactivite_jours: Some(inactivite),
+            date_cloture_approx: None,
+            action_recommandee: None,
+            motif_classification: None,
+        }
+    }
+
+    fn globale(config: &Value) -> ConfigTicket<'_> {
+        ConfigTicket {
+            seuils: Value::Null,
+            globale: config,
+        }
+    }
+
+    fn rule(conditions: RuleConditions) -> ClassificationRule {
+        ClassificationRule {
+            id: None,
+            position: 1,
+            name: "Test".to_string(),
+            is_active: true,
+            conditions,
+            action: "relancer".to_string(),
+            motif: "Règle test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_default_rules_reproduce_cascade() {
+        let config = config();
+        let rules = RuleSet::compile(&default_rules(), &config);
+        for (suivis, anc, inact) in [
+            (0, 200, 0),
+            (3, 91, 5),
+            (2, 20, 15),
+            (2, 10, 3),
+            (1, 90, 14),
+        ] {
+            let mut expected = ticket(suivis, anc, inact);
+            classify_ticket(&mut expected, &config);
+            let mut actual = ticket(suivis, anc, inact);
+            classify_ticket_with_rules(&mut actual, &rules, &config);
+            assert_eq!(actual.action_recommandee, expected.action_recommandee);
+            assert_eq!(actual.motif_classification, expected.motif_classification);
+        }
+    }
+
+    #[test]
+    fn test_optional_default_rules_use_other_thresholds() {
+        let config = config();
+        let mut rules = default_rules();
+        for r in &mut rules {
+            r.is_active = true;
+        }
+        let rules = RuleSet::compile(&rules, &config);
+
+        let mut t = ticket(2, 40, 61);
+        classify_ticket_with_rules(&mut t, &rules, &config);
+        assert_eq!(t.action_recommandee.as_deref(), Some("clôturer"));
+        assert_eq!(
+            t.motif_classification.as_deref(),
+            Some("Inactif depuis 61j (> 60j)")
+        );
+
+        let mut t = ticket(2, 40, 3);
+        classify_ticket_with_rules(&mut t, &rules, &config);
+        assert_eq!(t.action_recommandee.as_deref(), Some("relancer"));
+        assert_eq!(t.motif_classification.as_deref(), Some("Ancienneté > 30j"));
+    }
+
+    #[test]
+    fn test_group_override_changes_action() {
+        use crate::analyzer::thresholds::{PorteeSeuils, SurchargeSeuils};
+
+        let mut config = config();
+        config.surcharges_seuils.push(SurchargeSeuils {
+            portee: PorteeSeuils::Groupe,
+            valeur: "DSI > Support".to_string(),
+            seuil_tickets_technicien: None,
+            seuil_anciennete_cloturer: Some(60),
+            seuil_inactivite_cloturer: None,
+            seuil_anciennete_relancer: None,
+            seuil_inactivite_relancer: None,
+            seuil_couleur_vert: None,
+            seuil_couleur_jaune: None,
+            seuil_couleur_orange: None,
+        });
+        let rules = RuleSet::compile(&default_rules(), &config);
+
+        let mut t = ticket(3, 70, 5);
+        classify_ticket_with_rules(&mut t, &rules, &config);
+        assert_eq!(t.action_recommandee.as_deref(), Some("clôturer"));
+        assert_eq!(t.motif_classification.as_deref(), Some("Ancienneté > 60j"));
+        let mut expected = ticket(3, 70, 5);
+        classify_ticket(&mut expected, &config);
+        assert_eq!(t.motif_classification, expected.motif_classification);
+
+        let mut autre = ticket(3, 70, 5);
+        autre.groupe_principal = Some("DSI > Réseau".to_string());
+        classify_ticket_with_rules(&mut autre, &rules, &config);
+        assert_eq!(autre.action_recommandee.as_deref(), Some("suivre"));
+    }
+
+    #[test]
+    fn test_list_tree_and_text_conditions() {
+        let config = serde_json::to_value(config()).unwrap();
+        let r = compile_rule(
+            &rule(RuleConditions {
+                groupes: vec!["DSI > Support".to_string()],
+                priorites: vec!["haute".to_string()],
+                texte: Some(r"imprimante\s+hs".to_string()),
+                ..Default::default()
+            }),
+            &config,
+        )
+        .unwrap();
+        let t = ticket(1, 1, 1);
+        assert!(r.matches(&RuleTicket::from(&t), &globale(&config)));
+
+        let mut autre = ticket(1, 1, 1);
+        autre.groupe_principal = Some("DSI > Supports".to_string());
+        assert!(!r.matches(&RuleTicket::from(&autre), &globale(&config)));
+    }
+
+    #[test]
+    fn test_missing_age_fails_age_condition() {
+        let config = serde_json::to_value(config()).unwrap();
+        let r = compile_rule(
+            &rule(RuleConditions {
+                anciennete_inf: Some(Threshold::Valeur(10)),
+                ..Default::default()
+            }),
+            &config,
+        )
+        .unwrap();
+        let mut t = ticket(1, 5, 1);
+        assert!(r.matches(&RuleTicket::from(&t), &globale(&config)));
+        t.anciennete_jours = None;
+        assert!(!r.matches(&RuleTicket::from(&t), &globale(&config)));
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        let config = serde_json::to_value(config()).unwrap();
+        let mut r = rule(RuleConditions {
+            anciennete_sup: Some(Threshold::Config("seuilInconnu".to_string())),
+            ..Default::default()
+        });
+        assert!(compile_rule(&r, &config).is_err());
+
+        r.conditions = RuleConditions {
+            texte: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(compile_rule(&r, &config).is_err());
+
+        r.conditions = RuleConditions::default();
+        r.action = "archiver".to_string();
+        assert!(compile_rule(&r, &config).is_err());
+    }
+
+    #[test]
+    fn test_no_active_rule_falls_back_to_cascade() {
+        let config = config();
+        let rules = RuleSet::compile(&[], &config);
+        let mut t = ticket(0, 1, 1);
+        classify_ticket_with_rules(&mut t, &rules, &config);
+        assert_eq!(t.action_recommandee.as_deref(), Some("qualifier"));
+    }
+}
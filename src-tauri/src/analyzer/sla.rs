@@ -0,0 +1,534 @@
+//! Moteur SLA : délais de prise en charge (TTO) et de résolution (TTR) par
+//! priorité / type / catégorie (table `sla_definitions`).
+//!
+//! - Tickets terminés : respect du TTR (ouverture → résolution), ventilé par
+//!   période de résolution, groupe, technicien et définition.
+//! - Tickets vivants : temps restant ou dépassement à la date de référence
+//!   (date de l'import), TTO tant que le ticket n'est pas pris en charge.
+//!
+//! Les délais TTO / TTR sont des heures ouvrées du calendrier configuré,
+//! comme les délais affichés à côté ; l'horizon "à risque" reste en jours
+//! calendaires.
+//!
+//! Le TTO n'est pas mesuré sur les tickets terminés : l'export GLPI ne fournit
+//! pas la date de première prise en charge.
+
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::calendar::CalendrierCompile;
+use super::stats::{pct, round1};
+use super::temporal::period_key;
+
+/// Nombre maximal de tickets listés (dépassés / à risque) dans le tableau de bord.
+pub const MAX_TICKETS_LISTE: usize = 100;
+/// Horizon par défaut de la liste "va dépasser dans N jours".
+pub const HORIZON_DEFAUT_JOURS: f64 = 3.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaDefinition {
+    /// Absent pour une définition pas encore enregistrée.
+    pub id: Option<i64>,
+    pub name: String,
+    pub priorite: Option<String>,
+    pub type_ticket: Option<String>,
+    /// Préfixe : "Réseau" couvre "Réseau > Wifi".
+    pub categorie: Option<String>,
+    pub tto_heures: Option<f64>,
+    pub ttr_heures: Option<f64>,
+    pub is_active: bool,
+}
+
+impl SlaDefinition {
+    fn specificite(&self) -> usize {
+        [&self.priorite, &self.type_ticket, &self.categorie]
+            .iter()
+            .filter(|c| c.is_some())
+            .count()
+    }
+
+    fn applies(&self, t: &SlaTicket) -> bool {
+        let eq = |crit: &Option<String>, val: Option<&str>| match crit {
+            None => true,
+            Some(c) => val.is_some_and(|v| v.trim().eq_ignore_ascii_case(c.trim())),
+        };
+        let categorie_ok = match &self.categorie {
+            None => true,
+            Some(c) => t.categorie.as_deref().is_some_and(|v| {
+                let (v, c) = (v.trim().to_lowercase(), c.trim().to_lowercase());
+                v == c || v.starts_with(&format!("{c} >"))
+            }),
+        };
+        self.is_active
+            && eq(&self.priorite, t.priorite.as_deref())
+            && eq(&self.type_ticket, Some(&t.type_ticket))
+            && categorie_ok
+    }
+}
+
+/// Ticket chargé pour l'évaluation SLA.
+#[derive(Debug, Clone)]
+pub struct SlaTicket {
+    pub id: u64,
+    pub titre: String,
+    pub priorite: Option<String>,
+    pub type_ticket: String,
+    pub categorie: Option<String>,
+    pub groupe: Option<String>,
+    pub technicien: Option<String>,
+    pub est_vivant: bool,
+    /// Technicien affecté ou au moins un suivi.
+    pub pris_en_charge: bool,
+    pub date_ouverture: NaiveDateTime,
+    pub date_resolution: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaKpi {
+    pub nb_definitions: usize,
+    pub date_reference: String,
+    pub horizon_jours: f64,
+    pub resolution: SlaTaux,
+    pub par_periode: Vec<SlaVentilation>,
+    pub par_groupe: Vec<SlaVentilation>,
+    pub par_technicien: Vec<SlaVentilation>,
+    pub par_sla: Vec<SlaVentilation>,
+    pub vivants: SlaVivants,
+    /// Tickets vivants hors délai (TTO ou TTR), du plus en retard au moins en retard.
+    pub depasses: Vec<SlaEcheance>,
+    /// Tickets vivants qui dépasseront leur TTR dans l'horizon.
+    pub a_risque: Vec<SlaEcheance>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaTaux {
+    pub evalues: usize,
+    pub respectes: usize,
+    pub depasses: usize,
+    pub taux_respect: f64,
+    pub sans_sla: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaVentilation {
+    pub label: String,
+    pub evalues: usize,
+    pub depasses: usize,
+    pub taux_depassement: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaVivants {
+    pub evalues: usize,
+    pub dans_les_delais: usize,
+    pub ttr_depasses: usize,
+    /// Non pris en charge au-delà du TTO.
+    pub tto_depasses: usize,
+    pub a_risque: usize,
+    pub sans_sla: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SlaEcheance {
+    pub id: u64,
+    pub titre: String,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub sla: String,
+    /// "TTO" ou "TTR"
+    pub indicateur: String,
+    pub echeance: String,
+    /// Négatif = retard.
+    pub heures_restantes: f64,
+}
+
+/// Définition applicable : la plus spécifique, puis la plus ancienne.
+pub fn find_sla<'a>(defs: &'a [SlaDefinition], t: &SlaTicket) -> Option<&'a SlaDefinition> {
+    defs.iter()
+        .filter(|d| d.applies(t))
+        .max_by_key(|d| (d.specificite(), std::cmp::Reverse(d.id.unwrap_or(i64::MAX))))
+}
+
+#[derive(Default)]
+struct Compteur {
+    evalues: usize,
+    depasses: usize,
+}
+
+fn ventilation(map: BTreeMap<String, Compteur>) -> Vec<SlaVentilation> {
+    map.into_iter()
+        .map(|(label, c)| SlaVentilation {
+            label,
+            evalues: c.evalues,
+            depasses: c.depasses,
+            taux_depassement: pct(c.depasses, c.evalues),
+        })
+        .collect()
+}
+
+/// Calcule les indicateurs SLA.
+/// `limite` borne les listes de tickets dépassés / à risque (None = tout).
+pub fn compute_sla_kpi(
+    defs: &[SlaDefinition],
+    tickets: &[SlaTicket],
+    cal: &CalendrierCompile,
+    reference: NaiveDateTime,
+    granularity: &str,
+    horizon_jours: f64,
+    limite: Option<usize>,
+) -> SlaKpi {
+    let mut kpi = SlaKpi {
+        nb_definitions: defs.iter().filter(|d| d.is_active).count(),
+        date_reference: reference.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        horizon_jours,
+        ..Default::default()
+    };
+
+    let mut par_periode: BTreeMap<String, Compteur> = BTreeMap::new();
+    let mut par_groupe: BTreeMap<String, Compteur> = BTreeMap::new();
+    let mut par_technicien: BTreeMap<String, Compteur> = BTreeMap::new();
+    let mut par_sla: BTreeMap<String, Compteur> = BTreeMap::new();
+    let horizon = reference + Duration::minutes((horizon_jours * 24.0 * 60.0).round() as i64);
+
+    for t in tickets {
+        let sla = find_sla(defs, t);
+
+        if !t.est_vivant {
+            let (Some(sla), Some(ttr), Some(resolu)) =
+                (sla, sla.and_then(|s| s.ttr_heures), t.date_resolution)
+            else {
+                kpi.resolution.sans_sla += 1;
+                continue;
+            };
+            let depasse = cal.heures_ouvrees_signees(t.date_ouverture, resolu) > ttr;
+            kpi.resolution.evalues += 1;
+            if depasse {
+                kpi.resolution.depasses += 1;
+            } else {
+                kpi.resolution.respectes += 1;
+            }
+            let keys = [
+                (&mut par_periode, period_key(resolu, granularity)),
+                (
+                    &mut par_groupe,
+                    t.groupe.clone().unwrap_or_else(|| "(Sans groupe)".into()),
+                ),
+                (
+                    &mut par_technicien,
+                    t.technicien
+                        .clone()
+                        .unwrap_or_else(|| "(Non assigné)".into()),
+                ),
+                (&mut par_sla, sla.name.clone()),
+            ];
+            for (map, key) in keys {
+                let c = map.entry(key).or_default();
+                c.evalues += 1;
+                if depasse {
+                    c.depasses += 1;
+                }
+            }
+            continue;
+        }
+
+        // ── Vivants ──
+        let Some(sla) = sla.filter(|s| s.tto_heures.is_some() || s.ttr_heures.is_some()) else {
+            kpi.vivants.sans_sla += 1;
+            continue;
+        };
+        kpi.vivants.evalues += 1;
+        // Échéance = ouverture + délai en heures ouvrées
+        let echeance = |indicateur: &str, limite_h: f64| {
+            let ech =
+                cal.ajouter_minutes_ouvrees(t.date_ouverture, (limite_h * 60.0).round() as i64);
+            let e = SlaEcheance {
+                id: t.id,
+                titre: t.titre.clone(),
+                technicien: t.technicien.clone(),
+                groupe: t.groupe.clone(),
+                sla: sla.name.clone(),
+                indicateur: indicateur.to_string(),
+                echeance: ech.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                heures_restantes: round1(cal.heures_ouvrees_signees(reference, ech)),
+            };
+            (e, ech)
+        };
+
+        let mut en_retard = false;
+        if let Some(tto) = sla.tto_heures.filter(|_| !t.pris_en_charge) {
+            let (e, ech) = echeance("TTO", tto);
+            if ech < reference {
+                kpi.vivants.tto_depasses += 1;
+                kpi.depasses.push(e);
+                en_retard = true;
+            }
+        }
+        if let Some(ttr) = sla.ttr_heures {
+            let (e, ech) = echeance("TTR", ttr);
+            if ech < reference {
+                kpi.vivants.ttr_depasses += 1;
+                kpi.depasses.push(e);
+                en_retard = true;
+            } else if ech <= horizon {
+                kpi.vivants.a_risque += 1;
+                kpi.a_risque.push(e);
+            }
+        }
+        if !en_retard {
+            kpi.vivants.dans_les_delais += 1;
+        }
+    }
+
+    kpi.resolution.taux_respect = pct(kpi.resolution.respectes, kpi.resolution.evalues);
+    kpi.par_periode = ventilation(par_periode);
+    kpi.par_groupe = ventilation(par_groupe);
+    kpi.par_technicien = ventilation(par_technicien);
+    kpi.par_sla = ventilation(par_sla);
+
+    let by_remaining =
+        |a: &SlaEcheance, b: &SlaEcheance| a.heures_restantes.total_cmp(&b.heures_restantes);
+    kpi.depasses.sort_by(by_remaining);
+    kpi.a_risque.sort_by(by_remaining);
+    if let Some(n) = limite {
+        kpi.depasses.truncate(n);
+        kpi.a_risque.truncate(n);
+    }
+
+    kpi
+}
+
+/// Charge définitions et tickets d'un import puis calcule les indicateurs.
+/// Terminés filtrés sur la date de résolution ; vivants évalués à la date de
+/// l'import (le résultat reste stable tant que l'import ne change pas).
+pub fn load_sla_kpi(
+    conn: &rusqlite::Connection,
+    import_id: i64,
+    date_debut: Option<&str>,
+    date_fin: Option<&str>,
+    granularity: &str,
+    horizon_jours: f64,
+    limite: Option<usize>,
+) -> Result<SlaKpi, rusqlite::Error> {
+    use crate::db::queries;
+    let defs = queries::get_sla_definitions(conn)?;
+    let tickets = queries::get_sla_tickets(conn, import_id, date_debut, date_fin)?;
+    let reference = queries::get_import_reference_date(conn, import_id)?
+        .unwrap_or_else(|| chrono::Local::now().naive_local());
+    let cal = crate::config::get_calendrier_from_db(conn)?;
+    Ok(compute_sla_kpi(
+        &defs,
+        &tickets,
+        &cal,
+        reference,
+        granularity,
+        horizon_jours,
+        limite,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::calendar::{CalendrierOuvre, PlageOuverture};
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    /// Calendrier 24h/24, 7j/7 : heures ouvrées = heures calendaires.
+    fn continu() -> CalendrierCompile {
+        CalendrierOuvre {
+            plages: (1..=7)
+                .map(|jour| PlageOuverture {
+                    jour,
+                    debut: "00:00".into(),
+                    fin: "24:00".into(),
+                })
+                .collect(),
+            jours_feries: false,
+            fermetures: Vec::new(),
+        }
+        .compile()
+        .unwrap()
+    }
+
+    fn def(
+        id: i64,
+        priorite: Option<&str>,
+        categorie: Option<&str>,
+        tto: f64,
+        ttr: f64,
+    ) -> SlaDefinition {
+        SlaDefinition {
+            id: Some(id),
+            name: format!("SLA {id}"),
+            priorite: priorite.map(str::to_string),
+            type_ticket: None,
+            categorie: categorie.map(str::to_string),
+            tto_heures: Some(tto),
+            ttr_heures: Some(ttr),
+            is_active: true,
+        }
+    }
+
+    fn ticket(id: u64, priorite: &str, ouverture: &str, resolution: Option<&str>) -> SlaTicket {
+        SlaTicket {
+            id,
+            titre: format!("Ticket {id}"),
+            priorite: Some(priorite.to_string()),
+            type_ticket: "Incident".to_string(),
+            categorie: Some("Réseau > Wifi".to_string()),
+            groupe: Some("Support".to_string()),
+            technicien: Some("Alice".to_string()),
+            est_vivant: resolution.is_none(),
+            pris_en_charge: true,
+            date_ouverture: dt(ouverture),
+            date_resolution: resolution.map(dt),
+        }
+    }
+
+    #[test]
+    fn test_find_sla_most_specific() {
+        let defs = vec![
+            def(1, None, None, 8.0, 72.0),
+            def(2, Some("Haute"), None, 4.0, 24.0),
+            def(3, Some("Haute"), Some("Réseau"), 2.0, 12.0),
+        ];
+        let t = ticket(1, "Haute", "2025-01-06 09:00:00", None);
+        assert_eq!(find_sla(&defs, &t).unwrap().id, Some(3));
+        let t = ticket(2, "Basse", "2025-01-06 09:00:00", None);
+        assert_eq!(find_sla(&defs, &t).unwrap().id, Some(1));
+
+        let mut inactive = defs.clone();
+        inactive[2].is_active = false;
+        let t = ticket(3, "haute", "2025-01-06 09:00:00", None);
+        assert_eq!(find_sla(&inactive, &t).unwrap().id, Some(2));
+    }
+
+    #[test]
+    fn test_closed_breach_rates() {
+        let defs = vec![def(1, None, None, 8.0, 24.0)];
+        let tickets = vec![
+            ticket(
+                1,
+                "Moyenne",
+                "2025-01-06 09:00:00",
+                Some("2025-01-06 18:00:00"),
+            ),
+            ticket(
+                2,
+                "Moyenne",
+                "2025-01-06 09:00:00",
+                Some("2025-01-08 09:00:00"),
+            ),
+            ticket(
+                3,
+                "Moyenne",
+                "2025-02-03 09:00:00",
+                Some("2025-02-03 10:00:00"),
+            ),
+        ];
+        let kpi = compute_sla_kpi(
+            &defs,
+            &tickets,
+            &continu(),
+            dt("2025-03-01 00:00:00"),
+            "month",
+            3.0,
+            None,
+        );
+        assert_eq!(kpi.resolution.evalues, 3);
+        assert_eq!(kpi.resolution.depasses, 1);
+        assert_eq!(kpi.resolution.taux_respect, 66.7);
+        assert_eq!(kpi.par_periode.len(), 2);
+        assert_eq!(kpi.par_periode[0].label, "2025-01");
+        assert_eq!(kpi.par_periode[0].taux_depassement, 50.0);
+        assert_eq!(kpi.par_technicien[0].depasses, 1);
+    }
+
+    #[test]
+    fn test_live_remaining_overdue_and_at_risk() {
+        let defs = vec![def(1, None, None, 4.0, 72.0)];
+        let reference = dt("2025-01-10 12:00:00");
+        let mut non_pris = ticket(1, "Moyenne", "2025-01-10 06:00:00", None);
+        non_pris.pris_en_charge = false;
+        let tickets = vec![
+            ticket(2, "Moyenne", "2025-01-01 12:00:00", None), // TTR dépassé de 144h
+            ticket(3, "Moyenne", "2025-01-08 12:00:00", None), // 24h restantes → à risque
+            non_pris,                                          // TTO dépassé de 2h
+            ticket(4, "Moyenne", "2025-01-10 11:00:00", None), // 71h restantes
+        ];
+        let kpi = compute_sla_kpi(&defs, &tickets, &continu(), reference, "month", 2.0, None);
+        assert_eq!(kpi.vivants.evalues, 4);
+        assert_eq!(kpi.vivants.ttr_depasses, 1);
+        assert_eq!(kpi.vivants.tto_depasses, 1);
+        assert_eq!(kpi.vivants.a_risque, 1);
+        assert_eq!(kpi.vivants.dans_les_delais, 2);
+        assert_eq!(kpi.depasses[0].id, 2);
+        assert_eq!(kpi.depasses[0].heures_restantes, -144.0);
+        assert_eq!(kpi.depasses[1].indicateur, "TTO");
+        assert_eq!(kpi.a_risque[0].id, 3);
+        assert_eq!(kpi.a_risque[0].heures_restantes, 24.0);
+    }
+
+    #[test]
+    fn test_business_hours_calendar() {
+        let cal = CalendrierOuvre::default().compile().unwrap();
+        let defs = vec![def(1, None, None, 4.0, 8.0)];
+        // Vendredi 16h → lundi 15h : 1h30 + 5h30 = 7h ouvrées < 8h
+        let resolu = ticket(
+            1,
+            "Moyenne",
+            "2025-01-10 16:00:00",
+            Some("2025-01-13 15:00:00"),
+        );
+        // Vendredi 16h30, TTR 8h : échéance lundi 16h30
+        let vivant = ticket(2, "Moyenne", "2025-01-10 16:30:00", None);
+        let kpi = compute_sla_kpi(
+            &defs,
+            &[resolu, vivant],
+            &cal,
+            dt("2025-01-13 09:30:00"),
+            "month",
+            0.5,
+            None,
+        );
+        assert_eq!(kpi.resolution.respectes, 1);
+        assert_eq!(kpi.vivants.ttr_depasses, 0);
+        assert_eq!(kpi.a_risque[0].echeance, "2025-01-13T16:30:00");
+        assert_eq!(kpi.a_risque[0].heures_restantes, 6.0);
+    }
+
+    #[test]
+    fn test_without_definitions() {
+        let tickets = vec![
+            ticket(
+                1,
+                "Moyenne",
+                "2025-01-06 09:00:00",
+                Some("2025-01-06 18:00:00"),
+            ),
+            ticket(2, "Moyenne", "2025-01-06 09:00:00", None),
+        ];
+        let kpi = compute_sla_kpi(
+            &[],
+            &tickets,
+            &continu(),
+            dt("2025-03-01 00:00:00"),
+            "month",
+            3.0,
+            Some(10),
+        );
+        assert_eq!(kpi.resolution.sans_sla, 1);
+        assert_eq!(kpi.vivants.sans_sla, 1);
+        assert_eq!(kpi.resolution.taux_respect, 0.0);
+    }
+}
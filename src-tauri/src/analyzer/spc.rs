@@ -0,0 +1,326 @@
+//! Maîtrise statistique des procédés : cartes de contrôle sur les séries
+//! par période (arrivées, MTTR, taux N1).
+//!
+//! - carte aux valeurs individuelles avec étendue mobile (XmR) : σ estimé
+//!   par MR̄ / 1,128, limites à ± 3σ, étendue mobile bornée à 3,267 MR̄ ;
+//! - carte p pour les proportions : limites propres à chaque point selon son
+//!   effectif, p̄ ± 3 √(p̄(1 − p̄) / n).
+//!
+//! Les règles de Western Electric distinguent un signal du bruit ; chaque
+//! point porte les règles qu'il déclenche (fin de la fenêtre concernée).
+
+use serde::{Deserialize, Serialize};
+
+use super::stats::{moyenne, round2};
+
+/// d2 pour des étendues mobiles de 2 points.
+const D2: f64 = 1.128;
+/// D4 pour des étendues mobiles de 2 points.
+const D4: f64 = 3.267;
+
+pub const REGLES_WESTERN_ELECTRIC: [(u8, &str); 4] = [
+    (1, "1 point au-delà de 3σ"),
+    (2, "2 points sur 3 au-delà de 2σ, du même côté"),
+    (3, "4 points sur 5 au-delà de 1σ, du même côté"),
+    (4, "8 points consécutifs du même côté de la moyenne"),
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpcKpi {
+    pub granularite: String,
+    pub arrivees: CarteControle,
+    pub mttr: CarteControle,
+    pub taux_n1: CarteControle,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CarteControle {
+    pub indicateur: String,
+    /// "individuelle" (XmR) ou "p".
+    pub type_carte: String,
+    pub centre: Option<f64>,
+    pub sigma: Option<f64>,
+    /// Limites globales ; pour une carte p, à l'effectif moyen.
+    pub limite_sup: Option<f64>,
+    pub limite_inf: Option<f64>,
+    pub moyenne_etendue_mobile: Option<f64>,
+    pub limite_sup_etendue_mobile: Option<f64>,
+    pub points: Vec<PointControle>,
+    pub violations: Vec<ViolationControle>,
+    pub derniere_periode_hors_controle: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointControle {
+    pub periode: String,
+    pub valeur: f64,
+    /// Effectif de la période (carte p).
+    pub effectif: Option<i64>,
+    pub etendue_mobile: Option<f64>,
+    pub limite_sup: Option<f64>,
+    pub limite_inf: Option<f64>,
+    pub regles: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViolationControle {
+    pub periode: String,
+    pub regle: u8,
+    pub description: String,
+}
+
+/// Au moins `k` des `n` derniers écarts réduits (jusqu'à `i`) au-delà de
+/// `seuil` du même côté que le point `i`, lui-même au-delà.
+fn k_parmi_n(z: &[Option<f64>], i: usize, k: usize, n: usize, seuil: f64) -> bool {
+    let Some(zi) = z[i] else {
+        return false;
+    };
+    if zi.abs() <= seuil || i + 1 < n {
+        return false;
+    }
+    let signe = zi.signum();
+    z[i + 1 - n..=i]
+        .iter()
+        .filter(|v| v.is_some_and(|v| v * signe > seuil))
+        .count()
+        >= k
+}
+
+/// Règles de Western Electric déclenchées par chaque point.
+pub fn western_electric(valeurs: &[f64], centre: f64, sigmas: &[Option<f64>]) -> Vec<Vec<u8>> {
+    let z: Vec<Option<f64>> = valeurs
+        .iter()
+        .zip(sigmas)
+        .map(|(v, s)| s.filter(|s| *s > 0.0).map(|s| (v - centre) / s))
+        .collect();
+    (0..valeurs.len())
+        .map(|i| {
+            let mut regles = Vec::new();
+            if z[i].is_some_and(|v| v.abs() > 3.0) {
+                regles.push(1);
+            }
+            if k_parmi_n(&z, i, 2, 3, 2.0) {
+                regles.push(2);
+            }
+            if k_parmi_n(&z, i, 4, 5, 1.0) {
+                regles.push(3);
+            }
+            if i >= 7 {
+                let cote = (valeurs[i] - centre).signum();
+                if valeurs[i] != centre
+                    && valeurs[i - 7..=i]
+                        .iter()
+                        .all(|v| *v != centre && (v - centre).signum() == cote)
+                {
+                    regles.push(4);
+                }
+            }
+            regles
+        })
+        .collect()
+}
+
+fn finaliser(mut carte: CarteControle, regles: Vec<Vec<u8>>) -> CarteControle {
+    for (point, regles) in carte.points.iter_mut().zip(regles) {
+        for &r in &regles {
+            carte.violations.push(ViolationControle {
+                periode: point.periode.clone(),
+                regle: r,
+                description: REGLES_WESTERN_ELECTRIC[(r - 1) as usize].1.to_string(),
+            });
+        }
+        point.regles = regles;
+    }
+    carte.derniere_periode_hors_controle =
+        carte.points.last().is_some_and(|p| !p.regles.is_empty());
+    carte
+}
+
+/// Carte XmR d'une série (valeurs positives : limite basse bornée à 0).
+pub fn carte_individuelle(indicateur: &str, serie: &[(String, f64)]) -> CarteControle {
+    let valeurs: Vec<f64> = serie.iter().map(|p| p.1).collect();
+    let etendues: Vec<f64> = valeurs.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+    let centre = (!valeurs.is_empty()).then(|| moyenne(&valeurs));
+    let mr_bar = (!etendues.is_empty()).then(|| moyenne(&etendues));
+    let sigma = mr_bar.map(|mr| mr / D2);
+    let limites = centre
+        .zip(sigma)
+        .map(|(c, s)| (round2(c + 3.0 * s), round2((c - 3.0 * s).max(0.0))));
+
+    let points = serie
+        .iter()
+        .enumerate()
+        .map(|(i, (periode, valeur))| PointControle {
+            periode: periode.clone(),
+            valeur: *valeur,
+            effectif: None,
+            etendue_mobile: (i > 0).then(|| round2(etendues[i - 1])),
+            limite_sup: limites.map(|l| l.0),
+            limite_inf: limites.map(|l| l.1),
+            regles: Vec::new(),
+        })
+        .collect();
+    let carte = CarteControle {
+        indicateur: indicateur.to_string(),
+        type_carte: "individuelle".to_string(),
+        centre: centre.map(round2),
+        sigma: sigma.map(round2),
+        limite_sup: limites.map(|l| l.0),
+        limite_inf: limites.map(|l| l.1),
+        moyenne_etendue_mobile: mr_bar.map(round2),
+        limite_sup_etendue_mobile: mr_bar.map(|mr| round2(D4 * mr)),
+        points,
+        ..Default::default()
+    };
+    let regles = match centre {
+        Some(c) => western_electric(&valeurs, c, &vec![sigma; valeurs.len()]),
+        None => Vec::new(),
+    };
+    finaliser(carte, regles)
+}
+
+/// Carte p d'une proportion exprimée en % : (période, succès, effectif).
+pub fn carte_p(indicateur: &str, serie: &[(String, i64, i64)]) -> CarteControle {
+    let serie: Vec<&(String, i64, i64)> = serie.iter().filter(|p| p.2 > 0).collect();
+    let succes: i64 = serie.iter().map(|p| p.1).sum();
+    let effectif: i64 = serie.iter().map(|p| p.2).sum();
+    let p_bar = (effectif > 0).then(|| succes as f64 / effectif as f64);
+    let sigma_n = |n: f64| p_bar.map(|p| (p * (1.0 - p) / n).sqrt() * 100.0);
+    let limites = |s: Option<f64>| {
+        p_bar.zip(s).map(|(p, s)| {
+            let c = p * 100.0;
+            (
+                round2((c + 3.0 * s).min(100.0)),
+                round2((c - 3.0 * s).max(0.0)),
+            )
+        })
+    };
+
+    let valeurs: Vec<f64> = serie
+        .iter()
+        .map(|p| p.1 as f64 / p.2 as f64 * 100.0)
+        .collect();
+    let sigmas: Vec<Option<f64>> = serie.iter().map(|p| sigma_n(p.2 as f64)).collect();
+    let points = serie
+        .iter()
+        .zip(&valeurs)
+        .zip(&sigmas)
+        .map(|((p, v), s)| {
+            let l = limites(*s);
+            PointControle {
+                periode: p.0.clone(),
+                valeur: round2(*v),
+                effectif: Some(p.2),
+                etendue_mobile: None,
+                limite_sup: l.map(|l| l.0),
+                limite_inf: l.map(|l| l.1),
+                regles: Vec::new(),
+            }
+        })
+        .collect();
+    let sigma_moyen = (!serie.is_empty())
+        .then(|| sigma_n(effectif as f64 / serie.len() as f64))
+        .flatten();
+    let global = limites(sigma_moyen);
+    let carte = CarteControle {
+        indicateur: indicateur.to_string(),
+        type_carte: "p".to_string(),
+        centre: p_bar.map(|p| round2(p * 100.0)),
+        sigma: sigma_moyen.map(round2),
+        limite_sup: global.map(|l| l.0),
+        limite_inf: global.map(|l| l.1),
+        points,
+        ..Default::default()
+    };
+    let regles = match p_bar {
+        Some(p) => western_electric(&valeurs, p * 100.0, &sigmas),
+        None => Vec::new(),
+    };
+    finaliser(carte, regles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn serie(valeurs: &[f64]) -> Vec<(String, f64)> {
+        valeurs
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("P{i:02}"), *v))
+            .collect()
+    }
+
+    #[test]
+    fn test_xmr_limits_and_spike() {
+        let mut v = vec![10.0, 12.0, 11.0, 13.0, 10.0, 12.0, 11.0, 13.0, 10.0, 12.0];
+        v.push(40.0);
+        let carte = carte_individuelle("arrivees", &serie(&v));
+        // Moyenne 14, MR̄ = (2+1+2+3+2+1+2+3+2+28)/10 = 4,6
+        assert_eq!(carte.centre, Some(14.0));
+        assert_eq!(carte.moyenne_etendue_mobile, Some(4.6));
+        assert_eq!(carte.limite_sup, Some(26.23));
+        assert_eq!(carte.limite_inf, Some(1.77));
+        assert_eq!(carte.limite_sup_etendue_mobile, Some(15.03));
+        assert_eq!(carte.points[10].regles, vec![1]);
+        assert!(carte.derniere_periode_hors_controle);
+        assert!(carte.points[..10].iter().all(|p| !p.regles.contains(&1)));
+    }
+
+    #[test]
+    fn test_western_electric_rules() {
+        let sigma = vec![Some(1.0); 10];
+        // Règle 2 : 2 sur 3 au-delà de 2σ
+        let r = western_electric(
+            &[0.0, 2.5, 0.0, 2.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            0.0,
+            &sigma,
+        );
+        assert_eq!(r[3], vec![2]);
+        assert!(r[1].is_empty());
+        // Règle 3 : 4 sur 5 au-delà de 1σ
+        let r = western_electric(
+            &[1.5, 1.5, 0.2, 1.5, 1.5, 0.0, 0.0, 0.0, 0.0, 0.0],
+            0.0,
+            &sigma,
+        );
+        assert_eq!(r[4], vec![3]);
+        // Règle 4 : 8 points du même côté
+        let r = western_electric(
+            &[0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, -0.5, 0.5],
+            0.0,
+            &sigma,
+        );
+        assert_eq!(r[7], vec![4]);
+        assert!(r[9].is_empty());
+        // σ nul : aucune règle évaluable hormis la règle 4
+        let r = western_electric(&[5.0, 5.0], 5.0, &[Some(0.0), Some(0.0)]);
+        assert!(r.iter().all(Vec::is_empty));
+    }
+
+    #[test]
+    fn test_p_chart_per_point_limits() {
+        let serie: Vec<(String, i64, i64)> = vec![
+            ("2025-01".into(), 70, 100),
+            ("2025-02".into(), 18, 25),
+            ("2025-03".into(), 30, 100),
+            ("2025-04".into(), 0, 0),
+        ];
+        let carte = carte_p("taux_n1", &serie);
+        // p̄ = 118 / 225 ; la période sans effectif est ignorée
+        assert_eq!(carte.points.len(), 3);
+        assert_eq!(carte.centre, Some(52.44));
+        let (large, etroit) = (&carte.points[1], &carte.points[0]);
+        assert!(
+            large.limite_sup.unwrap() - large.limite_inf.unwrap()
+                > etroit.limite_sup.unwrap() - etroit.limite_inf.unwrap()
+        );
+        assert_eq!(carte.points[2].valeur, 30.0);
+        assert_eq!(carte.points[2].regles, vec![1]);
+        assert_eq!(carte.violations.len(), 2);
+    }
+}
@@ -42,6 +42,68 @@ pub fn ecart_type(values: &[f64]) -> f64 {
     variance.sqrt()
 }
 
+/// Least-squares slope of `y` against `x`. Returns None with fewer than
+/// two points or a constant `x`.
+pub fn pente(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mx = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let my = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = points.iter().map(|p| (p.0 - mx).powi(2)).sum();
+    if sxx == 0.0 {
+        return None;
+    }
+    let sxy: f64 = points.iter().map(|p| (p.0 - mx) * (p.1 - my)).sum();
+    Some(sxy / sxx)
+}
+
+/// Gini coefficient of non-negative values (0 = perfectly even).
+/// Returns None if the slice is empty or sums to zero.
+pub fn gini(values: &[f64]) -> Option<f64> {
+    let total: f64 = values.iter().sum();
+    if values.is_empty() || total <= 0.0 {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let n = sorted.len() as f64;
+    let pondere: f64 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (2.0 * (i as f64 + 1.0) - n - 1.0) * v)
+        .sum();
+    Some(pondere / (n * total))
+}
+
+/// Coefficient of variation (population standard deviation / mean).
+/// Returns None if the slice is empty or the mean is zero.
+pub fn coefficient_variation(values: &[f64]) -> Option<f64> {
+    let mean = moyenne(values);
+    (mean != 0.0).then(|| ecart_type(values) / mean)
+}
+
+/// Rounds to one decimal place.
+pub fn round1(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// Rounds to two decimal places.
+pub fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// Share of `n` in `total` as a percentage rounded to one decimal.
+/// Returns 0.0 if `total` is zero.
+pub fn pct(n: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        round1(n as f64 / total as f64 * 100.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,4 +203,46 @@ mod tests {
         // [0, 10] → mean=5, variance=25, std dev=5
         assert!((ecart_type(&[0.0, 10.0]) - 5.0).abs() < 1e-10);
     }
+
+    // --- pente ---
+
+    #[test]
+    fn test_pente() {
+        assert_eq!(pente(&[(0.0, 1.0), (1.0, 3.0), (2.0, 5.0)]), Some(2.0));
+        assert_eq!(pente(&[(1.0, 1.0)]), None);
+        assert_eq!(pente(&[(1.0, 1.0), (1.0, 2.0)]), None);
+    }
+
+    // --- gini / coefficient_variation ---
+
+    #[test]
+    fn test_gini() {
+        assert_eq!(gini(&[]), None);
+        assert_eq!(gini(&[0.0, 0.0]), None);
+        assert_eq!(gini(&[5.0, 5.0, 5.0]), Some(0.0));
+        // Tout sur une seule personne parmi 4 : (n - 1) / n
+        assert_eq!(gini(&[0.0, 0.0, 0.0, 8.0]), Some(0.75));
+        let g = gini(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert!((g - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_coefficient_variation() {
+        assert_eq!(coefficient_variation(&[]), None);
+        assert_eq!(coefficient_variation(&[4.0, 4.0]), Some(0.0));
+        // moyenne 5, écart-type 2
+        let cv = coefficient_variation(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]).unwrap();
+        assert!((cv - 0.4).abs() < 1e-9);
+    }
+
+    // --- round1 / round2 / pct ---
+
+    #[test]
+    fn test_arrondis_et_pourcentage() {
+        assert_eq!(round1(2.36), 2.4);
+        assert_eq!(round2(2.346), 2.35);
+        assert_eq!(pct(1, 3), 33.3);
+        assert_eq!(pct(2, 3), 66.7);
+        assert_eq!(pct(5, 0), 0.0);
+    }
 }
@@ -1,3 +1,7 @@
+use crate::analyzer::buckets::JeuTranches;
+use crate::analyzer::stats::pct;
+use crate::analyzer::thresholds::SeuilsEffectifs;
+use crate::analyzer::workload::etp_contractuel;
 use crate::commands::stock::{AgeRangeCount, TechnicianStock};
 use crate::config::AppConfig;
 
@@ -17,86 +21,55 @@ pub fn compute_median(values: &[f64]) -> f64 {
     }
 }
 
-/// Distribue des anciennetés en 4 tranches : <7j, 7-30j, 30-90j, >90j.
-#[allow(dead_code)]
-pub fn compute_age_distribution(anciennetes: &[i64]) -> Vec<AgeRangeCount> {
+/// Distribue des anciennetés (jours) selon un jeu de tranches configuré.
+/// `threshold_days` est la borne basse de la tranche, en jours entiers.
+pub fn compute_age_distribution(anciennetes: &[i64], jeu: &JeuTranches) -> Vec<AgeRangeCount> {
     let total = anciennetes.len();
-
-    let mut lt7: usize = 0;
-    let mut from7to30: usize = 0;
-    let mut from30to90: usize = 0;
-    let mut gt90: usize = 0;
-
-    for &a in anciennetes {
-        if a < 7 {
-            lt7 += 1;
-        } else if a < 30 {
-            from7to30 += 1;
-        } else if a < 90 {
-            from30to90 += 1;
-        } else {
-            gt90 += 1;
-        }
-    }
-
-    let pct = |count: usize| -> f64 {
-        if total == 0 {
-            0.0
-        } else {
-            count as f64 / total as f64 * 100.0
-        }
-    };
-
-    vec![
-        AgeRangeCount {
-            label: "< 7j".to_string(),
-            threshold_days: 7,
-            count: lt7,
-            percentage: pct(lt7),
-        },
-        AgeRangeCount {
-            label: "7-30j".to_string(),
-            threshold_days: 30,
-            count: from7to30,
-            percentage: pct(from7to30),
-        },
-        AgeRangeCount {
-            label: "30-90j".to_string(),
-            threshold_days: 90,
-            count: from30to90,
-            percentage: pct(from30to90),
-        },
-        AgeRangeCount {
-            label: "> 90j".to_string(),
-            threshold_days: usize::MAX,
-            count: gt90,
-            percentage: pct(gt90),
-        },
-    ]
+    let counts = jeu.compter(anciennetes.iter().map(|&a| a as f64));
+
+    jeu.libelles()
+        .into_iter()
+        .zip(jeu.bornes_basses_jours())
+        .zip(counts)
+        .map(|((label, borne), count)| AgeRangeCount {
+            label,
+            threshold_days: borne.floor() as usize,
+            count,
+            percentage: pct(count, total),
+        })
+        .collect()
 }
 
-/// Retourne la couleur RAG selon le nombre de tickets et les seuils de la config.
-/// Vert  : stock < seuil_couleur_vert
-/// Jaune : seuil_couleur_vert  <= stock < seuil_couleur_jaune
-/// Orange: seuil_couleur_jaune <= stock < seuil_couleur_orange
-/// Rouge : stock >= seuil_couleur_orange
-pub fn compute_couleur_seuil(stock: u32, config: &AppConfig) -> String {
-    if stock < config.seuil_couleur_vert {
+/// Retourne la couleur RAG selon la charge (stock par ETP) et les seuils effectifs.
+/// Vert  : charge < seuil_couleur_vert
+/// Jaune : seuil_couleur_vert  <= charge < seuil_couleur_jaune
+/// Orange: seuil_couleur_jaune <= charge < seuil_couleur_orange
+/// Rouge : charge >= seuil_couleur_orange
+pub fn compute_couleur_seuil(charge: f64, seuils: &SeuilsEffectifs) -> String {
+    if charge < seuils.seuil_couleur_vert as f64 {
         "vert".to_string()
-    } else if stock < config.seuil_couleur_jaune {
+    } else if charge < seuils.seuil_couleur_jaune as f64 {
         "jaune".to_string()
-    } else if stock < config.seuil_couleur_orange {
+    } else if charge < seuils.seuil_couleur_orange as f64 {
         "orange".to_string()
     } else {
         "rouge".to_string()
     }
 }
 
-/// Calcule ecart_seuil et couleur_seuil pour chaque technicien.
+/// Calcule la charge par ETP, ecart_seuil et couleur_seuil pour chaque technicien.
+/// Un technicien à 50 % porte la moitié du seuil d'un temps plein. Les seuils
+/// sont ceux du technicien, à défaut de son groupe majoritaire, à défaut globaux.
 pub fn enrich_technician_stock(techs: &mut Vec<TechnicianStock>, config: &AppConfig) {
     for tech in techs.iter_mut() {
-        tech.ecart_seuil = tech.total as i64 - config.seuil_tickets_technicien as i64;
-        tech.couleur_seuil = compute_couleur_seuil(tech.total as u32, config);
+        let seuils = config.seuils_pour(tech.groupe.as_deref(), Some(&tech.technicien));
+        let etp = etp_contractuel(&config.capacites_techniciens, &tech.technicien);
+        let charge = tech.total as f64 / etp;
+        tech.etp = etp;
+        tech.stock_par_etp = (charge * 10.0).round() / 10.0;
+        tech.ecart_seuil = (charge - seuils.seuil_tickets_technicien as f64).round() as i64;
+        tech.couleur_seuil = compute_couleur_seuil(charge, &seuils);
+        tech.seuils = seuils;
     }
 }
 
@@ -108,17 +81,9 @@ mod tests {
 
     fn default_config() -> AppConfig {
         AppConfig {
-            seuil_tickets_technicien: 20,
-            seuil_anciennete_cloturer: 90,
-            seuil_inactivite_cloturer: 60,
-            seuil_anciennete_relancer: 30,
-            seuil_inactivite_relancer: 14,
-            seuil_couleur_vert: 10,
-            seuil_couleur_jaune: 20,
-            seuil_couleur_orange: 40,
-            seuil_similarite_doublons: 0.92,
             statuts_vivants: vec![],
             statuts_termines: vec![],
+            ..Default::default()
         }
     }
 
@@ -146,9 +111,22 @@ mod tests {
 
     // --- compute_age_distribution ---
 
+    fn jeu_7_30_90() -> JeuTranches {
+        use crate::analyzer::buckets::UniteTranches;
+        JeuTranches {
+            nom: "test".to_string(),
+            unite: UniteTranches::Jours,
+            bornes: vec![7.0, 30.0, 90.0],
+            libelles: ["< 7j", "7-30j", "30-90j", "> 90j"]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        }
+    }
+
     #[test]
     fn test_age_distribution_vide() {
-        let dist = compute_age_distribution(&[]);
+        let dist = compute_age_distribution(&[], &jeu_7_30_90());
         assert_eq!(dist.len(), 4);
         for range in &dist {
             assert_eq!(range.count, 0);
@@ -159,7 +137,7 @@ mod tests {
     #[test]
     fn test_age_distribution_repartition() {
         let ages: Vec<i64> = vec![1, 5, 10, 25, 35, 60, 100, 200];
-        let dist = compute_age_distribution(&ages);
+        let dist = compute_age_distribution(&ages, &jeu_7_30_90());
 
         assert_eq!(dist[0].label, "< 7j");
         assert_eq!(dist[0].count, 2); // 1, 5
@@ -181,11 +159,12 @@ mod tests {
     #[test]
     fn test_age_distribution_bornes() {
         // 7 doit aller dans 7-30j (>= 7), 30 dans 30-90j (>= 30), 90 dans > 90j (>= 90)
-        let dist = compute_age_distribution(&[7, 30, 90]);
+        let dist = compute_age_distribution(&[7, 30, 90], &jeu_7_30_90());
         assert_eq!(dist[0].count, 0); // < 7j : aucun
         assert_eq!(dist[1].count, 1); // 7-30j : 7
         assert_eq!(dist[2].count, 1); // 30-90j : 30
         assert_eq!(dist[3].count, 1); // > 90j : 90
+        assert_eq!(dist[3].threshold_days, 90);
     }
 
     // --- compute_couleur_seuil ---
@@ -193,29 +172,29 @@ mod tests {
     #[test]
     fn test_couleur_vert() {
         let config = default_config(); // seuil_vert=10
-        assert_eq!(compute_couleur_seuil(0, &config), "vert");
-        assert_eq!(compute_couleur_seuil(9, &config), "vert");
+        assert_eq!(compute_couleur_seuil(0.0, &SeuilsEffectifs::globaux(&config)), "vert");
+        assert_eq!(compute_couleur_seuil(9.0, &SeuilsEffectifs::globaux(&config)), "vert");
     }
 
     #[test]
     fn test_couleur_jaune() {
         let config = default_config(); // seuil_vert=10, seuil_jaune=20
-        assert_eq!(compute_couleur_seuil(10, &config), "jaune");
-        assert_eq!(compute_couleur_seuil(19, &config), "jaune");
+        assert_eq!(compute_couleur_seuil(10.0, &SeuilsEffectifs::globaux(&config)), "jaune");
+        assert_eq!(compute_couleur_seuil(19.0, &SeuilsEffectifs::globaux(&config)), "jaune");
     }
 
     #[test]
     fn test_couleur_orange() {
         let config = default_config(); // seuil_jaune=20, seuil_orange=40
-        assert_eq!(compute_couleur_seuil(20, &config), "orange");
-        assert_eq!(compute_couleur_seuil(39, &config), "orange");
+        assert_eq!(compute_couleur_seuil(20.0, &SeuilsEffectifs::globaux(&config)), "orange");
+        assert_eq!(compute_couleur_seuil(39.0, &SeuilsEffectifs::globaux(&config)), "orange");
     }
 
     #[test]
     fn test_couleur_rouge() {
         let config = default_config(); // seuil_orange=40
-        assert_eq!(compute_couleur_seuil(40, &config), "rouge");
-        assert_eq!(compute_couleur_seuil(100, &config), "rouge");
+        assert_eq!(compute_couleur_seuil(40.0, &SeuilsEffectifs::globaux(&config)), "rouge");
+        assert_eq!(compute_couleur_seuil(100.0, &SeuilsEffectifs::globaux(&config)), "rouge");
     }
 
     // --- enrich_technician_stock ---
@@ -223,6 +202,7 @@ mod tests {
     fn make_tech(nom: &str, total: usize) -> TechnicianStock {
         TechnicianStock {
             technicien: nom.to_string(),
+            groupe: None,
             total,
             en_cours: 0,
             en_attente: 0,
@@ -231,9 +211,13 @@ mod tests {
             incidents: 0,
             demandes: 0,
             age_moyen_jours: 0.0,
+            age_moyen_jours_ouvres: 0.0,
             inactifs_14j: 0,
+            etp: 1.0,
+            stock_par_etp: 0.0,
             ecart_seuil: 0,
             couleur_seuil: String::new(),
+            seuils: Default::default(),
         }
     }
 
@@ -260,4 +244,25 @@ mod tests {
         assert_eq!(techs[3].ecart_seuil, 30);
         assert_eq!(techs[3].couleur_seuil, "rouge");
     }
+
+    #[test]
+    fn test_enrich_technician_stock_normalise_par_etp() {
+        let mut config = default_config(); // seuil=20, vert<10, jaune<20, orange<40
+        config.capacites_techniciens = vec![crate::analyzer::workload::CapaciteTechnicien {
+            technicien: "Bob".to_string(),
+            etp_pct: 50.0,
+            absences: vec![],
+        }];
+        let mut techs = vec![make_tech("Alice", 15), make_tech("Bob", 15)];
+        enrich_technician_stock(&mut techs, &config);
+
+        // Alice à temps plein : 15 → jaune
+        assert_eq!(techs[0].etp, 1.0);
+        assert_eq!(techs[0].couleur_seuil, "jaune");
+        // Bob à mi-temps : 15 tickets valent 30 par ETP → orange
+        assert_eq!(techs[1].etp, 0.5);
+        assert_eq!(techs[1].stock_par_etp, 30.0);
+        assert_eq!(techs[1].ecart_seuil, 10);
+        assert_eq!(techs[1].couleur_seuil, "orange");
+    }
 }
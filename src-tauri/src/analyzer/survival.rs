@@ -0,0 +1,366 @@
+//! Analyse de survie des tickets (estimateur de Kaplan–Meier).
+//!
+//! Le MTTR calculé sur les seuls tickets résolus sous-estime le délai réel
+//! quand le stock est ancien : les tickets vivants sont ici pris en compte
+//! comme observations censurées à droite (à leur âge à la date de l'import).
+//! Intervalles de confiance à 95 % : variance de Greenwood, transformation
+//! log(-log) ; intervalle de la médiane selon Brookmeyer–Crowley.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::stats::round1;
+
+const Z_95: f64 = 1.959_964;
+/// Horizons de la table "encore ouvert après N jours".
+pub const HORIZONS_JOURS: [u32; 9] = [1, 2, 7, 14, 30, 60, 90, 180, 365];
+/// Borne de la courbe jour par jour.
+const MAX_JOURS_COURBE: u32 = 730;
+/// Effectif minimal d'une modalité pour estimer sa médiane.
+const MIN_EFFECTIF: usize = 5;
+
+/// Observation : durée en jours, `resolu` = false si censurée (ticket vivant).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observation {
+    pub jours: f64,
+    pub resolu: bool,
+}
+
+/// Palier de la fonction de survie, à un instant d'événement.
+#[derive(Debug, Clone, PartialEq)]
+struct Palier {
+    jours: f64,
+    survie: f64,
+    ic_bas: f64,
+    ic_haut: f64,
+}
+
+/// Estimation de Kaplan–Meier (paliers aux instants de résolution).
+#[derive(Debug, Clone)]
+pub struct KaplanMeier {
+    paliers: Vec<Palier>,
+    pub effectif: usize,
+    pub evenements: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SurvieKpi {
+    pub effectif: usize,
+    pub resolus: usize,
+    /// Tickets vivants (censurés).
+    pub censures: usize,
+    pub mediane: MedianeSurvie,
+    /// Probabilité d'être encore ouvert, jour par jour.
+    pub courbe: Vec<PointSurvie>,
+    /// Même probabilité aux horizons usuels.
+    pub horizons: Vec<PointSurvie>,
+    pub par_type: Vec<SurvieDimension>,
+    pub par_priorite: Vec<SurvieDimension>,
+    pub par_groupe: Vec<SurvieDimension>,
+    pub par_categorie: Vec<SurvieDimension>,
+}
+
+/// Médiane de survie ; None quand la courbe (ou sa borne) ne descend pas sous 50 %.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MedianeSurvie {
+    pub jours: Option<f64>,
+    pub ic_bas: Option<f64>,
+    pub ic_haut: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PointSurvie {
+    pub jours: u32,
+    pub probabilite_ouvert: f64,
+    pub ic_bas: f64,
+    pub ic_haut: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SurvieDimension {
+    pub label: String,
+    pub effectif: usize,
+    pub resolus: usize,
+    pub censures: usize,
+    pub mediane: MedianeSurvie,
+}
+
+fn round3(v: f64) -> f64 {
+    (v * 1000.0).round() / 1000.0
+}
+
+impl KaplanMeier {
+    pub fn estimer(observations: &[Observation]) -> Self {
+        let mut obs: Vec<Observation> = observations
+            .iter()
+            .copied()
+            .filter(|o| o.jours.is_finite() && o.jours >= 0.0)
+            .collect();
+        // À instant égal, les résolutions précèdent les censures
+        obs.sort_by(|a, b| a.jours.total_cmp(&b.jours).then(b.resolu.cmp(&a.resolu)));
+
+        let effectif = obs.len();
+        let mut a_risque = effectif;
+        let mut survie = 1.0;
+        let mut greenwood = 0.0;
+        let mut evenements = 0;
+        let mut paliers = Vec::new();
+
+        let mut i = 0;
+        while i < obs.len() {
+            let t = obs[i].jours;
+            let (mut d, mut c) = (0usize, 0usize);
+            while i < obs.len() && obs[i].jours == t {
+                if obs[i].resolu {
+                    d += 1;
+                } else {
+                    c += 1;
+                }
+                i += 1;
+            }
+            if d > 0 {
+                let n = a_risque as f64;
+                survie *= 1.0 - d as f64 / n;
+                if a_risque > d {
+                    greenwood += d as f64 / (n * (n - d as f64));
+                }
+                evenements += d;
+                let (ic_bas, ic_haut) = intervalle_log_log(survie, greenwood);
+                paliers.push(Palier {
+                    jours: t,
+                    survie,
+                    ic_bas,
+                    ic_haut,
+                });
+            }
+            a_risque -= d + c;
+        }
+
+        Self {
+            paliers,
+            effectif,
+            evenements,
+        }
+    }
+
+    /// (S(t), borne basse, borne haute) : fonction en escalier continue à droite.
+    pub fn survie_a(&self, jours: f64) -> (f64, f64, f64) {
+        let idx = self.paliers.partition_point(|p| p.jours <= jours);
+        match idx {
+            0 => (1.0, 1.0, 1.0),
+            _ => {
+                let p = &self.paliers[idx - 1];
+                (p.survie, p.ic_bas, p.ic_haut)
+            }
+        }
+    }
+
+    fn premier_sous_mediane(&self, valeur: impl Fn(&Palier) -> f64) -> Option<f64> {
+        self.paliers
+            .iter()
+            .find(|p| valeur(p) <= 0.5)
+            .map(|p| round1(p.jours))
+    }
+
+    pub fn mediane(&self) -> MedianeSurvie {
+        MedianeSurvie {
+            jours: self.premier_sous_mediane(|p| p.survie),
+            ic_bas: self.premier_sous_mediane(|p| p.ic_haut),
+            ic_haut: self.premier_sous_mediane(|p| p.ic_bas),
+        }
+    }
+
+    fn point(&self, jours: u32) -> PointSurvie {
+        let (s, bas, haut) = self.survie_a(jours as f64);
+        PointSurvie {
+            jours,
+            probabilite_ouvert: round3(s),
+            ic_bas: round3(bas),
+            ic_haut: round3(haut),
+        }
+    }
+
+    /// Probabilité d'être encore ouvert à chaque jour entier, jusqu'à la plus
+    /// grande durée observée (bornée).
+    pub fn courbe(&self, max_jours: f64) -> Vec<PointSurvie> {
+        let fin = (max_jours.max(0.0).ceil() as u32).min(MAX_JOURS_COURBE);
+        (0..=fin).map(|j| self.point(j)).collect()
+    }
+
+    pub fn horizons(&self) -> Vec<PointSurvie> {
+        HORIZONS_JOURS.iter().map(|&j| self.point(j)).collect()
+    }
+}
+
+/// Intervalle à 95 % par transformation log(-log) de S.
+fn intervalle_log_log(survie: f64, greenwood: f64) -> (f64, f64) {
+    if survie <= 0.0 {
+        return (0.0, 0.0);
+    }
+    if survie >= 1.0 || greenwood <= 0.0 {
+        return (survie, survie);
+    }
+    let se = greenwood.sqrt() / survie.ln().abs();
+    let bas = survie.powf((Z_95 * se).exp());
+    let haut = survie.powf((-Z_95 * se).exp());
+    (bas, haut)
+}
+
+/// Ticket observé, avec ses dimensions de ventilation.
+#[derive(Debug, Clone)]
+pub struct TicketSurvie {
+    pub observation: Observation,
+    pub type_ticket: Option<String>,
+    pub priorite: Option<String>,
+    pub groupe: Option<String>,
+    pub categorie: Option<String>,
+}
+
+fn par_dimension(
+    tickets: &[TicketSurvie],
+    cle: impl Fn(&TicketSurvie) -> Option<&str>,
+) -> Vec<SurvieDimension> {
+    let mut groupes: BTreeMap<&str, Vec<Observation>> = BTreeMap::new();
+    for t in tickets {
+        if let Some(k) = cle(t).filter(|k| !k.is_empty()) {
+            groupes.entry(k).or_default().push(t.observation);
+        }
+    }
+    let mut result: Vec<SurvieDimension> = groupes
+        .into_iter()
+        .filter(|(_, obs)| obs.len() >= MIN_EFFECTIF)
+        .map(|(label, obs)| {
+            let km = KaplanMeier::estimer(&obs);
+            SurvieDimension {
+                label: label.to_string(),
+                effectif: km.effectif,
+                resolus: km.evenements,
+                censures: km.effectif - km.evenements,
+                mediane: km.mediane(),
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| b.effectif.cmp(&a.effectif).then(a.label.cmp(&b.label)));
+    result
+}
+
+/// Survie globale et par type, priorité, groupe et catégorie.
+pub fn compute_survie(tickets: &[TicketSurvie]) -> SurvieKpi {
+    let observations: Vec<Observation> = tickets.iter().map(|t| t.observation).collect();
+    let km = KaplanMeier::estimer(&observations);
+    let max_jours = observations.iter().map(|o| o.jours).fold(0.0_f64, f64::max);
+
+    SurvieKpi {
+        effectif: km.effectif,
+        resolus: km.evenements,
+        censures: km.effectif - km.evenements,
+        mediane: km.mediane(),
+        courbe: km.courbe(max_jours),
+        horizons: km.horizons(),
+        par_type: par_dimension(tickets, |t| t.type_ticket.as_deref()),
+        par_priorite: par_dimension(tickets, |t| t.priorite.as_deref()),
+        par_groupe: par_dimension(tickets, |t| t.groupe.as_deref()),
+        par_categorie: par_dimension(tickets, |t| t.categorie.as_deref()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obs(jours: f64, resolu: bool) -> Observation {
+        Observation { jours, resolu }
+    }
+
+    #[test]
+    fn test_without_censoring_matches_empirical() {
+        let data: Vec<Observation> = (1..=10).map(|j| obs(j as f64, true)).collect();
+        let km = KaplanMeier::estimer(&data);
+        assert_eq!(km.evenements, 10);
+        assert!((km.survie_a(3.0).0 - 0.7).abs() < 1e-9);
+        assert!((km.survie_a(2.5).0 - 0.8).abs() < 1e-9);
+        assert_eq!(km.survie_a(0.5).0, 1.0);
+        assert_eq!(km.survie_a(10.0).0, 0.0);
+        assert_eq!(km.mediane().jours, Some(5.0));
+    }
+
+    #[test]
+    fn test_censoring_textbook_example() {
+        // 6 résolus / 4 censurés : S(t) = 1, 0.9, 0.9·7/8, …
+        let data = vec![
+            obs(1.0, true),
+            obs(2.0, false),
+            obs(3.0, true),
+            obs(3.0, true),
+            obs(4.0, false),
+            obs(5.0, true),
+            obs(6.0, false),
+            obs(7.0, true),
+            obs(8.0, true),
+            obs(9.0, false),
+        ];
+        let km = KaplanMeier::estimer(&data);
+        let s3 = 0.9 * (1.0 - 2.0 / 8.0);
+        assert!((km.survie_a(3.0).0 - s3).abs() < 1e-9);
+        let s5 = s3 * (1.0 - 1.0 / 5.0);
+        assert!((km.survie_a(5.0).0 - s5).abs() < 1e-9);
+        let (s, bas, haut) = km.survie_a(5.0);
+        assert!(bas < s && s < haut);
+        assert!(bas >= 0.0 && haut <= 1.0);
+        // Médiane : premier instant où S <= 0.5 (S(5) = 0.54, S(7) = 0.36)
+        assert_eq!(km.mediane().jours, Some(7.0));
+    }
+
+    #[test]
+    fn test_censored_backlog_raises_median() {
+        // Résolus rapides + gros stock ancien : la médiane naïve sur les
+        // résolus (2j) sous-estime fortement le délai réel.
+        let mut data: Vec<Observation> = (0..10).map(|_| obs(2.0, true)).collect();
+        data.extend((0..30).map(|_| obs(100.0, false)));
+        let km = KaplanMeier::estimer(&data);
+        assert_eq!(km.mediane().jours, None);
+        assert!((km.survie_a(50.0).0 - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_compute_survie_breakdowns() {
+        let ticket = |jours: f64, resolu: bool, type_ticket: &str| TicketSurvie {
+            observation: obs(jours, resolu),
+            type_ticket: Some(type_ticket.to_string()),
+            priorite: None,
+            groupe: Some("Support".to_string()),
+            categorie: None,
+        };
+        let mut tickets: Vec<TicketSurvie> = (1..=6)
+            .map(|j| ticket(j as f64, true, "Incident"))
+            .collect();
+        tickets.extend((1..=3).map(|j| ticket(j as f64 * 10.0, false, "Demande")));
+
+        let kpi = compute_survie(&tickets);
+        assert_eq!(kpi.effectif, 9);
+        assert_eq!(kpi.resolus, 6);
+        assert_eq!(kpi.censures, 3);
+        assert_eq!(kpi.courbe.len(), 31);
+        assert_eq!(kpi.courbe[0].probabilite_ouvert, 1.0);
+        assert_eq!(kpi.horizons.len(), HORIZONS_JOURS.len());
+        // "Demande" a moins de MIN_EFFECTIF tickets
+        assert_eq!(kpi.par_type.len(), 1);
+        assert_eq!(kpi.par_type[0].label, "Incident");
+        assert_eq!(kpi.par_type[0].mediane.jours, Some(3.0));
+        assert_eq!(kpi.par_groupe[0].censures, 3);
+        assert!(kpi.par_priorite.is_empty());
+    }
+
+    #[test]
+    fn test_empty() {
+        let kpi = compute_survie(&[]);
+        assert_eq!(kpi.effectif, 0);
+        assert_eq!(kpi.mediane, MedianeSurvie::default());
+        assert_eq!(kpi.courbe.len(), 1);
+    }
+}
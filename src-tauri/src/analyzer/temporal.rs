@@ -33,6 +33,21 @@ pub fn generate_period_keys(
     }
 }
 
+/// Clé de période d'une date, au format de `generate_period_keys`
+/// (YYYY-MM-DD, YYYY-Www ISO, YYYY-MM, YYYY-Qn, YYYY).
+pub fn period_key(dt: NaiveDateTime, granularity: &str) -> String {
+    match granularity {
+        "day" => dt.format("%Y-%m-%d").to_string(),
+        "week" => {
+            let iw = dt.date().iso_week();
+            format!("{:04}-W{:02}", iw.year(), iw.week())
+        }
+        "quarter" => format!("{:04}-Q{}", dt.year(), (dt.month() - 1) / 3 + 1),
+        "year" => format!("{:04}", dt.year()),
+        _ => format!("{:04}-{:02}", dt.year(), dt.month()),
+    }
+}
+
 fn generate_day_keys(
     date_from: NaiveDateTime,
     date_to: NaiveDateTime,
@@ -228,6 +243,20 @@ mod tests {
         NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
     }
 
+    #[test]
+    fn test_period_key_matches_generated_keys() {
+        let d = dt("2025-01-01 10:00:00"); // mercredi, semaine ISO 2025-W01
+        assert_eq!(period_key(d, "day"), "2025-01-01");
+        assert_eq!(period_key(d, "week"), "2025-W01");
+        assert_eq!(period_key(d, "month"), "2025-01");
+        assert_eq!(period_key(d, "quarter"), "2025-Q1");
+        assert_eq!(period_key(d, "year"), "2025");
+        for gran in ["day", "week", "month", "quarter", "year"] {
+            let keys = generate_period_keys(d, d, gran);
+            assert_eq!(keys[0].0, period_key(d, gran));
+        }
+    }
+
     #[test]
     fn test_auto_granularity_day() {
         assert_eq!(auto_granularity(1), "day");
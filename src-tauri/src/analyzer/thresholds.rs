@@ -0,0 +1,376 @@
+//! Seuils par portée : surcharges des seuils globaux de `AppConfig` pour un
+//! chemin de groupe ou un technicien.
+//!
+//! Résolution champ par champ, du plus général au plus spécifique :
+//! global → groupes ancêtres (du plus court au plus long chemin) → groupe →
+//! technicien. Une surcharge de groupe s'applique aussi à ses sous-groupes
+//! (« DSI » couvre « DSI > Support »). Un champ non renseigné dans une
+//! surcharge hérite du niveau précédent.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PorteeSeuils {
+    Groupe,
+    Technicien,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SurchargeSeuils {
+    pub portee: PorteeSeuils,
+    /// Chemin de groupe (« DSI > Support ») ou nom du technicien.
+    pub valeur: String,
+    #[serde(default)]
+    pub seuil_tickets_technicien: Option<u32>,
+    #[serde(default)]
+    pub seuil_anciennete_cloturer: Option<u32>,
+    #[serde(default)]
+    pub seuil_inactivite_cloturer: Option<u32>,
+    #[serde(default)]
+    pub seuil_anciennete_relancer: Option<u32>,
+    #[serde(default)]
+    pub seuil_inactivite_relancer: Option<u32>,
+    #[serde(default)]
+    pub seuil_couleur_vert: Option<u32>,
+    #[serde(default)]
+    pub seuil_couleur_jaune: Option<u32>,
+    #[serde(default)]
+    pub seuil_couleur_orange: Option<u32>,
+}
+
+/// Seuils résolus pour une portée donnée.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeuilsEffectifs {
+    pub seuil_tickets_technicien: u32,
+    pub seuil_anciennete_cloturer: u32,
+    pub seuil_inactivite_cloturer: u32,
+    pub seuil_anciennete_relancer: u32,
+    pub seuil_inactivite_relancer: u32,
+    pub seuil_couleur_vert: u32,
+    pub seuil_couleur_jaune: u32,
+    pub seuil_couleur_orange: u32,
+}
+
+impl SeuilsEffectifs {
+    pub fn globaux(config: &AppConfig) -> Self {
+        Self {
+            seuil_tickets_technicien: config.seuil_tickets_technicien,
+            seuil_anciennete_cloturer: config.seuil_anciennete_cloturer,
+            seuil_inactivite_cloturer: config.seuil_inactivite_cloturer,
+            seuil_anciennete_relancer: config.seuil_anciennete_relancer,
+            seuil_inactivite_relancer: config.seuil_inactivite_relancer,
+            seuil_couleur_vert: config.seuil_couleur_vert,
+            seuil_couleur_jaune: config.seuil_couleur_jaune,
+            seuil_couleur_orange: config.seuil_couleur_orange,
+        }
+    }
+
+    fn appliquer(&mut self, s: &SurchargeSeuils) {
+        fn maj(champ: &mut u32, valeur: Option<u32>) {
+            if let Some(v) = valeur {
+                *champ = v;
+            }
+        }
+        maj(
+            &mut self.seuil_tickets_technicien,
+            s.seuil_tickets_technicien,
+        );
+        maj(
+            &mut self.seuil_anciennete_cloturer,
+            s.seuil_anciennete_cloturer,
+        );
+        maj(
+            &mut self.seuil_inactivite_cloturer,
+            s.seuil_inactivite_cloturer,
+        );
+        maj(
+            &mut self.seuil_anciennete_relancer,
+            s.seuil_anciennete_relancer,
+        );
+        maj(
+            &mut self.seuil_inactivite_relancer,
+            s.seuil_inactivite_relancer,
+        );
+        maj(&mut self.seuil_couleur_vert, s.seuil_couleur_vert);
+        maj(&mut self.seuil_couleur_jaune, s.seuil_couleur_jaune);
+        maj(&mut self.seuil_couleur_orange, s.seuil_couleur_orange);
+    }
+}
+
+/// Seuils effectifs d'une portée, pour l'écran de paramétrage.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeuilsPortee {
+    /// Absente pour les seuils globaux.
+    pub portee: Option<PorteeSeuils>,
+    pub valeur: Option<String>,
+    /// Groupe majoritaire retenu pour un technicien.
+    pub groupe: Option<String>,
+    /// Une surcharge est définie exactement pour cette portée.
+    pub surchargee: bool,
+    pub seuils: SeuilsEffectifs,
+}
+
+/// Vrai si `groupe` est le chemin `chemin` ou l'un de ses sous-groupes.
+fn couvre(chemin: &str, groupe: &str) -> bool {
+    groupe == chemin
+        || groupe
+            .strip_prefix(chemin)
+            .is_some_and(|reste| reste.starts_with(" > "))
+}
+
+/// Seuils applicables à un ticket ou un technicien, du plus spécifique au global.
+pub fn resoudre_seuils(
+    config: &AppConfig,
+    groupe: Option<&str>,
+    technicien: Option<&str>,
+) -> SeuilsEffectifs {
+    let mut seuils = SeuilsEffectifs::globaux(config);
+    if let Some(groupe) = groupe.filter(|g| !g.is_empty()) {
+        let mut groupes: Vec<&SurchargeSeuils> = config
+            .surcharges_seuils
+            .iter()
+            .filter(|s| s.portee == PorteeSeuils::Groupe && couvre(&s.valeur, groupe))
+            .collect();
+        groupes.sort_by_key(|s| s.valeur.len());
+        for s in groupes {
+            seuils.appliquer(s);
+        }
+    }
+    if let Some(technicien) = technicien.filter(|t| !t.is_empty()) {
+        if let Some(s) = config
+            .surcharges_seuils
+            .iter()
+            .find(|s| s.portee == PorteeSeuils::Technicien && s.valeur == technicien)
+        {
+            seuils.appliquer(s);
+        }
+    }
+    seuils
+}
+
+/// Seuils effectifs : global, puis chaque groupe et chaque technicien (ceux de
+/// l'import et ceux qui ont une surcharge), triés par nom.
+/// `techniciens` associe chaque technicien à son groupe majoritaire.
+pub fn seuils_par_portee(
+    config: &AppConfig,
+    groupes: &[String],
+    techniciens: &[(String, Option<String>)],
+) -> Vec<SeuilsPortee> {
+    let surchargee = |portee: PorteeSeuils, valeur: &str| {
+        config
+            .surcharges_seuils
+            .iter()
+            .any(|s| s.portee == portee && s.valeur == valeur)
+    };
+
+    let mut groupes: BTreeSet<&str> = groupes.iter().map(String::as_str).collect();
+    let mut techniciens: BTreeMap<&str, Option<&str>> = techniciens
+        .iter()
+        .map(|(t, g)| (t.as_str(), g.as_deref()))
+        .collect();
+    for s in &config.surcharges_seuils {
+        match s.portee {
+            PorteeSeuils::Groupe => {
+                groupes.insert(&s.valeur);
+            }
+            PorteeSeuils::Technicien => {
+                techniciens.entry(&s.valeur).or_insert(None);
+            }
+        }
+    }
+
+    let mut res = vec![SeuilsPortee {
+        portee: None,
+        valeur: None,
+        groupe: None,
+        surchargee: false,
+        seuils: SeuilsEffectifs::globaux(config),
+    }];
+    res.extend(groupes.into_iter().map(|g| SeuilsPortee {
+        portee: Some(PorteeSeuils::Groupe),
+        valeur: Some(g.to_string()),
+        groupe: None,
+        surchargee: surchargee(PorteeSeuils::Groupe, g),
+        seuils: resoudre_seuils(config, Some(g), None),
+    }));
+    res.extend(techniciens.into_iter().map(|(t, g)| SeuilsPortee {
+        portee: Some(PorteeSeuils::Technicien),
+        valeur: Some(t.to_string()),
+        groupe: g.map(String::from),
+        surchargee: surchargee(PorteeSeuils::Technicien, t),
+        seuils: resoudre_seuils(config, g, Some(t)),
+    }));
+    res
+}
+
+/// Vérifie les surcharges avant enregistrement : portée renseignée et unique,
+/// couleurs croissantes (vert ≤ jaune ≤ orange) une fois résolues.
+pub fn valider_surcharges(config: &AppConfig) -> Result<(), String> {
+    let mut vus = HashSet::new();
+    for s in &config.surcharges_seuils {
+        if s.valeur.trim().is_empty() {
+            return Err("Surcharge de seuils sans portée".to_string());
+        }
+        if !vus.insert((s.portee, s.valeur.as_str())) {
+            return Err(format!("Surcharge de seuils en double pour {}", s.valeur));
+        }
+        let r = match s.portee {
+            PorteeSeuils::Groupe => resoudre_seuils(config, Some(&s.valeur), None),
+            PorteeSeuils::Technicien => resoudre_seuils(config, None, Some(&s.valeur)),
+        };
+        if !(r.seuil_couleur_vert <= r.seuil_couleur_jaune
+            && r.seuil_couleur_jaune <= r.seuil_couleur_orange)
+        {
+            return Err(format!(
+                "{} : couleurs non croissantes ({} / {} / {})",
+                s.valeur, r.seuil_couleur_vert, r.seuil_couleur_jaune, r.seuil_couleur_orange
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AppConfig {
+        AppConfig {
+            statuts_vivants: vec![],
+            statuts_termines: vec![],
+            ..Default::default()
+        }
+    }
+
+    fn surcharge(portee: PorteeSeuils, valeur: &str) -> SurchargeSeuils {
+        SurchargeSeuils {
+            portee,
+            valeur: valeur.to_string(),
+            seuil_tickets_technicien: None,
+            seuil_anciennete_cloturer: None,
+            seuil_inactivite_cloturer: None,
+            seuil_anciennete_relancer: None,
+            seuil_inactivite_relancer: None,
+            seuil_couleur_vert: None,
+            seuil_couleur_jaune: None,
+            seuil_couleur_orange: None,
+        }
+    }
+
+    fn config_surchargee() -> AppConfig {
+        let mut c = config();
+        let mut dsi = surcharge(PorteeSeuils::Groupe, "DSI");
+        dsi.seuil_anciennete_cloturer = Some(180);
+        dsi.seuil_inactivite_relancer = Some(21);
+        let mut support = surcharge(PorteeSeuils::Groupe, "DSI > Support");
+        support.seuil_inactivite_relancer = Some(7);
+        let mut bob = surcharge(PorteeSeuils::Technicien, "Bob");
+        bob.seuil_inactivite_relancer = Some(3);
+        bob.seuil_couleur_orange = Some(30);
+        c.surcharges_seuils = vec![support, bob, dsi];
+        c
+    }
+
+    #[test]
+    fn test_resolution_globale_sans_surcharge() {
+        let c = config_surchargee();
+        let r = resoudre_seuils(&c, Some("RH"), Some("Alice"));
+        assert_eq!(r, SeuilsEffectifs::globaux(&c));
+        assert_eq!(
+            resoudre_seuils(&c, None, None),
+            SeuilsEffectifs::globaux(&c)
+        );
+    }
+
+    #[test]
+    fn test_resolution_du_plus_specifique_au_global() {
+        let c = config_surchargee();
+
+        let dsi = resoudre_seuils(&c, Some("DSI > Réseau"), None);
+        assert_eq!(dsi.seuil_anciennete_cloturer, 180);
+        assert_eq!(dsi.seuil_inactivite_relancer, 21);
+
+        // Le sous-groupe surcharge l'ancêtre, hérite des champs non renseignés
+        let support = resoudre_seuils(&c, Some("DSI > Support > N2"), None);
+        assert_eq!(support.seuil_inactivite_relancer, 7);
+        assert_eq!(support.seuil_anciennete_cloturer, 180);
+        assert_eq!(support.seuil_couleur_orange, 40);
+
+        // Le technicien l'emporte sur son groupe
+        let bob = resoudre_seuils(&c, Some("DSI > Support"), Some("Bob"));
+        assert_eq!(bob.seuil_inactivite_relancer, 3);
+        assert_eq!(bob.seuil_anciennete_cloturer, 180);
+        assert_eq!(bob.seuil_couleur_orange, 30);
+    }
+
+    #[test]
+    fn test_prefixe_sans_separateur_ne_couvre_pas() {
+        let c = config_surchargee();
+        let r = resoudre_seuils(&c, Some("DSI2"), None);
+        assert_eq!(r.seuil_anciennete_cloturer, 90);
+    }
+
+    #[test]
+    fn test_seuils_par_portee() {
+        let c = config_surchargee();
+        let portees = seuils_par_portee(
+            &c,
+            &["DSI > Support".to_string(), "RH".to_string()],
+            &[("Alice".to_string(), Some("DSI > Support".to_string()))],
+        );
+        let valeurs: Vec<Option<&str>> = portees.iter().map(|p| p.valeur.as_deref()).collect();
+        assert_eq!(
+            valeurs,
+            vec![
+                None,
+                Some("DSI"),
+                Some("DSI > Support"),
+                Some("RH"),
+                Some("Alice"),
+                Some("Bob")
+            ]
+        );
+        assert_eq!(portees[0].seuils, SeuilsEffectifs::globaux(&c));
+
+        // Alice hérite de son groupe majoritaire, sans surcharge propre
+        let alice = &portees[4];
+        assert!(!alice.surchargee);
+        assert_eq!(alice.seuils.seuil_inactivite_relancer, 7);
+        // Bob n'apparaît que par sa surcharge
+        let bob = &portees[5];
+        assert!(bob.surchargee);
+        assert_eq!(bob.groupe, None);
+        assert_eq!(bob.seuils.seuil_inactivite_relancer, 3);
+        assert_eq!(bob.seuils.seuil_anciennete_cloturer, 90);
+    }
+
+    #[test]
+    fn test_valider_surcharges() {
+        assert!(valider_surcharges(&config_surchargee()).is_ok());
+
+        let mut doublon = config_surchargee();
+        doublon
+            .surcharges_seuils
+            .push(surcharge(PorteeSeuils::Technicien, "Bob"));
+        assert!(valider_surcharges(&doublon).is_err());
+
+        let mut vide = config();
+        vide.surcharges_seuils = vec![surcharge(PorteeSeuils::Groupe, " ")];
+        assert!(valider_surcharges(&vide).is_err());
+
+        // vert 25 > jaune global 20
+        let mut inverse = config();
+        let mut s = surcharge(PorteeSeuils::Groupe, "DSI");
+        s.seuil_couleur_vert = Some(25);
+        inverse.surcharges_seuils = vec![s];
+        assert!(valider_surcharges(&inverse).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+//! Dépendance fournisseurs : part des tickets avec intervention, MTTR avec
+//! et sans fournisseur, tickets vivants bloqués et classement par
+//! fournisseur.
+//!
+//! Un ticket vivant est bloqué quand l'une de ses interventions n'a pas de
+//! date de fin (ou une fin postérieure à la date de référence). L'attente
+//! court depuis le début de l'intervention lorsqu'il est connu.
+
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::buckets::JeuTranches;
+use super::stats::{moyenne, pct, percentile, round1};
+use crate::parser::vendor::InterventionFournisseur;
+
+const NON_PRECISE: &str = "(non précisé)";
+const MAX_BLOQUES: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct TicketFournisseur {
+    pub id: u64,
+    pub titre: String,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub est_vivant: bool,
+    pub anciennete_jours: Option<f64>,
+    pub delai_resolution_jours: Option<f64>,
+    pub interventions: Vec<InterventionFournisseur>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FournisseurKpi {
+    pub date_reference: String,
+    pub total_tickets: usize,
+    pub tickets_avec_fournisseur: usize,
+    pub taux_dependance: f64,
+    pub total_interventions: usize,
+    pub mttr_avec_fournisseur: MttrComparatif,
+    pub mttr_sans_fournisseur: MttrComparatif,
+    /// Écart de MTTR médian (avec − sans), en jours.
+    pub surcout_median_jours: Option<f64>,
+    pub vivants_avec_fournisseur: usize,
+    pub vivants_bloques: usize,
+    pub distribution_age_bloques: Vec<TrancheAgeBloques>,
+    /// Bloqués, les plus anciens d'abord (tronqué).
+    pub bloques: Vec<TicketBloque>,
+    pub par_fournisseur: Vec<FournisseurStat>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MttrComparatif {
+    pub echantillon: usize,
+    pub moyenne_jours: Option<f64>,
+    pub mediane_jours: Option<f64>,
+    pub p90_jours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrancheAgeBloques {
+    pub label: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketBloque {
+    pub id: u64,
+    pub titre: String,
+    pub fournisseur: String,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub anciennete_jours: Option<f64>,
+    pub debut_intervention: Option<String>,
+    /// Jours écoulés depuis le début de l'intervention ouverte.
+    pub attente_jours: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FournisseurStat {
+    pub fournisseur: String,
+    pub tickets: usize,
+    pub interventions: usize,
+    pub part_tickets_fournisseur: f64,
+    pub vivants: usize,
+    pub bloques: usize,
+    pub mttr_median_jours: Option<f64>,
+    /// Durée médiane des interventions datées (début et fin connus).
+    pub duree_intervention_mediane_jours: Option<f64>,
+}
+
+fn jours(debut: NaiveDateTime, fin: NaiveDateTime) -> f64 {
+    (fin - debut).num_minutes() as f64 / 1440.0
+}
+
+fn mttr(delais: &[f64]) -> MttrComparatif {
+    let non_vide = !delais.is_empty();
+    MttrComparatif {
+        echantillon: delais.len(),
+        moyenne_jours: non_vide.then(|| round1(moyenne(delais))),
+        mediane_jours: non_vide.then(|| round1(percentile(delais, 50.0))),
+        p90_jours: non_vide.then(|| round1(percentile(delais, 90.0))),
+    }
+}
+
+fn nom(i: &InterventionFournisseur) -> &str {
+    i.fournisseur.as_deref().unwrap_or(NON_PRECISE)
+}
+
+fn est_ouverte(i: &InterventionFournisseur, reference: NaiveDateTime) -> bool {
+    i.date_fin.is_none_or(|f| f > reference)
+}
+
+#[derive(Default)]
+struct Cumul {
+    tickets: usize,
+    interventions: usize,
+    vivants: usize,
+    bloques: usize,
+    delais: Vec<f64>,
+    durees: Vec<f64>,
+}
+
+/// `jeu` : tranches d'ancienneté des bloqués, comme la vue stock.
+pub fn compute_fournisseur_kpi(
+    tickets: &[TicketFournisseur],
+    reference: NaiveDateTime,
+    jeu: &JeuTranches,
+) -> FournisseurKpi {
+    let mut avec = Vec::new();
+    let mut sans = Vec::new();
+    let mut par_fournisseur: HashMap<&str, Cumul> = HashMap::new();
+    let mut bloques = Vec::new();
+    let mut ages = Vec::new();
+    let mut kpi = FournisseurKpi {
+        date_reference: reference.format("%Y-%m-%d").to_string(),
+        total_tickets: tickets.len(),
+        ..Default::default()
+    };
+
+    for t in tickets {
+        if t.interventions.is_empty() {
+            sans.extend(t.delai_resolution_jours.filter(|_| !t.est_vivant));
+            continue;
+        }
+        kpi.tickets_avec_fournisseur += 1;
+        kpi.total_interventions += t.interventions.len();
+        if !t.est_vivant {
+            avec.extend(t.delai_resolution_jours);
+        }
+        let ouverte = t
+            .interventions
+            .iter()
+            .find(|i| est_ouverte(i, reference))
+            .filter(|_| t.est_vivant);
+        if t.est_vivant {
+            kpi.vivants_avec_fournisseur += 1;
+        }
+
+        let mut vus: Vec<&str> = Vec::new();
+        for i in &t.interventions {
+            let c = par_fournisseur.entry(nom(i)).or_default();
+            c.interventions += 1;
+            if let (Some(d), Some(f)) = (i.date_debut, i.date_fin) {
+                c.durees.push(jours(d, f).max(0.0));
+            }
+            if vus.contains(&nom(i)) {
+                continue;
+            }
+            vus.push(nom(i));
+            c.tickets += 1;
+            if t.est_vivant {
+                c.vivants += 1;
+            } else {
+                c.delais.extend(t.delai_resolution_jours);
+            }
+        }
+
+        if let Some(i) = ouverte {
+            kpi.vivants_bloques += 1;
+            if let Some(c) = par_fournisseur.get_mut(nom(i)) {
+                c.bloques += 1;
+            }
+            ages.extend(t.anciennete_jours);
+            bloques.push(TicketBloque {
+                id: t.id,
+                titre: t.titre.clone(),
+                fournisseur: nom(i).to_string(),
+                technicien: t.technicien.clone(),
+                groupe: t.groupe.clone(),
+                anciennete_jours: t.anciennete_jours,
+                debut_intervention: i.date_debut.map(|d| d.format("%Y-%m-%d %H:%M").to_string()),
+                attente_jours: i.date_debut.map(|d| round1(jours(d, reference).max(0.0))),
+            });
+        }
+    }
+
+    kpi.taux_dependance = pct(kpi.tickets_avec_fournisseur, kpi.total_tickets);
+    kpi.mttr_avec_fournisseur = mttr(&avec);
+    kpi.mttr_sans_fournisseur = mttr(&sans);
+    kpi.surcout_median_jours = kpi
+        .mttr_avec_fournisseur
+        .mediane_jours
+        .zip(kpi.mttr_sans_fournisseur.mediane_jours)
+        .map(|(a, s)| round1(a - s));
+    kpi.distribution_age_bloques = jeu
+        .libelles()
+        .into_iter()
+        .zip(jeu.compter(ages))
+        .map(|(label, count)| TrancheAgeBloques { label, count })
+        .collect();
+
+    bloques.sort_by(|a, b| {
+        b.anciennete_jours
+            .unwrap_or(0.0)
+            .total_cmp(&a.anciennete_jours.unwrap_or(0.0))
+            .then(a.id.cmp(&b.id))
+    });
+    bloques.truncate(MAX_BLOQUES);
+    kpi.bloques = bloques;
+
+    let avec_fournisseur = kpi.tickets_avec_fournisseur;
+    kpi.par_fournisseur = par_fournisseur
+        .into_iter()
+        .map(|(fournisseur, c)| FournisseurStat {
+            fournisseur: fournisseur.to_string(),
+            tickets: c.tickets,
+            interventions: c.interventions,
+            part_tickets_fournisseur: pct(c.tickets, avec_fournisseur),
+            vivants: c.vivants,
+            bloques: c.bloques,
+            mttr_median_jours: (!c.delais.is_empty()).then(|| round1(percentile(&c.delais, 50.0))),
+            duree_intervention_mediane_jours: (!c.durees.is_empty())
+                .then(|| round1(percentile(&c.durees, 50.0))),
+        })
+        .collect();
+    kpi.par_fournisseur.sort_by(|a, b| {
+        b.tickets
+            .cmp(&a.tickets)
+            .then(b.bloques.cmp(&a.bloques))
+            .then(a.fournisseur.cmp(&b.fournisseur))
+    });
+    kpi
+}
+
+/// Indicateurs fournisseurs de l'import, à sa date de référence.
+pub fn load_fournisseur_kpi(
+    conn: &rusqlite::Connection,
+    import_id: i64,
+) -> Result<FournisseurKpi, rusqlite::Error> {
+    use crate::db::queries;
+    let tickets = queries::get_tickets_fournisseur(conn, import_id)?;
+    let reference = queries::get_import_reference_date(conn, import_id)?
+        .unwrap_or_else(|| chrono::Local::now().naive_local());
+    let config = crate::config::get_config_from_db(conn)?;
+    Ok(compute_fournisseur_kpi(
+        &tickets,
+        reference,
+        &config.tranches.anciennete(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::buckets::TranchesDistribution;
+    use crate::parser::vendor::parse_interventions;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn ticket(
+        id: u64,
+        vivant: bool,
+        age: f64,
+        delai: Option<f64>,
+        brut: &str,
+    ) -> TicketFournisseur {
+        TicketFournisseur {
+            id,
+            titre: format!("T{id}"),
+            technicien: Some("Dupont".to_string()),
+            groupe: None,
+            est_vivant: vivant,
+            anciennete_jours: Some(age),
+            delai_resolution_jours: delai,
+            interventions: parse_interventions(brut),
+        }
+    }
+
+    #[test]
+    fn test_vendor_kpi() {
+        let tickets = vec![
+            ticket(
+                1,
+                false,
+                0.0,
+                Some(10.0),
+                "Orange du 01-03-2025 au 05-03-2025",
+            ),
+            ticket(
+                2,
+                false,
+                0.0,
+                Some(6.0),
+                "Dell 2025-03-02\nOrange 2025-03-03",
+            ),
+            ticket(3, false, 0.0, Some(2.0), ""),
+            ticket(4, false, 0.0, Some(4.0), ""),
+            ticket(5, true, 40.0, None, "Orange depuis le 2025-03-20"),
+            ticket(6, true, 3.0, None, "Dell du 2025-03-01 au 2025-03-02"),
+            ticket(7, true, 5.0, None, "Oui"),
+            ticket(8, true, 1.0, None, ""),
+        ];
+        let kpi = compute_fournisseur_kpi(
+            &tickets,
+            dt("2025-03-31 00:00:00"),
+            &TranchesDistribution::default().anciennete(),
+        );
+
+        assert_eq!(kpi.tickets_avec_fournisseur, 5);
+        assert_eq!(kpi.taux_dependance, 62.5);
+        assert_eq!(kpi.total_interventions, 6);
+        assert_eq!(kpi.mttr_avec_fournisseur.mediane_jours, Some(8.0));
+        assert_eq!(kpi.mttr_sans_fournisseur.mediane_jours, Some(3.0));
+        assert_eq!(kpi.surcout_median_jours, Some(5.0));
+
+        // Ticket 6 : intervention close ; 5 et 7 restent bloqués
+        assert_eq!(kpi.vivants_avec_fournisseur, 3);
+        assert_eq!(kpi.vivants_bloques, 2);
+        assert_eq!(kpi.bloques[0].id, 5);
+        assert_eq!(kpi.bloques[0].attente_jours, Some(11.0));
+        assert_eq!(kpi.bloques[1].fournisseur, NON_PRECISE);
+        assert_eq!(kpi.distribution_age_bloques[2].count, 1);
+        assert_eq!(kpi.distribution_age_bloques[4].count, 1);
+
+        let orange = &kpi.par_fournisseur[0];
+        assert_eq!(orange.fournisseur, "Orange");
+        assert_eq!((orange.tickets, orange.vivants, orange.bloques), (3, 1, 1));
+        assert_eq!(orange.mttr_median_jours, Some(8.0));
+        assert_eq!(orange.duree_intervention_mediane_jours, Some(4.0));
+        assert_eq!(kpi.par_fournisseur[1].fournisseur, "Dell");
+    }
+}
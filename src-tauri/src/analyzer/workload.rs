@@ -0,0 +1,418 @@
+//! Équité de charge : stock, entrants et sortants par technicien rapportés à
+//! sa capacité (ETP), dispersion par groupe (Gini, coefficient de variation)
+//! et techniciens sur- ou sous-chargés.
+//!
+//! - ETP contractuel : quotité configurée (temps partiel = ETP < 100 %),
+//!   100 % pour un technicien sans paramétrage ;
+//! - ETP effectif sur la fenêtre : ETP contractuel × part des jours ouvrés
+//!   sans absence. Les flux de la fenêtre sont rapportés à l'ETP effectif, le
+//!   stock courant à l'ETP contractuel ;
+//! - le groupe d'un technicien est celui de la majorité de ses tickets.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::calendar::{CalendrierCompile, Fermeture};
+use super::stats::{coefficient_variation, gini, moyenne, round2};
+
+/// Écart à la moyenne du groupe (stock / ETP) au-delà duquel un technicien
+/// est signalé sur- ou sous-chargé.
+pub const ECART_CHARGE_PCT: f64 = 25.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapaciteTechnicien {
+    pub technicien: String,
+    /// Quotité en % (100 = temps plein).
+    pub etp_pct: f64,
+    /// Congés, arrêts, formations…, bornes incluses.
+    #[serde(default)]
+    pub absences: Vec<Fermeture>,
+}
+
+/// Vérifie les capacités avant enregistrement.
+pub fn valider_capacites(capacites: &[CapaciteTechnicien]) -> Result<(), String> {
+    let mut vus = HashSet::new();
+    for c in capacites {
+        if c.technicien.trim().is_empty() {
+            return Err("Capacité sans technicien".to_string());
+        }
+        if !vus.insert(c.technicien.as_str()) {
+            return Err(format!("Capacité en double pour {}", c.technicien));
+        }
+        if !(c.etp_pct > 0.0 && c.etp_pct <= 100.0) {
+            return Err(format!(
+                "{} : ETP invalide {} (0 < ETP ≤ 100)",
+                c.technicien, c.etp_pct
+            ));
+        }
+        if let Some(a) = c.absences.iter().find(|a| a.debut > a.fin) {
+            return Err(format!(
+                "{} : absence inversée {} > {}",
+                c.technicien, a.debut, a.fin
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// ETP contractuel (1.0 = temps plein) d'un technicien.
+pub fn etp_contractuel(capacites: &[CapaciteTechnicien], technicien: &str) -> f64 {
+    capacites
+        .iter()
+        .find(|c| c.technicien == technicien)
+        .map_or(1.0, |c| c.etp_pct / 100.0)
+}
+
+/// (ETP effectif, jours ouvrés d'absence) sur [debut, fin].
+fn etp_effectif(
+    capacite: Option<&CapaciteTechnicien>,
+    cal: &CalendrierCompile,
+    debut: NaiveDate,
+    fin: NaiveDate,
+) -> (f64, usize) {
+    let Some(c) = capacite else {
+        return (1.0, 0);
+    };
+    let etp = c.etp_pct / 100.0;
+    let mut ouvres = 0;
+    let mut absents = 0;
+    let mut jour = debut;
+    while jour <= fin {
+        if cal.est_ouvre(jour) {
+            ouvres += 1;
+            if c.absences.iter().any(|a| (a.debut..=a.fin).contains(&jour)) {
+                absents += 1;
+            }
+        }
+        jour += Duration::days(1);
+    }
+    if ouvres == 0 {
+        return (etp, 0);
+    }
+    (etp * (ouvres - absents) as f64 / ouvres as f64, absents)
+}
+
+/// Stock et flux d'un couple technicien × groupe.
+#[derive(Debug, Clone)]
+pub struct ChargeBrute {
+    pub technicien: String,
+    pub groupe: Option<String>,
+    pub stock: usize,
+    pub entrants: usize,
+    pub sortants: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChargeTechnicien {
+    pub technicien: String,
+    pub groupe: Option<String>,
+    pub etp: f64,
+    pub etp_effectif: f64,
+    pub jours_absence: usize,
+    pub stock: usize,
+    pub entrants: usize,
+    pub sortants: usize,
+    pub stock_par_etp: f64,
+    /// `None` si le technicien a été absent toute la fenêtre.
+    pub entrants_par_etp: Option<f64>,
+    pub sortants_par_etp: Option<f64>,
+    /// Écart du stock / ETP à la moyenne du groupe, en %.
+    pub ecart_groupe_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DispersionGroupe {
+    pub groupe: String,
+    pub nb_techniciens: usize,
+    pub etp_total: f64,
+    pub stock: usize,
+    pub stock_par_etp_moyen: f64,
+    /// Gini du stock / ETP (0 = charge parfaitement répartie).
+    pub gini: Option<f64>,
+    pub coefficient_variation: Option<f64>,
+    pub surcharges: Vec<String>,
+    pub sous_charges: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EquiteCharge {
+    pub date_debut: String,
+    pub date_fin: String,
+    pub ecart_charge_pct: f64,
+    pub techniciens: Vec<ChargeTechnicien>,
+    pub par_groupe: Vec<DispersionGroupe>,
+    /// Du plus chargé au moins chargé relativement à son groupe.
+    pub surcharges: Vec<ChargeTechnicien>,
+    /// Du moins chargé au plus chargé relativement à son groupe.
+    pub sous_charges: Vec<ChargeTechnicien>,
+}
+
+/// Cumul d'un technicien, avec le poids de chacun de ses groupes.
+#[derive(Default)]
+struct Cumul<'a> {
+    stock: usize,
+    entrants: usize,
+    sortants: usize,
+    groupes: HashMap<Option<&'a str>, usize>,
+}
+
+fn par_etp(n: usize, etp: f64) -> Option<f64> {
+    (etp > 0.0).then(|| round2(n as f64 / etp))
+}
+
+/// Charges normalisées par technicien et dispersion par groupe.
+pub fn compute_equite(
+    lignes: &[ChargeBrute],
+    capacites: &[CapaciteTechnicien],
+    cal: &CalendrierCompile,
+    debut: NaiveDate,
+    fin: NaiveDate,
+) -> EquiteCharge {
+    // Agrégation par technicien, groupe majoritaire (stock + flux)
+    let mut par_tech: HashMap<&str, Cumul> = HashMap::new();
+    for l in lignes {
+        let c = par_tech.entry(l.technicien.as_str()).or_default();
+        c.stock += l.stock;
+        c.entrants += l.entrants;
+        c.sortants += l.sortants;
+        *c.groupes.entry(l.groupe.as_deref()).or_default() += l.stock + l.entrants + l.sortants;
+    }
+
+    let mut techniciens: Vec<ChargeTechnicien> = par_tech
+        .into_iter()
+        .map(|(tech, c)| {
+            let groupe = c
+                .groupes
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+                .and_then(|(g, _)| g.map(str::to_string));
+            let capacite = capacites.iter().find(|c| c.technicien == tech);
+            let etp = etp_contractuel(capacites, tech);
+            let (effectif, jours_absence) = etp_effectif(capacite, cal, debut, fin);
+            ChargeTechnicien {
+                technicien: tech.to_string(),
+                groupe,
+                etp: round2(etp),
+                etp_effectif: round2(effectif),
+                jours_absence,
+                stock: c.stock,
+                entrants: c.entrants,
+                sortants: c.sortants,
+                stock_par_etp: round2(c.stock as f64 / etp),
+                entrants_par_etp: par_etp(c.entrants, effectif),
+                sortants_par_etp: par_etp(c.sortants, effectif),
+                ecart_groupe_pct: None,
+            }
+        })
+        .collect();
+    techniciens.sort_by(|a, b| {
+        b.stock_par_etp
+            .total_cmp(&a.stock_par_etp)
+            .then(a.technicien.cmp(&b.technicien))
+    });
+
+    let mut groupes: Vec<&str> = techniciens
+        .iter()
+        .filter_map(|t| t.groupe.as_deref())
+        .collect();
+    groupes.sort_unstable();
+    groupes.dedup();
+    let groupes: Vec<String> = groupes.into_iter().map(str::to_string).collect();
+
+    let mut par_groupe = Vec::new();
+    for groupe in groupes {
+        let membres: Vec<usize> = (0..techniciens.len())
+            .filter(|&i| techniciens[i].groupe.as_deref() == Some(groupe.as_str()))
+            .collect();
+        let charges: Vec<f64> = membres
+            .iter()
+            .map(|&i| techniciens[i].stock_par_etp)
+            .collect();
+        let moyenne_groupe = moyenne(&charges);
+        let mut surcharges = Vec::new();
+        let mut sous_charges = Vec::new();
+        for &i in &membres {
+            let t = &mut techniciens[i];
+            // Un technicien seul dans son groupe n'a pas de point de comparaison
+            if membres.len() < 2 || moyenne_groupe <= 0.0 {
+                continue;
+            }
+            let ecart = round2((t.stock_par_etp - moyenne_groupe) / moyenne_groupe * 100.0);
+            t.ecart_groupe_pct = Some(ecart);
+            if ecart > ECART_CHARGE_PCT {
+                surcharges.push(t.technicien.clone());
+            } else if ecart < -ECART_CHARGE_PCT {
+                sous_charges.push(t.technicien.clone());
+            }
+        }
+        par_groupe.push(DispersionGroupe {
+            groupe,
+            nb_techniciens: membres.len(),
+            etp_total: round2(membres.iter().map(|&i| techniciens[i].etp).sum()),
+            stock: membres.iter().map(|&i| techniciens[i].stock).sum(),
+            stock_par_etp_moyen: round2(moyenne_groupe),
+            gini: gini(&charges).map(round2),
+            coefficient_variation: coefficient_variation(&charges).map(round2),
+            surcharges,
+            sous_charges,
+        });
+    }
+
+    let ecart = |t: &ChargeTechnicien| t.ecart_groupe_pct.unwrap_or(0.0);
+    let mut surcharges: Vec<ChargeTechnicien> = techniciens
+        .iter()
+        .filter(|t| ecart(t) > ECART_CHARGE_PCT)
+        .cloned()
+        .collect();
+    surcharges.sort_by(|a, b| ecart(b).total_cmp(&ecart(a)));
+    let mut sous_charges: Vec<ChargeTechnicien> = techniciens
+        .iter()
+        .filter(|t| ecart(t) < -ECART_CHARGE_PCT)
+        .cloned()
+        .collect();
+    sous_charges.sort_by(|a, b| ecart(a).total_cmp(&ecart(b)));
+
+    EquiteCharge {
+        date_debut: debut.format("%Y-%m-%d").to_string(),
+        date_fin: fin.format("%Y-%m-%d").to_string(),
+        ecart_charge_pct: ECART_CHARGE_PCT,
+        techniciens,
+        par_groupe,
+        surcharges,
+        sous_charges,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::calendar::CalendrierOuvre;
+
+    fn d(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn ligne(
+        tech: &str,
+        groupe: &str,
+        stock: usize,
+        entrants: usize,
+        sortants: usize,
+    ) -> ChargeBrute {
+        ChargeBrute {
+            technicien: tech.into(),
+            groupe: Some(groupe.into()),
+            stock,
+            entrants,
+            sortants,
+        }
+    }
+
+    fn capacite(tech: &str, etp_pct: f64, absences: &[(&str, &str)]) -> CapaciteTechnicien {
+        CapaciteTechnicien {
+            technicien: tech.into(),
+            etp_pct,
+            absences: absences
+                .iter()
+                .map(|(debut, fin)| Fermeture {
+                    debut: d(debut),
+                    fin: d(fin),
+                    libelle: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_fte_normalization() {
+        let cal = CalendrierOuvre::default().compile().unwrap();
+        // Semaine du 6 au 12 janvier 2025 : 5 jours ouvrés
+        let lignes = vec![
+            ligne("Alice", "Support", 10, 8, 6),
+            ligne("Bob", "Support", 10, 5, 5),
+            ligne("Bob", "Réseau", 1, 0, 0),
+        ];
+        let capacites = vec![capacite("Bob", 50.0, &[("2025-01-06", "2025-01-07")])];
+        let kpi = compute_equite(&lignes, &capacites, &cal, d("2025-01-06"), d("2025-01-12"));
+
+        let bob = kpi
+            .techniciens
+            .iter()
+            .find(|t| t.technicien == "Bob")
+            .unwrap();
+        assert_eq!(bob.groupe.as_deref(), Some("Support"));
+        assert_eq!(bob.stock, 11);
+        assert_eq!(bob.stock_par_etp, 22.0);
+        assert_eq!(bob.jours_absence, 2);
+        assert_eq!(bob.etp_effectif, 0.3);
+        assert_eq!(bob.entrants_par_etp, Some(16.67));
+
+        let alice = kpi
+            .techniciens
+            .iter()
+            .find(|t| t.technicien == "Alice")
+            .unwrap();
+        assert_eq!((alice.etp, alice.stock_par_etp), (1.0, 10.0));
+        assert_eq!(kpi.techniciens[0].technicien, "Bob");
+    }
+
+    #[test]
+    fn test_dispersion_and_outliers() {
+        let cal = CalendrierOuvre::default().compile().unwrap();
+        let lignes = vec![
+            ligne("A", "Support", 20, 0, 0),
+            ligne("B", "Support", 10, 0, 0),
+            ligne("C", "Support", 10, 0, 0),
+            ligne("D", "Support", 0, 0, 0),
+            ligne("E", "Réseau", 5, 0, 0),
+        ];
+        let kpi = compute_equite(&lignes, &[], &cal, d("2025-01-06"), d("2025-01-12"));
+        let support = kpi
+            .par_groupe
+            .iter()
+            .find(|g| g.groupe == "Support")
+            .unwrap();
+        assert_eq!(support.nb_techniciens, 4);
+        assert_eq!(support.stock_par_etp_moyen, 10.0);
+        assert_eq!(support.gini, Some(0.38));
+        assert_eq!(support.coefficient_variation, Some(0.71));
+        assert_eq!(support.surcharges, vec!["A"]);
+        assert_eq!(support.sous_charges, vec!["D"]);
+
+        let reseau = kpi
+            .par_groupe
+            .iter()
+            .find(|g| g.groupe == "Réseau")
+            .unwrap();
+        assert!(reseau.surcharges.is_empty());
+        assert_eq!(
+            kpi.techniciens
+                .iter()
+                .find(|t| t.technicien == "E")
+                .unwrap()
+                .ecart_groupe_pct,
+            None
+        );
+        assert_eq!(kpi.surcharges.len(), 1);
+        assert_eq!(kpi.sous_charges[0].technicien, "D");
+    }
+
+    #[test]
+    fn test_validate_capacities() {
+        assert!(valider_capacites(&[capacite("A", 80.0, &[])]).is_ok());
+        assert!(valider_capacites(&[capacite("A", 0.0, &[])]).is_err());
+        assert!(valider_capacites(&[capacite("A", 120.0, &[])]).is_err());
+        assert!(valider_capacites(&[capacite("A", 80.0, &[]), capacite("A", 50.0, &[])]).is_err());
+        assert!(
+            valider_capacites(&[capacite("A", 80.0, &[("2025-02-01", "2025-01-01")])]).is_err()
+        );
+        assert_eq!(etp_contractuel(&[capacite("A", 80.0, &[])], "A"), 0.8);
+        assert_eq!(etp_contractuel(&[], "A"), 1.0);
+    }
+}
@@ -0,0 +1,13 @@
+use crate::db::audit::{self, AuditEntry, AuditFilter};
+use crate::state::{AppState, DbAccess};
+
+/// Journal d'audit, du plus récent au plus ancien (filtre optionnel par
+/// action et par dates).
+#[tauri::command]
+pub async fn get_audit_log(
+    state: tauri::State<'_, AppState>,
+    filter: Option<AuditFilter>,
+) -> Result<Vec<AuditEntry>, String> {
+    let filter = filter.unwrap_or_default();
+    state.db(|conn| audit::get_audit_log(conn, &filter))
+}
@@ -2,6 +2,10 @@ use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 use crate::analyzer::bilan::{compute_bilan, compute_ventilation};
+use crate::analyzer::buckets::JeuTranches;
+use crate::analyzer::calendar::Delai;
+use crate::analyzer::comparison::{comparer, fenetres, ComparaisonRequest, Fenetre, KpiDelta, Mesure, Sens};
+use crate::analyzer::stats::{ecart_type, pct};
 use crate::analyzer::temporal::{auto_granularity, generate_period_keys};
 use crate::db::queries;
 use crate::state::{AppState, DbAccess};
@@ -13,6 +17,9 @@ pub struct BilanRequest {
     pub date_from: String,
     pub date_to: String,
     pub group_by: Option<String>,
+    /// Fenêtre de référence optionnelle (onglet "Comparaison" de l'export).
+    #[serde(default)]
+    pub comparaison: Option<ComparaisonRequest>,
 }
 
 #[derive(Serialize)]
@@ -22,6 +29,18 @@ pub struct BilanTemporel {
     pub totaux: BilanTotaux,
     pub ventilation: Option<Vec<BilanVentilation>>,
     pub resolution: Option<BilanResolution>,
+    pub comparaison: Option<BilanComparaison>,
+}
+
+/// Bilan de la fenêtre de référence et écarts avec la fenêtre courante.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BilanComparaison {
+    pub fenetre_courante: Fenetre,
+    pub fenetre_reference: Fenetre,
+    pub totaux: BilanTotaux,
+    pub resolution: Option<BilanResolution>,
+    pub deltas: Vec<KpiDelta>,
 }
 
 #[derive(Serialize)]
@@ -31,6 +50,9 @@ pub struct BilanResolution {
     pub total_resolus: usize,
     pub mttr_jours: f64,
     pub mediane_jours: f64,
+    pub ecart_type_jours: f64,
+    pub mttr_jours_ouvres: f64,
+    pub mediane_jours_ouvres: f64,
 }
 
 #[derive(Serialize)]
@@ -71,7 +93,7 @@ pub struct BilanVentilation {
     pub delta: i64,
 }
 
-fn parse_date_flexible(s: &str) -> Option<NaiveDateTime> {
+pub(crate) fn parse_date_flexible(s: &str) -> Option<NaiveDateTime> {
     for fmt in &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%SZ"] {
         if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
             return Some(dt);
@@ -96,7 +118,41 @@ pub(crate) fn run_bilan_logic(state: &AppState, request: &BilanRequest) -> Resul
         request.period.clone()
     };
 
-    let period_keys = generate_period_keys(date_from, date_to, &granularity);
+    let mut bilan = compute_window(state, date_from, date_to, &granularity, request.group_by.as_deref())?;
+
+    // Fenêtre de référence : même granularité, sans ventilation
+    if let Some(ref comparaison) = request.comparaison {
+        let (fenetre_courante, fenetre_reference) =
+            fenetres(date_from.date(), date_to.date(), comparaison)?;
+        let borne = |s: &str| parse_date_flexible(s).ok_or_else(|| format!("Date invalide: {s}"));
+        let reference = compute_window(
+            state,
+            borne(&fenetre_reference.date_debut)?,
+            borne(&fenetre_reference.date_fin)?,
+            &granularity,
+            None,
+        )?;
+        bilan.comparaison = Some(BilanComparaison {
+            fenetre_courante,
+            fenetre_reference,
+            deltas: deltas_bilan(&bilan, &reference),
+            totaux: reference.totaux,
+            resolution: reference.resolution,
+        });
+    }
+
+    Ok(bilan)
+}
+
+/// Bilan d'une fenêtre [date_from, date_to].
+fn compute_window(
+    state: &AppState,
+    date_from: NaiveDateTime,
+    date_to: NaiveDateTime,
+    granularity: &str,
+    group_by: Option<&str>,
+) -> Result<BilanTemporel, String> {
+    let period_keys = generate_period_keys(date_from, date_to, granularity);
 
     let from_str = date_from.format("%Y-%m-%d").to_string();
     let to_str = date_to.format("%Y-%m-%d").to_string();
@@ -104,16 +160,16 @@ pub(crate) fn run_bilan_logic(state: &AppState, request: &BilanRequest) -> Resul
     let stock_debut = state.db(|conn| queries::get_stock_at_date(conn, &from_str))?;
 
     let entrees = state.db(|conn| {
-        queries::get_bilan_entrees_par_periode(conn, &from_str, &to_str, &granularity, None)
+        queries::get_bilan_entrees_par_periode(conn, &from_str, &to_str, granularity, None)
     })?;
 
     let sorties = state.db(|conn| {
-        queries::get_bilan_sorties_par_periode(conn, &from_str, &to_str, &granularity, None)
+        queries::get_bilan_sorties_par_periode(conn, &from_str, &to_str, granularity, None)
     })?;
 
     let mut bilan = compute_bilan(&entrees, &sorties, &period_keys, stock_debut);
 
-    if let Some(ref group_by) = request.group_by {
+    if let Some(group_by) = group_by {
         let vent_data = if group_by == "technicien" {
             state.db(|conn| {
                 queries::get_bilan_ventilation_par_technicien(conn, &from_str, &to_str)
@@ -127,50 +183,81 @@ pub(crate) fn run_bilan_logic(state: &AppState, request: &BilanRequest) -> Resul
     }
 
     // Resolution distribution
-    let durations = state.db(|conn| {
-        queries::get_resolution_durations(conn, &from_str, &to_str)
+    let (delais, jeu) = state.db(|conn| {
+        let cal = crate::config::get_calendrier_from_db(conn)?;
+        let jeu = crate::config::get_config_from_db(conn)?.tranches.resolution();
+        Ok((queries::get_resolution_durations(conn, &cal, &from_str, &to_str)?, jeu))
     })?;
-    if !durations.is_empty() {
-        bilan.resolution = Some(build_resolution_distribution(&durations));
+    if !delais.is_empty() {
+        bilan.resolution = Some(build_resolution_distribution(&delais, &jeu));
     }
 
     Ok(bilan)
 }
 
-fn build_resolution_distribution(durations: &[f64]) -> BilanResolution {
+/// (clé, libellé, extraction, sens souhaité) d'un indicateur comparé.
+type Indicateur<T> = (&'static str, &'static str, fn(&T) -> Mesure, Sens);
+
+/// Écarts des indicateurs du bilan entre fenêtre courante et référence.
+fn deltas_bilan(c: &BilanTemporel, r: &BilanTemporel) -> Vec<KpiDelta> {
+    use Sens::*;
+    let totaux: [Indicateur<BilanTotaux>; 5] = [
+        ("totalEntrees", "Total entrants", |t| Mesure::comptage(t.total_entrees as f64), Neutre),
+        ("totalSorties", "Total sortants", |t| Mesure::comptage(t.total_sorties as f64), Hausse),
+        ("deltaGlobal", "Delta global", |t| Mesure::simple(Some(t.delta_global as f64)), Baisse),
+        ("moyenneEntrees", "Moy. entrants/période", |t| Mesure::simple(Some(t.moyenne_entrees_par_periode)), Neutre),
+        ("moyenneSorties", "Moy. sortants/période", |t| Mesure::simple(Some(t.moyenne_sorties_par_periode)), Hausse),
+    ];
+    let resolution: [Indicateur<BilanResolution>; 5] = [
+        ("totalResolus", "Tickets résolus", |r| Mesure::comptage(r.total_resolus as f64), Hausse),
+        ("mttr", "MTTR (j)", |r| Mesure::moyenne(r.mttr_jours, r.ecart_type_jours, r.total_resolus as i64), Baisse),
+        ("mediane", "Délai médian de résolution (j)", |r| Mesure::simple(Some(r.mediane_jours)), Baisse),
+        ("mttrOuvres", "MTTR (j ouvrés)", |r| Mesure::simple(Some(r.mttr_jours_ouvres)), Baisse),
+        ("medianeOuvres", "Délai médian (j ouvrés)", |r| Mesure::simple(Some(r.mediane_jours_ouvres)), Baisse),
+    ];
+
+    let mut deltas: Vec<KpiDelta> = totaux
+        .iter()
+        .map(|(cle, libelle, f, sens)| comparer(cle, libelle, f(&c.totaux), f(&r.totaux), *sens))
+        .collect();
+    let absent = Mesure::simple(None);
+    deltas.extend(resolution.iter().map(|(cle, libelle, f, sens)| {
+        let mesure = |b: &BilanTemporel| b.resolution.as_ref().map_or(absent, f);
+        comparer(cle, libelle, mesure(c), mesure(r), *sens)
+    }));
+    deltas
+}
+
+/// Distribution des délais de résolution selon le jeu de tranches configuré.
+fn build_resolution_distribution(delais: &[Delai], jeu: &JeuTranches) -> BilanResolution {
+    let durations: Vec<f64> = delais.iter().map(|d| d.jours).collect();
+    let ouvres: Vec<f64> = delais.iter().map(|d| d.jours_ouvres).collect();
     let total = durations.len();
-    let pct = |n: usize| -> f64 {
-        if total == 0 { 0.0 } else { (n as f64 / total as f64 * 1000.0).round() / 10.0 }
-    };
 
-    let mut lt24h = 0usize;
-    let mut lt48h = 0usize;
-    let mut lt7j = 0usize;
-    let mut lt30j = 0usize;
-    let mut ge30j = 0usize;
-
-    for &d in durations {
-        if d < 1.0 {
-            lt24h += 1;
-        } else if d < 2.0 {
-            lt48h += 1;
-        } else if d < 7.0 {
-            lt7j += 1;
-        } else if d < 30.0 {
-            lt30j += 1;
-        } else {
-            ge30j += 1;
-        }
-    }
+    let tranches = jeu
+        .libelles()
+        .into_iter()
+        .zip(jeu.compter(durations.iter().copied()))
+        .map(|(label, count)| ResolutionTranche { label, count, pourcentage: pct(count, total) })
+        .collect();
 
-    let tranches = vec![
-        ResolutionTranche { label: "< 24h".to_string(), count: lt24h, pourcentage: pct(lt24h) },
-        ResolutionTranche { label: "24h - 48h".to_string(), count: lt48h, pourcentage: pct(lt48h) },
-        ResolutionTranche { label: "2j - 7j".to_string(), count: lt7j, pourcentage: pct(lt7j) },
-        ResolutionTranche { label: "7j - 30j".to_string(), count: lt30j, pourcentage: pct(lt30j) },
-        ResolutionTranche { label: "> 30j".to_string(), count: ge30j, pourcentage: pct(ge30j) },
-    ];
+    let (mttr, mediane) = mttr_mediane(&durations);
+    let (mttr_ouvres, mediane_ouvres) = mttr_mediane(&ouvres);
+
+    BilanResolution {
+        tranches,
+        total_resolus: total,
+        mttr_jours: mttr,
+        mediane_jours: mediane,
+        ecart_type_jours: (ecart_type(&durations) * 10.0).round() / 10.0,
+        mttr_jours_ouvres: mttr_ouvres,
+        mediane_jours_ouvres: mediane_ouvres,
+    }
+}
 
+/// (moyenne, médiane) arrondies au dixième ; (0, 0) si vide.
+fn mttr_mediane(durations: &[f64]) -> (f64, f64) {
+    let total = durations.len();
     let sum: f64 = durations.iter().sum();
     let mttr = if total > 0 { (sum / total as f64 * 10.0).round() / 10.0 } else { 0.0 };
 
@@ -184,13 +271,7 @@ fn build_resolution_distribution(durations: &[f64]) -> BilanResolution {
     } else {
         (sorted[sorted.len() / 2] * 10.0).round() / 10.0
     };
-
-    BilanResolution {
-        tranches,
-        total_resolus: total,
-        mttr_jours: mttr,
-        mediane_jours: mediane,
-    }
+    (mttr, mediane)
 }
 
 #[tauri::command]
@@ -0,0 +1,58 @@
+use std::time::Instant;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::db::cache::{self, CacheDep, CacheKey, CacheStats};
+use crate::state::{AppState, DbAccess};
+
+/// Résout la clé de cache pour l'import actif et tente une lecture.
+/// La clé est renvoyée pour enregistrer le résultat une fois calculé.
+pub(crate) fn lookup_cached<T: DeserializeOwned>(
+    state: &AppState,
+    analysis_type: &str,
+    params: &Value,
+    deps: &[CacheDep],
+) -> Result<(CacheKey, Option<T>), String> {
+    state.db(|conn| {
+        let key = CacheKey::for_active_import(conn, analysis_type, params, deps)?;
+        let hit = key.get(conn)?;
+        Ok((key, hit))
+    })
+}
+
+/// Enregistre un résultat calculé. Un échec d'écriture n'est pas bloquant.
+pub(crate) fn store_cached<T: Serialize>(
+    state: &AppState,
+    key: &CacheKey,
+    result: &T,
+    start: Instant,
+) {
+    let duration_ms = start.elapsed().as_millis() as i64;
+    if let Err(e) = state.db(|conn| key.put(conn, result, duration_ms)) {
+        log::warn!("Écriture cache {} impossible: {e}", key.analysis_type);
+    }
+}
+
+#[tauri::command]
+pub async fn get_cache_stats(state: tauri::State<'_, AppState>) -> Result<CacheStats, String> {
+    state.db(cache::get_cache_stats)
+}
+
+/// Purge le cache d'analyse : tout, un type d'analyse, ou seulement les entrées périmées.
+/// Retourne le nombre d'entrées supprimées.
+#[tauri::command]
+pub async fn purge_analytics_cache(
+    state: tauri::State<'_, AppState>,
+    analysis_type: Option<String>,
+    stale_only: Option<bool>,
+) -> Result<usize, String> {
+    state.db(|conn| {
+        if stale_only.unwrap_or(false) {
+            cache::purge_stale_cache(conn)
+        } else {
+            cache::purge_cache(conn, analysis_type.as_deref())
+        }
+    })
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use crate::analyzer::capacity::{
+    compute_equipe, compute_par_dimension, CapaciteAnalyse, PERIODES_RECENTES_DEFAUT,
+    SEUIL_PERSISTANCE_DEFAUT,
+};
+use crate::analyzer::temporal::generate_period_keys;
+use crate::analyzer::workload::{compute_equite, EquiteCharge};
+use crate::commands::bilan::parse_date_flexible;
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::cache::CacheDep;
+use crate::db::queries;
+use crate::state::{AppState, DbAccess};
+
+/// Fenêtre par défaut : 90 jours jusqu'à la date de l'import.
+const FENETRE_DEFAUT_JOURS: i64 = 90;
+
+fn par_periode(rows: &[(String, usize)]) -> HashMap<&str, usize> {
+    rows.iter().map(|(k, v)| (k.as_str(), *v)).collect()
+}
+
+/// Taux d'arrivée, débit, loi de Little et résorption du stock par équipe.
+#[tauri::command]
+pub async fn get_capacity_analysis(
+    state: tauri::State<'_, AppState>,
+    date_debut: Option<String>,
+    date_fin: Option<String>,
+    granularity: Option<String>,
+    periodes_recentes: Option<usize>,
+    seuil_persistance: Option<usize>,
+) -> Result<CapaciteAnalyse, String> {
+    let start = Instant::now();
+    let gran = match granularity.as_deref() {
+        Some("day") => "day",
+        Some("month") => "month",
+        _ => "week",
+    };
+    let periodes_recentes = periodes_recentes.unwrap_or(PERIODES_RECENTES_DEFAUT).max(1);
+    let seuil_persistance = seuil_persistance.unwrap_or(SEUIL_PERSISTANCE_DEFAUT);
+
+    let params = serde_json::json!({
+        "dateDebut": date_debut,
+        "dateFin": date_fin,
+        "granularity": gran,
+        "periodesRecentes": periodes_recentes,
+        "seuilPersistance": seuil_persistance,
+    });
+    let (key, cached) =
+        lookup_cached::<CapaciteAnalyse>(&state, "capacity", &params, &[CacheDep::Tickets])?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let date_to = match date_fin.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de fin invalide: {s}"))?,
+        None => state
+            .db(|conn| queries::get_import_reference_date(conn, key.import_id))?
+            .unwrap_or_else(|| chrono::Local::now().naive_local()),
+    };
+    let date_from = match date_debut.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de début invalide: {s}"))?,
+        None => date_to - chrono::Duration::days(FENETRE_DEFAUT_JOURS - 1),
+    };
+    if date_from > date_to {
+        return Err("La date de début doit précéder la date de fin".to_string());
+    }
+    let period_keys = generate_period_keys(date_from, date_to, gran);
+    let from = date_from.format("%Y-%m-%d").to_string();
+    let to = date_to.format("%Y-%m-%d").to_string();
+
+    let result = state.db(|conn| {
+        let entrees = queries::get_bilan_entrees_par_periode(conn, &from, &to, gran, None)?;
+        let sorties = queries::get_bilan_sorties_par_periode(conn, &from, &to, gran, None)?;
+        let stock: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM tickets WHERE import_id = ?1 AND est_vivant = 1",
+            [key.import_id],
+            |row| row.get(0),
+        )?;
+        let global = compute_equipe(
+            "Global",
+            &par_periode(&entrees),
+            &par_periode(&sorties),
+            stock as usize,
+            &period_keys,
+            periodes_recentes,
+            seuil_persistance,
+        );
+
+        let dimension = |col: &str| {
+            Ok::<_, rusqlite::Error>(compute_par_dimension(
+                &queries::get_bilan_entrees_par_dimension(conn, &from, &to, gran, col)?,
+                &queries::get_bilan_sorties_par_dimension(conn, &from, &to, gran, col)?,
+                &queries::get_stock_vivant_par_dimension(conn, col)?,
+                &period_keys,
+                periodes_recentes,
+                seuil_persistance,
+            ))
+        };
+        let par_groupe = dimension("groupe_principal")?;
+        let par_technicien = dimension("technicien_principal")?;
+        let en_deficit = |equipes: &[crate::analyzer::capacity::CapaciteEquipe]| {
+            equipes
+                .iter()
+                .filter(|e| e.deficit_persistant)
+                .map(|e| e.label.clone())
+                .collect()
+        };
+
+        Ok(CapaciteAnalyse {
+            date_debut: from.clone(),
+            date_fin: to.clone(),
+            granularite: gran.to_string(),
+            periodes_recentes,
+            seuil_persistance,
+            global,
+            groupes_en_deficit: en_deficit(&par_groupe),
+            techniciens_en_deficit: en_deficit(&par_technicien),
+            par_groupe,
+            par_technicien,
+        })
+    })?;
+
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
+
+/// Charge par ETP des techniciens, dispersion par groupe (Gini, coefficient
+/// de variation) et techniciens sur- ou sous-chargés sur la fenêtre.
+#[tauri::command]
+pub async fn get_workload_fairness(
+    state: tauri::State<'_, AppState>,
+    date_debut: Option<String>,
+    date_fin: Option<String>,
+) -> Result<EquiteCharge, String> {
+    let start = Instant::now();
+    let params = serde_json::json!({
+        "dateDebut": date_debut,
+        "dateFin": date_fin,
+    });
+    let (key, cached) = lookup_cached::<EquiteCharge>(
+        &state,
+        "workload_fairness",
+        &params,
+        &[CacheDep::Tickets, CacheDep::Config],
+    )?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let date_to = match date_fin.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de fin invalide: {s}"))?,
+        None => state
+            .db(|conn| queries::get_import_reference_date(conn, key.import_id))?
+            .unwrap_or_else(|| chrono::Local::now().naive_local()),
+    };
+    let date_from = match date_debut.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de début invalide: {s}"))?,
+        None => date_to - chrono::Duration::days(FENETRE_DEFAUT_JOURS - 1),
+    };
+    if date_from > date_to {
+        return Err("La date de début doit précéder la date de fin".to_string());
+    }
+    let from = date_from.format("%Y-%m-%d").to_string();
+    let to = date_to.format("%Y-%m-%d").to_string();
+
+    let result = state.db(|conn| {
+        let lignes = queries::get_charges_techniciens(conn, key.import_id, &from, &to)?;
+        let config = crate::config::get_config_from_db(conn)?;
+        let cal = crate::config::get_calendrier_from_db(conn)?;
+        Ok(compute_equite(
+            &lignes,
+            &config.capacites_techniciens,
+            &cal,
+            date_from.date(),
+            date_to.date(),
+        ))
+    })?;
+
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
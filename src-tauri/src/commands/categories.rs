@@ -1,10 +1,21 @@
 use std::collections::HashMap;
+use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 
+use crate::analyzer::category_tree::{compute_hierarchie, CategorieHierarchie, Fenetre};
+use crate::analyzer::temporal::generate_period_keys;
+use crate::commands::bilan::parse_date_flexible;
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::commands::stock::TicketSummary;
+use crate::config::get_config_from_db;
+use crate::db::cache::CacheDep;
 use crate::db::queries;
 use crate::state::{AppState, DbAccess};
 
+/// Fenêtre par défaut des entrants : 12 mois jusqu'à la date de l'import.
+const FENETRE_DEFAUT_JOURS: i64 = 365;
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CategoriesRequest {
@@ -50,21 +61,17 @@ fn make_node(
         (count as f64 / total as f64 * 1000.0).round() / 10.0
     };
 
-    let children = if level < 3 {
-        let prefix = format!("{} > ", full_path);
-        let mut child_paths: Vec<String> = agg
-            .keys()
-            .filter(|k| k.starts_with(&prefix) && k[prefix.len()..].find(" > ").is_none())
-            .cloned()
-            .collect();
-        child_paths.sort_by(|a, b| agg[b].0.cmp(&agg[a].0));
-        child_paths
-            .into_iter()
-            .map(|p| make_node(&p, agg, total))
-            .collect()
-    } else {
-        vec![]
-    };
+    let prefix = format!("{} > ", full_path);
+    let mut child_paths: Vec<String> = agg
+        .keys()
+        .filter(|k| k.starts_with(&prefix) && k[prefix.len()..].find(" > ").is_none())
+        .cloned()
+        .collect();
+    child_paths.sort_by(|a, b| agg[b].0.cmp(&agg[a].0));
+    let children = child_paths
+        .into_iter()
+        .map(|p| make_node(&p, agg, total))
+        .collect();
 
     CategoryNode {
         name,
@@ -87,7 +94,7 @@ fn build_tree_nodes(
 
     for (path, count, incidents, demandes) in &raw {
         let parts: Vec<&str> = path.split(" > ").collect();
-        for depth in 1..=parts.len().min(3) {
+        for depth in 1..=parts.len() {
             let ancestor = parts[..depth].join(" > ");
             let entry = agg.entry(ancestor).or_insert((0, 0, 0));
             entry.0 += *count as usize;
@@ -141,3 +148,90 @@ pub async fn get_categories_tree(
         })
     })
 }
+
+// ─── Hiérarchie normalisée ───────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryHierarchyRequest {
+    pub date_debut: Option<String>,
+    pub date_fin: Option<String>,
+    /// "week" ou "month" (défaut).
+    pub granularity: Option<String>,
+}
+
+/// Arbre complet des catégories avec indicateurs propres et cumulés par nœud.
+#[tauri::command]
+pub async fn get_category_hierarchy(
+    state: tauri::State<'_, AppState>,
+    request: CategoryHierarchyRequest,
+) -> Result<CategorieHierarchie, String> {
+    let start = Instant::now();
+    let gran = match request.granularity.as_deref() {
+        Some("week") => "week",
+        _ => "month",
+    };
+    let params = serde_json::json!({
+        "granularity": gran,
+        "dateDebut": request.date_debut,
+        "dateFin": request.date_fin,
+    });
+    let (key, cached) = lookup_cached::<CategorieHierarchie>(
+        &state,
+        "category_hierarchy",
+        &params,
+        &[CacheDep::Tickets, CacheDep::Config],
+    )?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let (noeuds, tickets, reference, jeu) = state.db(|conn| {
+        Ok((
+            queries::get_categories(conn)?,
+            queries::get_tickets_categorie(conn, key.import_id)?,
+            queries::get_import_reference_date(conn, key.import_id)?,
+            get_config_from_db(conn)?.tranches.anciennete(),
+        ))
+    })?;
+    let date_to = match request.date_fin.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de fin invalide: {s}"))?,
+        None => reference.unwrap_or_else(|| chrono::Local::now().naive_local()),
+    };
+    let date_from = match request.date_debut.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de début invalide: {s}"))?,
+        None => date_to - chrono::Duration::days(FENETRE_DEFAUT_JOURS - 1),
+    };
+    if date_from > date_to {
+        return Err("La date de début doit précéder la date de fin".to_string());
+    }
+
+    let periodes = generate_period_keys(date_from, date_to, gran);
+    let fenetre = Fenetre {
+        granularite: gran,
+        periodes: &periodes,
+    };
+    let result = compute_hierarchie(&noeuds, &tickets, &fenetre, &jeu);
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
+
+/// Drill-down : tickets d'un nœud, descendants inclus par défaut.
+#[tauri::command]
+pub async fn get_category_tickets(
+    state: tauri::State<'_, AppState>,
+    categorie_id: i64,
+    inclure_descendants: Option<bool>,
+    vivants_seulement: Option<bool>,
+) -> Result<Vec<TicketSummary>, String> {
+    state.db(|conn| {
+        let import_id = queries::get_active_import_id(conn)?;
+        queries::get_category_tickets(
+            conn,
+            import_id,
+            categorie_id,
+            inclure_descendants.unwrap_or(true),
+            vivants_seulement.unwrap_or(false),
+        )
+    })
+}
@@ -0,0 +1,98 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::analyzer::cohort::{compute_cohortes, CohorteAnalyse};
+use crate::commands::bilan::parse_date_flexible;
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::cache::CacheDep;
+use crate::db::queries;
+use crate::state::{AppState, DbAccess};
+
+/// Fenêtre par défaut : 12 mois jusqu'à la date de l'import.
+const FENETRE_DEFAUT_JOURS: i64 = 365;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CohorteRequest {
+    /// "week" ou "month" (défaut).
+    pub granularity: Option<String>,
+    pub date_debut: Option<String>,
+    pub date_fin: Option<String>,
+    /// "type", "groupe" ou "categorie".
+    pub dimension: Option<String>,
+}
+
+fn dimension_column(dimension: Option<&str>) -> Result<Option<&'static str>, String> {
+    match dimension {
+        None => Ok(None),
+        Some("type") => Ok(Some("type_ticket")),
+        Some("groupe") => Ok(Some("groupe_principal")),
+        Some("categorie") => Ok(Some("categorie")),
+        Some(autre) => Err(format!("Dimension inconnue: {autre}")),
+    }
+}
+
+/// Calcul partagé entre la commande IPC et l'export Excel.
+pub(crate) fn run_cohort_logic(
+    state: &AppState,
+    request: &CohorteRequest,
+) -> Result<CohorteAnalyse, String> {
+    let start = Instant::now();
+    let gran = match request.granularity.as_deref() {
+        Some("week") => "week",
+        _ => "month",
+    };
+    let colonne = dimension_column(request.dimension.as_deref())?;
+
+    let params = serde_json::json!({
+        "granularity": gran,
+        "dateDebut": request.date_debut,
+        "dateFin": request.date_fin,
+        "dimension": request.dimension,
+    });
+    let (key, cached) =
+        lookup_cached::<CohorteAnalyse>(state, "cohort", &params, &[CacheDep::Tickets])?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let (tickets, reference) = state.db(|conn| {
+        Ok((
+            queries::get_tickets_cohorte(conn, key.import_id, colonne)?,
+            queries::get_import_reference_date(conn, key.import_id)?,
+        ))
+    })?;
+    let reference = reference.unwrap_or_else(|| chrono::Local::now().naive_local());
+    let date_to = match request.date_fin.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de fin invalide: {s}"))?,
+        None => reference,
+    };
+    let date_from = match request.date_debut.as_deref() {
+        Some(s) => parse_date_flexible(s).ok_or_else(|| format!("Date de début invalide: {s}"))?,
+        None => date_to - chrono::Duration::days(FENETRE_DEFAUT_JOURS - 1),
+    };
+    if date_from > date_to {
+        return Err("La date de début doit précéder la date de fin".to_string());
+    }
+
+    let result = compute_cohortes(
+        &tickets,
+        date_from,
+        date_to,
+        gran,
+        request.dimension.as_deref(),
+        reference,
+    );
+    store_cached(state, &key, &result, start);
+    Ok(result)
+}
+
+/// Cohortes par période d'ouverture : part résolue à 1, 7, 30 et 90 jours.
+#[tauri::command]
+pub async fn get_cohort_analysis(
+    state: tauri::State<'_, AppState>,
+    request: CohorteRequest,
+) -> Result<CohorteAnalyse, String> {
+    run_cohort_logic(&state, &request)
+}
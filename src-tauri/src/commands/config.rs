@@ -1,4 +1,12 @@
+use serde::Serialize;
+
+use crate::analyzer::calendar::jours_feries_france;
+use crate::analyzer::heatmap::valider_plannings;
+use crate::analyzer::thresholds::{seuils_par_portee, valider_surcharges, SeuilsPortee};
+use crate::analyzer::workload::valider_capacites;
 use crate::config::AppConfig;
+use crate::db::audit::{self, AuditAction};
+use crate::db::queries;
 use crate::state::{AppState, DbAccess};
 
 #[tauri::command]
@@ -10,12 +18,86 @@ pub async fn get_config(
     })
 }
 
+/// Les actions recommandées sont calculées à l'import : les seuils de
+/// classification modifiés (globaux ou surcharges) valent pour le prochain.
 #[tauri::command]
 pub async fn update_config(
     state: tauri::State<'_, AppState>,
     config: AppConfig,
 ) -> Result<(), String> {
+    config
+        .calendrier
+        .compile()
+        .map_err(|e| format!("Calendrier invalide: {e}"))?;
+    valider_plannings(&config.plannings_groupes)
+        .map_err(|e| format!("Planning invalide: {e}"))?;
+    valider_capacites(&config.capacites_techniciens)
+        .map_err(|e| format!("Capacité invalide: {e}"))?;
+    valider_surcharges(&config).map_err(|e| format!("Seuils invalides: {e}"))?;
+    config
+        .tranches
+        .valider()
+        .map_err(|e| format!("Tranches invalides: {e}"))?;
+    state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let before = serde_json::to_value(crate::config::get_config_from_db(&tx)?)
+            .unwrap_or_default();
+        crate::config::update_config_in_db(&tx, &config)?;
+        let after = serde_json::to_value(&config).unwrap_or_default();
+
+        // Seules les clés modifiées sont journalisées
+        let changed = audit::changed_keys(&before, &after);
+        if !changed.is_empty() {
+            audit::record(
+                &tx,
+                AuditAction::UpdateConfig,
+                Some(&changed.join(", ")),
+                Some(&audit::pick(&before, &changed)),
+                Some(&audit::pick(&after, &changed)),
+            )?;
+        }
+        tx.commit()
+    })
+}
+
+/// Seuils effectifs par portée : global, groupes et techniciens de l'import
+/// actif ainsi que les portées surchargées.
+#[tauri::command]
+pub async fn get_effective_thresholds(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SeuilsPortee>, String> {
     state.db(|conn| {
-        crate::config::update_config_in_db(conn, &config)
+        let config = crate::config::get_config_from_db(conn)?;
+        let (groupes, techniciens) = match queries::get_active_import_id(conn) {
+            Ok(import_id) => (
+                queries::get_groupes_import(conn, import_id)?,
+                queries::get_groupes_techniciens(conn, import_id)?,
+            ),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Default::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(seuils_par_portee(&config, &groupes, &techniciens))
     })
 }
+
+/// Jours fériés français d'une année (pour l'écran du calendrier ouvré).
+#[tauri::command]
+pub async fn get_jours_feries(annee: i32) -> Result<Vec<JourFerie>, String> {
+    if !(1583..=9999).contains(&annee) {
+        return Err(format!("Année invalide: {annee}"));
+    }
+    Ok(jours_feries_france(annee)
+        .into_iter()
+        .map(|(date, libelle)| JourFerie {
+            date: date.format("%Y-%m-%d").to_string(),
+            libelle: libelle.to_string(),
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JourFerie {
+    pub date: String,
+    pub libelle: String,
+}
@@ -1,7 +1,55 @@
-use crate::analyzer::dashboard::{build_dashboard_kpi, DashboardKpi};
-use crate::db::queries::get_active_import_id;
+use std::time::Instant;
+
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::analyzer::comparison::{deltas_dashboard, fenetres, ComparaisonRequest, Fenetre, KpiDelta};
+use crate::analyzer::dashboard::{build_dashboard_kpi, build_spc_kpi, DashboardKpi};
+use crate::analyzer::spc::SpcKpi;
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::cache::{CacheDep, CacheKey};
 use crate::state::{AppState, DbAccess};
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardComparaison {
+    pub fenetre_courante: Fenetre,
+    pub fenetre_reference: Fenetre,
+    pub courant: DashboardKpi,
+    pub reference: DashboardKpi,
+    pub deltas: Vec<KpiDelta>,
+}
+
+/// Dashboard d'une fenêtre, via le cache analytique.
+fn cached_dashboard_kpi(
+    conn: &Connection,
+    date_debut: &Option<String>,
+    date_fin: &Option<String>,
+    gran: &str,
+) -> Result<DashboardKpi, rusqlite::Error> {
+    let cache_params = serde_json::json!({
+        "dateDebut": date_debut,
+        "dateFin": date_fin,
+        "granularity": gran,
+    });
+    let key = CacheKey::for_active_import(
+        conn,
+        "dashboard_kpi",
+        &cache_params,
+        &[CacheDep::Tickets, CacheDep::Sla, CacheDep::Config],
+    )?;
+    if let Some(hit) = key.get(conn)? {
+        return Ok(hit);
+    }
+    let start = Instant::now();
+    let kpi = build_dashboard_kpi(conn, key.import_id, date_debut, date_fin, gran)?;
+    if let Err(e) = key.put(conn, &kpi, start.elapsed().as_millis() as i64) {
+        log::warn!("Écriture cache dashboard_kpi impossible: {e}");
+    }
+    Ok(kpi)
+}
+
 /// Returns the complete Dashboard KPI ITSM payload for the active import.
 ///
 /// Optional `date_debut` and `date_fin` parameters (ISO format) filter tickets
@@ -14,8 +62,74 @@ pub async fn get_dashboard_kpi(
     granularity: Option<String>,
 ) -> Result<DashboardKpi, String> {
     let gran = granularity.as_deref().unwrap_or("month");
+    state.db(|conn| cached_dashboard_kpi(conn, &date_debut, &date_fin, gran))
+}
+
+/// Cartes de contrôle (arrivées, MTTR, taux N1) par semaine ou par mois,
+/// avec limites de contrôle et violations des règles de Western Electric.
+#[tauri::command]
+pub async fn get_spc_charts(
+    state: tauri::State<'_, AppState>,
+    date_debut: Option<String>,
+    date_fin: Option<String>,
+    granularity: Option<String>,
+) -> Result<SpcKpi, String> {
+    let start = Instant::now();
+    let gran = granularity.as_deref().unwrap_or("month");
+    let params = serde_json::json!({
+        "dateDebut": date_debut,
+        "dateFin": date_fin,
+        "granularity": gran,
+    });
+    let (key, cached) =
+        lookup_cached::<SpcKpi>(&state, "spc_charts", &params, &[CacheDep::Tickets, CacheDep::Config])?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let result = state.db(|conn| build_spc_kpi(conn, key.import_id, &date_debut, &date_fin, gran))?;
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
+
+/// Dashboard de la fenêtre [date_debut, date_fin] comparé à une fenêtre de
+/// référence (période précédente, année précédente ou personnalisée).
+#[tauri::command]
+pub async fn get_dashboard_comparison(
+    state: tauri::State<'_, AppState>,
+    date_debut: String,
+    date_fin: String,
+    granularity: Option<String>,
+    comparaison: ComparaisonRequest,
+) -> Result<DashboardComparaison, String> {
+    let gran = granularity.as_deref().unwrap_or("month");
+    let parse = |s: &str| {
+        NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d")
+            .map_err(|_| format!("Date invalide: {s}"))
+    };
+    let (fenetre_courante, fenetre_reference) =
+        fenetres(parse(&date_debut)?, parse(&date_fin)?, &comparaison)?;
+
     state.db(|conn| {
-        let import_id = get_active_import_id(conn)?;
-        build_dashboard_kpi(conn, import_id, &date_debut, &date_fin, gran)
+        let courant = cached_dashboard_kpi(
+            conn,
+            &Some(fenetre_courante.date_debut.clone()),
+            &Some(fenetre_courante.date_fin.clone()),
+            gran,
+        )?;
+        let reference = cached_dashboard_kpi(
+            conn,
+            &Some(fenetre_reference.date_debut.clone()),
+            &Some(fenetre_reference.date_fin.clone()),
+            gran,
+        )?;
+        let deltas = deltas_dashboard(&courant, &reference);
+        Ok(DashboardComparaison {
+            fenetre_courante,
+            fenetre_reference,
+            courant,
+            reference,
+            deltas,
+        })
     })
 }
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
 
+use crate::analyzer::stats::pct;
 use crate::analyzer::temporal::{auto_granularity, generate_period_keys};
 use crate::db::queries;
 use crate::state::{AppState, DbAccess};
@@ -24,6 +25,8 @@ pub struct DelaisKpi {
     pub taux_48h: f64,
     pub mttr_jours: f64,
     pub mediane_jours: f64,
+    pub mttr_jours_ouvres: f64,
+    pub mediane_jours_ouvres: f64,
     pub total_resolus: usize,
     pub trend: Vec<DelaisTrend>,
     pub distribution: Vec<TrancheDelai>,
@@ -58,14 +61,6 @@ fn parse_date_flexible(s: &str) -> Option<chrono::NaiveDateTime> {
         .and_then(|d| d.and_hms_opt(0, 0, 0))
 }
 
-fn pct(n: usize, total: usize) -> f64 {
-    if total == 0 {
-        0.0
-    } else {
-        (n as f64 / total as f64 * 1000.0).round() / 10.0
-    }
-}
-
 /// Calcule (MTTR en jours, médiane en jours) à partir d'une liste de durées positives.
 fn compute_mttr_median(durations: &[f64]) -> (f64, f64) {
     if durations.is_empty() {
@@ -90,7 +85,7 @@ fn compute_mttr_median(durations: &[f64]) -> (f64, f64) {
 fn period_expr(granularity: &str, date_col: &str) -> String {
     match granularity {
         "day" => format!("strftime('%Y-%m-%d', {date_col})"),
-        "week" => format!("strftime('%Y-W%W', {date_col})"),
+        "week" => format!("strftime('%G-W%V', {date_col})"),
         "quarter" => format!(
             "(strftime('%Y', {date_col}) || '-Q' || ((CAST(strftime('%m', {date_col}) AS INTEGER) - 1) / 3 + 1))"
         ),
@@ -119,14 +114,21 @@ pub async fn get_delais_kpi(
     let to_str = date_to.format("%Y-%m-%d").to_string();
 
     // Get all resolution durations for the period (with optional category filters)
-    let durations = state.db(|conn| queries::get_resolution_durations_filtered(
-        conn,
-        &from_str,
-        &to_str,
-        request.categorie_niveau1.as_deref(),
-        request.categorie_niveau2.as_deref(),
-        request.categorie.as_deref(),
-    ))?;
+    let (delais, jeu) = state.db(|conn| {
+        let delais = queries::get_resolution_durations_filtered(
+            conn,
+            &crate::config::get_calendrier_from_db(conn)?,
+            &from_str,
+            &to_str,
+            request.categorie_niveau1.as_deref(),
+            request.categorie_niveau2.as_deref(),
+            request.categorie.as_deref(),
+        )?;
+        let jeu = crate::config::get_config_from_db(conn)?.tranches.resolution();
+        Ok((delais, jeu))
+    })?;
+
+    let durations: Vec<f64> = delais.iter().map(|d| d.jours).collect();
 
     // Global KPIs
     let total_resolus = durations.len();
@@ -137,33 +139,20 @@ pub async fn get_delais_kpi(
 
     let positive: Vec<f64> = durations.iter().copied().filter(|&d| d >= 0.0).collect();
     let (mttr_jours, mediane_jours) = compute_mttr_median(&positive);
+    let ouvres: Vec<f64> = delais.iter().map(|d| d.jours_ouvres).collect();
+    let (mttr_jours_ouvres, mediane_jours_ouvres) = compute_mttr_median(&ouvres);
 
-    // Distribution by tranches
-    let mut lt24 = 0usize;
-    let mut lt48 = 0usize;
-    let mut lt7j = 0usize;
-    let mut lt30j = 0usize;
-    let mut ge30j = 0usize;
-    for &d in &positive {
-        if d < 1.0 {
-            lt24 += 1;
-        } else if d < 2.0 {
-            lt48 += 1;
-        } else if d < 7.0 {
-            lt7j += 1;
-        } else if d < 30.0 {
-            lt30j += 1;
-        } else {
-            ge30j += 1;
-        }
-    }
-    let distribution = vec![
-        TrancheDelai { label: "< 24h".to_string(), count: lt24, pourcentage: pct(lt24, total_resolus) },
-        TrancheDelai { label: "24h - 48h".to_string(), count: lt48, pourcentage: pct(lt48, total_resolus) },
-        TrancheDelai { label: "2j - 7j".to_string(), count: lt7j, pourcentage: pct(lt7j, total_resolus) },
-        TrancheDelai { label: "7j - 30j".to_string(), count: lt30j, pourcentage: pct(lt30j, total_resolus) },
-        TrancheDelai { label: "> 30j".to_string(), count: ge30j, pourcentage: pct(ge30j, total_resolus) },
-    ];
+    // Distribution selon le jeu de tranches configuré
+    let distribution = jeu
+        .libelles()
+        .into_iter()
+        .zip(jeu.compter(positive.iter().copied()))
+        .map(|(label, count)| TrancheDelai {
+            label,
+            count,
+            pourcentage: pct(count, total_resolus),
+        })
+        .collect();
 
     // Trend by period: query individual durations with their period key
     let cat_n1 = request.categorie_niveau1.clone();
@@ -246,6 +235,8 @@ pub async fn get_delais_kpi(
         taux_48h,
         mttr_jours,
         mediane_jours,
+        mttr_jours_ouvres,
+        mediane_jours_ouvres,
         total_resolus,
         trend,
         distribution,
@@ -297,6 +288,8 @@ pub struct CategorieDelais {
     pub total_resolus: usize,
     pub mttr_jours: f64,
     pub mediane_jours: f64,
+    pub mttr_jours_ouvres: f64,
+    pub mediane_jours_ouvres: f64,
     pub taux_24h: f64,
     pub taux_48h: f64,
 }
@@ -326,6 +319,7 @@ pub async fn get_delais_par_categorie(
     let grouped = state.db(|conn| {
         queries::get_resolution_durations_by_category(
             conn,
+            &crate::config::get_calendrier_from_db(conn)?,
             &from_str,
             &to_str,
             request.categorie_niveau1.as_deref(),
@@ -336,17 +330,22 @@ pub async fn get_delais_par_categorie(
 
     let result = grouped
         .into_iter()
-        .map(|(cat, durations)| {
+        .map(|(cat, delais)| {
+            let durations: Vec<f64> = delais.iter().map(|d| d.jours).collect();
+            let ouvres: Vec<f64> = delais.iter().map(|d| d.jours_ouvres).collect();
             let total = durations.len();
             let lt24 = durations.iter().filter(|&&d| d < 1.0).count();
             let lt48 = durations.iter().filter(|&&d| d < 2.0).count();
             let (mttr, mediane) = compute_mttr_median(&durations);
+            let (mttr_ouvres, mediane_ouvres) = compute_mttr_median(&ouvres);
 
             CategorieDelais {
                 categorie: cat,
                 total_resolus: total,
                 mttr_jours: mttr,
                 mediane_jours: mediane,
+                mttr_jours_ouvres: mttr_ouvres,
+                mediane_jours_ouvres: mediane_ouvres,
                 taux_24h: pct(lt24, total),
                 taux_48h: pct(lt48, total),
             }
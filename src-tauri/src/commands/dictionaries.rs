@@ -0,0 +1,269 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::config::get_config_from_db;
+use crate::db::audit::{self, AuditAction};
+use crate::db::cache::CacheDep;
+use crate::db::queries;
+use crate::nlp::patterns::{
+    self, KeywordDictionaryEntry, PatternDictionary, PatternEvidence, SignalKind,
+};
+use crate::state::{AppState, DbAccess};
+
+/// La relance sans réponse dépend aussi des seuils d'inactivité (config).
+const SIGNALS_CACHE_DEPS: [CacheDep; 3] =
+    [CacheDep::Tickets, CacheDep::Dictionaries, CacheDep::Config];
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeywordSignalsResult {
+    pub total_analyses: usize,
+    pub resolu_non_clos: usize,
+    pub relance_sans_reponse: usize,
+    pub doublon_annule: usize,
+    pub tickets: Vec<KeywordSignalTicket>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeywordSignalTicket {
+    pub id: u64,
+    pub titre: String,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub inactivite_jours: Option<i64>,
+    pub action_recommandee: Option<String>,
+    pub signaux: Vec<PatternEvidence>,
+}
+
+fn validate_category(category: &str) -> Result<(), String> {
+    if patterns::CATEGORIES.contains(&category) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Catégorie inconnue: {category} (attendu : {})",
+            patterns::CATEGORIES.join(", ")
+        ))
+    }
+}
+
+/// État journalisé d'un mot-clé (sans l'horodatage de création).
+fn audit_value(entry: &KeywordDictionaryEntry) -> serde_json::Value {
+    serde_json::json!({
+        "category": entry.category,
+        "keyword": entry.keyword,
+        "isRegex": entry.is_regex,
+        "isActive": entry.is_active,
+    })
+}
+
+#[tauri::command]
+pub async fn get_keyword_dictionaries(
+    state: tauri::State<'_, AppState>,
+    category: Option<String>,
+) -> Result<Vec<KeywordDictionaryEntry>, String> {
+    state.db(|conn| queries::get_keyword_dictionaries(conn, category.as_deref()))
+}
+
+/// Ajoute un mot-clé (ou une regex) à un dictionnaire.
+#[tauri::command]
+pub async fn add_keyword_dictionary_entry(
+    state: tauri::State<'_, AppState>,
+    category: String,
+    keyword: String,
+    is_regex: Option<bool>,
+) -> Result<KeywordDictionaryEntry, String> {
+    let is_regex = is_regex.unwrap_or(false);
+    let keyword = keyword.trim().to_string();
+    validate_category(&category)?;
+    patterns::validate_keyword(&keyword, is_regex)?;
+
+    let existing = state.db(|conn| queries::get_keyword_dictionaries(conn, Some(&category)))?;
+    if existing.iter().any(|e| e.keyword == keyword) {
+        return Err(format!("« {keyword} » existe déjà dans {category}"));
+    }
+
+    state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO keyword_dictionaries (category, keyword, is_regex) VALUES (?1, ?2, ?3)",
+            rusqlite::params![category, keyword, is_regex as i64],
+        )?;
+        let id = tx.last_insert_rowid();
+        let entry = queries::get_keyword_dictionary_entry(&tx, id)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        audit::record(
+            &tx,
+            AuditAction::AddKeyword,
+            Some(&id.to_string()),
+            None,
+            Some(&audit_value(&entry)),
+        )?;
+        tx.commit()?;
+        Ok(entry)
+    })
+}
+
+/// Modifie un mot-clé, son mode regex ou son activation.
+#[tauri::command]
+pub async fn update_keyword_dictionary_entry(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+    keyword: Option<String>,
+    is_regex: Option<bool>,
+    is_active: Option<bool>,
+) -> Result<KeywordDictionaryEntry, String> {
+    let current = state
+        .db(|conn| queries::get_keyword_dictionary_entry(conn, id))?
+        .ok_or_else(|| format!("Mot-clé introuvable: {id}"))?;
+    let before = audit_value(&current);
+
+    let keyword = keyword
+        .map(|k| k.trim().to_string())
+        .unwrap_or(current.keyword);
+    let is_regex = is_regex.unwrap_or(current.is_regex);
+    let is_active = is_active.unwrap_or(current.is_active);
+    patterns::validate_keyword(&keyword, is_regex)?;
+
+    state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE keyword_dictionaries SET keyword = ?1, is_regex = ?2, is_active = ?3
+             WHERE id = ?4",
+            rusqlite::params![keyword, is_regex as i64, is_active as i64, id],
+        )?;
+        let entry = queries::get_keyword_dictionary_entry(&tx, id)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let after = audit_value(&entry);
+        if after != before {
+            audit::record(
+                &tx,
+                AuditAction::UpdateKeyword,
+                Some(&id.to_string()),
+                Some(&before),
+                Some(&after),
+            )?;
+        }
+        tx.commit()?;
+        Ok(entry)
+    })
+}
+
+#[tauri::command]
+pub async fn delete_keyword_dictionary_entry(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let deleted = state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let Some(before) = queries::get_keyword_dictionary_entry(&tx, id)? else {
+            return Ok(false);
+        };
+        tx.execute(
+            "DELETE FROM keyword_dictionaries WHERE id = ?1",
+            rusqlite::params![id],
+        )?;
+        audit::record(
+            &tx,
+            AuditAction::DeleteKeyword,
+            Some(&id.to_string()),
+            Some(&audit_value(&before)),
+            None,
+        )?;
+        tx.commit()?;
+        Ok(true)
+    })?;
+    if !deleted {
+        return Err(format!("Mot-clé introuvable: {id}"));
+    }
+    Ok(())
+}
+
+/// Tickets vivants de l'import actif présentant un signal dans leurs derniers
+/// suivis ou leur solution, avec les preuves (mot-clé, source, extrait).
+/// `kind` filtre sur un type de signal (doublon_annule, resolu_non_clos,
+/// relance_sans_reponse).
+#[tauri::command]
+pub async fn get_keyword_signals(
+    state: tauri::State<'_, AppState>,
+    kind: Option<String>,
+) -> Result<KeywordSignalsResult, String> {
+    let filter = match kind.as_deref() {
+        Some(k) => Some(SignalKind::parse(k).ok_or_else(|| format!("Signal inconnu: {k}"))?),
+        None => None,
+    };
+
+    let start = Instant::now();
+    let params = serde_json::json!({ "kind": kind });
+    let (key, cached) = lookup_cached(&state, "keyword_signals", &params, &SIGNALS_CACHE_DEPS)?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let (rows, entries, config) = state.db(|conn| {
+        let import_id = queries::get_active_import_id(conn)?;
+        Ok((
+            queries::get_pattern_ticket_texts(conn, import_id)?,
+            queries::get_active_keyword_entries(conn)?,
+            get_config_from_db(conn)?,
+        ))
+    })?;
+
+    let result = tokio::task::spawn_blocking(move || {
+        let dictionary = PatternDictionary::from_entries(&entries);
+        let mut result = KeywordSignalsResult {
+            total_analyses: rows.len(),
+            resolu_non_clos: 0,
+            relance_sans_reponse: 0,
+            doublon_annule: 0,
+            tickets: Vec::new(),
+        };
+
+        for row in rows {
+            let mut signaux = dictionary.detect(&row.suivis_description, &row.solution);
+            // Une relance n'est signalée qu'au-delà du seuil d'inactivité de relance
+            // du groupe / technicien du ticket
+            let seuil_relance = config
+                .seuils_pour(row.groupe.as_deref(), row.technicien.as_deref())
+                .seuil_inactivite_relancer as i64;
+            signaux.retain(|s| {
+                s.kind != SignalKind::RelanceSansReponse
+                    || row.inactivite_jours.unwrap_or(0) > seuil_relance
+            });
+            if let Some(f) = filter {
+                signaux.retain(|s| s.kind == f);
+            }
+            if signaux.is_empty() {
+                continue;
+            }
+            for s in &signaux {
+                match s.kind {
+                    SignalKind::ResoluNonClos => result.resolu_non_clos += 1,
+                    SignalKind::RelanceSansReponse => result.relance_sans_reponse += 1,
+                    SignalKind::DoublonAnnule => result.doublon_annule += 1,
+                }
+            }
+            result.tickets.push(KeywordSignalTicket {
+                id: row.id,
+                titre: row.titre,
+                technicien: row.technicien,
+                groupe: row.groupe,
+                inactivite_jours: row.inactivite_jours,
+                action_recommandee: row.action_recommandee,
+                signaux,
+            });
+        }
+
+        result
+            .tickets
+            .sort_by_key(|t| (t.signaux[0].kind, std::cmp::Reverse(t.inactivite_jours)));
+        result
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))?;
+
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
@@ -0,0 +1,51 @@
+use serde::Deserialize;
+
+use crate::analyzer::diff::{compute_import_diff, ImportDiff};
+use crate::db::queries;
+use crate::state::{AppState, DbAccess};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDiffRequest {
+    /// Import de référence. Défaut : import précédant `import_to`.
+    pub import_from: Option<i64>,
+    /// Import comparé. Défaut : import actif.
+    pub import_to: Option<i64>,
+}
+
+/// Résout le couple d'imports puis calcule le diff.
+pub(crate) fn run_import_diff(
+    state: &AppState,
+    request: &ImportDiffRequest,
+) -> Result<ImportDiff, String> {
+    let (from_id, to_id) = state.db(|conn| {
+        let to_id = match request.import_to {
+            Some(id) => id,
+            None => queries::get_active_import_id(conn)?,
+        };
+        let from_id = match request.import_from {
+            Some(id) => Some(id),
+            None => queries::get_previous_import_id(conn, to_id)?,
+        };
+        Ok((from_id, to_id))
+    })?;
+    let from_id = from_id
+        .ok_or_else(|| "Au moins deux imports sont nécessaires pour comparer".to_string())?;
+
+    if from_id == to_id {
+        return Err("Les deux imports comparés sont identiques".to_string());
+    }
+
+    let before = state.db(|conn| queries::get_ticket_snapshots(conn, from_id))?;
+    let after = state.db(|conn| queries::get_ticket_snapshots(conn, to_id))?;
+
+    Ok(compute_import_diff(from_id, to_id, &before, &after))
+}
+
+#[tauri::command]
+pub async fn get_import_diff(
+    state: tauri::State<'_, AppState>,
+    request: ImportDiffRequest,
+) -> Result<ImportDiff, String> {
+    run_import_diff(&state, &request)
+}
@@ -0,0 +1,31 @@
+use std::time::Instant;
+
+use crate::analyzer::escalation::{compute_escalade, EscaladeKpi, LIMITE_DEFAUT};
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::cache::CacheDep;
+use crate::db::queries;
+use crate::state::{AppState, DbAccess};
+
+/// Escalades entre groupes, ping-pong et impact des sauts sur le MTTR.
+#[tauri::command]
+pub async fn get_escalation_analysis(
+    state: tauri::State<'_, AppState>,
+    limite: Option<usize>,
+) -> Result<EscaladeKpi, String> {
+    let start = Instant::now();
+    let limite = limite.unwrap_or(LIMITE_DEFAUT).max(1);
+    let params = serde_json::json!({ "limite": limite });
+    let (key, cached) =
+        lookup_cached::<EscaladeKpi>(&state, "escalation", &params, &[CacheDep::Tickets])?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let result = state.db(|conn| {
+        let tickets = queries::get_parcours_tickets(conn, key.import_id)?;
+        Ok(compute_escalade(&tickets, limite))
+    })?;
+
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
@@ -3,13 +3,24 @@ use std::time::Instant;
 
 use serde::Serialize;
 
+use crate::analyzer::priority::load_priorite_kpi;
+use crate::analyzer::sla::{load_sla_kpi, HORIZON_DEFAUT_JOURS};
+use crate::analyzer::vendor::load_fournisseur_kpi;
+use crate::config::get_config_from_db;
+use crate::db::audit::{self, AuditFilter};
 use crate::db::queries;
+use crate::export::audit_report;
 use crate::export::bilan_report;
+use crate::export::cohort_report;
+use crate::export::diff_report;
 use crate::export::plan_action;
 use crate::export::stock_report;
 use crate::state::{AppState, DbAccess};
 
 use super::bilan::{run_bilan_logic, BilanRequest};
+use super::cohort::{run_cohort_logic, CohorteRequest};
+use super::diff::{run_import_diff, ImportDiffRequest};
+use super::stock::load_technicians_stock;
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -27,10 +38,32 @@ pub async fn export_excel_stock(
     let start = Instant::now();
 
     let overview = state.db(|conn| queries::get_stock_overview(conn))?;
-    let technicians = state.db(|conn| queries::get_technicians_stock(conn, None))?;
+    let technicians = state.db(|conn| load_technicians_stock(conn, None))?;
     let groups = state.db(|conn| queries::get_groups_stock(conn, None))?;
-
-    let bytes = stock_report::generate_stock_report(&overview, &technicians, &groups)
+    let sla = state.db(|conn| {
+        let import_id = queries::get_active_import_id(conn)?;
+        load_sla_kpi(conn, import_id, None, None, "month", HORIZON_DEFAUT_JOURS, None)
+    })?;
+
+    let priorites = state.db(|conn| {
+        let import_id = queries::get_active_import_id(conn)?;
+        load_priorite_kpi(conn, import_id, None, None, "month")
+    })?;
+    let fournisseurs = state.db(|conn| {
+        let import_id = queries::get_active_import_id(conn)?;
+        load_fournisseur_kpi(conn, import_id)
+    })?;
+    let config = state.db(get_config_from_db)?;
+
+    let bytes = stock_report::generate_stock_report(
+        &overview,
+        &technicians,
+        &groups,
+        &sla,
+        &priorites,
+        &fournisseurs,
+        &config,
+    )
         .map_err(|e| e.to_string())?;
 
     std::fs::write(&path, &bytes).map_err(|e| e.to_string())?;
@@ -51,7 +84,8 @@ pub async fn export_excel_bilan(
     let start = Instant::now();
 
     let bilan = run_bilan_logic(&state, &request)?;
-    let bytes = bilan_report::generate_bilan_report(&bilan, &request)
+    let config = state.db(get_config_from_db)?;
+    let bytes = bilan_report::generate_bilan_report(&bilan, &request, &config)
         .map_err(|e| e.to_string())?;
 
     std::fs::write(&path, &bytes).map_err(|e| e.to_string())?;
@@ -63,6 +97,72 @@ pub async fn export_excel_bilan(
     })
 }
 
+#[tauri::command]
+pub async fn export_excel_cohort(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    request: CohorteRequest,
+) -> Result<ExportResult, String> {
+    let start = Instant::now();
+
+    let analyse = run_cohort_logic(&state, &request)?;
+    let config = state.db(get_config_from_db)?;
+    let bytes =
+        cohort_report::generate_cohort_report(&analyse, &config).map_err(|e| e.to_string())?;
+
+    std::fs::write(&path, &bytes).map_err(|e| e.to_string())?;
+
+    Ok(ExportResult {
+        path,
+        size_bytes: bytes.len() as u64,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+#[tauri::command]
+pub async fn export_excel_diff(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    request: ImportDiffRequest,
+) -> Result<ExportResult, String> {
+    let start = Instant::now();
+
+    let diff = run_import_diff(&state, &request)?;
+    let config = state.db(get_config_from_db)?;
+    let bytes = diff_report::generate_diff_report(&diff, &config).map_err(|e| e.to_string())?;
+
+    std::fs::write(&path, &bytes).map_err(|e| e.to_string())?;
+
+    Ok(ExportResult {
+        path,
+        size_bytes: bytes.len() as u64,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+#[tauri::command]
+pub async fn export_excel_audit_log(
+    state: tauri::State<'_, AppState>,
+    path: String,
+    filter: Option<AuditFilter>,
+) -> Result<ExportResult, String> {
+    let start = Instant::now();
+
+    let filter = filter.unwrap_or_default();
+    let entries = state.db(|conn| audit::get_audit_log(conn, &filter))?;
+    let config = state.db(get_config_from_db)?;
+    let bytes =
+        audit_report::generate_audit_report(&entries, &config).map_err(|e| e.to_string())?;
+
+    std::fs::write(&path, &bytes).map_err(|e| e.to_string())?;
+
+    Ok(ExportResult {
+        path,
+        size_bytes: bytes.len() as u64,
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
 #[tauri::command]
 pub async fn export_excel_plan_action(
     state: tauri::State<'_, AppState>,
@@ -71,7 +171,7 @@ pub async fn export_excel_plan_action(
 ) -> Result<ExportResult, String> {
     let start = Instant::now();
 
-    let technicians = state.db(|conn| queries::get_technicians_stock(conn, None))?;
+    let technicians = state.db(|conn| load_technicians_stock(conn, None))?;
     let stats = technicians
         .into_iter()
         .find(|t| t.technicien == technician)
@@ -98,7 +198,7 @@ pub async fn export_all_plans_zip(
 ) -> Result<ExportResult, String> {
     let start = Instant::now();
 
-    let technicians = state.db(|conn| queries::get_technicians_stock(conn, None))?;
+    let technicians = state.db(|conn| load_technicians_stock(conn, None))?;
 
     let cursor = std::io::Cursor::new(Vec::new());
     let mut zip = zip::ZipWriter::new(cursor);
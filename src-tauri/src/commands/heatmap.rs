@@ -0,0 +1,53 @@
+use std::time::Instant;
+
+use serde::Deserialize;
+
+use crate::analyzer::heatmap::{load_heatmap_kpi, FiltreHoraire, HeatmapKpi};
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::cache::CacheDep;
+use crate::state::{AppState, DbAccess};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapRequest {
+    pub date_debut: Option<String>,
+    pub date_fin: Option<String>,
+    #[serde(flatten)]
+    pub filtre: FiltreHoraire,
+}
+
+/// Cartes de chaleur jour × heure (arrivées, résolutions, premières réponses)
+/// et couverture des plannings de groupe configurés.
+#[tauri::command]
+pub async fn get_heatmaps(
+    state: tauri::State<'_, AppState>,
+    request: HeatmapRequest,
+) -> Result<HeatmapKpi, String> {
+    let start = Instant::now();
+    let params = serde_json::json!({
+        "dateDebut": request.date_debut,
+        "dateFin": request.date_fin,
+        "filtre": request.filtre,
+    });
+    let (key, cached) = lookup_cached::<HeatmapKpi>(
+        &state,
+        "heatmaps",
+        &params,
+        &[CacheDep::Tickets, CacheDep::Config],
+    )?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let result = state.db(|conn| {
+        load_heatmap_kpi(
+            conn,
+            key.import_id,
+            &request.filtre,
+            request.date_debut.as_deref(),
+            request.date_fin.as_deref(),
+        )
+    })?;
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
@@ -3,6 +3,8 @@ use std::path::Path;
 use std::time::Instant;
 use tauri::ipc::Channel;
 
+use crate::db::audit::{self, AuditAction};
+use crate::db::cache::{bump_dependency, CacheDep};
 use crate::state::{AppState, DbAccess};
 
 #[derive(Clone, Serialize)]
@@ -52,6 +54,33 @@ pub struct ImportRecord {
     pub is_active: bool,
 }
 
+/// Résumé de l'import actif, état « avant » du journal d'audit.
+fn active_import_snapshot(
+    conn: &rusqlite::Connection,
+) -> Result<Option<serde_json::Value>, rusqlite::Error> {
+    match audit::active_import_id(conn)? {
+        Some(id) => audit::import_snapshot(conn, id),
+        None => Ok(None),
+    }
+}
+
+/// Journalise l'import dans la transaction qui persiste les tickets.
+fn record_import_audit(
+    tx: &rusqlite::Connection,
+    import_id: i64,
+    target: &str,
+    before: Option<&serde_json::Value>,
+) -> Result<i64, rusqlite::Error> {
+    let after = audit::import_snapshot(tx, import_id)?;
+    audit::record(
+        tx,
+        AuditAction::ImportCsv,
+        Some(target),
+        before,
+        after.as_ref(),
+    )
+}
+
 #[tauri::command]
 pub async fn import_csv(
     state: tauri::State<'_, AppState>,
@@ -97,18 +126,33 @@ pub async fn import_csv(
 
     // Load config for classification thresholds
     let config = state.db(|conn| crate::config::get_config_from_db(conn))?;
+    let rules = state.db(crate::db::queries::get_classification_rules)?;
+    let rules = crate::analyzer::rules::RuleSet::compile(&rules, &config);
+    let dictionary = state.db(crate::db::queries::get_active_keyword_entries)?;
+    let dictionary = crate::nlp::patterns::PatternDictionary::from_entries(&dictionary);
 
-    // Classify each ticket (vivants only — terminés are left unchanged)
+    // Classify each ticket with the stored rules (vivants only — terminés are
+    // left unchanged), then refine with keyword signals found in the latest followups
     let mut tickets = parse_output.tickets;
     for ticket in &mut tickets {
-        crate::analyzer::classifier::classify_ticket(ticket, &config);
+        crate::analyzer::rules::classify_ticket_with_rules(ticket, &rules, &config);
+        if ticket.est_vivant && !dictionary.is_empty() {
+            let signals = dictionary.detect(&ticket.suivis_description, &ticket.solution);
+            crate::analyzer::classifier::apply_pattern_signals(ticket, &signals, &config);
+        }
     }
 
     let total_tickets = tickets.len();
     let skipped_rows = parse_output.skipped_rows;
     let parse_duration_ms = parse_output.parse_duration_ms;
 
-    // Persist: either merge into active import or create a new one
+    // Persist in one transaction with its audit entry: either merge into the
+    // active import or create a new one
+    let target = if merge {
+        format!("{filename} (fusion)")
+    } else {
+        filename.clone()
+    };
     let import_id = if merge {
         // Merge mode: insert tickets into existing active import
         let active_id = state
@@ -116,10 +160,12 @@ pub async fn import_csv(
             .map_err(|_| "Aucun import actif pour la fusion. Importez d'abord un fichier.".to_string())?;
 
         state.db_mut(|conn| {
-            crate::db::insert::bulk_insert_tickets(conn, active_id, &tickets)?;
+            let tx = conn.transaction()?;
+            let before = active_import_snapshot(&tx)?;
+            crate::db::insert::insert_tickets(&tx, active_id, &tickets)?;
 
             // Recalculate import metadata from the merged ticket set
-            conn.execute(
+            tx.execute(
                 "UPDATE imports SET
                     parsed_rows = (SELECT COUNT(*) FROM tickets WHERE import_id = ?1),
                     vivants_count = (SELECT COUNT(*) FROM tickets WHERE import_id = ?1 AND est_vivant = 1),
@@ -130,6 +176,8 @@ pub async fn import_csv(
                 WHERE id = ?1",
                 rusqlite::params![active_id],
             )?;
+            record_import_audit(&tx, active_id, &target, before.as_ref())?;
+            tx.commit()?;
             Ok(active_id)
         })?
     } else {
@@ -150,7 +198,9 @@ pub async fn import_csv(
         let termines_count = tickets.len() - vivants_count;
 
         state.db_mut(|conn| {
-            conn.execute("UPDATE imports SET is_active = 0", [])?;
+            let tx = conn.transaction()?;
+            let before = active_import_snapshot(&tx)?;
+            tx.execute("UPDATE imports SET is_active = 0", [])?;
 
             let detected_json = serde_json::to_string(&parse_output.detected_columns)
                 .unwrap_or_else(|_| "[]".to_string());
@@ -159,7 +209,7 @@ pub async fn import_csv(
             let types_json = serde_json::to_string(&parse_output.unique_types)
                 .unwrap_or_else(|_| "[]".to_string());
 
-            conn.execute(
+            tx.execute(
                 "INSERT INTO imports (
                     filename, file_size_bytes, total_rows, parsed_rows, skipped_rows,
                     vivants_count, termines_count, date_range_from, date_range_to,
@@ -181,8 +231,10 @@ pub async fn import_csv(
                     parse_duration_ms as i64,
                 ],
             )?;
-            let import_id = conn.last_insert_rowid();
-            crate::db::insert::bulk_insert_tickets(conn, import_id, &tickets)?;
+            let import_id = tx.last_insert_rowid();
+            crate::db::insert::insert_tickets(&tx, import_id, &tickets)?;
+            record_import_audit(&tx, import_id, &target, before.as_ref())?;
+            tx.commit()?;
             Ok(import_id)
         })?
     };
@@ -331,12 +383,25 @@ pub async fn set_active_import(
     state: tauri::State<'_, AppState>,
     import_id: i64,
 ) -> Result<(), String> {
-    state.db(|conn| {
-        conn.execute(
+    state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let before = match audit::active_import_id(&tx)? {
+            Some(id) => audit::import_snapshot(&tx, id)?,
+            None => None,
+        };
+        tx.execute(
             "UPDATE imports SET is_active = 1 WHERE id = ?1",
             rusqlite::params![import_id],
         )?;
-        Ok(())
+        let after = audit::import_snapshot(&tx, import_id)?;
+        audit::record(
+            &tx,
+            AuditAction::SetActiveImport,
+            Some(&import_id.to_string()),
+            before.as_ref(),
+            after.as_ref(),
+        )?;
+        tx.commit()
     })
 }
 
@@ -345,9 +410,24 @@ pub async fn delete_import(
     state: tauri::State<'_, AppState>,
     import_id: i64,
 ) -> Result<(), String> {
-    state.db(|conn| {
-        conn.execute("DELETE FROM imports WHERE id = ?1", rusqlite::params![import_id])?;
-        Ok(())
+    state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let before = audit::import_snapshot(&tx, import_id)?;
+        let deleted = tx.execute(
+            "DELETE FROM imports WHERE id = ?1",
+            rusqlite::params![import_id],
+        )?;
+        if deleted > 0 {
+            bump_dependency(&tx, CacheDep::Tickets)?;
+            audit::record(
+                &tx,
+                AuditAction::DeleteImport,
+                Some(&import_id.to_string()),
+                before.as_ref(),
+                None,
+            )?;
+        }
+        tx.commit()
     })
 }
 
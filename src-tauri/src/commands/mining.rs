@@ -1,9 +1,11 @@
 use std::collections::HashMap;
+use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 
 use crate::analytics::anomalies::{find_duplicates, TicketForDuplicates};
 use crate::analytics::clustering::run_kmeans;
+use crate::commands::cache::{lookup_cached, store_cached};
 use crate::nlp::preprocessing::{
     build_stem_mapping, preprocess_text_with_originals, resolve_stem,
     StopWordFilter,
@@ -13,8 +15,13 @@ use crate::nlp::tfidf::{
     top_keywords_for_group,
 };
 use crate::config::get_config_from_db;
+use crate::db::audit::{self, AuditAction};
+use crate::db::cache::CacheDep;
 use crate::state::AppState;
 
+/// Les analyses textuelles dépendent des tickets et des stopwords utilisateur.
+const TEXT_CACHE_DEPS: &[CacheDep] = &[CacheDep::Tickets, CacheDep::Stopwords];
+
 // ── Structs IPC ───────────────────────────────────────────────────────────────
 
 #[derive(Deserialize)]
@@ -27,7 +34,7 @@ pub struct TextAnalysisRequest {
     pub include_resolved: Option<bool>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TextAnalysisResult {
     pub keywords: Vec<KeywordFrequency>,
@@ -36,7 +43,7 @@ pub struct TextAnalysisResult {
     pub ticket_map: HashMap<String, Vec<TicketRef>>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct KeywordFrequency {
     pub word: String,
@@ -45,7 +52,7 @@ pub struct KeywordFrequency {
     pub doc_frequency: usize,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GroupKeywords {
     pub group_name: String,
@@ -53,7 +60,7 @@ pub struct GroupKeywords {
     pub ticket_count: usize,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CorpusStats {
     pub total_documents: usize,
@@ -62,7 +69,7 @@ pub struct CorpusStats {
     pub avg_tokens_per_doc: f64,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClusterResult {
     pub clusters: Vec<Cluster>,
@@ -70,7 +77,7 @@ pub struct ClusterResult {
     pub total_tickets: usize,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Cluster {
     pub id: usize,
@@ -143,6 +150,20 @@ pub async fn run_text_analysis(
         " AND est_vivant = 1"
     };
 
+    let cache_params = serde_json::json!({
+        "corpus": request.corpus,
+        "scope": request.scope,
+        "groupBy": request.group_by,
+        "topN": top_n,
+        "includeResolved": include_resolved,
+    });
+    let (cache_key, cached) =
+        lookup_cached::<TextAnalysisResult>(&state, "text_analysis", &cache_params, TEXT_CACHE_DEPS)?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+    let start = Instant::now();
+
     let (texts, ticket_ids, ticket_titres, group_map, technician_names, user_stopwords): (
         Vec<String>,
         Vec<u64>,
@@ -355,6 +376,8 @@ pub async fn run_text_analysis(
         .await
         .map_err(|e| format!("spawn_blocking error: {e}"))??;
 
+    store_cached(&state, &cache_key, &result, start);
+
     Ok(result)
 }
 
@@ -372,6 +395,18 @@ pub async fn get_clusters(
         ""
     };
 
+    let cache_params = serde_json::json!({
+        "corpus": corpus,
+        "nClusters": n_clusters,
+        "vivantsOnly": vivants_only.unwrap_or(true),
+    });
+    let (cache_key, cached) =
+        lookup_cached::<ClusterResult>(&state, "clusters", &cache_params, TEXT_CACHE_DEPS)?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+    let start = Instant::now();
+
     let (texts, ticket_ids, technician_names, user_stopwords) = {
         let guard = state.db.lock().map_err(|e| format!("Lock error: {e}"))?;
         let conn = guard.as_ref().ok_or("Base de données non initialisée")?;
@@ -508,6 +543,8 @@ pub async fn get_clusters(
     .await
     .map_err(|e| format!("spawn_blocking error: {e}"))??;
 
+    store_cached(&state, &cache_key, &result, start);
+
     Ok(result)
 }
 
@@ -558,55 +595,18 @@ pub async fn detect_anomalies(
         }
     }
 
-    // 2) Tickets vivants inactifs (> 60 jours sans modification)
-    {
-        let mut stmt = conn
-            .prepare(
-                "SELECT id, titre, inactivite_jours, anciennete_jours, technicien_principal \
-                 FROM tickets WHERE import_id = ?1 AND est_vivant = 1 \
-                 AND inactivite_jours > 60 AND anciennete_jours <= 300 \
-                 ORDER BY inactivite_jours DESC",
-            )
-            .map_err(|e| format!("SQL: {e}"))?;
-        let rows = stmt
-            .query_map([import_id], |row| {
-                Ok((
-                    row.get::<_, i64>(0)?,
-                    row.get::<_, String>(1)?,
-                    row.get::<_, i64>(2)?,
-                    row.get::<_, Option<i64>>(3)?,
-                    row.get::<_, Option<String>>(4)?,
-                ))
-            })
-            .map_err(|e| format!("SQL: {e}"))?;
-        for row in rows {
-            let (id, titre, inact, anc, tech) = row.map_err(|e| format!("SQL: {e}"))?;
-            alerts.push(AnomalyAlert {
-                ticket_id: id as u64,
-                titre,
-                anomaly_type: "ticket_inactif".to_string(),
-                severity: if inact > 120 { "high" } else { "medium" }.to_string(),
-                description: format!(
-                    "Aucune activité depuis {} jours (ouvert depuis {} jours, tech: {})",
-                    inact,
-                    anc.unwrap_or(0),
-                    tech.as_deref().unwrap_or("non assigné")
-                ),
-                metric_value: inact as f64,
-                expected_range: "< 60 jours d'inactivité".to_string(),
-            });
-        }
-    }
-
-    // 3) Tickets vivants sans aucun suivi depuis > 30 jours
+    // 2) et 3) Tickets vivants inactifs, ou sans aucun suivi, selon les seuils
+    // du groupe / technicien du ticket (défaut : 60 jours d'inactivité,
+    // 30 jours d'ancienneté sans suivi)
     {
+        let config = get_config_from_db(conn).map_err(|e| format!("Config: {e}"))?;
         let mut stmt = conn
             .prepare(
-                "SELECT id, titre, anciennete_jours, technicien_principal \
+                "SELECT id, titre, inactivite_jours, anciennete_jours, nombre_suivis, \
+                 technicien_principal, groupe_principal \
                  FROM tickets WHERE import_id = ?1 AND est_vivant = 1 \
-                 AND nombre_suivis = 0 AND anciennete_jours > 30 \
-                 AND anciennete_jours <= 300 AND (inactivite_jours IS NULL OR inactivite_jours <= 60) \
-                 ORDER BY anciennete_jours DESC",
+                 AND anciennete_jours <= 300 \
+                 ORDER BY inactivite_jours DESC, anciennete_jours DESC",
             )
             .map_err(|e| format!("SQL: {e}"))?;
         let rows = stmt
@@ -614,26 +614,51 @@ pub async fn detect_anomalies(
                 Ok((
                     row.get::<_, i64>(0)?,
                     row.get::<_, String>(1)?,
-                    row.get::<_, i64>(2)?,
-                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, Option<i64>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, Option<String>>(6)?,
                 ))
             })
             .map_err(|e| format!("SQL: {e}"))?;
         for row in rows {
-            let (id, titre, anc, tech) = row.map_err(|e| format!("SQL: {e}"))?;
-            alerts.push(AnomalyAlert {
-                ticket_id: id as u64,
-                titre,
-                anomaly_type: "sans_suivi".to_string(),
-                severity: if anc > 90 { "high" } else { "medium" }.to_string(),
-                description: format!(
-                    "Aucun suivi depuis l'ouverture il y a {} jours (tech: {})",
-                    anc,
-                    tech.as_deref().unwrap_or("non assigné")
-                ),
-                metric_value: anc as f64,
-                expected_range: "Au moins 1 suivi attendu".to_string(),
-            });
+            let (id, titre, inact, anc, suivis, tech, groupe) =
+                row.map_err(|e| format!("SQL: {e}"))?;
+            let seuils = config.seuils_pour(groupe.as_deref(), tech.as_deref());
+            let seuil_inactif = seuils.seuil_inactivite_cloturer as i64;
+            let seuil_sans_suivi = seuils.seuil_anciennete_relancer as i64;
+            let tech = tech.as_deref().unwrap_or("non assigné");
+
+            match inact {
+                Some(inact) if inact > seuil_inactif => alerts.push(AnomalyAlert {
+                    ticket_id: id as u64,
+                    titre,
+                    anomaly_type: "ticket_inactif".to_string(),
+                    severity: if inact > 2 * seuil_inactif { "high" } else { "medium" }
+                        .to_string(),
+                    description: format!(
+                        "Aucune activité depuis {} jours (ouvert depuis {} jours, tech: {})",
+                        inact, anc, tech
+                    ),
+                    metric_value: inact as f64,
+                    expected_range: format!("< {seuil_inactif} jours d'inactivité"),
+                }),
+                _ if suivis == Some(0) && anc > seuil_sans_suivi => alerts.push(AnomalyAlert {
+                    ticket_id: id as u64,
+                    titre,
+                    anomaly_type: "sans_suivi".to_string(),
+                    severity: if anc > 3 * seuil_sans_suivi { "high" } else { "medium" }
+                        .to_string(),
+                    description: format!(
+                        "Aucun suivi depuis l'ouverture il y a {} jours (tech: {})",
+                        anc, tech
+                    ),
+                    metric_value: anc as f64,
+                    expected_range: "Au moins 1 suivi attendu".to_string(),
+                }),
+                _ => {}
+            }
         }
     }
 
@@ -995,7 +1020,7 @@ pub struct CooccurrenceRequest {
     pub include_resolved: Option<bool>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CooccurrenceNode {
     pub id: String,
@@ -1003,7 +1028,7 @@ pub struct CooccurrenceNode {
     pub doc_frequency: usize,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CooccurrenceEdgeIpc {
     pub source: String,
@@ -1011,14 +1036,14 @@ pub struct CooccurrenceEdgeIpc {
     pub weight: usize,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TicketRef {
     pub id: u64,
     pub titre: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CooccurrenceResult {
     pub nodes: Vec<CooccurrenceNode>,
@@ -1042,6 +1067,23 @@ pub async fn get_cooccurrence_network(
         " AND est_vivant = 1"
     };
 
+    let cache_params = serde_json::json!({
+        "corpus": request.corpus.as_deref().unwrap_or("titres"),
+        "topNNodes": top_n_nodes,
+        "maxEdges": max_edges,
+        "includeResolved": include_resolved,
+    });
+    let (cache_key, cached) = lookup_cached::<CooccurrenceResult>(
+        &state,
+        "cooccurrence_network",
+        &cache_params,
+        TEXT_CACHE_DEPS,
+    )?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+    let start = Instant::now();
+
     let (texts, ticket_ids, ticket_titres, technician_names, user_stopwords) = {
         let guard = state.db.lock().map_err(|e| format!("Lock error: {e}"))?;
         let conn = guard.as_ref().ok_or("Base de données non initialisée")?;
@@ -1190,6 +1232,8 @@ pub async fn get_cooccurrence_network(
         .await
         .map_err(|e| format!("spawn_blocking error: {e}"))??;
 
+    store_cached(&state, &cache_key, &result, start);
+
     Ok(result)
 }
 
@@ -1226,21 +1270,39 @@ pub struct MindMapNode {
 
 // ── User Stopwords ──────────────────────────────────────────────────────────
 
+fn list_user_stopwords(conn: &rusqlite::Connection) -> Result<Vec<String>, String> {
+    crate::db::queries::get_user_stopwords(conn).map_err(|e| format!("SQL stopwords: {e}"))
+}
+
+/// Journalise une modification des stopwords (liste avant / après).
+fn audit_stopwords(
+    conn: &rusqlite::Connection,
+    action: AuditAction,
+    words: &[String],
+    before: Vec<String>,
+) -> Result<(), String> {
+    let after = list_user_stopwords(conn)?;
+    if after == before {
+        return Ok(());
+    }
+    audit::record(
+        conn,
+        action,
+        Some(&words.join(", ")),
+        Some(&serde_json::json!(before)),
+        Some(&serde_json::json!(after)),
+    )
+    .map_err(|e| format!("SQL audit: {e}"))?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn get_user_stopwords(
     state: tauri::State<'_, AppState>,
 ) -> Result<Vec<String>, String> {
     let guard = state.db.lock().map_err(|e| format!("Lock error: {e}"))?;
     let conn = guard.as_ref().ok_or("Base de donnees non initialisee")?;
-    let mut stmt = conn
-        .prepare("SELECT word FROM user_stopwords ORDER BY word")
-        .map_err(|e| format!("SQL prepare: {e}"))?;
-    let words: Vec<String> = stmt
-        .query_map([], |row| row.get(0))
-        .map_err(|e| format!("SQL query: {e}"))?
-        .collect::<Result<Vec<String>, _>>()
-        .map_err(|e| format!("SQL collect: {e}"))?;
-    Ok(words)
+    list_user_stopwords(conn)
 }
 
 #[tauri::command]
@@ -1248,18 +1310,23 @@ pub async fn add_user_stopwords(
     state: tauri::State<'_, AppState>,
     words: Vec<String>,
 ) -> Result<(), String> {
-    let guard = state.db.lock().map_err(|e| format!("Lock error: {e}"))?;
-    let conn = guard.as_ref().ok_or("Base de donnees non initialisee")?;
-    let mut stmt = conn
-        .prepare("INSERT OR IGNORE INTO user_stopwords (word) VALUES (?1)")
-        .map_err(|e| format!("SQL prepare: {e}"))?;
-    for word in &words {
-        let w = word.trim().to_lowercase();
-        if !w.is_empty() {
-            stmt.execute([&w]).map_err(|e| format!("SQL insert: {e}"))?;
+    let mut guard = state.db.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let conn = guard.as_mut().ok_or("Base de donnees non initialisee")?;
+    let tx = conn.transaction().map_err(|e| format!("SQL transaction: {e}"))?;
+    let before = list_user_stopwords(&tx)?;
+    {
+        let mut stmt = tx
+            .prepare("INSERT OR IGNORE INTO user_stopwords (word) VALUES (?1)")
+            .map_err(|e| format!("SQL prepare: {e}"))?;
+        for word in &words {
+            let w = word.trim().to_lowercase();
+            if !w.is_empty() {
+                stmt.execute([&w]).map_err(|e| format!("SQL insert: {e}"))?;
+            }
         }
     }
-    Ok(())
+    audit_stopwords(&tx, AuditAction::AddStopwords, &words, before)?;
+    tx.commit().map_err(|e| format!("SQL commit: {e}"))
 }
 
 #[tauri::command]
@@ -1267,15 +1334,20 @@ pub async fn remove_user_stopwords(
     state: tauri::State<'_, AppState>,
     words: Vec<String>,
 ) -> Result<(), String> {
-    let guard = state.db.lock().map_err(|e| format!("Lock error: {e}"))?;
-    let conn = guard.as_ref().ok_or("Base de donnees non initialisee")?;
-    let mut stmt = conn
-        .prepare("DELETE FROM user_stopwords WHERE LOWER(word) = LOWER(?1)")
-        .map_err(|e| format!("SQL prepare: {e}"))?;
-    for word in &words {
-        stmt.execute([word.trim()]).map_err(|e| format!("SQL delete: {e}"))?;
+    let mut guard = state.db.lock().map_err(|e| format!("Lock error: {e}"))?;
+    let conn = guard.as_mut().ok_or("Base de donnees non initialisee")?;
+    let tx = conn.transaction().map_err(|e| format!("SQL transaction: {e}"))?;
+    let before = list_user_stopwords(&tx)?;
+    {
+        let mut stmt = tx
+            .prepare("DELETE FROM user_stopwords WHERE LOWER(word) = LOWER(?1)")
+            .map_err(|e| format!("SQL prepare: {e}"))?;
+        for word in &words {
+            stmt.execute([word.trim()]).map_err(|e| format!("SQL delete: {e}"))?;
+        }
     }
-    Ok(())
+    audit_stopwords(&tx, AuditAction::RemoveStopwords, &words, before)?;
+    tx.commit().map_err(|e| format!("SQL commit: {e}"))
 }
 
 // ── Mind Map Command ────────────────────────────────────────────────────────
@@ -8,5 +8,18 @@ pub mod export;
 pub mod config;
 pub mod search;
 pub mod analytics;
+pub mod cache;
 pub mod delais;
+pub mod diff;
 pub mod recommandation;
+pub mod workspace;
+pub mod dictionaries;
+pub mod audit;
+pub mod rules;
+pub mod sla;
+pub mod requesters;
+pub mod escalation;
+pub mod capacity;
+pub mod cohort;
+pub mod vendor;
+pub mod heatmap;
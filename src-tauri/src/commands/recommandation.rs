@@ -1,9 +1,9 @@
 use tauri::State;
 use crate::state::AppState;
+use crate::db::cache::{CacheDep, CacheKey};
 use crate::db::queries::{
     get_active_import_id, get_profiling_tickets, get_unassigned_tickets_for_attribution,
-    get_technician_stock_counts, get_cached_profiling, save_cached_profiling,
-    get_seuil_tickets, get_unassigned_ticket_stats,
+    get_technician_stock_counts, get_seuil_tickets, get_unassigned_ticket_stats,
 };
 use crate::recommandation::profiling::{build_profiles, ProfilingTicket};
 use crate::recommandation::scoring::{score_tickets, UnassignedTicket};
@@ -14,6 +14,11 @@ use crate::recommandation::types::{
 
 const PERIODE_PROFIL_MOIS: i64 = 3;
 const MAX_UNASSIGNED_TICKETS: usize = 500;
+const PROFILING_CACHE_TYPE: &str = "technician_profiles";
+
+fn profiling_cache_key(conn: &rusqlite::Connection, import_id: i64) -> Result<CacheKey, rusqlite::Error> {
+    CacheKey::new(conn, import_id, PROFILING_CACHE_TYPE, &serde_json::json!({}), &[CacheDep::Tickets])
+}
 
 #[tauri::command]
 pub async fn build_technician_profiles(
@@ -22,13 +27,14 @@ pub async fn build_technician_profiles(
     let start = std::time::Instant::now();
 
     // Phase 1: Read data from DB (hold lock briefly)
-    let (import_id, raw_tickets) = {
+    let (cache_key, raw_tickets) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.as_ref().ok_or("Base de données non initialisée")?;
         let import_id = get_active_import_id(conn).map_err(|e| e.to_string())?;
+        let cache_key = profiling_cache_key(conn, import_id).map_err(|e| e.to_string())?;
         let rows = get_profiling_tickets(conn, import_id, PERIODE_PROFIL_MOIS)
             .map_err(|e| e.to_string())?;
-        (import_id, rows)
+        (cache_key, rows)
     };
     // Lock released here
 
@@ -61,15 +67,14 @@ pub async fn build_technician_profiles(
     let result = ProfilingResult::from(&cached_data);
 
     // Phase 3: Write cache (hold lock briefly)
-    let json = serde_json::to_string(&cached_data)
-        .map_err(|e| format!("Erreur sérialisation JSON: {e}"))?;
     let duration_ms = start.elapsed().as_millis() as i64;
 
     {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         let conn = db.as_ref().ok_or("Base de données non initialisée")?;
-        save_cached_profiling(conn, import_id, &json, duration_ms)
-            .map_err(|e| e.to_string())?;
+        cache_key
+            .put(conn, &cached_data, duration_ms)
+            .map_err(|e| format!("Erreur écriture cache: {e}"))?;
     }
 
     Ok(result)
@@ -87,13 +92,11 @@ pub async fn get_assignment_recommendations(
 
         let import_id = get_active_import_id(conn).map_err(|e| e.to_string())?;
 
-        let json = get_cached_profiling(conn, import_id)
+        let profiling_data: CachedProfilingData = profiling_cache_key(conn, import_id)
+            .and_then(|key| key.get(conn))
             .map_err(|e| e.to_string())?
             .ok_or("Profils non calculés. Cliquez sur 'Analyser' d'abord.")?;
 
-        let profiling_data: CachedProfilingData = serde_json::from_str(&json)
-            .map_err(|e| format!("Erreur désérialisation cache: {e}"))?;
-
         let raw_tickets = get_unassigned_tickets_for_attribution(conn, import_id, MAX_UNASSIGNED_TICKETS)
             .map_err(|e| e.to_string())?;
 
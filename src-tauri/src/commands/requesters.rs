@@ -0,0 +1,161 @@
+use std::path::Path;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::analyzer::requesters::{
+    compute_demandeur_kpi, DemandeurKpi, NiveauDemandeur, TicketDemandeur, FENETRE_DEFAUT_JOURS,
+    LIMITE_DEFAUT, SEUIL_SIMILARITE_DEFAUT,
+};
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::audit::{self, AuditAction};
+use crate::db::cache::CacheDep;
+use crate::db::queries;
+use crate::nlp::preprocessing::{preprocess_text, StopWordFilter};
+use crate::parser::deserializers::parse_french_datetime;
+use crate::parser::directory::parse_annuaire;
+use crate::state::{AppState, DbAccess};
+
+const REQUESTER_CACHE_DEPS: [CacheDep; 3] =
+    [CacheDep::Tickets, CacheDep::Annuaire, CacheDep::Stopwords];
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnuaireImportResult {
+    pub filename: String,
+    pub entrees: usize,
+    pub lignes: usize,
+    pub ignorees: usize,
+    pub doublons: usize,
+}
+
+/// Importe un annuaire demandeur → service ; remplace l'annuaire existant.
+#[tauri::command]
+pub async fn import_annuaire_demandeurs(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<AnnuaireImportResult, String> {
+    let filename = Path::new(&path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(&path)
+        .to_string();
+    let bytes = std::fs::read(&path).map_err(|e| format!("Lecture de {filename}: {e}"))?;
+    let parse = parse_annuaire(&bytes).map_err(|e| e.to_string())?;
+
+    let entrees = state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let before = queries::count_annuaire(&tx)?;
+        let entrees = queries::replace_annuaire(&tx, &parse.entrees)?;
+        audit::record(
+            &tx,
+            AuditAction::ImportAnnuaire,
+            Some(&filename),
+            Some(&serde_json::json!({ "entrees": before })),
+            Some(&serde_json::json!({ "entrees": entrees })),
+        )?;
+        tx.commit()?;
+        Ok(entrees)
+    })?;
+
+    Ok(AnnuaireImportResult {
+        filename,
+        entrees,
+        lignes: parse.lignes,
+        ignorees: parse.ignorees,
+        doublons: parse.doublons,
+    })
+}
+
+#[tauri::command]
+pub async fn clear_annuaire_demandeurs(state: tauri::State<'_, AppState>) -> Result<(), String> {
+    state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let before = queries::count_annuaire(&tx)?;
+        if before > 0 {
+            tx.execute("DELETE FROM annuaire_demandeurs", [])?;
+            audit::record(
+                &tx,
+                AuditAction::ClearAnnuaire,
+                None,
+                Some(&serde_json::json!({ "entrees": before })),
+                None,
+            )?;
+        }
+        tx.commit()
+    })
+}
+
+/// KPI par demandeur ou par service (`niveau` = "demandeur" | "service").
+#[tauri::command]
+pub async fn get_requester_kpi(
+    state: tauri::State<'_, AppState>,
+    niveau: Option<String>,
+    fenetre_jours: Option<u32>,
+    seuil_similarite: Option<f64>,
+    limite: Option<usize>,
+) -> Result<DemandeurKpi, String> {
+    let start = Instant::now();
+    let niveau = NiveauDemandeur::parse(niveau.as_deref())?;
+    let fenetre_jours = fenetre_jours.unwrap_or(FENETRE_DEFAUT_JOURS);
+    let seuil = seuil_similarite.unwrap_or(SEUIL_SIMILARITE_DEFAUT);
+    if !(0.0..=1.0).contains(&seuil) || seuil == 0.0 {
+        return Err("Le seuil de similarité doit être compris entre 0 (exclu) et 1".to_string());
+    }
+    let limite = limite.unwrap_or(LIMITE_DEFAUT).max(1);
+
+    let params = serde_json::json!({
+        "niveau": niveau.as_str(),
+        "fenetreJours": fenetre_jours,
+        "seuilSimilarite": seuil,
+        "limite": limite,
+    });
+    let (key, cached) =
+        lookup_cached::<DemandeurKpi>(&state, "requester_kpi", &params, &REQUESTER_CACHE_DEPS)?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let result = state.db(|conn| {
+        let bruts = queries::get_tickets_demandeurs(conn, key.import_id)?;
+        let annuaire = queries::count_annuaire(conn)?;
+        let mut filter = StopWordFilter::new();
+        filter
+            .single_words
+            .extend(queries::get_user_stopwords(conn)?);
+
+        let mut sans_demandeur = 0;
+        let mut tickets = Vec::with_capacity(bruts.len());
+        for t in bruts {
+            let Some(date_ouverture) = parse_french_datetime(&t.date_ouverture) else {
+                continue;
+            };
+            if t.demandeur.is_empty() {
+                sans_demandeur += 1;
+                continue;
+            }
+            tickets.push(TicketDemandeur {
+                id: t.id,
+                radicaux: preprocess_text(&t.titre, &filter),
+                titre: t.titre,
+                demandeur: t.demandeur,
+                service: t.service,
+                date_ouverture,
+                est_vivant: t.est_vivant,
+                delai_resolution_jours: t.delai_resolution_jours,
+            });
+        }
+        Ok(compute_demandeur_kpi(
+            &tickets,
+            sans_demandeur,
+            annuaire,
+            niveau,
+            fenetre_jours,
+            seuil,
+            limite,
+        ))
+    })?;
+
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::analyzer::rules::{compile_rule, ClassificationRule, RuleSet};
+use crate::config::get_config_from_db;
+use crate::db::audit::{self, AuditAction};
+use crate::db::queries;
+use crate::state::{AppState, DbAccess};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDryRunResult {
+    pub total_vivants: usize,
+    pub regles: Vec<RuleDryRunCount>,
+    pub non_classes: usize,
+    pub par_action: Vec<ActionCount>,
+    /// Règles actives ignorées car invalides.
+    pub erreurs: Vec<RuleError>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDryRunCount {
+    pub rule_id: Option<i64>,
+    pub name: String,
+    pub action: String,
+    /// Tickets classés par cette règle (première règle satisfaite).
+    pub count: usize,
+    /// Tickets satisfaisant la règle, indépendamment de l'ordre.
+    pub correspondances: usize,
+    pub exemples: Vec<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCount {
+    pub action: String,
+    pub count: usize,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleError {
+    pub name: String,
+    pub erreur: String,
+}
+
+const NB_EXEMPLES: usize = 10;
+
+#[tauri::command]
+pub async fn get_classification_rules(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ClassificationRule>, String> {
+    state.db(queries::get_classification_rules)
+}
+
+/// Crée (id absent) ou met à jour une règle après validation.
+/// Prise en compte au prochain import.
+#[tauri::command]
+pub async fn save_classification_rule(
+    state: tauri::State<'_, AppState>,
+    rule: ClassificationRule,
+) -> Result<ClassificationRule, String> {
+    let config = state.db(get_config_from_db)?;
+    let config = serde_json::to_value(&config).map_err(|e| e.to_string())?;
+    compile_rule(&rule, &config)?;
+
+    let conditions = serde_json::to_string(&rule.conditions).map_err(|e| e.to_string())?;
+    let saved = state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let before = match rule.id {
+            Some(id) => Some(find_rule(&tx, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?),
+            None => None,
+        };
+        let id = match rule.id {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE classification_rules
+                     SET position = ?1, name = ?2, is_active = ?3, conditions = ?4,
+                         action = ?5, motif = ?6
+                     WHERE id = ?7",
+                    rusqlite::params![
+                        rule.position,
+                        rule.name.trim(),
+                        rule.is_active as i64,
+                        conditions,
+                        rule.action,
+                        rule.motif,
+                        id
+                    ],
+                )?;
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO classification_rules
+                        (position, name, is_active, conditions, action, motif)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    rusqlite::params![
+                        rule.position,
+                        rule.name.trim(),
+                        rule.is_active as i64,
+                        conditions,
+                        rule.action,
+                        rule.motif
+                    ],
+                )?;
+                tx.last_insert_rowid()
+            }
+        };
+        let saved = find_rule(&tx, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let action = if before.is_some() {
+            AuditAction::UpdateRule
+        } else {
+            AuditAction::AddRule
+        };
+        let before = before.as_ref().map(audit_value);
+        let after = audit_value(&saved);
+        if before.as_ref() != Some(&after) {
+            audit::record(
+                &tx,
+                action,
+                Some(&id.to_string()),
+                before.as_ref(),
+                Some(&after),
+            )?;
+        }
+        tx.commit()?;
+        Ok(saved)
+    })?;
+
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn delete_classification_rule(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let deleted = state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let Some(before) = find_rule(&tx, id)? else {
+            return Ok(false);
+        };
+        tx.execute(
+            "DELETE FROM classification_rules WHERE id = ?1",
+            rusqlite::params![id],
+        )?;
+        audit::record(
+            &tx,
+            AuditAction::DeleteRule,
+            Some(&id.to_string()),
+            Some(&audit_value(&before)),
+            None,
+        )?;
+        tx.commit()?;
+        Ok(true)
+    })?;
+    if !deleted {
+        return Err(format!("Règle introuvable: {id}"));
+    }
+    Ok(())
+}
+
+fn find_rule(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<ClassificationRule>, rusqlite::Error> {
+    Ok(queries::get_classification_rules(conn)?
+        .into_iter()
+        .find(|r| r.id == Some(id)))
+}
+
+/// État journalisé d'une règle (conditions, action, position).
+fn audit_value(rule: &ClassificationRule) -> serde_json::Value {
+    serde_json::to_value(rule).unwrap_or_default()
+}
+
+/// Simule un jeu de règles (celles fournies, sinon celles enregistrées) sur
+/// les tickets vivants de l'import actif, sans rien modifier.
+#[tauri::command]
+pub async fn dry_run_classification_rules(
+    state: tauri::State<'_, AppState>,
+    rules: Option<Vec<ClassificationRule>>,
+) -> Result<RuleDryRunResult, String> {
+    let (rules, config, tickets) = state.db(|conn| {
+        let rules = match rules {
+            Some(r) => r,
+            None => queries::get_classification_rules(conn)?,
+        };
+        let import_id = queries::get_active_import_id(conn)?;
+        Ok((
+            rules,
+            get_config_from_db(conn)?,
+            queries::get_rule_tickets(conn, import_id)?,
+        ))
+    })?;
+
+    tokio::task::spawn_blocking(move || {
+        let config_value = serde_json::to_value(&config).unwrap_or_default();
+        let erreurs: Vec<RuleError> = rules
+            .iter()
+            .filter(|r| r.is_active)
+            .filter_map(|r| {
+                compile_rule(r, &config_value)
+                    .err()
+                    .map(|erreur| RuleError {
+                        name: r.name.clone(),
+                        erreur,
+                    })
+            })
+            .collect();
+
+        let set = RuleSet::compile(&rules, &config);
+        let mut regles: Vec<RuleDryRunCount> = set
+            .rules
+            .iter()
+            .map(|r| RuleDryRunCount {
+                rule_id: r.id,
+                name: r.name.clone(),
+                action: r.action.clone(),
+                count: 0,
+                correspondances: 0,
+                exemples: Vec::new(),
+            })
+            .collect();
+        let mut non_classes = 0;
+        let mut par_action: BTreeMap<String, usize> = BTreeMap::new();
+
+        for row in &tickets {
+            let t = row.as_rule_ticket();
+            let seuils = set.config_ticket(&t);
+            for (i, rule) in set.rules.iter().enumerate() {
+                if rule.matches(&t, &seuils) {
+                    regles[i].correspondances += 1;
+                }
+            }
+            match set.evaluate(&t) {
+                Some((i, _)) => {
+                    let r = &mut regles[i];
+                    r.count += 1;
+                    if r.exemples.len() < NB_EXEMPLES {
+                        r.exemples.push(row.id);
+                    }
+                    *par_action.entry(r.action.clone()).or_default() += 1;
+                }
+                None => non_classes += 1,
+            }
+        }
+
+        RuleDryRunResult {
+            total_vivants: tickets.len(),
+            regles,
+            non_classes,
+            par_action: par_action
+                .into_iter()
+                .map(|(action, count)| ActionCount { action, count })
+                .collect(),
+            erreurs,
+        }
+    })
+    .await
+    .map_err(|e| format!("spawn_blocking error: {e}"))
+}
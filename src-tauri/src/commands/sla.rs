@@ -0,0 +1,211 @@
+use std::time::Instant;
+
+use crate::analyzer::sla::{load_sla_kpi, SlaDefinition, SlaKpi, HORIZON_DEFAUT_JOURS};
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::audit::{self, AuditAction};
+use crate::db::cache::CacheDep;
+use crate::db::queries;
+use crate::state::{AppState, DbAccess};
+
+#[tauri::command]
+pub async fn get_sla_definitions(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SlaDefinition>, String> {
+    state.db(queries::get_sla_definitions)
+}
+
+/// Crée (id absent) ou met à jour une définition SLA.
+#[tauri::command]
+pub async fn save_sla_definition(
+    state: tauri::State<'_, AppState>,
+    definition: SlaDefinition,
+) -> Result<SlaDefinition, String> {
+    let name = definition.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Nom de SLA vide".to_string());
+    }
+    if definition.tto_heures.is_none() && definition.ttr_heures.is_none() {
+        return Err("Au moins un délai (prise en charge ou résolution) est requis".to_string());
+    }
+    if [definition.tto_heures, definition.ttr_heures]
+        .iter()
+        .flatten()
+        .any(|h| !h.is_finite() || *h <= 0.0)
+    {
+        return Err("Les délais doivent être strictement positifs".to_string());
+    }
+    // Critère vide = tous
+    let critere = |c: &Option<String>| {
+        c.as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let definition = SlaDefinition {
+        name,
+        priorite: critere(&definition.priorite),
+        type_ticket: critere(&definition.type_ticket),
+        categorie: critere(&definition.categorie),
+        ..definition
+    };
+
+    let saved = state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let d = &definition;
+        let before = match d.id {
+            Some(id) => Some(find_sla(&tx, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?),
+            None => None,
+        };
+        let id = match d.id {
+            Some(id) => {
+                tx.execute(
+                    "UPDATE sla_definitions
+                     SET name = ?1, priorite = ?2, type_ticket = ?3, categorie = ?4,
+                         tto_heures = ?5, ttr_heures = ?6, is_active = ?7
+                     WHERE id = ?8",
+                    rusqlite::params![
+                        d.name,
+                        d.priorite,
+                        d.type_ticket,
+                        d.categorie,
+                        d.tto_heures,
+                        d.ttr_heures,
+                        d.is_active as i64,
+                        id
+                    ],
+                )?;
+                id
+            }
+            None => {
+                tx.execute(
+                    "INSERT INTO sla_definitions
+                        (name, priorite, type_ticket, categorie, tto_heures, ttr_heures, is_active)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    rusqlite::params![
+                        d.name,
+                        d.priorite,
+                        d.type_ticket,
+                        d.categorie,
+                        d.tto_heures,
+                        d.ttr_heures,
+                        d.is_active as i64
+                    ],
+                )?;
+                tx.last_insert_rowid()
+            }
+        };
+        let saved = find_sla(&tx, id)?.ok_or(rusqlite::Error::QueryReturnedNoRows)?;
+        let action = if before.is_some() {
+            AuditAction::UpdateSla
+        } else {
+            AuditAction::AddSla
+        };
+        let before = before.as_ref().map(audit_value);
+        let after = audit_value(&saved);
+        if before.as_ref() != Some(&after) {
+            audit::record(
+                &tx,
+                action,
+                Some(&id.to_string()),
+                before.as_ref(),
+                Some(&after),
+            )?;
+        }
+        tx.commit()?;
+        Ok(saved)
+    })?;
+
+    Ok(saved)
+}
+
+#[tauri::command]
+pub async fn delete_sla_definition(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let deleted = state.db_mut(|conn| {
+        let tx = conn.transaction()?;
+        let Some(before) = find_sla(&tx, id)? else {
+            return Ok(false);
+        };
+        tx.execute(
+            "DELETE FROM sla_definitions WHERE id = ?1",
+            rusqlite::params![id],
+        )?;
+        audit::record(
+            &tx,
+            AuditAction::DeleteSla,
+            Some(&id.to_string()),
+            Some(&audit_value(&before)),
+            None,
+        )?;
+        tx.commit()?;
+        Ok(true)
+    })?;
+    if !deleted {
+        return Err(format!("SLA introuvable: {id}"));
+    }
+    Ok(())
+}
+
+fn find_sla(
+    conn: &rusqlite::Connection,
+    id: i64,
+) -> Result<Option<SlaDefinition>, rusqlite::Error> {
+    Ok(queries::get_sla_definitions(conn)?
+        .into_iter()
+        .find(|d| d.id == Some(id)))
+}
+
+/// État journalisé d'une définition SLA (critères, délais, activation).
+fn audit_value(definition: &SlaDefinition) -> serde_json::Value {
+    serde_json::to_value(definition).unwrap_or_default()
+}
+
+/// Rapport SLA complet (listes non tronquées) de l'import actif.
+/// `date_debut` / `date_fin` filtrent les tickets terminés sur leur date de
+/// résolution ; `horizon_jours` définit la liste "va dépasser dans N jours".
+#[tauri::command]
+pub async fn get_sla_report(
+    state: tauri::State<'_, AppState>,
+    horizon_jours: Option<f64>,
+    date_debut: Option<String>,
+    date_fin: Option<String>,
+    granularity: Option<String>,
+) -> Result<SlaKpi, String> {
+    let start = Instant::now();
+    let horizon = horizon_jours
+        .filter(|h| h.is_finite() && *h >= 0.0)
+        .unwrap_or(HORIZON_DEFAUT_JOURS);
+    let gran = granularity.unwrap_or_else(|| "month".to_string());
+    let params = serde_json::json!({
+        "horizonJours": horizon,
+        "dateDebut": date_debut,
+        "dateFin": date_fin,
+        "granularity": gran,
+    });
+    let (key, cached) = lookup_cached::<SlaKpi>(
+        &state,
+        "sla_report",
+        &params,
+        &[CacheDep::Tickets, CacheDep::Sla, CacheDep::Config],
+    )?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let result = state.db(|conn| {
+        load_sla_kpi(
+            conn,
+            key.import_id,
+            date_debut.as_deref(),
+            date_fin.as_deref(),
+            &gran,
+            horizon,
+            None,
+        )
+    })?;
+
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
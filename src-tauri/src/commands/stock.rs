@@ -1,7 +1,13 @@
+use std::time::Instant;
+
 use serde::{Deserialize, Serialize};
 
+use crate::analyzer::backlog::{compute_backlog_series, BacklogSerie};
 use crate::analyzer::stock::enrich_technician_stock;
+use crate::analyzer::thresholds::SeuilsEffectifs;
+use crate::commands::cache::{lookup_cached, store_cached};
 use crate::config::get_config_from_db;
+use crate::db::cache::CacheDep;
 use crate::db::queries;
 use crate::state::{AppState, DbAccess};
 
@@ -13,6 +19,8 @@ pub struct StockOverview {
     pub par_statut: Vec<StatutCount>,
     pub age_moyen_jours: f64,
     pub age_median_jours: f64,
+    pub age_moyen_jours_ouvres: f64,
+    pub age_median_jours_ouvres: f64,
     pub par_type: TypeBreakdown,
     pub par_anciennete: Vec<AgeRangeCount>,
     pub inactifs_14j: usize,
@@ -58,6 +66,8 @@ pub struct StockFilters {
 #[serde(rename_all = "camelCase")]
 pub struct TechnicianStock {
     pub technicien: String,
+    /// Groupe majoritaire du stock vivant, pour la résolution des seuils.
+    pub groupe: Option<String>,
     pub total: usize,
     pub en_cours: usize,
     pub en_attente: usize,
@@ -66,9 +76,15 @@ pub struct TechnicianStock {
     pub incidents: usize,
     pub demandes: usize,
     pub age_moyen_jours: f64,
+    pub age_moyen_jours_ouvres: f64,
     pub inactifs_14j: usize,
+    /// ETP contractuel (1.0 = temps plein).
+    pub etp: f64,
+    pub stock_par_etp: f64,
     pub ecart_seuil: i64,
     pub couleur_seuil: String,
+    /// Seuils effectifs du technicien (surcharges groupe / technicien).
+    pub seuils: SeuilsEffectifs,
 }
 
 #[derive(Serialize)]
@@ -84,6 +100,7 @@ pub struct GroupStock {
     pub demandes: usize,
     pub nb_techniciens: usize,
     pub age_moyen_jours: f64,
+    pub age_moyen_jours_ouvres: f64,
 }
 
 #[derive(Serialize)]
@@ -136,17 +153,23 @@ pub async fn get_stock_overview(
     state.db(|conn| queries::get_stock_overview(conn))
 }
 
+/// Charge par technicien, ramenée à l'ETP de chacun.
+pub(crate) fn load_technicians_stock(
+    conn: &rusqlite::Connection,
+    filters: Option<&StockFilters>,
+) -> Result<Vec<TechnicianStock>, rusqlite::Error> {
+    let config = get_config_from_db(conn)?;
+    let mut techs = queries::get_technicians_stock(conn, filters)?;
+    enrich_technician_stock(&mut techs, &config);
+    Ok(techs)
+}
+
 #[tauri::command]
 pub async fn get_stock_by_technician(
     state: tauri::State<'_, AppState>,
     filters: Option<StockFilters>,
 ) -> Result<Vec<TechnicianStock>, String> {
-    state.db(|conn| {
-        let config = get_config_from_db(conn)?;
-        let mut techs = queries::get_technicians_stock(conn, filters.as_ref())?;
-        enrich_technician_stock(&mut techs, &config);
-        Ok(techs)
-    })
+    state.db(|conn| load_technicians_stock(conn, filters.as_ref()))
 }
 
 #[tauri::command]
@@ -164,6 +187,35 @@ pub async fn get_unassigned_tickets(
     state.db(|conn| queries::get_unassigned_tickets(conn))
 }
 
+/// Série du stock reconstitué sur toute la plage de l'import
+/// (`granularity` = "day" par défaut, ou "week").
+#[tauri::command]
+pub async fn get_backlog_series(
+    state: tauri::State<'_, AppState>,
+    granularity: Option<String>,
+) -> Result<BacklogSerie, String> {
+    let start = Instant::now();
+    let gran = match granularity.as_deref() {
+        Some("week") => "week",
+        _ => "day",
+    };
+    let params = serde_json::json!({ "granularity": gran });
+    let (key, cached) =
+        lookup_cached::<BacklogSerie>(&state, "backlog_series", &params, &[CacheDep::Tickets])?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let result = state.db(|conn| {
+        let tickets = queries::get_backlog_flux(conn, key.import_id)?;
+        let reference = queries::get_import_reference_date(conn, key.import_id)?;
+        Ok(compute_backlog_series(&tickets, reference.map(|d| d.date()), gran))
+    })?;
+
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn get_ticket_detail(
     state: tauri::State<'_, AppState>,
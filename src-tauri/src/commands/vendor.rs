@@ -0,0 +1,26 @@
+use std::time::Instant;
+
+use crate::analyzer::vendor::{load_fournisseur_kpi, FournisseurKpi};
+use crate::commands::cache::{lookup_cached, store_cached};
+use crate::db::cache::CacheDep;
+use crate::state::{AppState, DbAccess};
+
+/// Dépendance fournisseurs : MTTR avec / sans intervention, bloqués, classement.
+#[tauri::command]
+pub async fn get_vendor_kpi(state: tauri::State<'_, AppState>) -> Result<FournisseurKpi, String> {
+    let start = Instant::now();
+    let params = serde_json::json!({});
+    let (key, cached) = lookup_cached::<FournisseurKpi>(
+        &state,
+        "vendor_kpi",
+        &params,
+        &[CacheDep::Tickets, CacheDep::Config],
+    )?;
+    if let Some(hit) = cached {
+        return Ok(hit);
+    }
+
+    let result = state.db(|conn| load_fournisseur_kpi(conn, key.import_id))?;
+    store_cached(&state, &key, &result, start);
+    Ok(result)
+}
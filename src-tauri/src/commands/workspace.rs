@@ -0,0 +1,141 @@
+use serde::Serialize;
+
+use crate::state::AppState;
+use crate::workspace::{self, Workspace, WorkspaceContext, WorkspaceRegistry};
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceList {
+    pub active_id: String,
+    pub workspaces: Vec<Workspace>,
+}
+
+fn context(state: &AppState) -> Result<WorkspaceContext, String> {
+    state
+        .workspace
+        .lock()
+        .map_err(|e| format!("Mutex poisoned: {}", e))?
+        .clone()
+        .ok_or_else(|| "Workspace non initialisé".to_string())
+}
+
+fn load_registry(ctx: &WorkspaceContext) -> Result<WorkspaceRegistry, String> {
+    WorkspaceRegistry::load(&ctx.data_dir).map_err(|e| e.to_string())
+}
+
+fn open_workspace_db(
+    ctx: &WorkspaceContext,
+    ws: &Workspace,
+) -> Result<rusqlite::Connection, String> {
+    let path = workspace::db_path(&ctx.data_dir, ws);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Impossible de créer {:?}: {e}", parent))?;
+    }
+    let path_str = path
+        .to_str()
+        .ok_or_else(|| format!("Chemin DB invalide (non-UTF8): {:?}", path))?;
+    crate::db::setup::init_db(path_str).map_err(|e| format!("Erreur init DB ({}): {e}", path_str))
+}
+
+#[tauri::command]
+pub async fn list_workspaces(state: tauri::State<'_, AppState>) -> Result<WorkspaceList, String> {
+    let ctx = context(&state)?;
+    let registry = load_registry(&ctx)?;
+    Ok(WorkspaceList {
+        active_id: ctx.active_id,
+        workspaces: registry.workspaces,
+    })
+}
+
+/// Crée un workspace et initialise sa base (migrations + config par défaut).
+#[tauri::command]
+pub async fn create_workspace(
+    state: tauri::State<'_, AppState>,
+    name: String,
+) -> Result<Workspace, String> {
+    let guard = state
+        .workspace
+        .lock()
+        .map_err(|e| format!("Mutex poisoned: {}", e))?;
+    let ctx = guard.as_ref().ok_or("Workspace non initialisé")?;
+
+    let mut registry = load_registry(ctx)?;
+    let ws = registry.create(&name).map_err(|e| e.to_string())?;
+    drop(open_workspace_db(ctx, &ws)?);
+    registry.save(&ctx.data_dir).map_err(|e| e.to_string())?;
+    Ok(ws)
+}
+
+#[tauri::command]
+pub async fn rename_workspace(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    name: String,
+) -> Result<Workspace, String> {
+    let guard = state
+        .workspace
+        .lock()
+        .map_err(|e| format!("Mutex poisoned: {}", e))?;
+    let ctx = guard.as_ref().ok_or("Workspace non initialisé")?;
+
+    let mut registry = load_registry(ctx)?;
+    let ws = registry.rename(&id, &name).map_err(|e| e.to_string())?;
+    registry.save(&ctx.data_dir).map_err(|e| e.to_string())?;
+    Ok(ws)
+}
+
+/// Bascule sur un autre workspace. La nouvelle base est ouverte et le
+/// registre enregistré avant de remplacer la connexion : en cas d'échec, le
+/// workspace courant reste actif.
+#[tauri::command]
+pub async fn switch_workspace(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<Workspace, String> {
+    let mut guard = state
+        .workspace
+        .lock()
+        .map_err(|e| format!("Mutex poisoned: {}", e))?;
+    let ctx = guard.as_mut().ok_or("Workspace non initialisé")?;
+
+    let mut registry = load_registry(ctx)?;
+    let ws = registry
+        .get(&id)
+        .cloned()
+        .ok_or_else(|| format!("Workspace introuvable: {id}"))?;
+
+    let conn = if ws.id != ctx.active_id {
+        Some(open_workspace_db(ctx, &ws)?)
+    } else {
+        None
+    };
+
+    registry.last_used = ws.id.clone();
+    registry.save(&ctx.data_dir).map_err(|e| e.to_string())?;
+
+    if let Some(conn) = conn {
+        state.swap_connection(conn)?;
+        ctx.active_id = ws.id.clone();
+    }
+    Ok(ws)
+}
+
+/// Supprime un workspace inactif et sa base.
+#[tauri::command]
+pub async fn delete_workspace(state: tauri::State<'_, AppState>, id: String) -> Result<(), String> {
+    let guard = state
+        .workspace
+        .lock()
+        .map_err(|e| format!("Mutex poisoned: {}", e))?;
+    let ctx = guard.as_ref().ok_or("Workspace non initialisé")?;
+
+    let mut registry = load_registry(ctx)?;
+    let removed = registry
+        .remove(&id, &ctx.active_id)
+        .map_err(|e| e.to_string())?;
+    registry.save(&ctx.data_dir).map_err(|e| e.to_string())?;
+
+    workspace::remove_db_files(&workspace::db_path(&ctx.data_dir, &removed))
+        .map_err(|e| e.to_string())
+}
@@ -1,6 +1,11 @@
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 
+use crate::analyzer::buckets::TranchesDistribution;
+use crate::analyzer::calendar::{CalendrierCompile, CalendrierOuvre};
+use crate::analyzer::thresholds::{resoudre_seuils, SeuilsEffectifs, SurchargeSeuils};
+use crate::analyzer::workload::CapaciteTechnicien;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppConfig {
@@ -15,6 +20,77 @@ pub struct AppConfig {
     pub seuil_similarite_doublons: f64,
     pub statuts_vivants: Vec<String>,
     pub statuts_termines: Vec<String>,
+    /// Calendrier des délais en temps ouvré.
+    #[serde(default)]
+    pub calendrier: CalendrierOuvre,
+    /// Présence planifiée par groupe, pour la couverture des arrivées.
+    #[serde(default)]
+    pub plannings_groupes: Vec<PlanningGroupe>,
+    /// Quotité et absences par technicien, pour la charge par ETP.
+    #[serde(default)]
+    pub capacites_techniciens: Vec<CapaciteTechnicien>,
+    /// Seuils propres à un groupe (et ses sous-groupes) ou à un technicien.
+    #[serde(default)]
+    pub surcharges_seuils: Vec<SurchargeSeuils>,
+    /// Jeux de tranches des distributions (ancienneté, prise en charge, résolution).
+    #[serde(default)]
+    pub tranches: TranchesDistribution,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+/// Présence planifiée d'un groupe, par plages hebdomadaires.
+pub struct PlanningGroupe {
+    pub groupe: String,
+    pub plages: Vec<PlageEffectif>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlageEffectif {
+    /// 1 = lundi … 7 = dimanche
+    pub jour: u32,
+    /// "HH:MM"
+    pub debut: String,
+    pub fin: String,
+    /// Agents présents sur la plage.
+    pub effectif: f64,
+}
+
+impl Default for AppConfig {
+    /// Valeurs par défaut, remplacées par les clés présentes en base.
+    fn default() -> Self {
+        Self {
+            seuil_tickets_technicien: 20,
+            seuil_anciennete_cloturer: 90,
+            seuil_inactivite_cloturer: 60,
+            seuil_anciennete_relancer: 30,
+            seuil_inactivite_relancer: 14,
+            seuil_couleur_vert: 10,
+            seuil_couleur_jaune: 20,
+            seuil_couleur_orange: 40,
+            seuil_similarite_doublons: 0.92,
+            statuts_vivants: vec![
+                "Nouveau".into(),
+                "En cours (Attribué)".into(),
+                "En cours (Planifié)".into(),
+                "En attente".into(),
+            ],
+            statuts_termines: vec!["Clos".into(), "Résolu".into()],
+            calendrier: CalendrierOuvre::default(),
+            plannings_groupes: Vec::new(),
+            capacites_techniciens: Vec::new(),
+            surcharges_seuils: Vec::new(),
+            tranches: TranchesDistribution::default(),
+        }
+    }
+}
+
+impl AppConfig {
+    /// Seuils effectifs pour un groupe et/ou un technicien (global si aucun).
+    pub fn seuils_pour(&self, groupe: Option<&str>, technicien: Option<&str>) -> SeuilsEffectifs {
+        resoudre_seuils(self, groupe, technicien)
+    }
 }
 
 pub fn get_config_from_db(conn: &Connection) -> Result<AppConfig, rusqlite::Error> {
@@ -23,24 +99,7 @@ pub fn get_config_from_db(conn: &Connection) -> Result<AppConfig, rusqlite::Erro
         Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
     })?;
 
-    let mut config = AppConfig {
-        seuil_tickets_technicien: 20,
-        seuil_anciennete_cloturer: 90,
-        seuil_inactivite_cloturer: 60,
-        seuil_anciennete_relancer: 30,
-        seuil_inactivite_relancer: 14,
-        seuil_couleur_vert: 10,
-        seuil_couleur_jaune: 20,
-        seuil_couleur_orange: 40,
-        seuil_similarite_doublons: 0.92,
-        statuts_vivants: vec![
-            "Nouveau".into(),
-            "En cours (Attribué)".into(),
-            "En cours (Planifié)".into(),
-            "En attente".into(),
-        ],
-        statuts_termines: vec!["Clos".into(), "Résolu".into()],
-    };
+    let mut config = AppConfig::default();
 
     for row in rows {
         let (key, value) = row?;
@@ -74,6 +133,31 @@ pub fn get_config_from_db(conn: &Connection) -> Result<AppConfig, rusqlite::Erro
                     config.statuts_termines = v;
                 }
             }
+            "calendrier_ouvre" => {
+                if let Ok(v) = serde_json::from_str(&value) {
+                    config.calendrier = v;
+                }
+            }
+            "plannings_groupes" => {
+                if let Ok(v) = serde_json::from_str(&value) {
+                    config.plannings_groupes = v;
+                }
+            }
+            "capacites_techniciens" => {
+                if let Ok(v) = serde_json::from_str(&value) {
+                    config.capacites_techniciens = v;
+                }
+            }
+            "surcharges_seuils" => {
+                if let Ok(v) = serde_json::from_str(&value) {
+                    config.surcharges_seuils = v;
+                }
+            }
+            "tranches_distribution" => {
+                if let Ok(v) = serde_json::from_str(&value) {
+                    config.tranches = v;
+                }
+            }
             _ => {}
         }
     }
@@ -81,6 +165,18 @@ pub fn get_config_from_db(conn: &Connection) -> Result<AppConfig, rusqlite::Erro
     Ok(config)
 }
 
+/// Calendrier ouvré configuré, prêt au calcul. Un calendrier enregistré
+/// invalide est remplacé par le calendrier par défaut.
+pub fn get_calendrier_from_db(conn: &Connection) -> Result<CalendrierCompile, rusqlite::Error> {
+    let config = get_config_from_db(conn)?;
+    Ok(config.calendrier.compile().unwrap_or_else(|e| {
+        log::warn!("Calendrier ouvré invalide, calendrier par défaut utilisé: {e}");
+        CalendrierOuvre::default()
+            .compile()
+            .expect("calendrier par défaut valide")
+    }))
+}
+
 pub fn update_config_in_db(conn: &Connection, config: &AppConfig) -> Result<(), rusqlite::Error> {
     let pairs: Vec<(&str, String)> = vec![
         (
@@ -127,6 +223,26 @@ pub fn update_config_in_db(conn: &Connection, config: &AppConfig) -> Result<(),
             "statuts_termines",
             serde_json::to_string(&config.statuts_termines).unwrap_or_default(),
         ),
+        (
+            "calendrier_ouvre",
+            serde_json::to_string(&config.calendrier).unwrap_or_default(),
+        ),
+        (
+            "plannings_groupes",
+            serde_json::to_string(&config.plannings_groupes).unwrap_or_default(),
+        ),
+        (
+            "capacites_techniciens",
+            serde_json::to_string(&config.capacites_techniciens).unwrap_or_default(),
+        ),
+        (
+            "surcharges_seuils",
+            serde_json::to_string(&config.surcharges_seuils).unwrap_or_default(),
+        ),
+        (
+            "tranches_distribution",
+            serde_json::to_string(&config.tranches).unwrap_or_default(),
+        ),
     ];
 
     let mut stmt = conn.prepare_cached(
@@ -0,0 +1,317 @@
+//! Journal d'audit (table `audit_log`, ajout seul).
+//!
+//! Chaque commande modifiant les données écrit une entrée avec l'état avant
+//! et après (JSON), dans la même connexion que la modification, et
+//! l'utilisateur du système qui l'a faite.
+
+use std::sync::LazyLock;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Utilisateur du système d'exploitation (session Windows ou Unix).
+static ACTEUR: LazyLock<Option<String>> = LazyLock::new(|| {
+    ["USERNAME", "USER", "LOGNAME"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|v| v.trim().to_string())
+        .find(|v| !v.is_empty())
+});
+
+/// Actions journalisées.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    ImportCsv,
+    DeleteImport,
+    SetActiveImport,
+    UpdateConfig,
+    AddStopwords,
+    RemoveStopwords,
+    AddKeyword,
+    UpdateKeyword,
+    DeleteKeyword,
+    AddRule,
+    UpdateRule,
+    DeleteRule,
+    AddSla,
+    UpdateSla,
+    DeleteSla,
+    ImportAnnuaire,
+    ClearAnnuaire,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditAction::ImportCsv => "import_csv",
+            AuditAction::DeleteImport => "delete_import",
+            AuditAction::SetActiveImport => "set_active_import",
+            AuditAction::UpdateConfig => "update_config",
+            AuditAction::AddStopwords => "add_stopwords",
+            AuditAction::RemoveStopwords => "remove_stopwords",
+            AuditAction::AddKeyword => "add_keyword",
+            AuditAction::UpdateKeyword => "update_keyword",
+            AuditAction::DeleteKeyword => "delete_keyword",
+            AuditAction::AddRule => "add_rule",
+            AuditAction::UpdateRule => "update_rule",
+            AuditAction::DeleteRule => "delete_rule",
+            AuditAction::AddSla => "add_sla",
+            AuditAction::UpdateSla => "update_sla",
+            AuditAction::DeleteSla => "delete_sla",
+            AuditAction::ImportAnnuaire => "import_annuaire",
+            AuditAction::ClearAnnuaire => "clear_annuaire",
+        }
+    }
+
+    pub fn label(action: &str) -> &str {
+        match action {
+            "import_csv" => "Import CSV",
+            "delete_import" => "Suppression d'import",
+            "set_active_import" => "Activation d'import",
+            "update_config" => "Modification de la configuration",
+            "add_stopwords" => "Ajout de stopwords",
+            "remove_stopwords" => "Suppression de stopwords",
+            "add_keyword" => "Ajout d'un mot-clé de dictionnaire",
+            "update_keyword" => "Modification d'un mot-clé de dictionnaire",
+            "delete_keyword" => "Suppression d'un mot-clé de dictionnaire",
+            "add_rule" => "Ajout d'une règle de classification",
+            "update_rule" => "Modification d'une règle de classification",
+            "delete_rule" => "Suppression d'une règle de classification",
+            "add_sla" => "Ajout d'une définition SLA",
+            "update_sla" => "Modification d'une définition SLA",
+            "delete_sla" => "Suppression d'une définition SLA",
+            "import_annuaire" => "Import de l'annuaire des demandeurs",
+            "clear_annuaire" => "Suppression de l'annuaire des demandeurs",
+            other => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntry {
+    pub id: i64,
+    pub created_at: String,
+    pub action: String,
+    /// Utilisateur du système à l'origine de l'action.
+    pub acteur: Option<String>,
+    pub target: Option<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFilter {
+    pub action: Option<String>,
+    /// Bornes incluses, format YYYY-MM-DD.
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Ajoute une entrée au journal.
+pub fn record(
+    conn: &Connection,
+    action: AuditAction,
+    target: Option<&str>,
+    before: Option<&Value>,
+    after: Option<&Value>,
+) -> Result<i64, rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO audit_log (action, acteur, target, before_value, after_value)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![
+            action.as_str(),
+            ACTEUR.as_deref(),
+            target,
+            before.map(Value::to_string),
+            after.map(Value::to_string),
+        ],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Entrées du journal, des plus récentes aux plus anciennes.
+pub fn get_audit_log(
+    conn: &Connection,
+    filter: &AuditFilter,
+) -> Result<Vec<AuditEntry>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, created_at, action, target, before_value, after_value, acteur
+         FROM audit_log
+         WHERE (?1 IS NULL OR action = ?1)
+           AND (?2 IS NULL OR date(created_at) >= ?2)
+           AND (?3 IS NULL OR date(created_at) <= ?3)
+         ORDER BY id DESC
+         LIMIT ?4",
+    )?;
+    let limit = filter.limit.map(|l| l as i64).unwrap_or(-1);
+    let rows = stmt.query_map(
+        rusqlite::params![filter.action, filter.date_from, filter.date_to, limit],
+        |row| {
+            let before: Option<String> = row.get(4)?;
+            let after: Option<String> = row.get(5)?;
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                created_at: row.get(1)?,
+                action: row.get(2)?,
+                acteur: row.get(6)?,
+                target: row.get(3)?,
+                before: before.and_then(|s| serde_json::from_str(&s).ok()),
+                after: after.and_then(|s| serde_json::from_str(&s).ok()),
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+/// Résumé d'un import (avant suppression / activation).
+pub fn import_snapshot(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Option<Value>, rusqlite::Error> {
+    use rusqlite::OptionalExtension;
+    conn.query_row(
+        "SELECT filename, import_date, parsed_rows, vivants_count, termines_count,
+                date_range_from, date_range_to, is_active
+         FROM imports WHERE id = ?1",
+        rusqlite::params![import_id],
+        |row| {
+            Ok(serde_json::json!({
+                "importId": import_id,
+                "filename": row.get::<_, String>(0)?,
+                "importDate": row.get::<_, String>(1)?,
+                "totalTickets": row.get::<_, i64>(2)?,
+                "vivants": row.get::<_, i64>(3)?,
+                "termines": row.get::<_, i64>(4)?,
+                "dateRangeFrom": row.get::<_, Option<String>>(5)?,
+                "dateRangeTo": row.get::<_, Option<String>>(6)?,
+                "isActive": row.get::<_, i64>(7)? != 0,
+            }))
+        },
+    )
+    .optional()
+}
+
+/// Import actif, s'il existe (un seul grâce au trigger sur `is_active`).
+pub fn active_import_id(conn: &Connection) -> Result<Option<i64>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT MAX(id) FROM imports WHERE is_active = 1",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Clés de premier niveau dont la valeur diffère entre deux objets JSON.
+pub fn changed_keys(before: &Value, after: &Value) -> Vec<String> {
+    let (Some(b), Some(a)) = (before.as_object(), after.as_object()) else {
+        return Vec::new();
+    };
+    let mut keys: Vec<String> = a
+        .iter()
+        .filter(|(k, v)| b.get(*k) != Some(*v))
+        .map(|(k, _)| k.clone())
+        .chain(b.keys().filter(|k| !a.contains_key(*k)).cloned())
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Sous-objet restreint aux clés données (pour ne journaliser que les changements).
+pub fn pick(value: &Value, keys: &[String]) -> Value {
+    let picked = keys
+        .iter()
+        .filter_map(|k| value.get(k).map(|v| (k.clone(), v.clone())))
+        .collect();
+    Value::Object(picked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("sql/001_initial.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("sql/006_audit_log.sql"))
+            .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_record_and_query_roundtrip() {
+        let conn = setup();
+        record(
+            &conn,
+            AuditAction::UpdateConfig,
+            Some("seuilCouleurVert"),
+            Some(&json!({"seuilCouleurVert": 10})),
+            Some(&json!({"seuilCouleurVert": 12})),
+        )
+        .unwrap();
+        record(&conn, AuditAction::DeleteImport, Some("3"), None, None).unwrap();
+
+        let all = get_audit_log(&conn, &AuditFilter::default()).unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].action, "delete_import"); // plus récent d'abord
+        assert_eq!(all[0].acteur, *ACTEUR);
+        assert_eq!(all[1].before, Some(json!({"seuilCouleurVert": 10})));
+        assert_eq!(all[1].after, Some(json!({"seuilCouleurVert": 12})));
+
+        let filter = AuditFilter {
+            action: Some("update_config".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(get_audit_log(&conn, &filter).unwrap().len(), 1);
+
+        let filter = AuditFilter {
+            limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(get_audit_log(&conn, &filter).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_audit_log_is_append_only() {
+        let conn = setup();
+        let id = record(&conn, AuditAction::AddStopwords, None, None, None).unwrap();
+        assert!(conn
+            .execute("UPDATE audit_log SET action = 'x' WHERE id = ?1", [id])
+            .is_err());
+        assert!(conn
+            .execute("DELETE FROM audit_log WHERE id = ?1", [id])
+            .is_err());
+    }
+
+    #[test]
+    fn test_import_snapshot() {
+        let conn = setup();
+        assert!(import_snapshot(&conn, 1).unwrap().is_none());
+        conn.execute(
+            "INSERT INTO imports (
+                filename, total_rows, parsed_rows, skipped_rows,
+                vivants_count, termines_count,
+                detected_columns, unique_statuts, unique_types, is_active
+             ) VALUES ('a.csv', 10, 10, 0, 4, 6, '[]', '[]', '[]', 1)",
+            [],
+        )
+        .unwrap();
+        let snap = import_snapshot(&conn, 1).unwrap().unwrap();
+        assert_eq!(snap["filename"], "a.csv");
+        assert_eq!(snap["vivants"], 4);
+        assert_eq!(active_import_id(&conn).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_changed_keys() {
+        let before = json!({"a": 1, "b": [1, 2], "c": "x"});
+        let after = json!({"a": 1, "b": [1, 3], "d": true});
+        let keys = changed_keys(&before, &after);
+        assert_eq!(keys, vec!["b", "c", "d"]);
+        assert_eq!(pick(&before, &keys), json!({"b": [1, 2], "c": "x"}));
+    }
+}
@@ -0,0 +1,456 @@
+//! Cache générique des résultats d'analyse (table `analytics_cache`).
+//!
+//! Clé : (import, type d'analyse, paramètres canonicalisés). Chaque entrée
+//! mémorise l'empreinte des versions de ses dépendances (`cache_dependencies`,
+//! incrémentées par trigger, ou par `bump_dependency` pour les écritures en
+//! masse) : si l'une d'elles a changé, l'entrée est périmée.
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Source de données dont dépend un résultat mis en cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheDep {
+    Tickets,
+    Config,
+    Stopwords,
+    Dictionaries,
+    Sla,
+    Annuaire,
+}
+
+impl CacheDep {
+    pub fn scope(self) -> &'static str {
+        match self {
+            CacheDep::Tickets => "tickets",
+            CacheDep::Config => "config",
+            CacheDep::Stopwords => "stopwords",
+            CacheDep::Dictionaries => "dictionaries",
+            CacheDep::Sla => "sla",
+            CacheDep::Annuaire => "annuaire",
+        }
+    }
+}
+
+/// Clé de cache résolue : import, type, paramètres canoniques et empreinte
+/// des dépendances lue *avant* le calcul (un changement pendant le calcul
+/// rendra donc l'entrée périmée plutôt que silencieusement fausse).
+#[derive(Debug, Clone)]
+pub struct CacheKey {
+    pub import_id: i64,
+    pub analysis_type: String,
+    pub parameters: String,
+    pub dependencies: String,
+}
+
+impl CacheKey {
+    pub fn new(
+        conn: &Connection,
+        import_id: i64,
+        analysis_type: &str,
+        params: &Value,
+        deps: &[CacheDep],
+    ) -> Result<Self, rusqlite::Error> {
+        Ok(Self {
+            import_id,
+            analysis_type: analysis_type.to_string(),
+            parameters: canonical_json(params),
+            dependencies: dependency_stamp(conn, deps)?,
+        })
+    }
+
+    /// Clé pour l'import actif.
+    pub fn for_active_import(
+        conn: &Connection,
+        analysis_type: &str,
+        params: &Value,
+        deps: &[CacheDep],
+    ) -> Result<Self, rusqlite::Error> {
+        let import_id = super::queries::get_active_import_id(conn)?;
+        Self::new(conn, import_id, analysis_type, params, deps)
+    }
+
+    /// Lit un résultat valide. Une entrée périmée ou illisible est supprimée.
+    pub fn get<T: DeserializeOwned>(
+        &self,
+        conn: &Connection,
+    ) -> Result<Option<T>, rusqlite::Error> {
+        let row: Option<(i64, String, String)> = conn
+            .query_row(
+                "SELECT id, dependencies, result FROM analytics_cache
+                 WHERE import_id = ?1 AND analysis_type = ?2 AND parameters = ?3",
+                rusqlite::params![self.import_id, self.analysis_type, self.parameters],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+
+        let Some((id, deps, json)) = row else {
+            return Ok(None);
+        };
+
+        if deps != self.dependencies {
+            conn.execute("DELETE FROM analytics_cache WHERE id = ?1", [id])?;
+            return Ok(None);
+        }
+
+        match serde_json::from_str(&json) {
+            Ok(value) => {
+                conn.execute(
+                    "UPDATE analytics_cache
+                     SET hit_count = hit_count + 1, last_hit_at = datetime('now')
+                     WHERE id = ?1",
+                    [id],
+                )?;
+                Ok(Some(value))
+            }
+            Err(e) => {
+                log::warn!("Cache {} illisible, supprimé: {e}", self.analysis_type);
+                conn.execute("DELETE FROM analytics_cache WHERE id = ?1", [id])?;
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn put<T: Serialize>(
+        &self,
+        conn: &Connection,
+        result: &T,
+        duration_ms: i64,
+    ) -> Result<(), rusqlite::Error> {
+        let json = serde_json::to_string(result)
+            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO analytics_cache
+                (import_id, analysis_type, parameters, result, duration_ms, dependencies)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                self.import_id,
+                self.analysis_type,
+                self.parameters,
+                json,
+                duration_ms,
+                self.dependencies
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+/// Sérialisation JSON déterministe : clés triées, champs `null` omis
+/// (un `Option` absent et un `None` explicite donnent la même clé).
+pub fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> =
+                map.iter().filter(|(_, v)| !v.is_null()).collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let body: Vec<String> = entries
+                .into_iter()
+                .map(|(k, v)| format!("{}:{}", Value::String(k.clone()), canonical_json(v)))
+                .collect();
+            format!("{{{}}}", body.join(","))
+        }
+        Value::Array(items) => {
+            let body: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", body.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Empreinte "scope:version,..." des dépendances demandées.
+pub fn dependency_stamp(conn: &Connection, deps: &[CacheDep]) -> Result<String, rusqlite::Error> {
+    let mut stmt =
+        conn.prepare_cached("SELECT version FROM cache_dependencies WHERE scope = ?1")?;
+    let mut parts = Vec::with_capacity(deps.len());
+    for dep in deps {
+        let version: i64 = stmt
+            .query_row([dep.scope()], |row| row.get(0))
+            .optional()?
+            .unwrap_or(0);
+        parts.push(format!("{}:{}", dep.scope(), version));
+    }
+    Ok(parts.join(","))
+}
+
+/// Incrémente la version d'une dépendance : une fois par écriture en masse
+/// (suppression d'un import, reclassification), pas une fois par ligne.
+pub fn bump_dependency(conn: &Connection, dep: CacheDep) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "UPDATE cache_dependencies SET version = version + 1 WHERE scope = ?1",
+        [dep.scope()],
+    )?;
+    Ok(())
+}
+
+// ─── Statistiques & purge ─────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub total_entries: usize,
+    pub total_size_bytes: usize,
+    pub total_hits: usize,
+    /// Entrées dont l'empreinte ne correspond plus aux versions courantes.
+    pub stale_entries: usize,
+    pub par_type: Vec<CacheTypeStats>,
+    pub dependencies: Vec<CacheDependencyVersion>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheTypeStats {
+    pub analysis_type: String,
+    pub entries: usize,
+    pub size_bytes: usize,
+    pub hits: usize,
+    pub avg_duration_ms: f64,
+    pub last_computed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheDependencyVersion {
+    pub scope: String,
+    pub version: i64,
+}
+
+pub fn get_cache_stats(conn: &Connection) -> Result<CacheStats, rusqlite::Error> {
+    let dependencies: Vec<CacheDependencyVersion> = conn
+        .prepare("SELECT scope, version FROM cache_dependencies ORDER BY scope")?
+        .query_map([], |row| {
+            Ok(CacheDependencyVersion {
+                scope: row.get(0)?,
+                version: row.get(1)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let par_type: Vec<CacheTypeStats> = conn
+        .prepare(
+            "SELECT analysis_type, COUNT(*), COALESCE(SUM(LENGTH(result)), 0),
+                    COALESCE(SUM(hit_count), 0), COALESCE(AVG(duration_ms), 0),
+                    MAX(computed_at)
+             FROM analytics_cache
+             GROUP BY analysis_type
+             ORDER BY analysis_type",
+        )?
+        .query_map([], |row| {
+            Ok(CacheTypeStats {
+                analysis_type: row.get(0)?,
+                entries: row.get::<_, i64>(1)? as usize,
+                size_bytes: row.get::<_, i64>(2)? as usize,
+                hits: row.get::<_, i64>(3)? as usize,
+                avg_duration_ms: row.get(4)?,
+                last_computed_at: row.get(5)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let stale_entries = count_stale_entries(conn, &dependencies)?;
+
+    Ok(CacheStats {
+        total_entries: par_type.iter().map(|t| t.entries).sum(),
+        total_size_bytes: par_type.iter().map(|t| t.size_bytes).sum(),
+        total_hits: par_type.iter().map(|t| t.hits).sum(),
+        stale_entries,
+        par_type,
+        dependencies,
+    })
+}
+
+/// Une entrée est périmée si l'une des versions de son empreinte diffère
+/// de la version courante (les entrées sans empreinte ne sont pas comptées).
+fn count_stale_entries(
+    conn: &Connection,
+    current: &[CacheDependencyVersion],
+) -> Result<usize, rusqlite::Error> {
+    let stamps: Vec<String> = conn
+        .prepare("SELECT dependencies FROM analytics_cache WHERE dependencies != ''")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    Ok(stamps.iter().filter(|s| is_stale(s, current)).count())
+}
+
+fn is_stale(stamp: &str, current: &[CacheDependencyVersion]) -> bool {
+    stamp.split(',').any(|part| {
+        let Some((scope, version)) = part.split_once(':') else {
+            return true;
+        };
+        current
+            .iter()
+            .find(|d| d.scope == scope)
+            .map(|d| d.version.to_string() != version)
+            .unwrap_or(true)
+    })
+}
+
+/// Supprime les entrées du cache (toutes, ou d'un seul type). Retourne le nombre supprimé.
+pub fn purge_cache(
+    conn: &Connection,
+    analysis_type: Option<&str>,
+) -> Result<usize, rusqlite::Error> {
+    match analysis_type {
+        Some(t) => conn.execute("DELETE FROM analytics_cache WHERE analysis_type = ?1", [t]),
+        None => conn.execute("DELETE FROM analytics_cache", []),
+    }
+}
+
+/// Supprime uniquement les entrées périmées.
+pub fn purge_stale_cache(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    let current: Vec<CacheDependencyVersion> = conn
+        .prepare("SELECT scope, version FROM cache_dependencies")?
+        .query_map([], |row| {
+            Ok(CacheDependencyVersion {
+                scope: row.get(0)?,
+                version: row.get(1)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let entries: Vec<(i64, String)> = conn
+        .prepare("SELECT id, dependencies FROM analytics_cache WHERE dependencies != ''")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut deleted = 0;
+    for (id, stamp) in entries {
+        if is_stale(&stamp, &current) {
+            deleted += conn.execute("DELETE FROM analytics_cache WHERE id = ?1", [id])?;
+        }
+    }
+    Ok(deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn setup() -> (Connection, i64) {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(include_str!("sql/001_initial.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("sql/004_user_stopwords.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("sql/005_analytics_cache_deps.sql"))
+            .unwrap();
+        conn.execute(
+            "INSERT INTO imports (
+                filename, total_rows, parsed_rows, skipped_rows,
+                vivants_count, termines_count,
+                detected_columns, unique_statuts, unique_types, is_active
+             ) VALUES ('test.csv', 1, 1, 0, 1, 0, '[]', '[]', '[]', 1)",
+            [],
+        )
+        .unwrap();
+        let import_id = conn.last_insert_rowid();
+        (conn, import_id)
+    }
+
+    const DEPS: &[CacheDep] = &[CacheDep::Tickets, CacheDep::Stopwords];
+
+    #[test]
+    fn test_canonical_json_sorts_keys_and_drops_nulls() {
+        let a = json!({"b": 1, "a": {"y": [1, 2], "x": null}, "c": null});
+        let b = json!({"a": {"y": [1, 2]}, "b": 1});
+        assert_eq!(canonical_json(&a), canonical_json(&b));
+        assert_eq!(canonical_json(&b), r#"{"a":{"y":[1,2]},"b":1}"#);
+    }
+
+    #[test]
+    fn test_put_then_get_roundtrip_counts_hits() {
+        let (conn, import_id) = setup();
+        let key = CacheKey::new(&conn, import_id, "demo", &json!({"n": 5}), DEPS).unwrap();
+
+        assert!(key.get::<Vec<u32>>(&conn).unwrap().is_none());
+        key.put(&conn, &vec![1u32, 2, 3], 42).unwrap();
+
+        // Paramètres équivalents dans un autre ordre → même clé
+        let key2 =
+            CacheKey::new(&conn, import_id, "demo", &json!({"n": 5, "x": null}), DEPS).unwrap();
+        assert_eq!(key2.get::<Vec<u32>>(&conn).unwrap(), Some(vec![1, 2, 3]));
+
+        let stats = get_cache_stats(&conn).unwrap();
+        assert_eq!(stats.total_entries, 1);
+        assert_eq!(stats.total_hits, 1);
+        assert_eq!(stats.par_type[0].analysis_type, "demo");
+    }
+
+    #[test]
+    fn test_stopword_change_invalidates_entry() {
+        let (conn, import_id) = setup();
+        let key = CacheKey::new(&conn, import_id, "demo", &json!({}), DEPS).unwrap();
+        key.put(&conn, &"ok", 1).unwrap();
+
+        conn.execute(
+            "INSERT INTO user_stopwords (word) VALUES ('imprimante')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(get_cache_stats(&conn).unwrap().stale_entries, 1);
+
+        let fresh = CacheKey::new(&conn, import_id, "demo", &json!({}), DEPS).unwrap();
+        assert!(fresh.get::<String>(&conn).unwrap().is_none());
+        // L'entrée périmée a été supprimée à la lecture
+        assert_eq!(get_cache_stats(&conn).unwrap().total_entries, 0);
+    }
+
+    #[test]
+    fn test_unrelated_change_keeps_entry() {
+        let (conn, import_id) = setup();
+        let key = CacheKey::new(&conn, import_id, "demo", &json!({}), DEPS).unwrap();
+        key.put(&conn, &"ok", 1).unwrap();
+
+        // Config ne fait pas partie des dépendances
+        conn.execute(
+            "UPDATE config SET value = '25' WHERE key = 'seuil_tickets_technicien'",
+            [],
+        )
+        .unwrap();
+        let same = CacheKey::new(&conn, import_id, "demo", &json!({}), DEPS).unwrap();
+        assert_eq!(same.get::<String>(&conn).unwrap().as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn test_bulk_ticket_update_bumps_version_once() {
+        let (conn, import_id) = setup();
+        for id in 1..=3 {
+            conn.execute(
+                "INSERT INTO tickets (id, import_id, statut, date_ouverture) VALUES (?1, ?2, 'Nouveau', '2026-01-01T00:00:00')",
+                rusqlite::params![id, import_id],
+            )
+            .unwrap();
+        }
+        // Version 1 : insertion de l'import
+        conn.execute("UPDATE tickets SET statut = 'Clos'", [])
+            .unwrap();
+        let stamp = || dependency_stamp(&conn, &[CacheDep::Tickets]).unwrap();
+        assert_eq!(stamp(), "tickets:1");
+
+        bump_dependency(&conn, CacheDep::Tickets).unwrap();
+        assert_eq!(stamp(), "tickets:2");
+    }
+
+    #[test]
+    fn test_purge_by_type_and_stale() {
+        let (conn, import_id) = setup();
+        CacheKey::new(&conn, import_id, "a", &json!({}), DEPS)
+            .unwrap()
+            .put(&conn, &1, 1)
+            .unwrap();
+        CacheKey::new(&conn, import_id, "b", &json!({}), &[CacheDep::Tickets])
+            .unwrap()
+            .put(&conn, &2, 1)
+            .unwrap();
+
+        conn.execute("INSERT INTO user_stopwords (word) VALUES ('test')", [])
+            .unwrap();
+        assert_eq!(purge_stale_cache(&conn).unwrap(), 1);
+        assert_eq!(purge_cache(&conn, Some("b")).unwrap(), 1);
+        assert_eq!(get_cache_stats(&conn).unwrap().total_entries, 0);
+    }
+}
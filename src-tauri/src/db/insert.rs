@@ -1,14 +1,16 @@
+use std::collections::HashMap;
+
 use rusqlite::Connection;
 
 use crate::parser::types::GlpiTicketNormalized;
 
-pub fn bulk_insert_tickets(
-    conn: &mut Connection,
+/// Insère les tickets d'un import. La transaction est ouverte par
+/// l'appelant, qui y écrit aussi l'import et son entrée d'audit.
+pub fn insert_tickets(
+    tx: &Connection,
     import_id: i64,
     tickets: &[GlpiTicketNormalized],
 ) -> Result<usize, rusqlite::Error> {
-    let tx = conn.transaction()?;
-
     {
         let mut stmt = tx.prepare_cached(
             "INSERT OR REPLACE INTO tickets (
@@ -20,7 +22,7 @@ pub fn bulk_insert_tickets(
                 groupe_niveau1, groupe_niveau2, groupe_niveau3,
                 categorie, categorie_niveau1, categorie_niveau2,
                 date_resolution, est_vivant, anciennete_jours, inactivite_jours, date_cloture_approx,
-                action_recommandee, motif_classification
+                action_recommandee, motif_classification, categorie_id, impact
             ) VALUES (
                 ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8,
                 ?9, ?10, ?11, ?12,
@@ -30,11 +32,13 @@ pub fn bulk_insert_tickets(
                 ?21, ?22, ?23,
                 ?24, ?25, ?26,
                 ?27, ?28, ?29, ?30, ?31,
-                ?32, ?33
+                ?32, ?33, ?34, ?35
             )",
         )?;
+        let mut categories: HashMap<String, i64> = HashMap::new();
 
         for t in tickets {
+            let categorie_id = resolve_categorie(tx, &t.categorie_chemin, &mut categories)?;
             stmt.execute(rusqlite::params![
                 t.id,
                 import_id,
@@ -69,10 +73,47 @@ pub fn bulk_insert_tickets(
                 t.date_cloture_approx,
                 t.action_recommandee,
                 t.motif_classification,
+                categorie_id,
+                t.impact,
             ])?;
         }
     }
 
-    tx.commit()?;
     Ok(tickets.len())
 }
+
+/// Identifiant du nœud `categories` d'un chemin, créé avec ses ancêtres si
+/// besoin. `cache` évite de relire les chemins déjà résolus.
+fn resolve_categorie(
+    conn: &Connection,
+    chemin: &[String],
+    cache: &mut HashMap<String, i64>,
+) -> Result<Option<i64>, rusqlite::Error> {
+    let mut parent_id: Option<i64> = None;
+    let mut prefixe = String::new();
+    for (i, nom) in chemin.iter().enumerate() {
+        if i > 0 {
+            prefixe.push_str(" > ");
+        }
+        prefixe.push_str(nom);
+        let id = match cache.get(&prefixe) {
+            Some(&id) => id,
+            None => {
+                conn.execute(
+                    "INSERT OR IGNORE INTO categories (parent_id, nom, chemin, niveau)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    rusqlite::params![parent_id, nom, prefixe, (i + 1) as i64],
+                )?;
+                let id: i64 = conn.query_row(
+                    "SELECT id FROM categories WHERE chemin = ?1",
+                    [&prefixe],
+                    |row| row.get(0),
+                )?;
+                cache.insert(prefixe.clone(), id);
+                id
+            }
+        };
+        parent_id = Some(id);
+    }
+    Ok(parent_id)
+}
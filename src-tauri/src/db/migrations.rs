@@ -22,6 +22,34 @@ const MIGRATIONS: &[Migration] = &[
         version: 4,
         sql: include_str!("sql/004_user_stopwords.sql"),
     },
+    Migration {
+        version: 5,
+        sql: include_str!("sql/005_analytics_cache_deps.sql"),
+    },
+    Migration {
+        version: 6,
+        sql: include_str!("sql/006_audit_log.sql"),
+    },
+    Migration {
+        version: 7,
+        sql: include_str!("sql/007_classification_rules.sql"),
+    },
+    Migration {
+        version: 8,
+        sql: include_str!("sql/008_sla.sql"),
+    },
+    Migration {
+        version: 9,
+        sql: include_str!("sql/009_annuaire_demandeurs.sql"),
+    },
+    Migration {
+        version: 10,
+        sql: include_str!("sql/010_categories.sql"),
+    },
+    Migration {
+        version: 11,
+        sql: include_str!("sql/011_impact.sql"),
+    },
 ];
 
 pub fn run_migrations(conn: &Connection) -> Result<(), rusqlite::Error> {
@@ -48,3 +76,68 @@ pub fn run_migrations(conn: &Connection) -> Result<(), rusqlite::Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reprise_categories_rogne_les_niveaux() {
+        let conn = Connection::open_in_memory().unwrap();
+        // Schéma antérieur aux catégories, avec la tolérance de `run_migrations`
+        for m in MIGRATIONS.iter().filter(|m| m.version < 10) {
+            if let Err(e) = conn.execute_batch(m.sql) {
+                assert!(e.to_string().contains("duplicate column name"), "{e}");
+            }
+        }
+        conn.execute(
+            "INSERT INTO imports (id, filename, total_rows, parsed_rows, vivants_count,
+                                  termines_count, detected_columns, unique_statuts, unique_types)
+             VALUES (1, 'a.csv', 3, 3, 3, 0, '[]', '[]', '[]')",
+            [],
+        )
+        .unwrap();
+        for (id, categorie) in [
+            (1, "Matériel > Poste "),
+            (2, " Matériel  >  > Poste > "),
+            (3, "Matériel > Imprimante"),
+        ] {
+            conn.execute(
+                "INSERT INTO tickets (id, import_id, statut, date_ouverture, categorie)
+                 VALUES (?1, 1, 'Nouveau', '2024-01-01', ?2)",
+                rusqlite::params![id, categorie],
+            )
+            .unwrap();
+        }
+
+        conn.execute_batch(MIGRATIONS[9].sql).unwrap();
+
+        let chemins: Vec<(String, i64, bool)> = conn
+            .prepare("SELECT chemin, niveau, parent_id IS NOT NULL FROM categories ORDER BY chemin")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            chemins,
+            vec![
+                ("Matériel".to_string(), 1, false),
+                ("Matériel > Imprimante".to_string(), 2, true),
+                ("Matériel > Poste".to_string(), 2, true),
+            ]
+        );
+
+        let poste: Vec<Option<String>> = conn
+            .prepare(
+                "SELECT c.chemin FROM tickets t LEFT JOIN categories c ON c.id = t.categorie_id
+                 WHERE t.id IN (1, 2) ORDER BY t.id",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(poste, vec![Some("Matériel > Poste".to_string()); 2]);
+    }
+}
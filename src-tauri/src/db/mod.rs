@@ -2,3 +2,5 @@ pub mod setup;
 pub mod migrations;
 pub mod insert;
 pub mod queries;
+pub mod cache;
+pub mod audit;
@@ -1,11 +1,14 @@
 use rusqlite::{params_from_iter, types::Value, Connection};
 
+use crate::analyzer::calendar::{CalendrierCompile, Delai};
 use crate::commands::import::{ImportRecord, TechHistory, TechHistoryKpi, TechHistoryPeriod};
 use crate::commands::search::TicketSearchResult;
+use crate::analyzer::stock::compute_age_distribution;
 use crate::commands::stock::{
-    AgeRangeCount, GroupStock, StatutCount, StockFilters, StockOverview, TicketDetail, TicketSummary,
+    GroupStock, StatutCount, StockFilters, StockOverview, TicketDetail, TicketSummary,
     TechnicianStock, TypeBreakdown,
 };
+use crate::config::get_calendrier_from_db;
 
 // ─── Helpers privés ───────────────────────────────────────────────────────────
 
@@ -82,6 +85,51 @@ fn apply_filters(sql: &mut String, params: &mut Vec<Value>, filters: &StockFilte
     }
 }
 
+/// Âges ouvrés (jours) des tickets vivants à la date de l'import, regroupés
+/// selon `key_col` (tous sous la clé vide si None). Le calendrier est chargé
+/// une fois par l'appelant.
+fn ages_ouvres(
+    conn: &Connection,
+    cal: &CalendrierCompile,
+    import_id: i64,
+    key_col: Option<&str>,
+    filters: Option<&StockFilters>,
+) -> Result<std::collections::HashMap<String, Vec<f64>>, rusqlite::Error> {
+    let reference = get_import_reference_date(conn, import_id)?
+        .unwrap_or_else(|| chrono::Local::now().naive_local());
+    let mut sql = format!(
+        "SELECT {}, date_ouverture FROM tickets WHERE import_id = ?1 AND est_vivant = 1",
+        key_col.map_or("''".to_string(), |c| format!("COALESCE({c}, '')")),
+    );
+    let mut params: Vec<Value> = vec![Value::Integer(import_id)];
+    if let Some(f) = filters {
+        apply_filters(&mut sql, &mut params, f);
+    }
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(params), |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+    })?;
+    let mut map: std::collections::HashMap<String, Vec<f64>> = std::collections::HashMap::new();
+    for row in rows {
+        let (key, ouverture) = row?;
+        let ouverture = ouverture
+            .as_deref()
+            .and_then(crate::parser::deserializers::parse_french_datetime);
+        if let Some(o) = ouverture {
+            map.entry(key).or_default().push(cal.jours_ouvres(o, reference));
+        }
+    }
+    Ok(map)
+}
+
+fn moyenne_arrondie(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        (values.iter().sum::<f64>() / values.len() as f64 * 10.0).round() / 10.0
+    }
+}
+
 // ─── Fonctions de requête publiques ───────────────────────────────────────────
 
 /// Vue d'ensemble du stock : totaux, statuts, types, distribution d'âge, inactifs.
@@ -147,6 +195,11 @@ pub fn get_stock_overview(conn: &Connection) -> Result<StockOverview, rusqlite::
         (ages.iter().sum::<f64>() / ages.len() as f64 * 10.0).round() / 10.0
     };
     let age_median_jours = mediane(&ages);
+    let ages_ouvres = ages_ouvres(conn, &get_calendrier_from_db(conn)?, import_id, None, None)?
+        .remove("")
+        .unwrap_or_default();
+    let age_moyen_jours_ouvres = moyenne_arrondie(&ages_ouvres);
+    let age_median_jours_ouvres = (mediane(&ages_ouvres) * 10.0).round() / 10.0;
 
     // 3. Par type (incidents / demandes vivants) — un seul scan
     let (incidents, demandes) = conn.query_row(
@@ -164,61 +217,16 @@ pub fn get_stock_overview(conn: &Connection) -> Result<StockOverview, rusqlite::
         },
     )?;
 
-    // 4. Distribution par tranches d'ancienneté — un seul scan
-    let (lt1, range1_2, range2_7, range7_30, ge30) = conn.query_row(
-        "SELECT
-            SUM(CASE WHEN anciennete_jours < 1  THEN 1 ELSE 0 END),
-            SUM(CASE WHEN anciennete_jours >= 1  AND anciennete_jours < 2  THEN 1 ELSE 0 END),
-            SUM(CASE WHEN anciennete_jours >= 2  AND anciennete_jours < 7  THEN 1 ELSE 0 END),
-            SUM(CASE WHEN anciennete_jours >= 7  AND anciennete_jours < 30 THEN 1 ELSE 0 END),
-            SUM(CASE WHEN anciennete_jours >= 30 THEN 1 ELSE 0 END)
-         FROM tickets
-         WHERE import_id = ?1 AND est_vivant = 1",
-        rusqlite::params![import_id],
-        |row| {
-            Ok((
-                row.get::<_, Option<i64>>(0)?.unwrap_or(0) as usize,
-                row.get::<_, Option<i64>>(1)?.unwrap_or(0) as usize,
-                row.get::<_, Option<i64>>(2)?.unwrap_or(0) as usize,
-                row.get::<_, Option<i64>>(3)?.unwrap_or(0) as usize,
-                row.get::<_, Option<i64>>(4)?.unwrap_or(0) as usize,
-            ))
-        },
-    )?;
-
-    let total_v = total_vivants.max(1) as f64;
-    let par_anciennete = vec![
-        AgeRangeCount {
-            label: "< 24h".to_string(),
-            threshold_days: 0,
-            count: lt1,
-            percentage: (lt1 as f64 / total_v * 1000.0).round() / 10.0,
-        },
-        AgeRangeCount {
-            label: "24h - 48h".to_string(),
-            threshold_days: 1,
-            count: range1_2,
-            percentage: (range1_2 as f64 / total_v * 1000.0).round() / 10.0,
-        },
-        AgeRangeCount {
-            label: "48h - 7j".to_string(),
-            threshold_days: 2,
-            count: range2_7,
-            percentage: (range2_7 as f64 / total_v * 1000.0).round() / 10.0,
-        },
-        AgeRangeCount {
-            label: "7j - 30j".to_string(),
-            threshold_days: 7,
-            count: range7_30,
-            percentage: (range7_30 as f64 / total_v * 1000.0).round() / 10.0,
-        },
-        AgeRangeCount {
-            label: "> 30j".to_string(),
-            threshold_days: 30,
-            count: ge30,
-            percentage: (ge30 as f64 / total_v * 1000.0).round() / 10.0,
-        },
-    ];
+    // 4. Distribution par tranches d'ancienneté (jeu de tranches configuré)
+    let anciennetes: Vec<i64> = conn
+        .prepare(
+            "SELECT anciennete_jours FROM tickets
+             WHERE import_id = ?1 AND est_vivant = 1 AND anciennete_jours IS NOT NULL",
+        )?
+        .query_map(rusqlite::params![import_id], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    let jeu = crate::config::get_config_from_db(conn)?.tranches.anciennete();
+    let par_anciennete = compute_age_distribution(&anciennetes, &jeu);
 
     // 5. Inactifs — un seul scan
     let (inactifs_14j, inactifs_30j) = conn.query_row(
@@ -251,6 +259,8 @@ pub fn get_stock_overview(conn: &Connection) -> Result<StockOverview, rusqlite::
         par_statut,
         age_moyen_jours,
         age_median_jours,
+        age_moyen_jours_ouvres,
+        age_median_jours_ouvres,
         par_type: TypeBreakdown { incidents, demandes },
         par_anciennete,
         inactifs_14j,
@@ -277,7 +287,14 @@ pub fn get_technicians_stock(
                SUM(CASE WHEN type_ticket = 'Incident'       THEN 1 ELSE 0 END) AS incidents,
                SUM(CASE WHEN type_ticket = 'Demande'        THEN 1 ELSE 0 END) AS demandes,
                COALESCE(AVG(CAST(anciennete_jours AS REAL)), 0.0) AS age_moyen,
-               SUM(CASE WHEN inactivite_jours >= 14 THEN 1 ELSE 0 END) AS inactifs_14j
+               SUM(CASE WHEN inactivite_jours >= 14 THEN 1 ELSE 0 END) AS inactifs_14j,
+               (SELECT g.groupe_principal FROM tickets g
+                WHERE g.import_id = ?1 AND g.est_vivant = 1
+                  AND g.technicien_principal = tickets.technicien_principal
+                  AND g.groupe_principal IS NOT NULL AND g.groupe_principal != ''
+                GROUP BY g.groupe_principal
+                ORDER BY COUNT(*) DESC, g.groupe_principal
+                LIMIT 1) AS groupe
         FROM tickets
         WHERE import_id = ?1 AND est_vivant = 1
           AND technicien_principal IS NOT NULL AND technicien_principal != ''"
@@ -302,20 +319,25 @@ pub fn get_technicians_stock(
             let demandes = row.get::<_, i64>(7)? as usize;
             let age_moyen: f64 = row.get(8)?;
             let inactifs_14j = row.get::<_, i64>(9)? as usize;
+            let groupe: Option<String> = row.get(10)?;
             Ok((
                 tech, total, en_cours, en_attente, planifie, nouveau,
-                incidents, demandes, age_moyen, inactifs_14j,
+                incidents, demandes, age_moyen, inactifs_14j, groupe,
             ))
         })?
         .collect::<Result<Vec<_>, _>>()?;
+    let cal = get_calendrier_from_db(conn)?;
+    let ouvres = ages_ouvres(conn, &cal, import_id, Some("technicien_principal"), filters)?;
 
     Ok(rows
         .into_iter()
         .map(
             |(tech, total, en_cours, en_attente, planifie, nouveau,
-              incidents, demandes, age_moyen, inactifs_14j)| {
+              incidents, demandes, age_moyen, inactifs_14j, groupe)| {
+                let age_ouvre = ouvres.get(&tech).map_or(0.0, |v| moyenne_arrondie(v));
                 TechnicianStock {
                     technicien: tech,
+                    groupe,
                     total,
                     en_cours,
                     en_attente,
@@ -324,9 +346,13 @@ pub fn get_technicians_stock(
                     incidents,
                     demandes,
                     age_moyen_jours: (age_moyen * 10.0).round() / 10.0,
+                    age_moyen_jours_ouvres: age_ouvre,
                     inactifs_14j,
+                    etp: 1.0,
+                    stock_par_etp: total as f64,
                     ecart_seuil: total as i64 - seuil,
                     couleur_seuil: couleur_charge(total, seuil),
+                    seuils: Default::default(),
                 }
             },
         )
@@ -362,12 +388,16 @@ pub fn get_groups_stock(
         " GROUP BY groupe_principal, groupe_niveau1, groupe_niveau2 ORDER BY total DESC",
     );
 
+    let cal = get_calendrier_from_db(conn)?;
+    let ouvres = ages_ouvres(conn, &cal, import_id, Some("groupe_principal"), filters)?;
     let mut stmt = conn.prepare(&sql)?;
     let rows = stmt
         .query_map(params_from_iter(params), |row| {
             let age_moyen: f64 = row.get(9)?;
+            let groupe = row.get::<_, Option<String>>(0)?.unwrap_or_default();
             Ok(GroupStock {
-                groupe: row.get::<_, Option<String>>(0)?.unwrap_or_default(),
+                age_moyen_jours_ouvres: ouvres.get(&groupe).map_or(0.0, |v| moyenne_arrondie(v)),
+                groupe,
                 groupe_niveau1: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
                 groupe_niveau2: row.get(2)?,
                 total: row.get::<_, i64>(3)? as usize,
@@ -876,11 +906,12 @@ pub fn search_tickets_fts(
 
 // ─── Fonctions Bilan temporel ────────────────────────────────────────────────
 
-/// Construit l'expression SQLite pour la granularité de période.
+/// Construit l'expression SQLite pour la granularité de période. Les clés
+/// suivent `temporal::period_key` (semaines ISO : `%G-W%V`).
 fn periode_expr(granularity: &str, col: &str) -> String {
     match granularity {
         "day" => format!("strftime('%Y-%m-%d', {col})"),
-        "week" => format!("strftime('%Y-W%W', {col})"),
+        "week" => format!("strftime('%G-W%V', {col})"),
         "quarter" => format!(
             "CASE \
              WHEN strftime('%m', {col}) BETWEEN '01' AND '03' THEN strftime('%Y', {col}) || '-Q1' \
@@ -903,35 +934,8 @@ pub fn get_bilan_entrees_par_periode(
     granularity: &str,
     filters: Option<&StockFilters>,
 ) -> Result<Vec<(String, usize)>, rusqlite::Error> {
-    let import_id = get_active_import_id(conn)?;
-    let periode = periode_expr(granularity, "date_ouverture");
-
-    let mut sql = format!(
-        "SELECT {periode} AS periode, COUNT(*) AS n \
-         FROM tickets \
-         WHERE import_id = ?1 \
-           AND date_ouverture >= ?2 \
-           AND date_ouverture < date(?3, '+1 day')"
-    );
-
-    let mut params: Vec<Value> = vec![
-        Value::Integer(import_id),
-        Value::Text(date_from.to_string()),
-        Value::Text(date_to.to_string()),
-    ];
-
-    if let Some(f) = filters {
-        apply_filters(&mut sql, &mut params, f);
-    }
-    sql.push_str(" GROUP BY periode ORDER BY periode");
-
-    let mut stmt = conn.prepare(&sql)?;
-    let rows = stmt
-        .query_map(params_from_iter(params), |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(rows)
+    flux_par_periode(conn, Flux::Entrees, date_from, date_to, granularity, filters, None)
+        .map(|rows| rows.into_iter().map(|(p, _, n)| (p, n)).collect())
 }
 
 /// Flux sortants : tickets terminés (statut IN ('Résolu','Clos')) dont
@@ -943,16 +947,69 @@ pub fn get_bilan_sorties_par_periode(
     granularity: &str,
     filters: Option<&StockFilters>,
 ) -> Result<Vec<(String, usize)>, rusqlite::Error> {
+    flux_par_periode(conn, Flux::Sorties, date_from, date_to, granularity, filters, None)
+        .map(|rows| rows.into_iter().map(|(p, _, n)| (p, n)).collect())
+}
+
+/// Flux entrants par période et par valeur de `dimension`
+/// (`groupe_principal` ou `technicien_principal`) : (période, valeur, n).
+pub fn get_bilan_entrees_par_dimension(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+    granularity: &str,
+    dimension: &str,
+) -> Result<Vec<(String, String, usize)>, rusqlite::Error> {
+    flux_par_periode(conn, Flux::Entrees, date_from, date_to, granularity, None, Some(dimension))
+}
+
+/// Flux sortants par période et par valeur de `dimension` : (période, valeur, n).
+pub fn get_bilan_sorties_par_dimension(
+    conn: &Connection,
+    date_from: &str,
+    date_to: &str,
+    granularity: &str,
+    dimension: &str,
+) -> Result<Vec<(String, String, usize)>, rusqlite::Error> {
+    flux_par_periode(conn, Flux::Sorties, date_from, date_to, granularity, None, Some(dimension))
+}
+
+#[derive(Clone, Copy)]
+enum Flux {
+    Entrees,
+    Sorties,
+}
+
+fn flux_par_periode(
+    conn: &Connection,
+    flux: Flux,
+    date_from: &str,
+    date_to: &str,
+    granularity: &str,
+    filters: Option<&StockFilters>,
+    dimension: Option<&str>,
+) -> Result<Vec<(String, String, usize)>, rusqlite::Error> {
     let import_id = get_active_import_id(conn)?;
-    let periode = periode_expr(granularity, "date_resolution");
+    let (date_col, condition) = match flux {
+        Flux::Entrees => ("date_ouverture", ""),
+        Flux::Sorties => ("date_resolution", " AND statut IN ('Résolu', 'Clos')"),
+    };
+    let periode = periode_expr(granularity, date_col);
+    let (select_dim, where_dim, group_dim) = match dimension {
+        Some(col) => (
+            col.to_string(),
+            format!(" AND {col} IS NOT NULL AND {col} != ''"),
+            ", dimension",
+        ),
+        None => ("''".to_string(), String::new(), ""),
+    };
 
     let mut sql = format!(
-        "SELECT {periode} AS periode, COUNT(*) AS n \
+        "SELECT {periode} AS periode, {select_dim} AS dimension, COUNT(*) AS n \
          FROM tickets \
-         WHERE import_id = ?1 \
-           AND statut IN ('Résolu', 'Clos') \
-           AND date_resolution >= ?2 \
-           AND date_resolution < date(?3, '+1 day')"
+         WHERE import_id = ?1{condition} \
+           AND {date_col} >= ?2 \
+           AND {date_col} < date(?3, '+1 day'){where_dim}"
     );
 
     let mut params: Vec<Value> = vec![
@@ -964,26 +1021,51 @@ pub fn get_bilan_sorties_par_periode(
     if let Some(f) = filters {
         apply_filters(&mut sql, &mut params, f);
     }
-    sql.push_str(" GROUP BY periode ORDER BY periode");
+    sql.push_str(&format!(" GROUP BY periode{group_dim} ORDER BY periode"));
 
     let mut stmt = conn.prepare(&sql)?;
     let rows = stmt
         .query_map(params_from_iter(params), |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)? as usize,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Stock vivant de l'import actif par valeur de `dimension`.
+pub fn get_stock_vivant_par_dimension(
+    conn: &Connection,
+    dimension: &str,
+) -> Result<Vec<(String, usize)>, rusqlite::Error> {
+    let import_id = get_active_import_id(conn)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {dimension}, COUNT(*) FROM tickets \
+         WHERE import_id = ?1 AND est_vivant = 1 \
+           AND {dimension} IS NOT NULL AND {dimension} != '' \
+         GROUP BY {dimension}"
+    ))?;
+    let rows = stmt
+        .query_map([import_id], |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
         })?
         .collect::<Result<Vec<_>, _>>()?;
     Ok(rows)
 }
 
-/// Durées de résolution (en jours) des tickets clos sur une plage de dates.
+/// Durées de résolution (calendaires et ouvrées) des tickets clos sur une plage de dates.
 pub fn get_resolution_durations(
     conn: &Connection,
+    cal: &CalendrierCompile,
     date_from: &str,
     date_to: &str,
-) -> Result<Vec<f64>, rusqlite::Error> {
+) -> Result<Vec<Delai>, rusqlite::Error> {
     let import_id = get_active_import_id(conn)?;
     let mut stmt = conn.prepare_cached(
-        "SELECT julianday(date_cloture_approx) - julianday(date_ouverture)
+        "SELECT date_ouverture, date_cloture_approx
          FROM tickets
          WHERE import_id = ?1
            AND est_vivant = 0
@@ -994,10 +1076,10 @@ pub fn get_resolution_durations(
     )?;
     let rows = stmt
         .query_map(rusqlite::params![import_id, date_from, date_to], |row| {
-            row.get::<_, f64>(0)
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
         })?
         .filter_map(|r| r.ok())
-        .filter(|v| *v >= 0.0)
+        .filter_map(|(debut, fin)| cal.delai_str(&debut, &fin))
         .collect();
     Ok(rows)
 }
@@ -1158,18 +1240,19 @@ pub fn get_distinct_categories_for_delais(
     Ok(rows)
 }
 
-/// Durées de résolution filtrées par catégorie (en jours).
+/// Durées de résolution (calendaires et ouvrées) filtrées par catégorie.
 pub fn get_resolution_durations_filtered(
     conn: &Connection,
+    cal: &CalendrierCompile,
     date_from: &str,
     date_to: &str,
     cat_niveau1: Option<&str>,
     cat_niveau2: Option<&str>,
     categorie: Option<&str>,
-) -> Result<Vec<f64>, rusqlite::Error> {
+) -> Result<Vec<Delai>, rusqlite::Error> {
     let import_id = get_active_import_id(conn)?;
     let mut sql = "\
-        SELECT julianday(date_cloture_approx) - julianday(date_ouverture) \
+        SELECT date_ouverture, date_cloture_approx \
         FROM tickets \
         WHERE import_id = ?1 \
           AND est_vivant = 0 \
@@ -1198,23 +1281,24 @@ pub fn get_resolution_durations_filtered(
     let mut stmt = conn.prepare(&sql)?;
     let rows = stmt
         .query_map(rusqlite::params_from_iter(params.iter().map(|p| p.as_ref())), |row| {
-            row.get::<_, f64>(0)
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
         })?
         .filter_map(|r| r.ok())
-        .filter(|v| *v >= 0.0)
+        .filter_map(|(debut, fin)| cal.delai_str(&debut, &fin))
         .collect();
     Ok(rows)
 }
 
-/// Durées de résolution groupées par catégorie.
+/// Durées de résolution (calendaires et ouvrées) groupées par catégorie.
 pub fn get_resolution_durations_by_category(
     conn: &Connection,
+    cal: &CalendrierCompile,
     date_from: &str,
     date_to: &str,
     cat_niveau1: Option<&str>,
     cat_niveau2: Option<&str>,
     categorie: Option<&str>,
-) -> Result<Vec<(String, Vec<f64>)>, rusqlite::Error> {
+) -> Result<Vec<(String, Vec<Delai>)>, rusqlite::Error> {
     let import_id = get_active_import_id(conn)?;
 
     let group_col = if categorie.is_some() {
@@ -1228,7 +1312,7 @@ pub fn get_resolution_durations_by_category(
     };
 
     let mut sql = format!(
-        "SELECT {group_col}, julianday(date_cloture_approx) - julianday(date_ouverture) \
+        "SELECT {group_col}, date_ouverture, date_cloture_approx \
          FROM tickets \
          WHERE import_id = ?1 \
            AND est_vivant = 0 \
@@ -1259,15 +1343,19 @@ pub fn get_resolution_durations_by_category(
     sql.push_str(&format!(" ORDER BY {group_col}"));
 
     let mut stmt = conn.prepare(&sql)?;
-    let rows: Vec<(String, f64)> = stmt
+    let rows: Vec<(String, Delai)> = stmt
         .query_map(rusqlite::params_from_iter(params.iter().map(|p| p.as_ref())), |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
         })?
         .filter_map(|r| r.ok())
-        .filter(|(_, d)| *d >= 0.0)
+        .filter_map(|(cat, debut, fin)| Some((cat, cal.delai_str(&debut, &fin)?)))
         .collect();
 
-    let mut map: std::collections::BTreeMap<String, Vec<f64>> = std::collections::BTreeMap::new();
+    let mut map: std::collections::BTreeMap<String, Vec<Delai>> = std::collections::BTreeMap::new();
     for (cat, dur) in rows {
         map.entry(cat).or_default().push(dur);
     }
@@ -1460,6 +1548,34 @@ mod tests {
         assert_eq!(techs[1].technicien, "Martin");
         assert_eq!(techs[1].total, 1);
         assert_eq!(techs[1].nouveau, 1); // ticket 3 : Nouveau
+        assert_eq!(techs[0].groupe.as_deref(), Some("_DSI > _SUPPORT"));
+        assert_eq!(techs[1].groupe.as_deref(), Some("_DSI > _INFRA"));
+    }
+
+    #[test]
+    fn test_portees_seuils() {
+        let (conn, import_id) = setup();
+        // Dupont : un ticket clos dans un autre groupe, le stock vivant l'emporte
+        conn.execute(
+            "INSERT INTO tickets (
+                id, import_id, titre, statut, type_ticket, demandeur, date_ouverture,
+                est_vivant, technicien_principal, groupe_principal
+             ) VALUES (6, ?1, 'Ticket 6', 'Clos', 'Incident', 'user1', '2026-01-02', 0,
+                       'Dupont', '_DSI > _INFRA')",
+            rusqlite::params![import_id],
+        )
+        .unwrap();
+        assert_eq!(
+            get_groupes_import(&conn, import_id).unwrap(),
+            vec!["_DSI > _INFRA".to_string(), "_DSI > _SUPPORT".to_string()]
+        );
+        assert_eq!(
+            get_groupes_techniciens(&conn, import_id).unwrap(),
+            vec![
+                ("Dupont".to_string(), Some("_DSI > _SUPPORT".to_string())),
+                ("Martin".to_string(), Some("_DSI > _INFRA".to_string())),
+            ]
+        );
     }
 
     #[test]
@@ -1604,6 +1720,85 @@ mod tests {
         assert_eq!(couleur_charge(41, 20), "rouge");  // > 2.0 → rouge
         assert_eq!(couleur_charge(5, 0), "rouge");    // seuil 0 → rouge
     }
+
+    // ─── Drill-down catégories ──────────────────────────────────────────────
+
+    #[test]
+    fn test_category_tickets_subtree() {
+        let (conn, import_id) = setup();
+        conn.execute_batch(include_str!("sql/010_categories.sql")).unwrap();
+        let chemins = [(1, "Matériel > Poste > Écran"), (2, "Matériel > Poste"), (4, "Matériel")];
+        for (id, chemin) in chemins {
+            conn.execute(
+                "UPDATE tickets SET categorie = ?1 WHERE id = ?2",
+                rusqlite::params![chemin, id],
+            )
+            .unwrap();
+        }
+        // Rejoue la reprise des chemins de la migration
+        let reprise = include_str!("sql/010_categories.sql").split("-- Reprise").nth(1).unwrap();
+        conn.execute_batch(&format!("--{reprise}")).unwrap();
+
+        let poste: i64 = conn
+            .query_row("SELECT id FROM categories WHERE chemin = 'Matériel > Poste'", [], |r| r.get(0))
+            .unwrap();
+        let ids = |desc, vivants| -> Vec<u64> {
+            get_category_tickets(&conn, import_id, poste, desc, vivants)
+                .unwrap()
+                .iter()
+                .map(|t| t.id)
+                .collect()
+        };
+        assert_eq!(ids(true, false), vec![2, 1]);
+        assert_eq!(ids(false, false), vec![2]);
+
+        let racine: i64 = conn
+            .query_row("SELECT id FROM categories WHERE chemin = 'Matériel'", [], |r| r.get(0))
+            .unwrap();
+        let vivants = get_category_tickets(&conn, import_id, racine, true, true).unwrap();
+        assert_eq!(vivants.iter().map(|t| t.id).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(get_categories(&conn).unwrap().len(), 3);
+    }
+
+    // ─── Cartes de chaleur horaires ─────────────────────────────────────────
+
+    #[test]
+    fn test_tickets_horaires_filters() {
+        use crate::analyzer::heatmap::FiltreHoraire;
+        let (conn, import_id) = setup();
+        conn.execute_batch(include_str!("sql/003_date_resolution.sql")).unwrap();
+        conn.execute_batch(
+            "UPDATE tickets SET date_ouverture = date_ouverture || 'T09:30:00';
+             UPDATE tickets SET categorie = 'Réseau > Wifi' WHERE id = 1;
+             UPDATE tickets SET categorie = 'Réseau' WHERE id = 3;
+             UPDATE tickets SET categorie = 'Réseaux' WHERE id = 2;
+             UPDATE tickets SET date_resolution = '2026-01-02T16:45:00' WHERE id = 4;",
+        )
+        .unwrap();
+
+        let tous = get_tickets_horaires(&conn, import_id, &FiltreHoraire::default()).unwrap();
+        assert_eq!(tous.len(), 5);
+        assert!(tous.iter().all(|t| t.ouverture.format("%H:%M").to_string() == "09:30"));
+
+        let reseau = FiltreHoraire {
+            categorie: Some("Réseau".into()),
+            ..Default::default()
+        };
+        assert_eq!(get_tickets_horaires(&conn, import_id, &reseau).unwrap().len(), 2);
+
+        let support_incidents = FiltreHoraire {
+            groupe: Some("_DSI > _SUPPORT".into()),
+            type_ticket: Some("Incident".into()),
+            ..Default::default()
+        };
+        let t = get_tickets_horaires(&conn, import_id, &support_incidents).unwrap();
+        assert_eq!(t.len(), 2);
+        assert!(t.iter().all(|t| t.groupe.as_deref() == Some("_DSI > _SUPPORT")));
+        // Seul le ticket terminé porte une résolution
+        let resolus: Vec<String> =
+            t.iter().filter_map(|t| t.resolution).map(|r| r.format("%H:%M").to_string()).collect();
+        assert_eq!(resolus, vec!["16:45"]);
+    }
 }
 
 // ─── Fonctions Recommandation / Profiling ─────────────────────────────────────
@@ -1710,40 +1905,6 @@ pub(crate) fn get_technician_stock_counts(
     Ok(map)
 }
 
-/// Read cached profiling data from analytics_cache.
-pub(crate) fn get_cached_profiling(
-    conn: &Connection,
-    import_id: i64,
-) -> Result<Option<String>, rusqlite::Error> {
-    let result = conn.query_row(
-        "SELECT result FROM analytics_cache
-         WHERE import_id = ?1 AND analysis_type = 'technician_profiles' AND parameters = '{}'",
-        rusqlite::params![import_id],
-        |row| row.get::<_, String>(0),
-    );
-
-    match result {
-        Ok(json) => Ok(Some(json)),
-        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-        Err(e) => Err(e),
-    }
-}
-
-/// Write profiling data to analytics_cache.
-pub(crate) fn save_cached_profiling(
-    conn: &Connection,
-    import_id: i64,
-    json_result: &str,
-    duration_ms: i64,
-) -> Result<(), rusqlite::Error> {
-    conn.execute(
-        "INSERT OR REPLACE INTO analytics_cache (import_id, analysis_type, parameters, result, duration_ms)
-         VALUES (?1, 'technician_profiles', '{}', ?2, ?3)",
-        rusqlite::params![import_id, json_result, duration_ms],
-    )?;
-    Ok(())
-}
-
 /// Get count and average age of unassigned vivant tickets.
 pub(crate) fn get_unassigned_ticket_stats(
     conn: &Connection,
@@ -1884,6 +2045,43 @@ mod tests_bilan {
         }
     }
 
+    #[test]
+    fn test_week_keys_match_iso_across_year_boundary() {
+        use crate::analyzer::temporal::{generate_period_keys, period_key};
+
+        let dt = |s: &str| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S").unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        let expr = periode_expr("week", "?1");
+        for date in [
+            "2024-12-30T09:00:00", // ISO 2025-W01
+            "2025-01-05T09:00:00",
+            "2025-12-29T09:00:00", // ISO 2026-W01
+            "2026-01-01T09:00:00",
+            "2026-03-02T09:00:00",
+            "2027-01-01T09:00:00", // ISO 2026-W53
+            "2027-01-04T09:00:00",
+        ] {
+            let sql: String = conn
+                .query_row(&format!("SELECT {expr}"), [date], |r| r.get(0))
+                .unwrap();
+            assert_eq!(sql, period_key(dt(date), "week"), "{date}");
+        }
+
+        // Chaque clé SQL d'une fenêtre à cheval sur deux années existe dans
+        // les périodes générées
+        let (conn, _) = setup_bilan();
+        let rows =
+            get_bilan_entrees_par_periode(&conn, "2025-12-01", "2026-02-28", "week", None).unwrap();
+        let keys =
+            generate_period_keys(dt("2025-12-01T00:00:00"), dt("2026-02-28T00:00:00"), "week");
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows[0].0, "2025-W51");
+        assert_eq!(rows[1].0, "2026-W02");
+        for (p, _) in &rows {
+            assert!(keys.iter().any(|k| &k.0 == p), "{p}");
+        }
+    }
+
     #[test]
     fn test_entrees_avec_filtre_type() {
         // Filtre Incident : T10 (jan), T11 (jan), T13 (fev) → jan=2, fev=1
@@ -1914,6 +2112,34 @@ mod tests_bilan {
         assert!(rows.is_empty());
     }
 
+    #[test]
+    fn test_flux_par_dimension() {
+        // Entrants jan+fev : SUPPORT T10, T11 (jan) ; INFRA T12, T13 (fev)
+        // Sortants : Dupont T11 (jan), T14 (fev) ; Martin T13 (fev)
+        let (conn, _) = setup_bilan();
+        let mut entrees =
+            get_bilan_entrees_par_dimension(&conn, "2026-01-01", "2026-02-28", "month", "groupe_principal")
+                .unwrap();
+        entrees.sort();
+        assert_eq!(
+            entrees,
+            vec![
+                ("2026-01".to_string(), "_DSI > _SUPPORT".to_string(), 2),
+                ("2026-02".to_string(), "_DSI > _INFRA".to_string(), 2),
+            ]
+        );
+        let sorties =
+            get_bilan_sorties_par_dimension(&conn, "2026-01-01", "2026-02-28", "month", "technicien_principal")
+                .unwrap();
+        let dupont: usize = sorties.iter().filter(|r| r.1 == "Dupont").map(|r| r.2).sum();
+        assert_eq!(dupont, 2);
+        assert_eq!(sorties.len(), 3);
+
+        let mut stock = get_stock_vivant_par_dimension(&conn, "technicien_principal").unwrap();
+        stock.sort();
+        assert_eq!(stock, vec![("Dupont".to_string(), 1), ("Martin".to_string(), 2)]);
+    }
+
     // ─── get_bilan_sorties_par_periode ───────────────────────────────────────
 
     #[test]
@@ -2081,3 +2307,912 @@ mod tests_bilan {
         assert!(get_bilan_ventilation_par_groupe(&conn, "2026-01-01", "2026-01-31").is_err());
     }
 }
+
+// ─── Diff entre imports ───────────────────────────────────────────────────────
+
+/// Import précédant `import_id` (par id croissant), s'il existe.
+pub(crate) fn get_previous_import_id(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Option<i64>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT MAX(id) FROM imports WHERE id < ?1",
+        rusqlite::params![import_id],
+        |row| row.get::<_, Option<i64>>(0),
+    )
+}
+
+/// Instantané de tous les tickets d'un import (vivants et terminés) pour le diff.
+pub(crate) fn get_ticket_snapshots(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<crate::analyzer::diff::TicketSnapshot>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, titre, statut, est_vivant, technicien_principal, groupe_principal,
+                COALESCE(priorite_label, CAST(priorite AS TEXT)), categorie
+         FROM tickets
+         WHERE import_id = ?1",
+    )?;
+
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        Ok(crate::analyzer::diff::TicketSnapshot {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            statut: row.get(2)?,
+            est_vivant: row.get::<_, i64>(3)? != 0,
+            technicien: row.get(4)?,
+            groupe: row.get(5)?,
+            priorite: row.get(6)?,
+            categorie: row.get(7)?,
+        })
+    })?;
+
+    rows.collect()
+}
+
+// ─── Stopwords utilisateur ───────────────────────────────────────────────────
+
+/// Stopwords ajoutés par l'utilisateur, triés.
+pub(crate) fn get_user_stopwords(conn: &Connection) -> Result<Vec<String>, rusqlite::Error> {
+    conn.prepare("SELECT word FROM user_stopwords ORDER BY word")?
+        .query_map([], |row| row.get(0))?
+        .collect()
+}
+
+// ─── Dictionnaires de mots-clés ───────────────────────────────────────────────
+
+/// Entrées actives (catégorie, mot-clé, is_regex) pour la détection de patterns.
+pub(crate) fn get_active_keyword_entries(
+    conn: &Connection,
+) -> Result<Vec<(String, String, bool)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT category, keyword, is_regex
+         FROM keyword_dictionaries
+         WHERE is_active = 1
+         ORDER BY category, id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get::<_, i64>(2)? != 0))
+    })?;
+    rows.collect()
+}
+
+fn map_keyword_entry(
+    row: &rusqlite::Row<'_>,
+) -> Result<crate::nlp::patterns::KeywordDictionaryEntry, rusqlite::Error> {
+    Ok(crate::nlp::patterns::KeywordDictionaryEntry {
+        id: row.get(0)?,
+        category: row.get(1)?,
+        keyword: row.get(2)?,
+        is_regex: row.get::<_, i64>(3)? != 0,
+        is_active: row.get::<_, i64>(4)? != 0,
+        created_at: row.get(5)?,
+    })
+}
+
+pub(crate) fn get_keyword_dictionaries(
+    conn: &Connection,
+    category: Option<&str>,
+) -> Result<Vec<crate::nlp::patterns::KeywordDictionaryEntry>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, category, keyword, is_regex, is_active, created_at
+         FROM keyword_dictionaries
+         WHERE ?1 IS NULL OR category = ?1
+         ORDER BY category, keyword",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![category], map_keyword_entry)?;
+    rows.collect()
+}
+
+pub(crate) fn get_keyword_dictionary_entry(
+    conn: &Connection,
+    id: i64,
+) -> Result<Option<crate::nlp::patterns::KeywordDictionaryEntry>, rusqlite::Error> {
+    use rusqlite::OptionalExtension;
+    conn.query_row(
+        "SELECT id, category, keyword, is_regex, is_active, created_at
+         FROM keyword_dictionaries WHERE id = ?1",
+        rusqlite::params![id],
+        map_keyword_entry,
+    )
+    .optional()
+}
+
+/// Textes des tickets vivants d'un import pour la détection de patterns.
+pub(crate) fn get_pattern_ticket_texts(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<PatternTicketRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, titre, technicien_principal, groupe_principal, inactivite_jours,
+                action_recommandee, suivis_description, solution
+         FROM tickets
+         WHERE import_id = ?1 AND est_vivant = 1 AND COALESCE(nombre_suivis, 0) > 0
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        Ok(PatternTicketRow {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            technicien: row.get(2)?,
+            groupe: row.get(3)?,
+            inactivite_jours: row.get(4)?,
+            action_recommandee: row.get(5)?,
+            suivis_description: row.get(6)?,
+            solution: row.get(7)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub(crate) struct PatternTicketRow {
+    pub id: u64,
+    pub titre: String,
+    pub technicien: Option<String>,
+    pub groupe: Option<String>,
+    pub inactivite_jours: Option<i64>,
+    pub action_recommandee: Option<String>,
+    pub suivis_description: String,
+    pub solution: String,
+}
+
+// ─── Règles de classification ─────────────────────────────────────────────────
+
+/// Toutes les règles (actives ou non), par position croissante.
+pub(crate) fn get_classification_rules(
+    conn: &Connection,
+) -> Result<Vec<crate::analyzer::rules::ClassificationRule>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, position, name, is_active, conditions, action, motif
+         FROM classification_rules
+         ORDER BY position, id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        let conditions: String = row.get(4)?;
+        let conditions = serde_json::from_str(&conditions).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+        })?;
+        Ok(crate::analyzer::rules::ClassificationRule {
+            id: Some(row.get(0)?),
+            position: row.get(1)?,
+            name: row.get(2)?,
+            is_active: row.get::<_, i64>(3)? != 0,
+            conditions,
+            action: row.get(5)?,
+            motif: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Tickets vivants d'un import, avec les champs évalués par les règles.
+pub(crate) fn get_rule_tickets(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<crate::analyzer::rules::RuleTicketRow>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, titre, statut, type_ticket, priorite_label, groupe_principal, categorie,
+                anciennete_jours, inactivite_jours, COALESCE(nombre_suivis, 0),
+                suivis_description, solution, technicien_principal
+         FROM tickets
+         WHERE import_id = ?1 AND est_vivant = 1
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        Ok(crate::analyzer::rules::RuleTicketRow {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            statut: row.get(2)?,
+            type_ticket: row.get(3)?,
+            priorite: row.get(4)?,
+            groupe: row.get(5)?,
+            technicien: row.get(12)?,
+            categorie: row.get(6)?,
+            anciennete: row.get(7)?,
+            inactivite: row.get(8)?,
+            nombre_suivis: row.get(9)?,
+            suivis: row.get(10)?,
+            solution: row.get(11)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// ─── SLA ──────────────────────────────────────────────────────────────────────
+
+/// Toutes les définitions SLA (actives ou non).
+pub(crate) fn get_sla_definitions(
+    conn: &Connection,
+) -> Result<Vec<crate::analyzer::sla::SlaDefinition>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, priorite, type_ticket, categorie, tto_heures, ttr_heures, is_active
+         FROM sla_definitions
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(crate::analyzer::sla::SlaDefinition {
+            id: Some(row.get(0)?),
+            name: row.get(1)?,
+            priorite: row.get(2)?,
+            type_ticket: row.get(3)?,
+            categorie: row.get(4)?,
+            tto_heures: row.get(5)?,
+            ttr_heures: row.get(6)?,
+            is_active: row.get::<_, i64>(7)? != 0,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Tickets d'un import pour l'évaluation SLA : tous les vivants, et les
+/// terminés dont la résolution tombe dans la période (bornes incluses).
+pub(crate) fn get_sla_tickets(
+    conn: &Connection,
+    import_id: i64,
+    date_debut: Option<&str>,
+    date_fin: Option<&str>,
+) -> Result<Vec<crate::analyzer::sla::SlaTicket>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, titre, priorite_label, type_ticket, categorie, groupe_principal,
+                technicien_principal, est_vivant,
+                (COALESCE(technicien_principal, '') != '' OR COALESCE(nombre_suivis, 0) > 0),
+                date_ouverture, COALESCE(date_resolution, date_cloture_approx)
+         FROM tickets
+         WHERE import_id = ?1
+           AND (est_vivant = 1 OR (
+                (?2 IS NULL OR COALESCE(date_resolution, date_cloture_approx) >= ?2)
+            AND (?3 IS NULL OR COALESCE(date_resolution, date_cloture_approx) < date(?3, '+1 day'))))
+         ORDER BY id",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id, date_debut, date_fin], |row| {
+        let ouverture: String = row.get(9)?;
+        let resolution: Option<String> = row.get(10)?;
+        // Date d'ouverture illisible : ticket ignoré
+        let Some(date_ouverture) = crate::parser::deserializers::parse_french_datetime(&ouverture) else {
+            return Ok(None);
+        };
+        Ok(Some(crate::analyzer::sla::SlaTicket {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            priorite: row.get(2)?,
+            type_ticket: row.get(3)?,
+            categorie: row.get(4)?,
+            groupe: row.get::<_, Option<String>>(5)?.filter(|s| !s.is_empty()),
+            technicien: row.get::<_, Option<String>>(6)?.filter(|s| !s.is_empty()),
+            est_vivant: row.get::<_, i64>(7)? != 0,
+            pris_en_charge: row.get::<_, i64>(8)? != 0,
+            date_ouverture,
+            date_resolution: resolution.as_deref().and_then(crate::parser::deserializers::parse_french_datetime),
+        }))
+    })?;
+    rows.filter_map(Result::transpose).collect()
+}
+
+/// Date de l'import, référence des échéances des tickets vivants.
+pub(crate) fn get_import_reference_date(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Option<chrono::NaiveDateTime>, rusqlite::Error> {
+    use rusqlite::OptionalExtension;
+    let date: Option<String> = conn
+        .query_row(
+            "SELECT import_date FROM imports WHERE id = ?1",
+            rusqlite::params![import_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(date.as_deref().and_then(crate::parser::deserializers::parse_french_datetime))
+}
+
+// ─── Reconstitution du stock ─────────────────────────────────────────────────
+
+/// Dates d'entrée et de sortie du stock de chaque ticket de l'import.
+/// Sortie = date de résolution, à défaut date de clôture approchée ; un
+/// terminé sans aucune de ces dates est ignoré (sortie inconnue).
+pub(crate) fn get_backlog_flux(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<crate::analyzer::backlog::TicketFlux>, rusqlite::Error> {
+    use crate::parser::deserializers::parse_french_datetime;
+    let mut stmt = conn.prepare(
+        "SELECT date_ouverture, est_vivant,
+                COALESCE(NULLIF(date_resolution, ''), date_cloture_approx),
+                technicien_principal, groupe_principal, categorie, type_ticket
+         FROM tickets
+         WHERE import_id = ?1 AND date_ouverture IS NOT NULL",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        let ouverture: String = row.get(0)?;
+        let vivant: bool = row.get(1)?;
+        let sortie: Option<String> = row.get(2)?;
+        let Some(ouverture) = parse_french_datetime(&ouverture) else {
+            return Ok(None);
+        };
+        let sortie = match (vivant, sortie.as_deref().and_then(parse_french_datetime)) {
+            (true, _) => None,
+            (false, Some(s)) => Some(s.date()),
+            (false, None) => return Ok(None),
+        };
+        Ok(Some(crate::analyzer::backlog::TicketFlux {
+            ouverture: ouverture.date(),
+            sortie,
+            technicien: row.get(3)?,
+            groupe: row.get(4)?,
+            categorie: row.get(5)?,
+            type_ticket: row.get(6)?,
+        }))
+    })?;
+    let mut result = Vec::new();
+    for row in rows {
+        result.extend(row?);
+    }
+    Ok(result)
+}
+
+// ─── Réouvertures / FCR ──────────────────────────────────────────────────────
+
+/// Nombre d'imports antérieurs à `import_id` (historique exploitable).
+pub(crate) fn count_imports_before(conn: &Connection, import_id: i64) -> Result<i64, rusqlite::Error> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM imports WHERE id < ?1",
+        rusqlite::params![import_id],
+        |row| row.get(0),
+    )
+}
+
+/// Résolutions observées dans un import, filtrées sur leur date (bornes
+/// incluses) :
+/// - terminés : date de résolution (à défaut clôture approchée) ;
+/// - vivants terminés dans un import antérieur : date de cette clôture ;
+/// - vivants dont un suivi mentionne une réouverture : dernière modification.
+pub(crate) fn get_resolutions_observees(
+    conn: &Connection,
+    import_id: i64,
+    date_debut: Option<&str>,
+    date_fin: Option<&str>,
+) -> Result<Vec<crate::analyzer::reopen::ResolutionObservee>, rusqlite::Error> {
+    use crate::analyzer::reopen::{mentionne_reouverture, tours_de_suivi, ResolutionObservee, SourceReouverture};
+    use crate::parser::deserializers::parse_french_datetime;
+
+    let borne = |s: Option<&str>| {
+        s.and_then(|s| chrono::NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d").ok())
+    };
+    let (debut, fin) = (borne(date_debut), borne(date_fin));
+
+    // Clôture antérieure suivie d'un retour à l'état vivant (jusqu'à cet import)
+    let mut stmt = conn.prepare(
+        "SELECT t.est_vivant,
+                COALESCE(NULLIF(t.date_resolution, ''), t.date_cloture_approx),
+                t.derniere_modification, t.suivis_description, t.nombre_suivis,
+                t.technicien_principal, t.groupe_principal, t.categorie,
+                (SELECT MAX(COALESCE(NULLIF(o.date_resolution, ''), o.date_cloture_approx))
+                 FROM tickets o
+                 WHERE o.id = t.id AND o.import_id < ?1 AND o.est_vivant = 0
+                   AND EXISTS (SELECT 1 FROM tickets n
+                               WHERE n.id = o.id AND n.est_vivant = 1
+                                 AND n.import_id > o.import_id AND n.import_id <= ?1))
+         FROM tickets t
+         WHERE t.import_id = ?1",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        let vivant: bool = row.get(0)?;
+        let resolution: Option<String> = row.get(1)?;
+        let modification: Option<String> = row.get(2)?;
+        let suivis: String = row.get(3)?;
+        let cloture_anterieure: Option<String> = row.get(8)?;
+
+        let historique = cloture_anterieure.is_some();
+        let reouverture = if historique {
+            Some(SourceReouverture::Historique)
+        } else if mentionne_reouverture(&suivis) {
+            Some(SourceReouverture::Suivis)
+        } else {
+            None
+        };
+        let date = match (vivant, reouverture) {
+            (false, _) => resolution,
+            (true, Some(SourceReouverture::Historique)) => cloture_anterieure,
+            (true, Some(SourceReouverture::Suivis)) => modification,
+            (true, None) => None,
+        };
+        let Some(date) = date.as_deref().and_then(parse_french_datetime) else {
+            return Ok(None);
+        };
+        Ok(Some(ResolutionObservee {
+            date,
+            termine: !vivant,
+            reouverture,
+            tours_suivi: tours_de_suivi(&suivis, row.get(4)?),
+            technicien: row.get(5)?,
+            groupe: row.get(6)?,
+            categorie: row.get(7)?,
+        }))
+    })?;
+
+    let mut result = Vec::new();
+    for row in rows {
+        if let Some(r) = row? {
+            let jour = r.date.date();
+            if debut.is_some_and(|d| jour < d) || fin.is_some_and(|f| jour > f) {
+                continue;
+            }
+            result.push(r);
+        }
+    }
+    Ok(result)
+}
+
+// ─── Demandeurs / annuaire ───────────────────────────────────────────────────
+
+/// Ticket de l'import avec son service (annuaire, comparaison insensible à
+/// la casse). Délai de résolution en jours calendaires, comme le MTTR.
+pub(crate) struct TicketDemandeurBrut {
+    pub id: u64,
+    pub titre: String,
+    pub demandeur: String,
+    pub service: Option<String>,
+    pub date_ouverture: String,
+    pub est_vivant: bool,
+    pub delai_resolution_jours: Option<f64>,
+}
+
+pub(crate) fn get_tickets_demandeurs(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<TicketDemandeurBrut>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.titre, TRIM(t.demandeur), a.service, t.date_ouverture, t.est_vivant,
+                CASE WHEN t.est_vivant = 0 AND t.date_cloture_approx IS NOT NULL
+                     THEN julianday(t.date_cloture_approx) - julianday(t.date_ouverture) END
+         FROM tickets t
+         LEFT JOIN annuaire_demandeurs a ON a.demandeur = TRIM(t.demandeur) COLLATE NOCASE
+         WHERE t.import_id = ?1 AND t.date_ouverture IS NOT NULL",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        Ok(TicketDemandeurBrut {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            demandeur: row.get(2)?,
+            service: row.get(3)?,
+            date_ouverture: row.get(4)?,
+            est_vivant: row.get(5)?,
+            delai_resolution_jours: row.get::<_, Option<f64>>(6)?.filter(|d| *d >= 0.0),
+        })
+    })?;
+    rows.collect()
+}
+
+/// Remplace intégralement l'annuaire (à appeler dans une transaction).
+pub(crate) fn replace_annuaire(
+    conn: &Connection,
+    entrees: &[crate::parser::directory::EntreeAnnuaire],
+) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM annuaire_demandeurs", [])?;
+    let mut stmt =
+        conn.prepare("INSERT OR REPLACE INTO annuaire_demandeurs (demandeur, service) VALUES (?1, ?2)")?;
+    for e in entrees {
+        stmt.execute(rusqlite::params![e.demandeur, e.service])?;
+    }
+    count_annuaire(conn)
+}
+
+pub(crate) fn count_annuaire(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    conn.query_row("SELECT COUNT(*) FROM annuaire_demandeurs", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map(|n| n as usize)
+}
+
+// ─── Parcours d'escalade ─────────────────────────────────────────────────────
+
+pub(crate) fn get_parcours_tickets(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<crate::analyzer::escalation::TicketParcours>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, titre, groupes, techniciens, est_vivant,
+                CASE WHEN est_vivant = 0 AND date_cloture_approx IS NOT NULL
+                     THEN julianday(date_cloture_approx) - julianday(date_ouverture) END
+         FROM tickets
+         WHERE import_id = ?1",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        let groupes: String = row.get(2)?;
+        let techniciens: String = row.get(3)?;
+        Ok(crate::analyzer::escalation::TicketParcours {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            groupes: serde_json::from_str(&groupes).unwrap_or_default(),
+            techniciens: serde_json::from_str(&techniciens).unwrap_or_default(),
+            est_vivant: row.get(4)?,
+            delai_resolution_jours: row.get::<_, Option<f64>>(5)?.filter(|d| *d >= 0.0),
+        })
+    })?;
+    rows.collect()
+}
+
+// ─── Cohortes ────────────────────────────────────────────────────────────────
+
+/// Tickets de l'import pour l'analyse de cohortes ; `dimension_col` est une
+/// colonne validée par l'appelant. Les terminés sans date de sortie sont
+/// ignorés.
+pub(crate) fn get_tickets_cohorte(
+    conn: &Connection,
+    import_id: i64,
+    dimension_col: Option<&str>,
+) -> Result<Vec<crate::analyzer::cohort::TicketCohorte>, rusqlite::Error> {
+    use crate::parser::deserializers::parse_french_datetime;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT date_ouverture, est_vivant,
+                COALESCE(NULLIF(date_resolution, ''), date_cloture_approx), {}
+         FROM tickets
+         WHERE import_id = ?1 AND date_ouverture IS NOT NULL",
+        dimension_col.unwrap_or("NULL"),
+    ))?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        let ouverture: String = row.get(0)?;
+        let vivant: bool = row.get(1)?;
+        let sortie: Option<String> = row.get(2)?;
+        let Some(ouverture) = parse_french_datetime(&ouverture) else {
+            return Ok(None);
+        };
+        let sortie = match (vivant, sortie.as_deref().and_then(parse_french_datetime)) {
+            (true, _) => None,
+            (false, Some(s)) => Some(s),
+            (false, None) => return Ok(None),
+        };
+        Ok(Some(crate::analyzer::cohort::TicketCohorte {
+            ouverture,
+            sortie,
+            dimension: row.get(3)?,
+        }))
+    })?;
+    let mut result = Vec::new();
+    for row in rows {
+        result.extend(row?);
+    }
+    Ok(result)
+}
+
+// ─── Hiérarchie des catégories ───────────────────────────────────────────────
+
+pub(crate) fn get_categories(
+    conn: &Connection,
+) -> Result<Vec<crate::analyzer::category_tree::NoeudCategorie>, rusqlite::Error> {
+    let mut stmt =
+        conn.prepare_cached("SELECT id, parent_id, nom, chemin, niveau FROM categories")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(crate::analyzer::category_tree::NoeudCategorie {
+            id: row.get(0)?,
+            parent_id: row.get(1)?,
+            nom: row.get(2)?,
+            chemin: row.get(3)?,
+            niveau: row.get::<_, i64>(4)? as usize,
+        })
+    })?;
+    rows.collect()
+}
+
+pub(crate) fn get_tickets_categorie(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<crate::analyzer::category_tree::TicketCategorie>, rusqlite::Error> {
+    use crate::parser::deserializers::parse_french_datetime;
+    let mut stmt = conn.prepare(
+        "SELECT categorie_id, date_ouverture, date_cloture_approx, est_vivant,
+                anciennete_jours, techniciens, technicien_principal,
+                COALESCE(nombre_suivis, 0),
+                CASE WHEN est_vivant = 0 AND date_cloture_approx IS NOT NULL
+                     THEN julianday(date_cloture_approx) - julianday(date_ouverture) END
+         FROM tickets
+         WHERE import_id = ?1 AND date_ouverture IS NOT NULL",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        let ouverture: String = row.get(1)?;
+        let Some(ouverture) = parse_french_datetime(&ouverture) else {
+            return Ok(None);
+        };
+        let est_vivant: bool = row.get(3)?;
+        let techniciens: String = row.get(5)?;
+        let nb_techniciens = serde_json::from_str::<Vec<String>>(&techniciens)
+            .map(|v| v.len())
+            .unwrap_or(0);
+        let principal: Option<String> = row.get(6)?;
+        // N1 strict, comme le tableau de bord
+        let n1 = !est_vivant
+            && nb_techniciens <= 1
+            && principal.is_some_and(|p| !p.is_empty())
+            && row.get::<_, i64>(7)? <= 1;
+        Ok(Some(crate::analyzer::category_tree::TicketCategorie {
+            categorie_id: row.get(0)?,
+            ouverture,
+            sortie: row
+                .get::<_, Option<String>>(2)?
+                .as_deref()
+                .and_then(parse_french_datetime),
+            est_vivant,
+            age_jours: row.get::<_, Option<i64>>(4)?.map(|a| a as f64),
+            delai_resolution_jours: row.get::<_, Option<f64>>(8)?.filter(|d| *d >= 0.0),
+            n1,
+        }))
+    })?;
+    let mut result = Vec::new();
+    for row in rows {
+        result.extend(row?);
+    }
+    Ok(result)
+}
+
+/// Tickets d'une catégorie (et de ses descendants), du plus ancien au plus
+/// récent.
+pub(crate) fn get_category_tickets(
+    conn: &Connection,
+    import_id: i64,
+    categorie_id: i64,
+    inclure_descendants: bool,
+    vivants_seulement: bool,
+) -> Result<Vec<TicketSummary>, rusqlite::Error> {
+    let mut sql = String::from(
+        "WITH RECURSIVE sous_arbre(id) AS (
+             SELECT ?2
+             UNION
+             SELECT c.id FROM categories c JOIN sous_arbre s ON c.parent_id = s.id
+             WHERE ?3
+         )
+         SELECT id, titre, statut, type_ticket, technicien_principal, groupe_principal,
+                date_ouverture, derniere_modification, anciennete_jours, inactivite_jours,
+                nombre_suivis, action_recommandee, motif_classification
+         FROM tickets
+         WHERE import_id = ?1 AND categorie_id IN (SELECT id FROM sous_arbre)",
+    );
+    if vivants_seulement {
+        sql.push_str(" AND est_vivant = 1");
+    }
+    sql.push_str(" ORDER BY COALESCE(anciennete_jours, 0) DESC, id");
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt
+        .query_map(
+            rusqlite::params![import_id, categorie_id, inclure_descendants],
+            |row| {
+                Ok(TicketSummary {
+                    id: row.get::<_, u64>(0)?,
+                    titre: row.get(1)?,
+                    statut: row.get(2)?,
+                    type_ticket: row.get(3)?,
+                    technicien_principal: row.get(4)?,
+                    groupe_principal: row.get(5)?,
+                    date_ouverture: row.get(6)?,
+                    derniere_modification: row.get(7)?,
+                    anciennete_jours: row.get(8)?,
+                    inactivite_jours: row.get(9)?,
+                    nombre_suivis: row.get::<_, Option<i64>>(10)?.map(|v| v as u32),
+                    action_recommandee: row.get(11)?,
+                    motif_classification: row.get(12)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+// ─── Priorités ───────────────────────────────────────────────────────────────
+
+/// Tickets ouverts entre `date_debut` et `date_fin` (bornes incluses, dates
+/// ISO) avec priorité, urgence et impact.
+pub(crate) fn get_tickets_priorite(
+    conn: &Connection,
+    import_id: i64,
+    date_debut: Option<&str>,
+    date_fin: Option<&str>,
+    vivants_seulement: bool,
+) -> Result<Vec<crate::analyzer::priority::TicketPriorite>, rusqlite::Error> {
+    use crate::parser::deserializers::parse_french_datetime;
+
+    let borne = |s: Option<&str>| {
+        s.and_then(|s| chrono::NaiveDate::parse_from_str(s.get(..10).unwrap_or(s), "%Y-%m-%d").ok())
+    };
+    let (debut, fin) = (borne(date_debut), borne(date_fin));
+
+    let mut sql = String::from(
+        "SELECT id, titre, priorite, urgence, impact, date_ouverture, est_vivant,
+                CASE WHEN est_vivant = 0 AND date_cloture_approx IS NOT NULL
+                     THEN julianday(date_cloture_approx) - julianday(date_ouverture) END,
+                technicien_principal
+         FROM tickets
+         WHERE import_id = ?1 AND date_ouverture IS NOT NULL",
+    );
+    if vivants_seulement {
+        sql.push_str(" AND est_vivant = 1");
+    }
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        let ouverture: String = row.get(5)?;
+        let Some(ouverture) = parse_french_datetime(&ouverture) else {
+            return Ok(None);
+        };
+        if debut.is_some_and(|d| ouverture.date() < d) || fin.is_some_and(|f| ouverture.date() > f) {
+            return Ok(None);
+        }
+        Ok(Some(crate::analyzer::priority::TicketPriorite {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            priorite: row.get(2)?,
+            urgence: row.get(3)?,
+            impact: row.get(4)?,
+            ouverture,
+            est_vivant: row.get(6)?,
+            delai_resolution_jours: row.get::<_, Option<f64>>(7)?.filter(|d| *d >= 0.0),
+            technicien: row.get::<_, Option<String>>(8)?.filter(|t| !t.is_empty()),
+        }))
+    })?;
+    let mut result = Vec::new();
+    for row in rows {
+        result.extend(row?);
+    }
+    Ok(result)
+}
+
+// ─── Interventions fournisseurs ──────────────────────────────────────────────
+
+pub(crate) fn get_tickets_fournisseur(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<crate::analyzer::vendor::TicketFournisseur>, rusqlite::Error> {
+    use crate::parser::vendor::parse_interventions;
+    let mut stmt = conn.prepare(
+        "SELECT id, titre, technicien_principal, groupe_principal, est_vivant, anciennete_jours,
+                CASE WHEN est_vivant = 0 AND date_cloture_approx IS NOT NULL
+                     THEN julianday(date_cloture_approx) - julianday(date_ouverture) END,
+                intervention_fournisseur
+         FROM tickets
+         WHERE import_id = ?1",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| {
+        let brut: Option<String> = row.get(7)?;
+        Ok(crate::analyzer::vendor::TicketFournisseur {
+            id: row.get::<_, i64>(0)? as u64,
+            titre: row.get(1)?,
+            technicien: row.get::<_, Option<String>>(2)?.filter(|t| !t.is_empty()),
+            groupe: row.get::<_, Option<String>>(3)?.filter(|g| !g.is_empty()),
+            est_vivant: row.get(4)?,
+            anciennete_jours: row.get::<_, Option<i64>>(5)?.map(|a| a as f64),
+            delai_resolution_jours: row.get::<_, Option<f64>>(6)?.filter(|d| *d >= 0.0),
+            interventions: parse_interventions(brut.as_deref().unwrap_or("")),
+        })
+    })?;
+    rows.collect()
+}
+
+// ─── Cartes de chaleur horaires ──────────────────────────────────────────────
+
+pub(crate) fn get_tickets_horaires(
+    conn: &Connection,
+    import_id: i64,
+    filtre: &crate::analyzer::heatmap::FiltreHoraire,
+) -> Result<Vec<crate::analyzer::heatmap::TicketHoraire>, rusqlite::Error> {
+    use crate::parser::deserializers::parse_french_datetime;
+
+    let mut sql = String::from(
+        "SELECT groupe_principal, date_ouverture,
+                CASE WHEN est_vivant = 0 THEN COALESCE(date_resolution, date_cloture_approx) END,
+                CASE WHEN est_vivant = 0 THEN derniere_modification END
+         FROM tickets
+         WHERE import_id = ?1 AND date_ouverture IS NOT NULL",
+    );
+    let mut params: Vec<String> = Vec::new();
+    if let Some(ref g) = filtre.groupe {
+        params.push(g.clone());
+        sql.push_str(&format!(" AND groupe_principal = ?{}", params.len() + 1));
+    }
+    if let Some(ref t) = filtre.type_ticket {
+        params.push(t.clone());
+        sql.push_str(&format!(" AND type_ticket = ?{}", params.len() + 1));
+    }
+    if let Some(ref c) = filtre.categorie {
+        params.push(c.clone());
+        let n = params.len() + 1;
+        sql.push_str(&format!(" AND (categorie = ?{n} OR categorie LIKE ?{n} || ' > %')"));
+    }
+
+    let mut stmt = conn.prepare(&sql)?;
+    let mut bind: Vec<&dyn rusqlite::types::ToSql> = vec![&import_id];
+    bind.extend(params.iter().map(|p| p as &dyn rusqlite::types::ToSql));
+    let rows = stmt.query_map(bind.as_slice(), |row| {
+        let date = |i: usize| -> rusqlite::Result<Option<chrono::NaiveDateTime>> {
+            Ok(row.get::<_, Option<String>>(i)?.as_deref().and_then(parse_french_datetime))
+        };
+        let Some(ouverture) = date(1)? else {
+            return Ok(None);
+        };
+        Ok(Some(crate::analyzer::heatmap::TicketHoraire {
+            groupe: row.get::<_, Option<String>>(0)?.filter(|g| !g.is_empty()),
+            ouverture,
+            resolution: date(2)?,
+            premiere_reponse: date(3)?,
+        }))
+    })?;
+    rows.filter_map(|r| r.transpose()).collect()
+}
+
+// ─── Équité de charge ────────────────────────────────────────────────────────
+
+/// Stock vivant et flux de la fenêtre par couple technicien × groupe.
+pub(crate) fn get_charges_techniciens(
+    conn: &Connection,
+    import_id: i64,
+    date_from: &str,
+    date_to: &str,
+) -> Result<Vec<crate::analyzer::workload::ChargeBrute>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT technicien_principal, groupe_principal,
+                SUM(CASE WHEN est_vivant = 1 THEN 1 ELSE 0 END),
+                SUM(CASE WHEN date_ouverture >= ?2 AND date_ouverture < date(?3, '+1 day')
+                         THEN 1 ELSE 0 END),
+                SUM(CASE WHEN est_vivant = 0
+                          AND COALESCE(date_resolution, date_cloture_approx) >= ?2
+                          AND COALESCE(date_resolution, date_cloture_approx) < date(?3, '+1 day')
+                         THEN 1 ELSE 0 END)
+         FROM tickets
+         WHERE import_id = ?1
+           AND technicien_principal IS NOT NULL AND technicien_principal != ''
+         GROUP BY technicien_principal, groupe_principal",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id, date_from, date_to], |row| {
+        Ok(crate::analyzer::workload::ChargeBrute {
+            technicien: row.get(0)?,
+            groupe: row.get::<_, Option<String>>(1)?.filter(|g| !g.is_empty()),
+            stock: row.get::<_, i64>(2)? as usize,
+            entrants: row.get::<_, i64>(3)? as usize,
+            sortants: row.get::<_, i64>(4)? as usize,
+        })
+    })?;
+    rows.collect()
+}
+
+// ─── Seuils par portée ───────────────────────────────────────────────────────
+
+/// Groupes distincts de l'import.
+pub(crate) fn get_groupes_import(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<String>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT groupe_principal FROM tickets
+         WHERE import_id = ?1 AND groupe_principal IS NOT NULL AND groupe_principal != ''
+         ORDER BY groupe_principal",
+    )?;
+    let rows = stmt.query_map(rusqlite::params![import_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+/// Techniciens de l'import avec leur groupe majoritaire (stock vivant
+/// d'abord), pour la résolution des seuils.
+pub(crate) fn get_groupes_techniciens(
+    conn: &Connection,
+    import_id: i64,
+) -> Result<Vec<(String, Option<String>)>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT technicien_principal, NULLIF(groupe_principal, '')
+         FROM tickets
+         WHERE import_id = ?1
+           AND technicien_principal IS NOT NULL AND technicien_principal != ''
+         GROUP BY technicien_principal, NULLIF(groupe_principal, '')
+         ORDER BY technicien_principal,
+                  NULLIF(groupe_principal, '') IS NULL,
+                  SUM(est_vivant) DESC,
+                  NULLIF(groupe_principal, '')",
+    )?;
+    let lignes = stmt
+        .query_map(rusqlite::params![import_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut techniciens: Vec<(String, Option<String>)> = Vec::new();
+    for (tech, groupe) in lignes {
+        if techniciens.last().map(|(t, _)| t) != Some(&tech) {
+            techniciens.push((tech, groupe));
+        }
+    }
+    Ok(techniciens)
+}
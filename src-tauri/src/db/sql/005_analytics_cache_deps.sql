@@ -0,0 +1,80 @@
+-- ============================================================
+-- TABLE : cache_dependencies
+-- Version de chaque source de données dont dépendent les résultats
+-- d'analytics_cache. Incrémentée par trigger à chaque modification ;
+-- une entrée de cache dont l'empreinte ne correspond plus est périmée.
+-- Les tickets s'écrivent en masse : pas de trigger par ligne, la version
+-- suit l'import (insertion / fusion) et le code qui les modifie
+-- (db::cache::bump_dependency).
+-- ============================================================
+CREATE TABLE IF NOT EXISTS cache_dependencies (
+    scope    TEXT PRIMARY KEY,
+    version  INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT OR IGNORE INTO cache_dependencies (scope) VALUES
+    ('tickets'), ('config'), ('stopwords'), ('dictionaries');
+
+-- Tickets : nouvel import, fusion (recalcul des compteurs)
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_imports_insert
+    AFTER INSERT ON imports
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'tickets';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_imports_merge
+    AFTER UPDATE OF parsed_rows, vivants_count, termines_count ON imports
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'tickets';
+END;
+
+-- Configuration
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_config_insert
+    AFTER INSERT ON config
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'config';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_config_update
+    AFTER UPDATE ON config
+    WHEN OLD.value IS NOT NEW.value
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'config';
+END;
+
+-- Stopwords utilisateur
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_stopwords_insert
+    AFTER INSERT ON user_stopwords
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'stopwords';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_stopwords_delete
+    AFTER DELETE ON user_stopwords
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'stopwords';
+END;
+
+-- Dictionnaires de mots-clés
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_dictionaries_insert
+    AFTER INSERT ON keyword_dictionaries
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'dictionaries';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_dictionaries_update
+    AFTER UPDATE ON keyword_dictionaries
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'dictionaries';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_dictionaries_delete
+    AFTER DELETE ON keyword_dictionaries
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'dictionaries';
+END;
+
+-- Empreinte des dépendances au moment du calcul + statistiques d'usage
+ALTER TABLE analytics_cache ADD COLUMN dependencies TEXT NOT NULL DEFAULT '';
+ALTER TABLE analytics_cache ADD COLUMN hit_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE analytics_cache ADD COLUMN last_hit_at TEXT;
@@ -0,0 +1,31 @@
+-- ============================================================
+-- TABLE : audit_log
+-- Journal des actions modifiant les données (imports, config,
+-- stopwords). Ajout seul : modification et suppression refusées.
+-- before_value / after_value : JSON (NULL si sans objet).
+-- acteur : utilisateur du système à l'origine de l'action.
+-- ============================================================
+CREATE TABLE IF NOT EXISTS audit_log (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+    action        TEXT NOT NULL,
+    acteur        TEXT,
+    target        TEXT,
+    before_value  TEXT,
+    after_value   TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_created ON audit_log(created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_action ON audit_log(action, created_at);
+
+CREATE TRIGGER IF NOT EXISTS trg_audit_log_no_update
+    BEFORE UPDATE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log est en ajout seul');
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_audit_log_no_delete
+    BEFORE DELETE ON audit_log
+BEGIN
+    SELECT RAISE(ABORT, 'audit_log est en ajout seul');
+END;
@@ -0,0 +1,33 @@
+-- ============================================================
+-- TABLE : classification_rules
+-- Règles de classification des tickets vivants, évaluées par
+-- position croissante : la première règle satisfaite l'emporte.
+-- conditions : JSON (voir commands::rules::RuleConditions). Un seuil
+-- peut être un nombre ou le nom d'un paramètre de config
+-- (ex. "seuilAncienneteCloturer").
+-- motif : gabarit, {anciennete} {inactivite} {suivis} et les
+-- paramètres de config entre accolades sont remplacés.
+-- ============================================================
+CREATE TABLE IF NOT EXISTS classification_rules (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    position    INTEGER NOT NULL,
+    name        TEXT NOT NULL,
+    is_active   INTEGER NOT NULL DEFAULT 1,
+    conditions  TEXT NOT NULL DEFAULT '{}',
+    action      TEXT NOT NULL,
+    motif       TEXT NOT NULL,
+    created_at  TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_classification_rules_position ON classification_rules(position);
+
+-- Jeu par défaut : reproduit la cascade Zombie > Ancien > Inactif > Normal.
+-- Deux règles inactives exploitent seuil_inactivite_cloturer et
+-- seuil_anciennete_relancer, à activer au besoin.
+INSERT INTO classification_rules (position, name, is_active, conditions, action, motif) VALUES
+    (10, 'Zombie', 1, '{"suivisInf":1}', 'qualifier', 'Ticket sans suivi'),
+    (20, 'Ancien', 1, '{"ancienneteSup":"seuilAncienneteCloturer"}', 'clôturer', 'Ancienneté > {seuilAncienneteCloturer}j'),
+    (30, 'Inactif prolongé', 0, '{"inactiviteSup":"seuilInactiviteCloturer"}', 'clôturer', 'Inactif depuis {inactivite}j (> {seuilInactiviteCloturer}j)'),
+    (40, 'Inactif', 1, '{"inactiviteSup":"seuilInactiviteRelancer"}', 'relancer', 'Inactif depuis {inactivite}j'),
+    (50, 'Ancienneté à relancer', 0, '{"ancienneteSup":"seuilAncienneteRelancer"}', 'relancer', 'Ancienneté > {seuilAncienneteRelancer}j'),
+    (1000, 'En cours normal', 1, '{}', 'suivre', 'En cours normal');
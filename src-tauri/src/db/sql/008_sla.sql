@@ -0,0 +1,49 @@
+-- ============================================================
+-- TABLE : sla_definitions
+-- Engagements de service : délai de prise en charge (TTO) et de
+-- résolution (TTR), en heures. Critères optionnels (NULL = tous) ;
+-- la définition la plus spécifique s'applique à un ticket
+-- (catégorie : préfixe, couvre les sous-catégories).
+-- ============================================================
+CREATE TABLE IF NOT EXISTS sla_definitions (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    name          TEXT NOT NULL,
+    priorite      TEXT,
+    type_ticket   TEXT,
+    categorie     TEXT,
+    tto_heures    REAL,
+    ttr_heures    REAL,
+    is_active     INTEGER NOT NULL DEFAULT 1,
+    created_at    TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Jeu par défaut par priorité (à adapter aux engagements réels)
+INSERT INTO sla_definitions (name, priorite, tto_heures, ttr_heures) VALUES
+    ('Standard', NULL, 8, 72),
+    ('Très haute', 'Très haute', 1, 8),
+    ('Haute', 'Haute', 4, 24),
+    ('Majeure', 'Majeure', 4, 24),
+    ('Moyenne', 'Moyenne', 8, 72),
+    ('Basse', 'Basse', 24, 120),
+    ('Très basse', 'Très basse', 48, 240);
+
+-- Invalidation du cache d'analyse
+INSERT OR IGNORE INTO cache_dependencies (scope) VALUES ('sla');
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_sla_insert
+    AFTER INSERT ON sla_definitions
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'sla';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_sla_update
+    AFTER UPDATE ON sla_definitions
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'sla';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_sla_delete
+    AFTER DELETE ON sla_definitions
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'sla';
+END;
@@ -0,0 +1,33 @@
+-- ============================================================
+-- TABLE : annuaire_demandeurs
+-- Rattachement optionnel demandeur → service, importé depuis un
+-- fichier annuaire (remplacé intégralement à chaque import).
+-- ============================================================
+CREATE TABLE IF NOT EXISTS annuaire_demandeurs (
+    demandeur     TEXT PRIMARY KEY COLLATE NOCASE,
+    service       TEXT NOT NULL,
+    imported_at   TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_tickets_demandeur ON tickets(import_id, demandeur);
+
+-- Invalidation du cache d'analyse
+INSERT OR IGNORE INTO cache_dependencies (scope) VALUES ('annuaire');
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_annuaire_insert
+    AFTER INSERT ON annuaire_demandeurs
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'annuaire';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_annuaire_update
+    AFTER UPDATE ON annuaire_demandeurs
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'annuaire';
+END;
+
+CREATE TRIGGER IF NOT EXISTS trg_cache_dep_annuaire_delete
+    AFTER DELETE ON annuaire_demandeurs
+BEGIN
+    UPDATE cache_dependencies SET version = version + 1 WHERE scope = 'annuaire';
+END;
@@ -0,0 +1,65 @@
+-- ============================================================
+-- TABLE : categories
+-- Hiérarchie ITIL complète (profondeur quelconque), un nœud par
+-- chemin "Niveau 1 > Niveau 2 > ...", partagée entre imports.
+-- ============================================================
+CREATE TABLE IF NOT EXISTS categories (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    parent_id   INTEGER REFERENCES categories(id),
+    nom         TEXT NOT NULL,
+    chemin      TEXT NOT NULL UNIQUE,
+    niveau      INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_categories_parent ON categories(parent_id);
+
+ALTER TABLE tickets ADD COLUMN categorie_id INTEGER REFERENCES categories(id);
+
+CREATE INDEX IF NOT EXISTS idx_tickets_categorie_id ON tickets(import_id, categorie_id);
+
+-- Reprise des tickets existants : chaque catégorie est découpée sur " > ",
+-- niveaux rognés et segments vides ignorés, comme à l'import.
+CREATE TEMP TABLE reprise_categories AS
+WITH RECURSIVE segments(source, rang, nom, reste) AS (
+    SELECT categorie, 0, '', categorie || ' > '
+    FROM (SELECT DISTINCT categorie FROM tickets WHERE categorie IS NOT NULL)
+    UNION ALL
+    SELECT source,
+           rang + 1,
+           TRIM(substr(reste, 1, instr(reste, ' > ') - 1)),
+           substr(reste, instr(reste, ' > ') + 3)
+    FROM segments
+    WHERE reste != ''
+),
+niveaux(source, niveau, nom) AS (
+    SELECT source, ROW_NUMBER() OVER (PARTITION BY source ORDER BY rang), nom
+    FROM segments
+    WHERE nom != ''
+),
+chemins(source, niveau, nom, chemin) AS (
+    SELECT source, niveau, nom, nom FROM niveaux WHERE niveau = 1
+    UNION ALL
+    SELECT n.source, n.niveau, n.nom, c.chemin || ' > ' || n.nom
+    FROM chemins c
+    JOIN niveaux n ON n.source = c.source AND n.niveau = c.niveau + 1
+)
+SELECT source, niveau, nom, chemin FROM chemins;
+
+INSERT OR IGNORE INTO categories (nom, chemin, niveau)
+SELECT nom, chemin, MIN(niveau) FROM reprise_categories GROUP BY chemin ORDER BY MIN(niveau);
+
+UPDATE categories
+SET parent_id = (SELECT p.id FROM categories p
+                 WHERE p.chemin = substr(categories.chemin, 1,
+                                         length(categories.chemin) - length(categories.nom) - 3))
+WHERE niveau > 1;
+
+-- Le chemin complet d'une catégorie est son préfixe le plus profond
+UPDATE tickets
+SET categorie_id = (SELECT c.id FROM reprise_categories r
+                    JOIN categories c ON c.chemin = r.chemin
+                    WHERE r.source = tickets.categorie
+                    ORDER BY r.niveau DESC LIMIT 1)
+WHERE categorie IS NOT NULL;
+
+DROP TABLE reprise_categories;
@@ -0,0 +1,5 @@
+-- ============================================================
+-- Impact GLPI (1 = très bas … 5 = très haut), base de la matrice
+-- urgence × impact → priorité.
+-- ============================================================
+ALTER TABLE tickets ADD COLUMN impact INTEGER;
@@ -0,0 +1,87 @@
+use crate::config::AppConfig;
+use crate::db::audit::{AuditAction, AuditEntry};
+use crate::error::AppError;
+use crate::export::{create_header_format, portees_exportees, write_seuils_sheet};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde_json::Value;
+
+fn xlsx_err(e: XlsxError) -> AppError {
+    AppError::Custom(e.to_string())
+}
+
+/// Génère l'export du journal d'audit : une ligne par action, valeurs
+/// avant / après au format JSON compact, puis les seuils en vigueur.
+pub fn generate_audit_report(
+    entries: &[AuditEntry],
+    config: &AppConfig,
+) -> Result<Vec<u8>, AppError> {
+    let mut wb = Workbook::new();
+    write_journal(&mut wb, entries).map_err(xlsx_err)?;
+    write_seuils_sheet(&mut wb, &portees_exportees(config, &[]), false).map_err(xlsx_err)?;
+    wb.save_to_buffer().map_err(xlsx_err)
+}
+
+fn json_cell(value: &Option<Value>) -> String {
+    value.as_ref().map(Value::to_string).unwrap_or_default()
+}
+
+fn write_journal(wb: &mut Workbook, entries: &[AuditEntry]) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Journal d'audit")?;
+
+    let hdr = create_header_format();
+    let wrap = Format::new().set_text_wrap();
+
+    let headers = ["Date", "Acteur", "Action", "Cible", "Avant", "Après"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(0, col as u16, *h, &hdr)?;
+    }
+
+    for (i, e) in entries.iter().enumerate() {
+        let row = (i + 1) as u32;
+        ws.write(row, 0, &e.created_at)?;
+        ws.write(row, 1, e.acteur.as_deref().unwrap_or(""))?;
+        ws.write(row, 2, AuditAction::label(&e.action))?;
+        ws.write(row, 3, e.target.as_deref().unwrap_or(""))?;
+        ws.write_with_format(row, 4, json_cell(&e.before), &wrap)?;
+        ws.write_with_format(row, 5, json_cell(&e.after), &wrap)?;
+    }
+
+    ws.set_column_width(0, 20)?;
+    ws.set_column_width(1, 16)?;
+    ws.set_column_width(2, 32)?;
+    ws.set_column_width(3, 30)?;
+    ws.set_column_width(4, 60)?;
+    ws.set_column_width(5, 60)?;
+    ws.set_freeze_panes(1, 0)?;
+    if !entries.is_empty() {
+        ws.autofilter(0, 0, entries.len() as u32, 5)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_generate_audit_report_xlsx_signature() {
+        let entries = vec![AuditEntry {
+            id: 1,
+            created_at: "2026-03-02 10:15:00".into(),
+            action: "update_config".into(),
+            acteur: Some("jdupont".into()),
+            target: Some("seuilCouleurVert".into()),
+            before: Some(json!({"seuilCouleurVert": 10})),
+            after: Some(json!({"seuilCouleurVert": 12})),
+        }];
+        let bytes = generate_audit_report(&entries, &AppConfig::default()).unwrap();
+        assert_eq!(bytes[0], 0x50, "Expected PK byte 0");
+        assert_eq!(bytes[1], 0x4B, "Expected PK byte 1");
+
+        let empty = generate_audit_report(&[], &AppConfig::default()).unwrap();
+        assert_eq!(empty[0], 0x50);
+    }
+}
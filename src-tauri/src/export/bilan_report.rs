@@ -1,24 +1,35 @@
-use crate::commands::bilan::{BilanRequest, BilanTemporel};
+use crate::commands::bilan::{BilanComparaison, BilanRequest, BilanTemporel};
+use crate::config::AppConfig;
 use crate::error::AppError;
-use crate::export::{create_header_format, create_integer_format, create_number_format};
-use rust_xlsxwriter::{Workbook, XlsxError};
+use crate::export::{
+    create_header_format, create_integer_format, create_number_format, create_percent_format,
+    portees_exportees, write_seuils_sheet,
+};
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
 
 fn xlsx_err(e: XlsxError) -> AppError {
     AppError::Custom(e.to_string())
 }
 
-/// Génère le rapport bilan d'activité Excel, 3 onglets (RG-049, US022):
+/// Génère le rapport bilan d'activité Excel, 5 onglets (RG-049, US022):
 /// - "Volume"     : tableau entrants/sortants/delta/stock cumulé par période
 /// - "Tendance"   : KPI globaux + série temporelle pour graphique côté frontend
 /// - "Ventilation": répartition par technicien/groupe (si disponible)
+/// - "Comparaison": écarts avec la fenêtre de référence (si demandée)
+/// - "Seuils"     : seuils en vigueur
 pub fn generate_bilan_report(
     bilan: &BilanTemporel,
     _request: &BilanRequest,
+    config: &AppConfig,
 ) -> Result<Vec<u8>, AppError> {
     let mut wb = Workbook::new();
     write_volume(&mut wb, bilan).map_err(xlsx_err)?;
     write_tendance(&mut wb, bilan).map_err(xlsx_err)?;
     write_ventilation(&mut wb, bilan).map_err(xlsx_err)?;
+    if let Some(ref comp) = bilan.comparaison {
+        write_comparaison(&mut wb, comp).map_err(xlsx_err)?;
+    }
+    write_seuils_sheet(&mut wb, &portees_exportees(config, &[]), true).map_err(xlsx_err)?;
     wb.save_to_buffer().map_err(xlsx_err)
 }
 
@@ -117,6 +128,22 @@ fn write_tendance(wb: &mut Workbook, bilan: &BilanTemporel) -> Result<(), XlsxEr
         ws.write_with_format(row, 3, p.delta as f64, &int)?;
     }
 
+    // Distribution des délais de résolution (jeu de tranches configuré)
+    if let Some(ref res) = bilan.resolution {
+        let pct = create_percent_format();
+        let tranche_headers = ["Délai de résolution", "Nb tickets", "%"];
+        for (col, h) in tranche_headers.iter().enumerate() {
+            ws.write_with_format(series_row, (col + 5) as u16, *h, &hdr)?;
+        }
+        for (i, t) in res.tranches.iter().enumerate() {
+            let row = series_row + 1 + i as u32;
+            ws.write(row, 5, t.label.as_str())?;
+            ws.write_with_format(row, 6, t.count as f64, &int)?;
+            ws.write_with_format(row, 7, t.pourcentage / 100.0, &pct)?;
+        }
+        ws.set_column_width(5, 22)?;
+    }
+
     ws.set_column_width(0, 24)?;
     ws.set_column_width(1, 18)?;
 
@@ -161,6 +188,63 @@ fn write_ventilation(wb: &mut Workbook, bilan: &BilanTemporel) -> Result<(), Xls
     Ok(())
 }
 
+// ── Onglet 4 : Comparaison ───────────────────────────────────────────────────
+
+fn write_comparaison(wb: &mut Workbook, comp: &BilanComparaison) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Comparaison")?;
+
+    let hdr = create_header_format();
+    let num = create_number_format();
+    let favorable = Format::new().set_font_color("006100");
+    let defavorable = Format::new().set_font_color("9C0006");
+
+    ws.write_with_format(0, 0, "Période courante", &hdr)?;
+    ws.write(0, 1, format!("{} → {}", comp.fenetre_courante.date_debut, comp.fenetre_courante.date_fin))?;
+    ws.write_with_format(1, 0, "Période de référence", &hdr)?;
+    ws.write(1, 1, format!("{} → {}", comp.fenetre_reference.date_debut, comp.fenetre_reference.date_fin))?;
+
+    let header_row = 3u32;
+    let headers = [
+        "Indicateur", "Courant", "Référence", "Écart", "Écart %", "Tendance", "Significativité", "z",
+    ];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(header_row, col as u16, *h, &hdr)?;
+    }
+
+    for (i, d) in comp.deltas.iter().enumerate() {
+        let row = header_row + 1 + i as u32;
+        ws.write(row, 0, d.libelle.as_str())?;
+        for (col, val) in [(1u16, d.courant), (2, d.reference), (3, d.delta_absolu), (4, d.delta_relatif_pct)] {
+            if let Some(v) = val {
+                ws.write_with_format(row, col, v, &num)?;
+            }
+        }
+        if let Some(ref t) = d.tendance {
+            match d.favorable {
+                Some(true) => ws.write_with_format(row, 5, t.as_str(), &favorable)?,
+                Some(false) => ws.write_with_format(row, 5, t.as_str(), &defavorable)?,
+                None => ws.write(row, 5, t.as_str())?,
+            };
+        }
+        ws.write(row, 6, d.significativite.replace('_', " "))?;
+        if let Some(z) = d.z_score {
+            ws.write_with_format(row, 7, z, &num)?;
+        }
+    }
+
+    if !comp.deltas.is_empty() {
+        ws.set_freeze_panes(header_row + 1, 0)?;
+    }
+    ws.set_column_width(0, 34)?;
+    ws.set_column_width(1, 26)?;
+    for col in 2u16..=7 {
+        ws.set_column_width(col, 14)?;
+    }
+
+    Ok(())
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -218,6 +302,7 @@ mod tests {
             },
             ventilation,
             resolution: None,
+            comparaison: None,
         }
     }
 
@@ -227,14 +312,36 @@ mod tests {
             date_from: "2026-01-01".into(),
             date_to: "2026-02-28".into(),
             group_by: None,
+            comparaison: None,
         }
     }
 
+    #[test]
+    fn test_generate_bilan_report_with_resolution() {
+        use crate::commands::bilan::{BilanResolution, ResolutionTranche};
+        let mut bilan = make_bilan(false);
+        bilan.resolution = Some(BilanResolution {
+            tranches: vec![
+                ResolutionTranche { label: "< 4h".into(), count: 3, pourcentage: 60.0 },
+                ResolutionTranche { label: "> 4h".into(), count: 2, pourcentage: 40.0 },
+            ],
+            total_resolus: 5,
+            mttr_jours: 1.2,
+            mediane_jours: 0.1,
+            ecart_type_jours: 2.0,
+            mttr_jours_ouvres: 1.0,
+            mediane_jours_ouvres: 0.1,
+        });
+        let bytes = generate_bilan_report(&bilan, &make_request(), &AppConfig::default()).unwrap();
+        assert_eq!(bytes[0], 0x50);
+        assert_eq!(bytes[1], 0x4B);
+    }
+
     #[test]
     fn test_generate_bilan_report_xlsx_signature() {
         let bilan = make_bilan(false);
         let request = make_request();
-        let result = generate_bilan_report(&bilan, &request);
+        let result = generate_bilan_report(&bilan, &request, &AppConfig::default());
         assert!(result.is_ok(), "generate_bilan_report failed: {:?}", result.err());
         let bytes = result.unwrap();
         assert!(bytes.len() > 4, "XLSX too small");
@@ -246,13 +353,36 @@ mod tests {
     fn test_generate_bilan_report_with_ventilation() {
         let bilan = make_bilan(true);
         let request = make_request();
-        let result = generate_bilan_report(&bilan, &request);
+        let result = generate_bilan_report(&bilan, &request, &AppConfig::default());
         assert!(result.is_ok(), "generate_bilan_report with ventilation failed: {:?}", result.err());
         let bytes = result.unwrap();
         assert_eq!(bytes[0], 0x50);
         assert_eq!(bytes[1], 0x4B);
     }
 
+    #[test]
+    fn test_generate_bilan_report_with_comparison() {
+        use crate::analyzer::comparison::{comparer, Fenetre, Mesure, Sens};
+        use crate::commands::bilan::BilanComparaison;
+
+        let reference = make_bilan(false);
+        let mut bilan = make_bilan(false);
+        bilan.comparaison = Some(BilanComparaison {
+            fenetre_courante: Fenetre { date_debut: "2026-01-01".into(), date_fin: "2026-02-28".into() },
+            fenetre_reference: Fenetre { date_debut: "2025-01-01".into(), date_fin: "2025-02-28".into() },
+            deltas: vec![
+                comparer("totalEntrees", "Total entrants", Mesure::comptage(18.0), Mesure::comptage(12.0), Sens::Neutre),
+                comparer("totalSorties", "Total sortants", Mesure::comptage(17.0), Mesure::comptage(20.0), Sens::Hausse),
+                comparer("mttr", "MTTR (j)", Mesure::simple(Some(4.0)), Mesure::simple(None), Sens::Baisse),
+            ],
+            totaux: reference.totaux,
+            resolution: None,
+        });
+        let bytes = generate_bilan_report(&bilan, &make_request(), &AppConfig::default()).unwrap();
+        assert_eq!(bytes[0], 0x50);
+        assert_eq!(bytes[1], 0x4B);
+    }
+
     #[test]
     fn test_generate_bilan_report_empty_periods() {
         let bilan = BilanTemporel {
@@ -266,9 +396,10 @@ mod tests {
             },
             ventilation: None,
             resolution: None,
+            comparaison: None,
         };
         let request = make_request();
-        let result = generate_bilan_report(&bilan, &request);
+        let result = generate_bilan_report(&bilan, &request, &AppConfig::default());
         assert!(result.is_ok());
         let bytes = result.unwrap();
         assert_eq!(bytes[0], 0x50);
@@ -0,0 +1,135 @@
+use crate::analyzer::cohort::{CohorteAnalyse, CohorteLigne};
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::export::{
+    create_header_format, create_integer_format, create_percent_format, portees_exportees,
+    write_seuils_sheet,
+};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+fn xlsx_err(e: XlsxError) -> AppError {
+    AppError::Custom(e.to_string())
+}
+
+/// Génère le rapport de cohortes, onglet "Cohortes" : tableau global puis
+/// un bloc par modalité de la dimension choisie ; onglet "Seuils" en vigueur.
+pub fn generate_cohort_report(
+    analyse: &CohorteAnalyse,
+    config: &AppConfig,
+) -> Result<Vec<u8>, AppError> {
+    let mut wb = Workbook::new();
+    write_cohortes(&mut wb, analyse).map_err(xlsx_err)?;
+    write_seuils_sheet(&mut wb, &portees_exportees(config, &[]), false).map_err(xlsx_err)?;
+    wb.save_to_buffer().map_err(xlsx_err)
+}
+
+fn write_cohortes(wb: &mut Workbook, analyse: &CohorteAnalyse) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Cohortes")?;
+
+    let titre = Format::new().set_bold().set_font_size(12);
+    ws.write_with_format(0, 0, "Cohortes par période d'ouverture", &titre)?;
+    ws.write(
+        1,
+        0,
+        format!(
+            "Observé au {} — taux calculés sur les tickets ayant atteint l'horizon",
+            analyse.date_reference
+        ),
+    )?;
+
+    let mut row = write_bloc(ws, 3, "Tous tickets", &analyse.cohortes, &analyse.horizons)?;
+    for d in &analyse.par_dimension {
+        row = write_bloc(ws, row + 1, &d.label, &d.cohortes, &analyse.horizons)?;
+    }
+
+    ws.set_freeze_panes(0, 1)?;
+    ws.set_column_width(0, 22)?;
+    for col in 1..=(analyse.horizons.len() as u16 + 2) {
+        ws.set_column_width(col, 14)?;
+    }
+    Ok(())
+}
+
+/// Écrit un bloc (titre, en-tête, lignes) et retourne la ligne suivante.
+fn write_bloc(
+    ws: &mut Worksheet,
+    first_row: u32,
+    label: &str,
+    cohortes: &[CohorteLigne],
+    horizons: &[i64],
+) -> Result<u32, XlsxError> {
+    let hdr = create_header_format();
+    let int = create_integer_format();
+    let pct = create_percent_format();
+    let bold = Format::new().set_bold();
+
+    ws.write_with_format(first_row, 0, label, &bold)?;
+    let header_row = first_row + 1;
+    ws.write_with_format(header_row, 0, "Période", &hdr)?;
+    ws.write_with_format(header_row, 1, "Ouverts", &hdr)?;
+    for (i, h) in horizons.iter().enumerate() {
+        ws.write_with_format(header_row, 2 + i as u16, format!("Résolus ≤ {h} j"), &hdr)?;
+    }
+    let col_ouverts = 2 + horizons.len() as u16;
+    ws.write_with_format(header_row, col_ouverts, "Encore ouverts", &hdr)?;
+
+    for (i, c) in cohortes.iter().enumerate() {
+        let r = header_row + 1 + i as u32;
+        ws.write(r, 0, c.period_label.as_str())?;
+        ws.write_with_format(r, 1, c.effectif as f64, &int)?;
+        for (j, h) in c.horizons.iter().enumerate() {
+            // Horizon non atteint : cellule vide
+            if let Some(taux) = h.taux_resolus {
+                ws.write_with_format(r, 2 + j as u16, taux / 100.0, &pct)?;
+            }
+        }
+        if c.effectif > 0 {
+            ws.write_with_format(r, col_ouverts, c.taux_encore_ouverts / 100.0, &pct)?;
+        }
+    }
+    Ok(header_row + 1 + cohortes.len() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::cohort::{CohorteDimension, CohorteHorizon};
+
+    fn ligne(key: &str, effectif: usize) -> CohorteLigne {
+        CohorteLigne {
+            period_key: key.into(),
+            period_label: key.into(),
+            effectif,
+            horizons: [1, 7, 30, 90]
+                .iter()
+                .map(|&jours| CohorteHorizon {
+                    jours,
+                    observables: effectif,
+                    resolus: effectif / 2,
+                    taux_resolus: (jours < 90).then_some(50.0),
+                })
+                .collect(),
+            encore_ouverts: 1,
+            taux_encore_ouverts: 10.0,
+        }
+    }
+
+    #[test]
+    fn test_generate_cohort_report() {
+        let analyse = CohorteAnalyse {
+            granularite: "month".into(),
+            dimension: Some("type".into()),
+            date_reference: "2026-03-01".into(),
+            horizons: vec![1, 7, 30, 90],
+            cohortes: vec![ligne("2026-01", 10), ligne("2026-02", 0)],
+            par_dimension: vec![CohorteDimension {
+                label: "Incident".into(),
+                effectif: 10,
+                cohortes: vec![ligne("2026-01", 10)],
+            }],
+        };
+        let bytes = generate_cohort_report(&analyse, &AppConfig::default()).unwrap();
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,263 @@
+use crate::analyzer::diff::{DiffAggregate, DiffChange, DiffTicket, ImportDiff};
+use crate::config::AppConfig;
+use crate::error::AppError;
+use crate::export::{
+    create_header_format, create_integer_format, portees_exportees, write_seuils_sheet,
+};
+use rust_xlsxwriter::{Workbook, XlsxError};
+
+fn xlsx_err(e: XlsxError) -> AppError {
+    AppError::Custom(e.to_string())
+}
+
+/// Génère le rapport de diff entre deux imports :
+/// - "Résumé"        : compteurs par type de mouvement
+/// - "Mouvements"    : nouveaux / disparus / clôturés / réouverts
+/// - "Changements"   : réaffectations, priorité, catégorie (avant → après)
+/// - "Par technicien" / "Par groupe" : agrégats
+/// - "Seuils"        : seuils en vigueur
+pub fn generate_diff_report(diff: &ImportDiff, config: &AppConfig) -> Result<Vec<u8>, AppError> {
+    let mut wb = Workbook::new();
+    write_resume(&mut wb, diff).map_err(xlsx_err)?;
+    write_mouvements(&mut wb, diff).map_err(xlsx_err)?;
+    write_changements(&mut wb, diff).map_err(xlsx_err)?;
+    write_aggregates(
+        &mut wb,
+        "Par technicien",
+        "Technicien",
+        &diff.par_technicien,
+    )
+    .map_err(xlsx_err)?;
+    write_aggregates(&mut wb, "Par groupe", "Groupe", &diff.par_groupe).map_err(xlsx_err)?;
+    write_seuils_sheet(&mut wb, &portees_exportees(config, &[]), false).map_err(xlsx_err)?;
+    wb.save_to_buffer().map_err(xlsx_err)
+}
+
+// ── Onglet 1 : Résumé ────────────────────────────────────────────────────────
+
+fn write_resume(wb: &mut Workbook, diff: &ImportDiff) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Résumé")?;
+
+    let hdr = create_header_format();
+    let int = create_integer_format();
+
+    ws.write_with_format(0, 0, "Indicateur", &hdr)?;
+    ws.write_with_format(0, 1, "Valeur", &hdr)?;
+
+    let r = &diff.resume;
+    let rows: &[(&str, usize)] = &[
+        ("Import de référence", diff.import_from as usize),
+        ("Import comparé", diff.import_to as usize),
+        ("Nouveaux", r.nouveaux),
+        ("Disparus", r.disparus),
+        ("Clôturés", r.clotures),
+        ("Réouverts", r.reouverts),
+        ("Changement de technicien", r.changements_technicien),
+        ("Changement de groupe", r.changements_groupe),
+        ("Changement de priorité", r.changements_priorite),
+        ("Changement de catégorie", r.changements_categorie),
+    ];
+    for (i, (label, val)) in rows.iter().enumerate() {
+        let row = (i + 1) as u32;
+        ws.write(row, 0, *label)?;
+        ws.write_with_format(row, 1, *val as f64, &int)?;
+    }
+
+    ws.set_column_width(0, 28)?;
+    ws.set_column_width(1, 14)?;
+
+    Ok(())
+}
+
+// ── Onglet 2 : Mouvements ────────────────────────────────────────────────────
+
+fn write_mouvements(wb: &mut Workbook, diff: &ImportDiff) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Mouvements")?;
+
+    let hdr = create_header_format();
+
+    let headers = ["Mouvement", "ID", "Titre", "Statut", "Technicien", "Groupe"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(0, col as u16, *h, &hdr)?;
+    }
+
+    let sections: [(&str, &[DiffTicket]); 4] = [
+        ("Nouveau", &diff.nouveaux),
+        ("Disparu", &diff.disparus),
+        ("Clôturé", &diff.clotures),
+        ("Réouvert", &diff.reouverts),
+    ];
+
+    let mut row = 1u32;
+    for (mouvement, tickets) in sections {
+        for t in tickets {
+            ws.write(row, 0, mouvement)?;
+            ws.write(row, 1, t.id as f64)?;
+            ws.write(row, 2, t.titre.as_str())?;
+            ws.write(row, 3, t.statut.as_str())?;
+            ws.write(row, 4, t.technicien.as_deref().unwrap_or(""))?;
+            ws.write(row, 5, t.groupe.as_deref().unwrap_or(""))?;
+            row += 1;
+        }
+    }
+
+    if row > 1 {
+        ws.set_freeze_panes(1, 0)?;
+        ws.autofilter(0, 0, row - 1, (headers.len() - 1) as u16)?;
+    }
+
+    ws.set_column_width(0, 14)?;
+    ws.set_column_width(1, 8)?;
+    ws.set_column_width(2, 45)?;
+    ws.set_column_width(3, 18)?;
+    ws.set_column_width(4, 24)?;
+    ws.set_column_width(5, 30)?;
+
+    Ok(())
+}
+
+// ── Onglet 3 : Changements ───────────────────────────────────────────────────
+
+fn write_changements(wb: &mut Workbook, diff: &ImportDiff) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Changements")?;
+
+    let hdr = create_header_format();
+
+    let headers = ["Champ", "ID", "Titre", "Avant", "Après"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(0, col as u16, *h, &hdr)?;
+    }
+
+    let sections: [(&str, &[DiffChange]); 4] = [
+        ("Technicien", &diff.changements_technicien),
+        ("Groupe", &diff.changements_groupe),
+        ("Priorité", &diff.changements_priorite),
+        ("Catégorie", &diff.changements_categorie),
+    ];
+
+    let mut row = 1u32;
+    for (champ, changes) in sections {
+        for c in changes {
+            ws.write(row, 0, champ)?;
+            ws.write(row, 1, c.id as f64)?;
+            ws.write(row, 2, c.titre.as_str())?;
+            ws.write(row, 3, c.avant.as_deref().unwrap_or(""))?;
+            ws.write(row, 4, c.apres.as_deref().unwrap_or(""))?;
+            row += 1;
+        }
+    }
+
+    if row > 1 {
+        ws.set_freeze_panes(1, 0)?;
+        ws.autofilter(0, 0, row - 1, (headers.len() - 1) as u16)?;
+    }
+
+    ws.set_column_width(0, 14)?;
+    ws.set_column_width(1, 8)?;
+    ws.set_column_width(2, 45)?;
+    ws.set_column_width(3, 28)?;
+    ws.set_column_width(4, 28)?;
+
+    Ok(())
+}
+
+// ── Onglets 4/5 : Agrégats ───────────────────────────────────────────────────
+
+fn write_aggregates(
+    wb: &mut Workbook,
+    sheet_name: &str,
+    label_header: &str,
+    aggregates: &[DiffAggregate],
+) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name(sheet_name)?;
+
+    let hdr = create_header_format();
+    let int = create_integer_format();
+
+    let headers = [
+        label_header,
+        "Nouveaux",
+        "Disparus",
+        "Clôturés",
+        "Réouverts",
+        "Transferts entrants",
+        "Transferts sortants",
+    ];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(0, col as u16, *h, &hdr)?;
+    }
+
+    for (i, a) in aggregates.iter().enumerate() {
+        let row = (i + 1) as u32;
+        ws.write(row, 0, a.label.as_str())?;
+        ws.write_with_format(row, 1, a.nouveaux as f64, &int)?;
+        ws.write_with_format(row, 2, a.disparus as f64, &int)?;
+        ws.write_with_format(row, 3, a.clotures as f64, &int)?;
+        ws.write_with_format(row, 4, a.reouverts as f64, &int)?;
+        ws.write_with_format(row, 5, a.transferts_entrants as f64, &int)?;
+        ws.write_with_format(row, 6, a.transferts_sortants as f64, &int)?;
+    }
+
+    if !aggregates.is_empty() {
+        let last_row = aggregates.len() as u32;
+        ws.set_freeze_panes(1, 0)?;
+        ws.autofilter(0, 0, last_row, (headers.len() - 1) as u16)?;
+    }
+
+    ws.set_column_width(0, 30)?;
+    for col in 1u16..=6 {
+        ws.set_column_width(col, 14)?;
+    }
+
+    Ok(())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::diff::{compute_import_diff, TicketSnapshot};
+
+    fn snap(id: u64, vivant: bool, tech: &str) -> TicketSnapshot {
+        TicketSnapshot {
+            id,
+            titre: format!("Ticket {id}"),
+            statut: "En cours".into(),
+            est_vivant: vivant,
+            technicien: Some(tech.into()),
+            groupe: Some("DSI > Support".into()),
+            priorite: Some("Moyenne".into()),
+            categorie: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_diff_report_xlsx_signature() {
+        let before = vec![snap(1, true, "Alice"), snap(2, true, "Bob")];
+        let after = vec![snap(1, false, "Alice"), snap(3, true, "Bob")];
+        let diff = compute_import_diff(1, 2, &before, &after);
+
+        let result = generate_diff_report(&diff, &AppConfig::default());
+        assert!(
+            result.is_ok(),
+            "generate_diff_report failed: {:?}",
+            result.err()
+        );
+        let bytes = result.unwrap();
+        assert_eq!(bytes[0], 0x50, "Expected PK byte 0");
+        assert_eq!(bytes[1], 0x4B, "Expected PK byte 1");
+    }
+
+    #[test]
+    fn test_generate_diff_report_empty() {
+        let diff = compute_import_diff(1, 2, &[], &[]);
+        let bytes = generate_diff_report(&diff, &AppConfig::default()).unwrap();
+        assert_eq!(bytes[0], 0x50);
+        assert_eq!(bytes[1], 0x4B);
+    }
+}
@@ -1,11 +1,18 @@
 pub mod bilan_report;
+pub mod cohort_report;
+pub mod audit_report;
+pub mod diff_report;
 pub mod plan_action;
 pub mod stock_report;
 
 use rust_xlsxwriter::{
-    ConditionalFormatCell, ConditionalFormatCellRule, Format, FormatBorder, Worksheet, XlsxError,
+    ConditionalFormatCell, ConditionalFormatCellRule, Format, FormatBorder, Workbook, Worksheet,
+    XlsxError,
 };
 
+use crate::analyzer::thresholds::{seuils_par_portee, PorteeSeuils, SeuilsEffectifs, SeuilsPortee};
+use crate::config::AppConfig;
+
 /// En-tête bleu #2C5F8A, texte blanc, gras, bordure fine (RG-052)
 pub fn create_header_format() -> Format {
     Format::new()
@@ -32,73 +39,218 @@ pub fn create_percent_format() -> Format {
     Format::new().set_num_format("0.0%")
 }
 
-/// Formatage conditionnel RAG 4 niveaux sur une colonne (RG-054).
-/// `seuils` = (vert_max, jaune_max, orange_max)
-/// Vert ≤ s1 | Jaune s1+1..=s2 | Orange s2+1..=s3 | Rouge > s3
+/// Formatage conditionnel RAG 4 niveaux sur une colonne (RG-054), mêmes
+/// bornes que `compute_couleur_seuil` à l'écran :
+/// Vert < vert | Jaune vert..jaune | Orange jaune..orange | Rouge ≥ orange
+/// (borne basse incluse). Les règles s'arrêtent à la première satisfaite.
 pub fn apply_rag_conditional_format(
     ws: &mut Worksheet,
     first_row: u32,
     col: u16,
     last_row: u32,
-    seuils: (u32, u32, u32),
+    seuils: &SeuilsEffectifs,
 ) -> Result<(), XlsxError> {
-    let (s1, s2, s3) = seuils;
-
-    let green = Format::new()
-        .set_background_color("C6EFCE")
-        .set_font_color("006100");
-    let yellow = Format::new()
-        .set_background_color("FFEB9C")
-        .set_font_color("9C6500");
-    let orange = Format::new()
-        .set_background_color("F4B084")
-        .set_font_color("833C0C");
-    let red = Format::new()
-        .set_background_color("FFC7CE")
-        .set_font_color("9C0006");
+    let regles = [
+        (
+            ConditionalFormatCellRule::LessThan(seuils.seuil_couleur_vert as f64),
+            RAG_VERT,
+        ),
+        (
+            ConditionalFormatCellRule::LessThan(seuils.seuil_couleur_jaune as f64),
+            RAG_JAUNE,
+        ),
+        (
+            ConditionalFormatCellRule::LessThan(seuils.seuil_couleur_orange as f64),
+            RAG_ORANGE,
+        ),
+        (
+            ConditionalFormatCellRule::GreaterThanOrEqualTo(seuils.seuil_couleur_orange as f64),
+            RAG_ROUGE,
+        ),
+    ];
+    for (regle, couleurs) in regles {
+        ws.add_conditional_format(
+            first_row,
+            col,
+            last_row,
+            col,
+            &ConditionalFormatCell::new()
+                .set_rule(regle)
+                .set_format(rag_format(couleurs))
+                .set_stop_if_true(true),
+        )?;
+    }
+    Ok(())
+}
 
+/// (fond, police) des niveaux RAG.
+const RAG_VERT: (&str, &str) = ("C6EFCE", "006100");
+const RAG_JAUNE: (&str, &str) = ("FFEB9C", "9C6500");
+const RAG_ORANGE: (&str, &str) = ("F4B084", "833C0C");
+const RAG_ROUGE: (&str, &str) = ("FFC7CE", "9C0006");
+
+fn rag_format((fond, police): (&str, &str)) -> Format {
+    Format::new()
+        .set_background_color(fond)
+        .set_font_color(police)
+}
+
+/// Surbrillance rouge des valeurs strictement supérieures à `seuil` (RG-054).
+pub fn apply_red_above(
+    ws: &mut Worksheet,
+    first_row: u32,
+    col: u16,
+    last_row: u32,
+    seuil: u32,
+) -> Result<(), XlsxError> {
     ws.add_conditional_format(
         first_row,
         col,
         last_row,
         col,
         &ConditionalFormatCell::new()
-            .set_rule(ConditionalFormatCellRule::LessThanOrEqualTo(s1 as f64))
-            .set_format(&green),
-    )?;
-    ws.add_conditional_format(
-        first_row,
-        col,
-        last_row,
-        col,
-        &ConditionalFormatCell::new()
-            .set_rule(ConditionalFormatCellRule::Between(
-                (s1 + 1) as f64,
-                s2 as f64,
-            ))
-            .set_format(&yellow),
-    )?;
-    ws.add_conditional_format(
-        first_row,
-        col,
-        last_row,
-        col,
-        &ConditionalFormatCell::new()
-            .set_rule(ConditionalFormatCellRule::Between(
-                (s2 + 1) as f64,
-                s3 as f64,
-            ))
-            .set_format(&orange),
-    )?;
-    ws.add_conditional_format(
-        first_row,
-        col,
-        last_row,
-        col,
-        &ConditionalFormatCell::new()
-            .set_rule(ConditionalFormatCellRule::GreaterThan(s3 as f64))
-            .set_format(&red),
+            .set_rule(ConditionalFormatCellRule::GreaterThan(seuil as f64))
+            .set_format(rag_format(RAG_ROUGE)),
     )?;
+    Ok(())
+}
+
+/// Seuils documentés par l'onglet « Seuils » : global et portées surchargées.
+/// `techniciens` associe un technicien à son groupe majoritaire, pour
+/// résoudre ses seuils comme à l'écran.
+pub fn portees_exportees(
+    config: &AppConfig,
+    techniciens: &[(String, Option<String>)],
+) -> Vec<SeuilsPortee> {
+    seuils_par_portee(config, &[], techniciens)
+        .into_iter()
+        .filter(|p| p.portee.is_none() || p.surchargee)
+        .collect()
+}
+
+/// Onglet « Seuils » : valeurs utilisées par l'export, une ligne par portée.
+/// `legende_rag` ajoute la légende des couleurs, pour les classeurs qui
+/// colorent la charge (stock, bilan).
+pub fn write_seuils_sheet(
+    wb: &mut Workbook,
+    portees: &[SeuilsPortee],
+    legende_rag: bool,
+) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Seuils")?;
+
+    let hdr = create_header_format();
+    let int = create_integer_format();
+
+    let headers = [
+        "Portée",
+        "Valeur",
+        "Groupe retenu",
+        "Stock cible / tech.",
+        "Ancienneté à clôturer (j)",
+        "Inactivité à clôturer (j)",
+        "Ancienneté à relancer (j)",
+        "Inactivité à relancer (j)",
+        "Vert <",
+        "Jaune <",
+        "Orange <",
+    ];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(0, col as u16, *h, &hdr)?;
+    }
+
+    for (i, p) in portees.iter().enumerate() {
+        let row = (i + 1) as u32;
+        let portee = match p.portee {
+            None => "Global",
+            Some(PorteeSeuils::Groupe) => "Groupe",
+            Some(PorteeSeuils::Technicien) => "Technicien",
+        };
+        ws.write(row, 0, portee)?;
+        ws.write(row, 1, p.valeur.as_deref().unwrap_or(""))?;
+        ws.write(row, 2, p.groupe.as_deref().unwrap_or(""))?;
+        let s = &p.seuils;
+        let valeurs = [
+            s.seuil_tickets_technicien,
+            s.seuil_anciennete_cloturer,
+            s.seuil_inactivite_cloturer,
+            s.seuil_anciennete_relancer,
+            s.seuil_inactivite_relancer,
+            s.seuil_couleur_vert,
+            s.seuil_couleur_jaune,
+            s.seuil_couleur_orange,
+        ];
+        for (j, v) in valeurs.iter().enumerate() {
+            ws.write_with_format(row, (j + 3) as u16, *v as f64, &int)?;
+        }
+    }
+
+    if legende_rag {
+        // Légende RAG (charge = stock / ETP)
+        let legende = [
+            ("Vert", "charge < seuil vert", RAG_VERT),
+            ("Jaune", "seuil vert ≤ charge < seuil jaune", RAG_JAUNE),
+            ("Orange", "seuil jaune ≤ charge < seuil orange", RAG_ORANGE),
+            ("Rouge", "charge ≥ seuil orange", RAG_ROUGE),
+        ];
+        let debut = portees.len() as u32 + 2;
+        ws.write_with_format(debut, 0, "Couleur", &hdr)?;
+        ws.write_with_format(debut, 1, "Règle (stock / ETP)", &hdr)?;
+        for (i, (libelle, regle, couleurs)) in legende.iter().enumerate() {
+            let row = debut + 1 + i as u32;
+            ws.write_with_format(row, 0, *libelle, &rag_format(*couleurs))?;
+            ws.write(row, 1, *regle)?;
+        }
+        ws.write(
+            debut + 6,
+            0,
+            "Plan d'action : ancienneté en rouge au-delà du seuil d'ancienneté à clôturer.",
+        )?;
+    }
+
+    ws.set_freeze_panes(1, 0)?;
+    ws.set_column_width(0, 14)?;
+    ws.set_column_width(1, 32)?;
+    ws.set_column_width(2, 24)?;
+    for col in 3u16..=10 {
+        ws.set_column_width(col, 14)?;
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portees_exportees_global_et_surcharges() {
+        use crate::analyzer::thresholds::{PorteeSeuils, SurchargeSeuils};
+        let mut config = AppConfig::default();
+        config.surcharges_seuils = vec![SurchargeSeuils {
+            portee: PorteeSeuils::Groupe,
+            valeur: "DSI > Support".into(),
+            seuil_tickets_technicien: None,
+            seuil_anciennete_cloturer: None,
+            seuil_inactivite_cloturer: None,
+            seuil_anciennete_relancer: None,
+            seuil_inactivite_relancer: None,
+            seuil_couleur_vert: Some(5),
+            seuil_couleur_jaune: None,
+            seuil_couleur_orange: None,
+        }];
+        let techniciens = vec![(
+            "Alice Martin".to_string(),
+            Some("DSI > Support".to_string()),
+        )];
+        let portees = portees_exportees(&config, &techniciens);
+
+        // Global + le groupe surchargé ; Alice n'a pas de surcharge propre
+        assert_eq!(portees.len(), 2);
+        assert!(portees[0].portee.is_none());
+        assert_eq!(portees[0].seuils.seuil_couleur_vert, 10);
+        assert_eq!(portees[1].valeur.as_deref(), Some("DSI > Support"));
+        assert_eq!(portees[1].seuils.seuil_couleur_vert, 5);
+        assert_eq!(portees[1].seuils.seuil_couleur_jaune, 20);
+    }
+}
@@ -1,19 +1,19 @@
+use crate::analyzer::thresholds::{PorteeSeuils, SeuilsPortee};
 use crate::commands::stock::{TechnicianStock, TicketSummary};
 use crate::error::AppError;
 use crate::export::{
-    apply_rag_conditional_format, create_header_format, create_integer_format, create_number_format,
+    apply_rag_conditional_format, apply_red_above, create_header_format, create_integer_format,
+    create_number_format, write_seuils_sheet,
 };
-use rust_xlsxwriter::{Format, Workbook, XlsxError};
-
-const SEUILS_RAG: (u32, u32, u32) = (10, 20, 40);
-const SEUIL_ANCIENNETE_ROUGE: u32 = 90;
+use rust_xlsxwriter::{Workbook, XlsxError};
 
 fn xlsx_err(e: XlsxError) -> AppError {
     AppError::Custom(e.to_string())
 }
 
-/// Génère le plan d'action individuel technicien, 3 onglets (RG-055, US021).
-/// Retourne les bytes XLSX.
+/// Génère le plan d'action individuel technicien, 4 onglets (RG-055, US021).
+/// Les seuils appliqués sont ceux du technicien (`stats.seuils`), documentés
+/// dans l'onglet « Seuils ». Retourne les bytes XLSX.
 pub fn generate_plan_action(
     technician: &str,
     stats: &TechnicianStock,
@@ -21,8 +21,17 @@ pub fn generate_plan_action(
 ) -> Result<Vec<u8>, AppError> {
     let mut wb = Workbook::new();
     write_entretien(&mut wb, technician, stats).map_err(xlsx_err)?;
-    write_detail_tickets(&mut wb, tickets).map_err(xlsx_err)?;
+    write_detail_tickets(&mut wb, tickets, stats.seuils.seuil_anciennete_cloturer)
+        .map_err(xlsx_err)?;
     write_checklist(&mut wb, tickets).map_err(xlsx_err)?;
+    let portee = SeuilsPortee {
+        portee: Some(PorteeSeuils::Technicien),
+        valeur: Some(technician.to_string()),
+        groupe: stats.groupe.clone(),
+        surchargee: false,
+        seuils: stats.seuils.clone(),
+    };
+    write_seuils_sheet(&mut wb, &[portee], false).map_err(xlsx_err)?;
     wb.save_to_buffer().map_err(xlsx_err)
 }
 
@@ -65,12 +74,16 @@ fn write_entretien(
         }
     }
 
+    // Row 7: charge rapportée à l'ETP
+    ws.write(7, 0, "Stock / ETP")?;
+    ws.write_with_format(7, 1, s.stock_par_etp, &num)?;
+
     // Row 8: couleur seuil (texte)
     ws.write(8, 0, "Couleur seuil")?;
     ws.write(8, 1, s.couleur_seuil.as_str())?;
 
-    // RAG sur la cellule Stock total (ligne 2, col 1)
-    apply_rag_conditional_format(ws, 2, 1, 2, SEUILS_RAG)?;
+    // RAG sur la cellule Stock / ETP (ligne 7, col 1), seuils du technicien
+    apply_rag_conditional_format(ws, 7, 1, 7, &s.seuils)?;
 
     ws.set_column_width(0, 22)?;
     ws.set_column_width(1, 18)?;
@@ -80,7 +93,11 @@ fn write_entretien(
 
 // ── Onglet 2 : Détail tickets ─────────────────────────────────────────────────
 
-fn write_detail_tickets(wb: &mut Workbook, tickets: &[TicketSummary]) -> Result<(), XlsxError> {
+fn write_detail_tickets(
+    wb: &mut Workbook,
+    tickets: &[TicketSummary],
+    seuil_anciennete: u32,
+) -> Result<(), XlsxError> {
     let ws = wb.add_worksheet();
     ws.set_name("Détail tickets")?;
 
@@ -102,7 +119,7 @@ fn write_detail_tickets(wb: &mut Workbook, tickets: &[TicketSummary]) -> Result<
         ws.write_with_format(0, col as u16, *h, &hdr)?;
     }
 
-    // Trier par ancienneté décroissante (RG-054 — tickets >90j visibles en haut)
+    // Trier par ancienneté décroissante (RG-054 — tickets anciens visibles en haut)
     let mut sorted: Vec<&TicketSummary> = tickets.iter().collect();
     sorted.sort_by(|a, b| {
         b.anciennete_jours
@@ -157,22 +174,8 @@ fn write_detail_tickets(wb: &mut Workbook, tickets: &[TicketSummary]) -> Result<
         // Auto-filtre
         ws.autofilter(0, 0, last_row, (headers.len() - 1) as u16)?;
 
-        // Surbrillance rouge sur ancienneté > 90j (col 5) (RG-054)
-        use rust_xlsxwriter::{ConditionalFormatCell, ConditionalFormatCellRule};
-        let red = Format::new()
-            .set_background_color("FFC7CE")
-            .set_font_color("9C0006");
-        ws.add_conditional_format(
-            1,
-            5,
-            last_row,
-            5,
-            &ConditionalFormatCell::new()
-                .set_rule(ConditionalFormatCellRule::GreaterThan(
-                    SEUIL_ANCIENNETE_ROUGE as f64,
-                ))
-                .set_format(&red),
-        )?;
+        // Surbrillance rouge au-delà du seuil d'ancienneté à clôturer (col 5) (RG-054)
+        apply_red_above(ws, 1, 5, last_row, seuil_anciennete)?;
     }
 
     ws.set_column_width(0, 8)?;
@@ -255,10 +258,12 @@ fn write_checklist(wb: &mut Workbook, tickets: &[TicketSummary]) -> Result<(), X
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::thresholds::SeuilsEffectifs;
 
     fn make_stats() -> TechnicianStock {
         TechnicianStock {
             technicien: "Bob Dupont".into(),
+            groupe: Some("DSI".into()),
             total: 12,
             en_cours: 8,
             en_attente: 4,
@@ -267,9 +272,19 @@ mod tests {
             incidents: 7,
             demandes: 5,
             age_moyen_jours: 55.2,
+            age_moyen_jours_ouvres: 55.2,
             inactifs_14j: 2,
+            etp: 1.0,
+            stock_par_etp: 12.0,
             ecart_seuil: 2,
             couleur_seuil: "jaune".into(),
+            seuils: SeuilsEffectifs {
+                seuil_tickets_technicien: 10,
+                seuil_couleur_vert: 10,
+                seuil_couleur_jaune: 20,
+                seuil_couleur_orange: 40,
+                ..Default::default()
+            },
         }
     }
 
@@ -1,28 +1,44 @@
+use crate::analyzer::priority::PrioriteKpi;
+use crate::analyzer::sla::{SlaEcheance, SlaKpi, SlaVentilation};
+use crate::analyzer::vendor::FournisseurKpi;
 use crate::commands::stock::{AgeRangeCount, GroupStock, StockOverview, TechnicianStock};
 use crate::error::AppError;
+use crate::config::AppConfig;
 use crate::export::{
     apply_rag_conditional_format, create_header_format, create_integer_format,
-    create_number_format, create_percent_format,
+    create_number_format, create_percent_format, portees_exportees, write_seuils_sheet,
 };
 use rust_xlsxwriter::{Workbook, XlsxError};
 
-const SEUILS_RAG: (u32, u32, u32) = (10, 20, 40);
-
 fn xlsx_err(e: XlsxError) -> AppError {
     AppError::Custom(e.to_string())
 }
 
-/// Génère le rapport stock Excel 3 onglets (RG-049, US020).
+/// Génère le rapport stock Excel 7 onglets (RG-049, US020) : vue globale,
+/// techniciens, groupes, SLA, priorités, fournisseurs et seuils appliqués.
 /// Retourne les bytes XLSX via workbook.save_to_buffer().
 pub fn generate_stock_report(
     overview: &StockOverview,
     technicians: &[TechnicianStock],
     groups: &[GroupStock],
+    sla: &SlaKpi,
+    priorites: &PrioriteKpi,
+    fournisseurs: &FournisseurKpi,
+    config: &AppConfig,
 ) -> Result<Vec<u8>, AppError> {
     let mut wb = Workbook::new();
     write_vue_globale(&mut wb, overview).map_err(xlsx_err)?;
     write_technicians(&mut wb, technicians).map_err(xlsx_err)?;
     write_groups(&mut wb, groups).map_err(xlsx_err)?;
+    write_sla(&mut wb, sla).map_err(xlsx_err)?;
+    write_priorites(&mut wb, priorites).map_err(xlsx_err)?;
+    write_fournisseurs(&mut wb, fournisseurs).map_err(xlsx_err)?;
+    let techniciens: Vec<(String, Option<String>)> = technicians
+        .iter()
+        .map(|t| (t.technicien.clone(), t.groupe.clone()))
+        .collect();
+    write_seuils_sheet(&mut wb, &portees_exportees(config, &techniciens), true)
+        .map_err(xlsx_err)?;
     wb.save_to_buffer().map_err(xlsx_err)
 }
 
@@ -107,6 +123,8 @@ fn write_technicians(wb: &mut Workbook, technicians: &[TechnicianStock]) -> Resu
         "Âge moyen (j)",
         "Inactifs 14j",
         "Couleur seuil",
+        "ETP",
+        "Stock / ETP",
     ];
     for (col, h) in headers.iter().enumerate() {
         ws.write_with_format(0, col as u16, *h, &hdr)?;
@@ -124,6 +142,8 @@ fn write_technicians(wb: &mut Workbook, technicians: &[TechnicianStock]) -> Resu
         ws.write_with_format(row, 6, t.age_moyen_jours, &num)?;
         ws.write_with_format(row, 7, t.inactifs_14j as f64, &int)?;
         ws.write(row, 8, t.couleur_seuil.as_str())?;
+        ws.write_with_format(row, 9, t.etp, &num)?;
+        ws.write_with_format(row, 10, t.stock_par_etp, &num)?;
     }
 
     if !technicians.is_empty() {
@@ -135,8 +155,12 @@ fn write_technicians(wb: &mut Workbook, technicians: &[TechnicianStock]) -> Resu
         // Auto-filtre (RG-053)
         ws.autofilter(0, 0, last_row, (headers.len() - 1) as u16)?;
 
-        // Formatage conditionnel RAG sur colonne "Stock" (col 1) (RG-054)
-        apply_rag_conditional_format(ws, 1, 1, last_row, SEUILS_RAG)?;
+        // Formatage conditionnel RAG sur colonne "Stock / ETP" (col 10) (RG-054),
+        // ligne par ligne : chaque technicien a ses seuils effectifs
+        for (i, t) in technicians.iter().enumerate() {
+            let row = (i + 1) as u32;
+            apply_rag_conditional_format(ws, row, 10, row, &t.seuils)?;
+        }
     }
 
     // Largeurs colonnes
@@ -145,7 +169,9 @@ fn write_technicians(wb: &mut Workbook, technicians: &[TechnicianStock]) -> Resu
     for col in 2u16..=7 {
         ws.set_column_width(col, 14)?;
     }
-    ws.set_column_width(8, 14)?;
+    for col in 8u16..=10 {
+        ws.set_column_width(col, 14)?;
+    }
 
     Ok(())
 }
@@ -210,11 +236,344 @@ fn write_groups(wb: &mut Workbook, groups: &[GroupStock]) -> Result<(), XlsxErro
     Ok(())
 }
 
+// ── Onglet 4 : SLA ───────────────────────────────────────────────────────────
+
+fn write_sla(wb: &mut Workbook, sla: &SlaKpi) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("SLA")?;
+
+    let hdr = create_header_format();
+    let int = create_integer_format();
+    let num = create_number_format();
+    let pct = create_percent_format();
+
+    // Synthèse
+    ws.write_with_format(0, 0, "Indicateur", &hdr)?;
+    ws.write_with_format(0, 1, "Valeur", &hdr)?;
+    let kpis: &[(&str, f64)] = &[
+        ("Définitions actives", sla.nb_definitions as f64),
+        ("Terminés évalués", sla.resolution.evalues as f64),
+        ("Terminés hors délai", sla.resolution.depasses as f64),
+        ("Terminés sans SLA", sla.resolution.sans_sla as f64),
+        ("Vivants évalués", sla.vivants.evalues as f64),
+        ("Vivants dans les délais", sla.vivants.dans_les_delais as f64),
+        ("Vivants TTR dépassé", sla.vivants.ttr_depasses as f64),
+        ("Vivants TTO dépassé", sla.vivants.tto_depasses as f64),
+        ("Vivants à risque", sla.vivants.a_risque as f64),
+    ];
+    ws.write(1, 0, "Respect TTR (terminés)")?;
+    ws.write_with_format(1, 1, sla.resolution.taux_respect / 100.0, &pct)?;
+    for (i, (label, val)) in kpis.iter().enumerate() {
+        let row = (i + 2) as u32;
+        ws.write(row, 0, *label)?;
+        ws.write_with_format(row, 1, *val, &int)?;
+    }
+    let row = (kpis.len() + 2) as u32;
+    ws.write(row, 0, "Date de référence")?;
+    ws.write(row, 1, sla.date_reference.as_str())?;
+    ws.write(row + 1, 0, "Horizon à risque (j)")?;
+    ws.write_with_format(row + 1, 1, sla.horizon_jours, &num)?;
+
+    // Ventilations des terminés, l'une sous l'autre
+    let sections: [(&str, &[SlaVentilation]); 4] = [
+        ("Période", &sla.par_periode),
+        ("SLA", &sla.par_sla),
+        ("Groupe", &sla.par_groupe),
+        ("Technicien", &sla.par_technicien),
+    ];
+    let mut next_row = row + 3;
+    for (titre, items) in sections {
+        for (col, h) in [titre, "Évalués", "Hors délai", "% hors délai"].iter().enumerate() {
+            ws.write_with_format(next_row, col as u16, *h, &hdr)?;
+        }
+        for (i, v) in items.iter().enumerate() {
+            let r = next_row + 1 + i as u32;
+            ws.write(r, 0, v.label.as_str())?;
+            ws.write_with_format(r, 1, v.evalues as f64, &int)?;
+            ws.write_with_format(r, 2, v.depasses as f64, &int)?;
+            ws.write_with_format(r, 3, v.taux_depassement / 100.0, &pct)?;
+        }
+        next_row += items.len() as u32 + 2;
+    }
+
+    // Échéances des vivants
+    next_row = write_echeances(ws, next_row, "Tickets hors délai", &sla.depasses, &hdr, &int, &num)?;
+    write_echeances(ws, next_row, "Tickets à risque", &sla.a_risque, &hdr, &int, &num)?;
+
+    ws.set_column_width(0, 28)?;
+    ws.set_column_width(1, 40)?;
+    for col in 2u16..=7 {
+        ws.set_column_width(col, 16)?;
+    }
+
+    Ok(())
+}
+
+/// Écrit une liste d'échéances ; retourne la première ligne libre après un blanc.
+fn write_echeances(
+    ws: &mut rust_xlsxwriter::Worksheet,
+    start_row: u32,
+    titre: &str,
+    echeances: &[SlaEcheance],
+    hdr: &rust_xlsxwriter::Format,
+    int: &rust_xlsxwriter::Format,
+    num: &rust_xlsxwriter::Format,
+) -> Result<u32, XlsxError> {
+    let headers = [titre, "Titre", "Technicien", "Groupe", "SLA", "Indicateur", "Échéance", "Heures restantes"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(start_row, col as u16, *h, hdr)?;
+    }
+    for (i, e) in echeances.iter().enumerate() {
+        let row = start_row + 1 + i as u32;
+        ws.write_with_format(row, 0, e.id as f64, int)?;
+        ws.write(row, 1, e.titre.as_str())?;
+        ws.write(row, 2, e.technicien.as_deref().unwrap_or(""))?;
+        ws.write(row, 3, e.groupe.as_deref().unwrap_or(""))?;
+        ws.write(row, 4, e.sla.as_str())?;
+        ws.write(row, 5, e.indicateur.as_str())?;
+        ws.write(row, 6, e.echeance.as_str())?;
+        ws.write_with_format(row, 7, e.heures_restantes, num)?;
+    }
+    Ok(start_row + echeances.len() as u32 + 2)
+}
+
+// ── Onglet 5 : Priorités ─────────────────────────────────────────────────────
+
+fn write_priorites(wb: &mut Workbook, p: &PrioriteKpi) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Priorités")?;
+
+    let hdr = create_header_format();
+    let int = create_integer_format();
+    let num = create_number_format();
+    let pct = create_percent_format();
+
+    // Cohérence avec la matrice GLPI
+    let c = &p.coherence;
+    ws.write_with_format(0, 0, "Indicateur", &hdr)?;
+    ws.write_with_format(0, 1, "Valeur", &hdr)?;
+    let kpis: &[(&str, usize)] = &[
+        ("Tickets", p.total_tickets),
+        ("Sans priorité", p.sans_priorite),
+        ("Sans urgence", p.sans_urgence),
+        ("Évalués", c.evalues),
+        ("Incohérents", c.incoherents),
+        ("Surclassés", c.surclasses),
+        ("Sous-classés", c.sousclasses),
+        ("Majeures (non évaluées)", c.majeures),
+    ];
+    for (i, (label, val)) in kpis.iter().enumerate() {
+        let row = (i + 1) as u32;
+        ws.write(row, 0, *label)?;
+        ws.write_with_format(row, 1, *val as f64, &int)?;
+    }
+    let mut row = kpis.len() as u32 + 1;
+    ws.write(row, 0, "Taux d'incohérence")?;
+    ws.write_with_format(row, 1, c.taux_incoherence / 100.0, &pct)?;
+    ws.write(row + 1, 0, "Référence")?;
+    ws.write(
+        row + 1,
+        1,
+        if p.impact_disponible { "Matrice urgence × impact" } else { "Plage par urgence (impact absent)" },
+    )?;
+    row += 3;
+
+    // Matrice priorité × urgence (stock vivant et MTTR)
+    let headers = ["Priorité", "Urgence", "Tickets", "Vivants", "Terminés", "MTTR moyen (j)", "MTTR médian (j)"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(row, col as u16, *h, &hdr)?;
+    }
+    for cell in &p.priorite_urgence {
+        row += 1;
+        ws.write_with_format(row, 0, cell.ligne as f64, &int)?;
+        ws.write_with_format(row, 1, cell.colonne as f64, &int)?;
+        ws.write_with_format(row, 2, cell.tickets as f64, &int)?;
+        ws.write_with_format(row, 3, cell.vivants as f64, &int)?;
+        ws.write_with_format(row, 4, cell.termines as f64, &int)?;
+        if let Some(v) = cell.mttr_moyen_jours {
+            ws.write_with_format(row, 5, v, &num)?;
+        }
+        if let Some(v) = cell.mttr_median_jours {
+            ws.write_with_format(row, 6, v, &num)?;
+        }
+    }
+    row += 2;
+
+    // Dérive par période d'ouverture
+    let headers = ["Période", "Tickets", "Priorité moyenne", "% priorité haute", "% incohérents"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(row, col as u16, *h, &hdr)?;
+    }
+    for d in &p.derive {
+        row += 1;
+        ws.write(row, 0, d.period_label.as_str())?;
+        ws.write_with_format(row, 1, d.total as f64, &int)?;
+        if let Some(m) = d.priorite_moyenne {
+            ws.write_with_format(row, 2, m, &num)?;
+        }
+        ws.write_with_format(row, 3, d.part_haute / 100.0, &pct)?;
+        if let Some(t) = d.taux_incoherence {
+            ws.write_with_format(row, 4, t / 100.0, &pct)?;
+        }
+    }
+    row += 2;
+
+    // Tickets vivants incohérents
+    let headers = ["Ticket vivant incohérent", "Titre", "Technicien", "Priorité", "Urgence", "Impact", "Attendue", "Écart"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(row, col as u16, *h, &hdr)?;
+    }
+    for t in c.tickets.iter().filter(|t| t.est_vivant) {
+        row += 1;
+        ws.write_with_format(row, 0, t.id as f64, &int)?;
+        ws.write(row, 1, t.titre.as_str())?;
+        ws.write(row, 2, t.technicien.as_deref().unwrap_or(""))?;
+        ws.write_with_format(row, 3, t.priorite as f64, &int)?;
+        ws.write_with_format(row, 4, t.urgence as f64, &int)?;
+        if let Some(i) = t.impact {
+            ws.write_with_format(row, 5, i as f64, &int)?;
+        }
+        let attendue = if t.priorite_attendue_min == t.priorite_attendue_max {
+            t.priorite_attendue_min.to_string()
+        } else {
+            format!("{}-{}", t.priorite_attendue_min, t.priorite_attendue_max)
+        };
+        ws.write(row, 6, attendue.as_str())?;
+        ws.write_with_format(row, 7, t.ecart as f64, &int)?;
+    }
+
+    ws.set_column_width(0, 28)?;
+    ws.set_column_width(1, 40)?;
+    for col in 2u16..=7 {
+        ws.set_column_width(col, 16)?;
+    }
+
+    Ok(())
+}
+
+// ── Onglet 6 : Fournisseurs ──────────────────────────────────────────────────
+
+fn write_fournisseurs(wb: &mut Workbook, f: &FournisseurKpi) -> Result<(), XlsxError> {
+    let ws = wb.add_worksheet();
+    ws.set_name("Fournisseurs")?;
+
+    let hdr = create_header_format();
+    let int = create_integer_format();
+    let num = create_number_format();
+    let pct = create_percent_format();
+
+    // Synthèse
+    ws.write_with_format(0, 0, "Indicateur", &hdr)?;
+    ws.write_with_format(0, 1, "Valeur", &hdr)?;
+    let kpis: &[(&str, usize)] = &[
+        ("Tickets", f.total_tickets),
+        ("Tickets avec fournisseur", f.tickets_avec_fournisseur),
+        ("Interventions", f.total_interventions),
+        ("Vivants avec fournisseur", f.vivants_avec_fournisseur),
+        ("Vivants bloqués fournisseur", f.vivants_bloques),
+    ];
+    for (i, (label, val)) in kpis.iter().enumerate() {
+        let row = (i + 1) as u32;
+        ws.write(row, 0, *label)?;
+        ws.write_with_format(row, 1, *val as f64, &int)?;
+    }
+    let mut row = kpis.len() as u32 + 1;
+    ws.write(row, 0, "Taux de dépendance")?;
+    ws.write_with_format(row, 1, f.taux_dependance / 100.0, &pct)?;
+    let mttrs = [
+        ("MTTR médian avec fournisseur (j)", f.mttr_avec_fournisseur.mediane_jours),
+        ("MTTR médian sans fournisseur (j)", f.mttr_sans_fournisseur.mediane_jours),
+        ("Surcoût médian (j)", f.surcout_median_jours),
+    ];
+    for (label, val) in mttrs {
+        row += 1;
+        ws.write(row, 0, label)?;
+        if let Some(v) = val {
+            ws.write_with_format(row, 1, v, &num)?;
+        }
+    }
+    row += 1;
+    ws.write(row, 0, "Date de référence")?;
+    ws.write(row, 1, f.date_reference.as_str())?;
+    row += 2;
+
+    // Ancienneté des bloqués
+    ws.write_with_format(row, 0, "Ancienneté des bloqués", &hdr)?;
+    ws.write_with_format(row, 1, "Tickets", &hdr)?;
+    for t in &f.distribution_age_bloques {
+        row += 1;
+        ws.write(row, 0, t.label.as_str())?;
+        ws.write_with_format(row, 1, t.count as f64, &int)?;
+    }
+    row += 2;
+
+    // Classement par fournisseur
+    let headers = [
+        "Fournisseur",
+        "Tickets",
+        "Interventions",
+        "% des tickets fournisseur",
+        "Vivants",
+        "Bloqués",
+        "MTTR médian (j)",
+        "Durée médiane intervention (j)",
+    ];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(row, col as u16, *h, &hdr)?;
+    }
+    for s in &f.par_fournisseur {
+        row += 1;
+        ws.write(row, 0, s.fournisseur.as_str())?;
+        ws.write_with_format(row, 1, s.tickets as f64, &int)?;
+        ws.write_with_format(row, 2, s.interventions as f64, &int)?;
+        ws.write_with_format(row, 3, s.part_tickets_fournisseur / 100.0, &pct)?;
+        ws.write_with_format(row, 4, s.vivants as f64, &int)?;
+        ws.write_with_format(row, 5, s.bloques as f64, &int)?;
+        if let Some(v) = s.mttr_median_jours {
+            ws.write_with_format(row, 6, v, &num)?;
+        }
+        if let Some(v) = s.duree_intervention_mediane_jours {
+            ws.write_with_format(row, 7, v, &num)?;
+        }
+    }
+    row += 2;
+
+    // Tickets vivants bloqués
+    let headers = ["Ticket bloqué", "Titre", "Fournisseur", "Technicien", "Groupe", "Ancienneté (j)", "Début intervention", "Attente (j)"];
+    for (col, h) in headers.iter().enumerate() {
+        ws.write_with_format(row, col as u16, *h, &hdr)?;
+    }
+    for b in &f.bloques {
+        row += 1;
+        ws.write_with_format(row, 0, b.id as f64, &int)?;
+        ws.write(row, 1, b.titre.as_str())?;
+        ws.write(row, 2, b.fournisseur.as_str())?;
+        ws.write(row, 3, b.technicien.as_deref().unwrap_or(""))?;
+        ws.write(row, 4, b.groupe.as_deref().unwrap_or(""))?;
+        if let Some(v) = b.anciennete_jours {
+            ws.write_with_format(row, 5, v, &int)?;
+        }
+        ws.write(row, 6, b.debut_intervention.as_deref().unwrap_or(""))?;
+        if let Some(v) = b.attente_jours {
+            ws.write_with_format(row, 7, v, &num)?;
+        }
+    }
+
+    ws.set_column_width(0, 32)?;
+    ws.set_column_width(1, 40)?;
+    for col in 2u16..=7 {
+        ws.set_column_width(col, 18)?;
+    }
+
+    Ok(())
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analyzer::thresholds::SeuilsEffectifs;
     use crate::commands::stock::{AgeRangeCount, StatutCount, TypeBreakdown};
 
     fn make_overview() -> StockOverview {
@@ -234,7 +593,9 @@ mod tests {
                 },
             ],
             age_moyen_jours: 45.5,
+            age_moyen_jours_ouvres: 45.5,
             age_median_jours: 30.0,
+            age_median_jours_ouvres: 30.0,
             par_type: TypeBreakdown {
                 incidents: 60,
                 demandes: 40,
@@ -268,6 +629,7 @@ mod tests {
     fn make_technician() -> TechnicianStock {
         TechnicianStock {
             technicien: "Alice Martin".into(),
+            groupe: Some("DSI > Support".into()),
             total: 25,
             en_cours: 15,
             en_attente: 10,
@@ -276,9 +638,19 @@ mod tests {
             incidents: 15,
             demandes: 10,
             age_moyen_jours: 42.3,
+            age_moyen_jours_ouvres: 42.3,
             inactifs_14j: 3,
+            etp: 1.0,
+            stock_par_etp: 25.0,
             ecart_seuil: 5,
             couleur_seuil: "orange".into(),
+            seuils: SeuilsEffectifs {
+                seuil_tickets_technicien: 20,
+                seuil_couleur_vert: 10,
+                seuil_couleur_jaune: 20,
+                seuil_couleur_orange: 40,
+                ..Default::default()
+            },
         }
     }
 
@@ -294,15 +666,92 @@ mod tests {
             demandes: 30,
             nb_techniciens: 5,
             age_moyen_jours: 38.0,
+            age_moyen_jours_ouvres: 38.0,
         }
     }
 
+    fn make_sla() -> SlaKpi {
+        SlaKpi {
+            nb_definitions: 2,
+            date_reference: "2025-03-01T08:00:00".into(),
+            horizon_jours: 3.0,
+            par_sla: vec![SlaVentilation {
+                label: "Standard".into(),
+                evalues: 10,
+                depasses: 2,
+                taux_depassement: 20.0,
+            }],
+            depasses: vec![SlaEcheance {
+                id: 42,
+                titre: "Imprimante HS".into(),
+                technicien: Some("Alice Martin".into()),
+                groupe: None,
+                sla: "Standard".into(),
+                indicateur: "TTR".into(),
+                echeance: "2025-02-27T08:00:00".into(),
+                heures_restantes: -48.0,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn make_priorites() -> PrioriteKpi {
+        use crate::analyzer::priority::{compute_priorite_kpi, TicketPriorite};
+        let ticket = |id: u64, priorite: i32, vivant: bool| TicketPriorite {
+            id,
+            titre: format!("T{id}"),
+            priorite: Some(priorite),
+            urgence: Some(5),
+            impact: Some(5),
+            ouverture: chrono::NaiveDate::from_ymd_opt(2025, 2, id as u32)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+            est_vivant: vivant,
+            delai_resolution_jours: (!vivant).then_some(2.0),
+            technicien: Some("Dupont".into()),
+        };
+        compute_priorite_kpi(&[ticket(1, 5, false), ticket(2, 2, true)], "month")
+    }
+
+    fn make_fournisseurs() -> FournisseurKpi {
+        use crate::analyzer::vendor::{compute_fournisseur_kpi, TicketFournisseur};
+        use crate::parser::vendor::parse_interventions;
+        let ticket = |id: u64, vivant: bool, brut: &str| TicketFournisseur {
+            id,
+            titre: format!("T{id}"),
+            technicien: Some("Dupont".into()),
+            groupe: None,
+            est_vivant: vivant,
+            anciennete_jours: Some(12.0),
+            delai_resolution_jours: (!vivant).then_some(3.0),
+            interventions: parse_interventions(brut),
+        };
+        let reference = chrono::NaiveDate::from_ymd_opt(2025, 3, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        compute_fournisseur_kpi(
+            &[ticket(1, true, "Orange depuis le 2025-02-10"), ticket(2, false, "")],
+            reference,
+            &crate::analyzer::buckets::TranchesDistribution::default().anciennete(),
+        )
+    }
+
     #[test]
     fn test_generate_stock_report_xlsx_signature() {
         let overview = make_overview();
         let technicians = vec![make_technician()];
         let groups = vec![make_group()];
-        let result = generate_stock_report(&overview, &technicians, &groups);
+        let result = generate_stock_report(
+            &overview,
+            &technicians,
+            &groups,
+            &make_sla(),
+            &make_priorites(),
+            &make_fournisseurs(),
+            &AppConfig::default(),
+        );
         assert!(result.is_ok(), "generate_stock_report failed: {:?}", result.err());
         let bytes = result.unwrap();
         assert!(bytes.len() > 4, "XLSX too small");
@@ -314,7 +763,15 @@ mod tests {
     #[test]
     fn test_generate_stock_report_empty_slices() {
         let overview = make_overview();
-        let result = generate_stock_report(&overview, &[], &[]);
+        let result = generate_stock_report(
+            &overview,
+            &[],
+            &[],
+            &SlaKpi::default(),
+            &PrioriteKpi::default(),
+            &FournisseurKpi::default(),
+            &AppConfig::default(),
+        );
         assert!(result.is_ok());
         let bytes = result.unwrap();
         assert_eq!(bytes[0], 0x50);
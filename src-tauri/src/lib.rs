@@ -9,6 +9,7 @@ mod nlp;
 mod parser;
 mod recommandation;
 mod state;
+mod workspace;
 
 use state::AppState;
 use std::sync::Mutex;
@@ -82,6 +83,7 @@ pub fn run() {
 
     let app_state = AppState {
         db: Mutex::new(None),
+        workspace: Mutex::new(None),
     };
 
     let result = tauri::Builder::default()
@@ -92,11 +94,16 @@ pub fn run() {
         .manage(app_state)
         .setup(|app| {
             let app_handle = app.handle().clone();
-            let db_path = app_handle
+            let data_dir = app_handle
                 .path()
                 .app_data_dir()
-                .map_err(|e| format!("Impossible de résoudre app_data_dir: {e}"))?
-                .join("glpi_dashboard.db");
+                .map_err(|e| format!("Impossible de résoudre app_data_dir: {e}"))?;
+
+            // Dernier workspace utilisé (par défaut : glpi_dashboard.db historique)
+            let registry = workspace::WorkspaceRegistry::load(&data_dir)
+                .map_err(|e| format!("Registre des workspaces illisible: {e}"))?;
+            let active = registry.last_used().clone();
+            let db_path = workspace::db_path(&data_dir, &active);
 
             if let Some(parent) = db_path.parent() {
                 std::fs::create_dir_all(parent)
@@ -112,6 +119,11 @@ pub fn run() {
 
             let state: tauri::State<AppState> = app.state();
             *state.db.lock().map_err(|e| format!("Mutex poisonné: {e}"))? = Some(conn);
+            *state.workspace.lock().map_err(|e| format!("Mutex poisonné: {e}"))? =
+                Some(workspace::WorkspaceContext {
+                    data_dir,
+                    active_id: active.id,
+                });
 
             Ok(())
         })
@@ -123,6 +135,7 @@ pub fn run() {
             commands::import::set_active_import,
             commands::import::get_technician_history,
             commands::import::get_all_technicians,
+            commands::diff::get_import_diff,
             // Stock
             commands::stock::get_stock_overview,
             commands::stock::get_stock_by_technician,
@@ -130,10 +143,16 @@ pub fn run() {
             commands::stock::get_ticket_detail,
             commands::stock::get_technician_tickets,
             commands::stock::get_unassigned_tickets,
+            commands::stock::get_backlog_series,
             // Bilan
             commands::bilan::get_bilan_temporel,
+            commands::capacity::get_capacity_analysis,
+            commands::capacity::get_workload_fairness,
+            commands::cohort::get_cohort_analysis,
             // Catégories
             commands::categories::get_categories_tree,
+            commands::categories::get_category_hierarchy,
+            commands::categories::get_category_tickets,
             // Data mining
             commands::mining::run_text_analysis,
             commands::mining::get_clusters,
@@ -148,17 +167,28 @@ pub fn run() {
             // Export
             commands::export::export_excel_stock,
             commands::export::export_excel_bilan,
+            commands::export::export_excel_cohort,
+            commands::export::export_excel_diff,
+            commands::export::export_excel_audit_log,
             commands::export::export_excel_plan_action,
             commands::export::export_all_plans_zip,
             // Config
             commands::config::get_config,
             commands::config::update_config,
+            commands::config::get_effective_thresholds,
+            commands::config::get_jours_feries,
             // Search
             commands::search::search_tickets,
             // Analytics
             commands::analytics::predict_workload,
+            commands::cache::get_cache_stats,
+            commands::cache::purge_analytics_cache,
             // Dashboard KPI
             commands::dashboard::get_dashboard_kpi,
+            commands::dashboard::get_dashboard_comparison,
+            commands::dashboard::get_spc_charts,
+            // Cartes de chaleur horaires
+            commands::heatmap::get_heatmaps,
             // Délais prise en charge
             commands::delais::get_delais_kpi,
             commands::delais::get_distinct_categories_for_delais,
@@ -167,6 +197,38 @@ pub fn run() {
             commands::recommandation::build_technician_profiles,
             commands::recommandation::get_assignment_recommendations,
             commands::recommandation::get_unassigned_ticket_stats_cmd,
+            // Workspaces
+            commands::workspace::list_workspaces,
+            commands::workspace::create_workspace,
+            commands::workspace::rename_workspace,
+            commands::workspace::switch_workspace,
+            commands::workspace::delete_workspace,
+            // Dictionnaires de mots-clés
+            commands::dictionaries::get_keyword_dictionaries,
+            commands::dictionaries::add_keyword_dictionary_entry,
+            commands::dictionaries::update_keyword_dictionary_entry,
+            commands::dictionaries::delete_keyword_dictionary_entry,
+            commands::dictionaries::get_keyword_signals,
+            // Journal d'audit
+            commands::audit::get_audit_log,
+            // Règles de classification
+            commands::rules::get_classification_rules,
+            commands::rules::save_classification_rule,
+            commands::rules::delete_classification_rule,
+            commands::rules::dry_run_classification_rules,
+            // Escalades
+            commands::escalation::get_escalation_analysis,
+            // Fournisseurs
+            commands::vendor::get_vendor_kpi,
+            // Demandeurs
+            commands::requesters::import_annuaire_demandeurs,
+            commands::requesters::clear_annuaire_demandeurs,
+            commands::requesters::get_requester_kpi,
+            // SLA
+            commands::sla::get_sla_definitions,
+            commands::sla::save_sla_definition,
+            commands::sla::delete_sla_definition,
+            commands::sla::get_sla_report,
         ])
         .run(tauri::generate_context!());
 
@@ -189,6 +251,10 @@ mod e2e_tests {
             .unwrap();
         conn.execute_batch(include_str!("db/sql/003_date_resolution.sql"))
             .unwrap();
+        conn.execute_batch(include_str!("db/sql/010_categories.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("db/sql/011_impact.sql"))
+            .unwrap();
         conn
     }
 
@@ -257,7 +323,7 @@ mod e2e_tests {
         .expect("Import record insertion failed");
         let import_id = conn.last_insert_rowid();
 
-        crate::db::insert::bulk_insert_tickets(&mut conn, import_id, &tickets)
+        crate::db::insert::insert_tickets(&conn, import_id, &tickets)
             .expect("Bulk insert failed");
 
         // 4. Verify get_stock_overview
@@ -380,8 +446,7 @@ mod e2e_tests {
         .expect("Import record insertion failed");
         let import_id = conn.last_insert_rowid();
 
-        let mut conn = conn;
-        crate::db::insert::bulk_insert_tickets(&mut conn, import_id, &tickets)
+        crate::db::insert::insert_tickets(&conn, import_id, &tickets)
             .expect("Bulk insert failed");
 
         Some(conn)
@@ -397,6 +462,7 @@ mod e2e_tests {
 
         let state = crate::state::AppState {
             db: std::sync::Mutex::new(Some(conn)),
+            workspace: std::sync::Mutex::new(None),
         };
 
         let request = crate::commands::bilan::BilanRequest {
@@ -404,6 +470,7 @@ mod e2e_tests {
             date_from: "2025-01-01".to_string(),
             date_to: "2026-12-31".to_string(),
             group_by: None,
+            comparaison: None,
         };
 
         let bilan = crate::commands::bilan::run_bilan_logic(&state, &request)
@@ -460,10 +527,26 @@ mod e2e_tests {
             .expect("get_technicians_stock failed");
         let groups = crate::db::queries::get_groups_stock(&conn, None)
             .expect("get_groups_stock failed");
-
-        let bytes =
-            crate::export::stock_report::generate_stock_report(&overview, &technicians, &groups)
-                .expect("generate_stock_report failed");
+        let import_id =
+            crate::db::queries::get_active_import_id(&conn).expect("no active import");
+        let sla = crate::analyzer::sla::load_sla_kpi(&conn, import_id, None, None, "month", 3.0, None)
+            .expect("load_sla_kpi failed");
+        let priorites =
+            crate::analyzer::priority::load_priorite_kpi(&conn, import_id, None, None, "month")
+                .expect("load_priorite_kpi failed");
+        let fournisseurs = crate::analyzer::vendor::load_fournisseur_kpi(&conn, import_id)
+            .expect("load_fournisseur_kpi failed");
+
+        let bytes = crate::export::stock_report::generate_stock_report(
+            &overview,
+            &technicians,
+            &groups,
+            &sla,
+            &priorites,
+            &fournisseurs,
+            &crate::config::AppConfig::default(),
+        )
+        .expect("generate_stock_report failed");
 
         assert!(bytes.len() > 4, "XLSX bytes should be non-trivial");
         assert_eq!(bytes[0], 0x50, "First byte should be 0x50 (P)");
@@ -510,6 +593,7 @@ mod e2e_tests {
 
         let state = crate::state::AppState {
             db: std::sync::Mutex::new(Some(conn)),
+            workspace: std::sync::Mutex::new(None),
         };
 
         let request = crate::commands::bilan::BilanRequest {
@@ -517,13 +601,18 @@ mod e2e_tests {
             date_from: "2025-01-01".to_string(),
             date_to: "2026-12-31".to_string(),
             group_by: None,
+            comparaison: None,
         };
 
         let bilan = crate::commands::bilan::run_bilan_logic(&state, &request)
             .expect("run_bilan_logic failed");
 
         let bytes =
-            crate::export::bilan_report::generate_bilan_report(&bilan, &request)
+            crate::export::bilan_report::generate_bilan_report(
+                &bilan,
+                &request,
+                &crate::config::AppConfig::default(),
+            )
                 .expect("generate_bilan_report failed");
 
         assert!(bytes.len() > 4, "XLSX bytes should be non-trivial");
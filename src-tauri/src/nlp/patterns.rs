@@ -1,2 +1,394 @@
-// Détection patterns résolution/relance dans textes de suivis
-// Sera implémenté en Phase 2
+//! Détection de patterns résolution / relance / annulation dans les derniers
+//! suivis et la solution des tickets vivants, à partir des dictionnaires
+//! `keyword_dictionaries` (mots-clés simples ou regex, insensibles à la casse).
+//! Un mot-clé simple est recherché en mot entier, et ses occurrences niées
+//! (« non terminé », « pas encore réglé ») sont ignorées.
+//!
+//! Les suivis exportés par GLPI vont du plus ancien au plus récent (découpage
+//! dans `parser::suivis`) : le dernier segment est le suivi le plus récent.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::preprocessing::strip_html;
+use crate::parser::suivis::decouper_suivis;
+
+pub const CATEGORIE_RESOLUTION: &str = "resolution";
+pub const CATEGORIE_RELANCE: &str = "relance";
+pub const CATEGORIE_ANNULATION: &str = "annulation";
+pub const CATEGORIE_EXCLUSION: &str = "exclusion";
+pub const CATEGORIES: [&str; 4] = [
+    CATEGORIE_RESOLUTION,
+    CATEGORIE_RELANCE,
+    CATEGORIE_ANNULATION,
+    CATEGORIE_EXCLUSION,
+];
+
+/// Nombre de suivis récents examinés.
+const NB_DERNIERS_SUIVIS: usize = 2;
+/// Contexte (en caractères) conservé de part et d'autre d'une correspondance.
+const CONTEXTE_EXTRAIT: usize = 50;
+
+/// Négation juste avant un mot-clé simple.
+static NEGATION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(non|pas|jamais)(\s+encore)?[\s-]+$").expect("regex négation invalide")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    /// Mot-clé d'annulation ou de doublon.
+    DoublonAnnule,
+    /// Mot-clé de résolution alors que le ticket est toujours vivant.
+    ResoluNonClos,
+    /// Le suivi le plus récent est une relance (pas de réponse depuis).
+    RelanceSansReponse,
+}
+
+impl SignalKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            SignalKind::DoublonAnnule => "Doublon / annulé",
+            SignalKind::ResoluNonClos => "Apparemment résolu mais non clos",
+            SignalKind::RelanceSansReponse => "Relancé sans réponse",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "doublon_annule" => Some(SignalKind::DoublonAnnule),
+            "resolu_non_clos" => Some(SignalKind::ResoluNonClos),
+            "relance_sans_reponse" => Some(SignalKind::RelanceSansReponse),
+            _ => None,
+        }
+    }
+}
+
+/// Entrée de `keyword_dictionaries`.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct KeywordDictionaryEntry {
+    pub id: i64,
+    pub category: String,
+    pub keyword: String,
+    pub is_regex: bool,
+    pub is_active: bool,
+    pub created_at: String,
+}
+
+/// Preuve d'un signal : mot-clé trouvé, texte source et extrait.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatternEvidence {
+    pub kind: SignalKind,
+    pub keyword: String,
+    /// "solution" ou "suivi"
+    pub source: String,
+    pub extrait: String,
+}
+
+struct KeywordMatcher {
+    keyword: String,
+    regex: Regex,
+    is_regex: bool,
+}
+
+impl KeywordMatcher {
+    fn new(keyword: &str, is_regex: bool) -> Result<Self, regex::Error> {
+        let pattern = if is_regex {
+            format!("(?i){keyword}")
+        } else {
+            // Frontières de mot, sauf sur un bord non alphanumérique
+            let borne = |c: Option<char>| {
+                if c.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                    r"\b"
+                } else {
+                    ""
+                }
+            };
+            format!(
+                "(?i){}{}{}",
+                borne(keyword.chars().next()),
+                regex::escape(keyword),
+                borne(keyword.chars().last())
+            )
+        };
+        Ok(Self {
+            keyword: keyword.to_string(),
+            regex: Regex::new(&pattern)?,
+            is_regex,
+        })
+    }
+
+    /// Première occurrence ; pour un mot-clé simple, hors occurrences niées.
+    fn find<'t>(&self, text: &'t str) -> Option<regex::Match<'t>> {
+        self.regex
+            .find_iter(text)
+            .find(|m| self.is_regex || !NEGATION_REGEX.is_match(&text[..m.start()]))
+    }
+}
+
+/// Dictionnaires compilés, prêts pour l'analyse d'un lot de tickets.
+#[derive(Default)]
+pub struct PatternDictionary {
+    resolution: Vec<KeywordMatcher>,
+    relance: Vec<KeywordMatcher>,
+    annulation: Vec<KeywordMatcher>,
+    exclusion: Vec<KeywordMatcher>,
+}
+
+/// Vérifie qu'un mot-clé est utilisable (non vide, regex valide).
+pub fn validate_keyword(keyword: &str, is_regex: bool) -> Result<(), String> {
+    if keyword.trim().is_empty() {
+        return Err("Mot-clé vide".to_string());
+    }
+    KeywordMatcher::new(keyword, is_regex)
+        .map(|_| ())
+        .map_err(|e| format!("Expression régulière invalide: {e}"))
+}
+
+impl PatternDictionary {
+    /// Construit les dictionnaires à partir de (catégorie, mot-clé, is_regex).
+    /// Les regex invalides et catégories inconnues sont ignorées.
+    pub fn from_entries(entries: &[(String, String, bool)]) -> Self {
+        let mut dict = Self::default();
+        for (category, keyword, is_regex) in entries {
+            let matcher = match KeywordMatcher::new(keyword, *is_regex) {
+                Ok(m) => m,
+                Err(e) => {
+                    log::warn!("Mot-clé '{keyword}' ({category}) ignoré: {e}");
+                    continue;
+                }
+            };
+            match category.as_str() {
+                CATEGORIE_RESOLUTION => dict.resolution.push(matcher),
+                CATEGORIE_RELANCE => dict.relance.push(matcher),
+                CATEGORIE_ANNULATION => dict.annulation.push(matcher),
+                CATEGORIE_EXCLUSION => dict.exclusion.push(matcher),
+                _ => {}
+            }
+        }
+        dict
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resolution.is_empty() && self.relance.is_empty() && self.annulation.is_empty()
+    }
+
+    /// Analyse les derniers suivis et la solution d'un ticket.
+    /// Retourne au plus une preuve par type de signal, par ordre de priorité
+    /// (doublon/annulé > résolu non clos > relance sans réponse).
+    pub fn detect(&self, suivis_description: &str, solution: &str) -> Vec<PatternEvidence> {
+        let suivis: Vec<String> = decouper_suivis(suivis_description)
+            .into_iter()
+            .map(|s| strip_html(s).trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let recents: Vec<&str> = suivis
+            .iter()
+            .rev()
+            .take(NB_DERNIERS_SUIVIS)
+            .map(String::as_str)
+            .filter(|s| !self.is_excluded(s))
+            .collect();
+
+        let solution = strip_html(solution);
+        let solution = solution.trim();
+        let solution = (!solution.is_empty() && !self.is_excluded(solution)).then_some(solution);
+
+        // Sources examinées : solution d'abord, puis suivis du plus récent au plus ancien
+        let mut sources: Vec<(&str, &str)> = Vec::new();
+        if let Some(s) = solution {
+            sources.push(("solution", s));
+        }
+        sources.extend(recents.iter().map(|s| ("suivi", *s)));
+
+        let mut evidences = Vec::new();
+
+        if let Some(ev) = first_match(&self.annulation, &sources, SignalKind::DoublonAnnule) {
+            evidences.push(ev);
+        }
+        if let Some(ev) = first_match(&self.resolution, &sources, SignalKind::ResoluNonClos) {
+            evidences.push(ev);
+        }
+        // Relance : uniquement si le suivi le plus récent (non exclu) en est une
+        if let Some(dernier) = suivis.last().filter(|s| !self.is_excluded(s)) {
+            let dernier_source = [("suivi", dernier.as_str())];
+            if let Some(ev) = first_match(
+                &self.relance,
+                &dernier_source,
+                SignalKind::RelanceSansReponse,
+            ) {
+                evidences.push(ev);
+            }
+        }
+
+        evidences
+    }
+
+    fn is_excluded(&self, text: &str) -> bool {
+        self.exclusion.iter().any(|m| m.find(text).is_some())
+    }
+}
+
+fn first_match(
+    matchers: &[KeywordMatcher],
+    sources: &[(&str, &str)],
+    kind: SignalKind,
+) -> Option<PatternEvidence> {
+    for (source, text) in sources {
+        for m in matchers {
+            if let Some(found) = m.find(text) {
+                return Some(PatternEvidence {
+                    kind,
+                    keyword: m.keyword.clone(),
+                    source: source.to_string(),
+                    extrait: extrait(text, found.start(), found.end()),
+                });
+            }
+        }
+    }
+    None
+}
+
+/// Extrait autour d'une correspondance, borné aux frontières de caractères.
+fn extrait(text: &str, start: usize, end: usize) -> String {
+    let debut = text[..start]
+        .char_indices()
+        .rev()
+        .nth(CONTEXTE_EXTRAIT - 1)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let fin = text[end..]
+        .char_indices()
+        .nth(CONTEXTE_EXTRAIT)
+        .map(|(i, _)| end + i)
+        .unwrap_or(text.len());
+
+    let mut out = String::new();
+    if debut > 0 {
+        out.push('…');
+    }
+    out.push_str(text[debut..fin].trim());
+    if fin < text.len() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(category: &str, keyword: &str, is_regex: bool) -> (String, String, bool) {
+        (category.to_string(), keyword.to_string(), is_regex)
+    }
+
+    fn dictionary() -> PatternDictionary {
+        PatternDictionary::from_entries(&[
+            entry("resolution", "réglé", false),
+            entry("resolution", r"re-?fonctionne", true),
+            entry("relance", "sans nouvelles", false),
+            entry("annulation", "doublon", false),
+            entry("exclusion", "mail collecteur", false),
+        ])
+    }
+
+    #[test]
+    fn test_resolution_in_solution() {
+        let ev = dictionary().detect("Prise en charge", "Problème RÉGLÉ après redémarrage");
+        assert_eq!(ev.len(), 1);
+        assert_eq!(ev[0].kind, SignalKind::ResoluNonClos);
+        assert_eq!(ev[0].source, "solution");
+        assert_eq!(ev[0].keyword, "réglé");
+    }
+
+    #[test]
+    fn test_regex_keyword_in_recent_followup() {
+        let ev = dictionary().detect("Diagnostic en cours\nL'imprimante refonctionne", "");
+        assert_eq!(ev[0].kind, SignalKind::ResoluNonClos);
+        assert_eq!(ev[0].source, "suivi");
+    }
+
+    #[test]
+    fn test_only_recent_followups_are_scanned() {
+        // "réglé" est dans un suivi trop ancien
+        let ev = dictionary().detect("Souci réglé\nNouveau souci\nEn cours\nAnalyse", "");
+        assert!(ev.is_empty());
+    }
+
+    #[test]
+    fn test_relance_only_when_last_followup() {
+        let d = dictionary();
+        let ev = d.detect(
+            "Question posée\nToujours sans nouvelles de l'utilisateur",
+            "",
+        );
+        assert_eq!(ev.len(), 1);
+        assert_eq!(ev[0].kind, SignalKind::RelanceSansReponse);
+
+        // L'utilisateur a répondu après la relance
+        let ev = d.detect("Toujours sans nouvelles\nRéponse de l'utilisateur", "");
+        assert!(ev.is_empty());
+    }
+
+    #[test]
+    fn test_multiline_followup_is_one_segment() {
+        // Le dernier suivi s'étend sur deux lignes : la relance reste détectée
+        let ev = dictionary().detect(
+            "Question posée<hr>Toujours sans nouvelles de l'utilisateur\nMerci de revenir vers nous",
+            "",
+        );
+        assert_eq!(ev.len(), 1);
+        assert_eq!(ev[0].kind, SignalKind::RelanceSansReponse);
+    }
+
+    #[test]
+    fn test_exclusion_masks_segment() {
+        let ev = dictionary().detect("Doublon créé par le mail collecteur", "");
+        assert!(ev.is_empty());
+    }
+
+    #[test]
+    fn test_priority_order() {
+        let ev = dictionary().detect("Doublon du 1234, problème réglé", "");
+        let kinds: Vec<SignalKind> = ev.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![SignalKind::DoublonAnnule, SignalKind::ResoluNonClos]
+        );
+    }
+
+    #[test]
+    fn test_plain_keyword_whole_word_and_not_negated() {
+        let d = PatternDictionary::from_entries(&[entry("resolution", "terminé", false)]);
+        assert!(d.detect("Intervention non terminé", "").is_empty());
+        assert!(d.detect("Déploiement pas encore terminé", "").is_empty());
+        assert!(d.detect("Périmètre déterminé ce matin", "").is_empty());
+        assert!(d.detect("Traitement non-terminé", "").is_empty());
+
+        let ev = d.detect("Déploiement non terminé hier, terminé ce matin", "");
+        assert_eq!(ev.len(), 1);
+        assert!(ev[0].extrait.contains("ce matin"));
+    }
+
+    #[test]
+    fn test_invalid_regex_is_ignored() {
+        let d = PatternDictionary::from_entries(&[entry("resolution", "(", true)]);
+        assert!(d.is_empty());
+        assert!(validate_keyword("(", true).is_err());
+        assert!(validate_keyword("(", false).is_ok());
+        assert!(validate_keyword("  ", false).is_err());
+    }
+
+    #[test]
+    fn test_extrait_bounds_utf8() {
+        let text = format!("{}réglé{}", "é".repeat(80), "à".repeat(80));
+        let start = text.find("réglé").unwrap();
+        let ex = extrait(&text, start, start + "réglé".len());
+        assert!(ex.starts_with('…') && ex.ends_with('…'));
+        assert!(ex.contains("réglé"));
+    }
+}
@@ -20,6 +20,7 @@ const REQUIRED: &[&str] = &[
 const OPTIONAL: &[&str] = &[
     "Priorité",
     "Urgence",
+    "Impact",
     "Demandeur - Demandeur",
     "Dernière modification",
     "Suivis - Nombre de suivis",
@@ -163,6 +164,7 @@ mod tests {
             "Catégorie",
             "Priorité",
             "Urgence",
+            "Impact",
             "Demandeur - Demandeur",
             "Dernière modification",
             "Suivis - Nombre de suivis",
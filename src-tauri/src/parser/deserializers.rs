@@ -89,6 +89,38 @@ pub fn parse_opt_i32(s: &str) -> Option<i32> {
     trimmed.parse::<i32>().ok()
 }
 
+/// Niveau GLPI (priorité, urgence) exporté en chiffre ou en libellé
+/// ("Haute" → 4, "Majeure" → 6).
+pub fn parse_niveau_glpi(s: &str) -> Option<i32> {
+    if let Some(n) = parse_opt_i32(s) {
+        return Some(n);
+    }
+    match s.trim().to_lowercase().as_str() {
+        "très basse" | "tres basse" => Some(1),
+        "basse" => Some(2),
+        "moyenne" => Some(3),
+        "haute" => Some(4),
+        "très haute" | "tres haute" => Some(5),
+        "majeure" => Some(6),
+        _ => None,
+    }
+}
+
+/// Impact GLPI : mêmes niveaux, libellés au masculin ("Haut" → 4).
+pub fn parse_impact_glpi(s: &str) -> Option<i32> {
+    if let Some(n) = parse_opt_i32(s) {
+        return Some(n);
+    }
+    match s.trim().to_lowercase().as_str() {
+        "très bas" | "tres bas" => Some(1),
+        "bas" => Some(2),
+        "moyen" => Some(3),
+        "haut" => Some(4),
+        "très haut" | "tres haut" => Some(5),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,4 +220,23 @@ mod tests {
         assert_eq!(parse_opt_i32("3"), Some(3));
         assert_eq!(parse_opt_i32("invalid"), None);
     }
+
+    #[test]
+    fn test_parse_niveau_glpi() {
+        assert_eq!(parse_niveau_glpi("4"), Some(4));
+        assert_eq!(parse_niveau_glpi(" Très haute "), Some(5));
+        assert_eq!(parse_niveau_glpi("Moyenne"), Some(3));
+        assert_eq!(parse_niveau_glpi("Majeure"), Some(6));
+        assert_eq!(parse_niveau_glpi(""), None);
+        assert_eq!(parse_niveau_glpi("Urgent"), None);
+    }
+
+    #[test]
+    fn test_parse_impact_glpi() {
+        assert_eq!(parse_impact_glpi("Haut"), Some(4));
+        assert_eq!(parse_impact_glpi(" Très bas "), Some(1));
+        assert_eq!(parse_impact_glpi("Moyen"), Some(3));
+        assert_eq!(parse_impact_glpi("2"), Some(2));
+        assert_eq!(parse_impact_glpi("Haute"), None);
+    }
 }
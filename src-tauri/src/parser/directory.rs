@@ -0,0 +1,194 @@
+//! Lecture d'un annuaire demandeur → service (CSV).
+//!
+//! Le fichier doit comporter une ligne d'en-tête avec une colonne demandeur
+//! ("Demandeur", "Utilisateur", "Nom", "Login"…) et une colonne service
+//! ("Service", "Département", "Direction", "Entité"). Séparateur détecté
+//! sur la ligne d'en-tête (';', ',' ou tabulation), encodage comme les
+//! exports GLPI (UTF-8 avec ou sans BOM, sinon Windows-1252).
+
+use std::collections::HashMap;
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::error::AppError;
+
+use super::pipeline::decode_to_utf8;
+
+const COLONNES_DEMANDEUR: [&str; 5] = ["demandeur", "utilisateur", "login", "nom", "requester"];
+/// Candidats comparés aux en-têtes normalisés (minuscules, sans accents).
+const COLONNES_SERVICE: [&str; 4] = ["service", "departement", "direction", "entite"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntreeAnnuaire {
+    pub demandeur: String,
+    pub service: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnuaireParse {
+    pub entrees: Vec<EntreeAnnuaire>,
+    pub lignes: usize,
+    /// Lignes sans demandeur ou sans service.
+    pub ignorees: usize,
+    /// Demandeurs présents plusieurs fois (la dernière ligne l'emporte).
+    pub doublons: usize,
+}
+
+fn detect_delimiter(entete: &str) -> u8 {
+    [b';', b'\t', b',']
+        .into_iter()
+        .max_by_key(|&d| entete.bytes().filter(|&b| b == d).count())
+        .filter(|&d| entete.as_bytes().contains(&d))
+        .unwrap_or(b';')
+}
+
+/// En-tête en minuscules ASCII, accents retirés.
+fn normaliser(entete: &str) -> String {
+    entete
+        .trim()
+        .nfd()
+        .filter(char::is_ascii)
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn find_column(headers: &csv::StringRecord, candidats: &[&str]) -> Option<usize> {
+    // En-tête égal à un candidat d'abord, puis en-tête dont un mot est un
+    // candidat ("Nom complet" oui, "Prénom" non)
+    let noms: Vec<String> = headers.iter().map(normaliser).collect();
+    candidats
+        .iter()
+        .find_map(|c| noms.iter().position(|n| n == c))
+        .or_else(|| {
+            candidats.iter().find_map(|c| {
+                noms.iter().position(|n| {
+                    n.split(|ch: char| !ch.is_ascii_alphanumeric())
+                        .any(|mot| mot == *c)
+                })
+            })
+        })
+}
+
+pub fn parse_annuaire(bytes: &[u8]) -> Result<AnnuaireParse, AppError> {
+    let contenu = decode_to_utf8(bytes);
+    let entete = contenu.lines().next().unwrap_or_default();
+    if entete.trim().is_empty() {
+        return Err(AppError::EmptyFile);
+    }
+
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(entete))
+        .has_headers(true)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(contenu.as_bytes());
+
+    let headers = rdr.headers()?.clone();
+    let mut manquantes = Vec::new();
+    let col_demandeur = find_column(&headers, &COLONNES_DEMANDEUR);
+    let col_service = find_column(&headers, &COLONNES_SERVICE);
+    if col_demandeur.is_none() {
+        manquantes.push("Demandeur".to_string());
+    }
+    if col_service.is_none() {
+        manquantes.push("Service".to_string());
+    }
+    let (Some(col_demandeur), Some(col_service)) = (col_demandeur, col_service) else {
+        return Err(AppError::MissingColumns(manquantes));
+    };
+
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut entrees: Vec<EntreeAnnuaire> = Vec::new();
+    let (mut lignes, mut ignorees, mut doublons) = (0, 0, 0);
+    for record in rdr.records() {
+        let record = record?;
+        lignes += 1;
+        let demandeur = record.get(col_demandeur).unwrap_or_default();
+        let service = record.get(col_service).unwrap_or_default();
+        if demandeur.is_empty() || service.is_empty() {
+            ignorees += 1;
+            continue;
+        }
+        let entree = EntreeAnnuaire {
+            demandeur: demandeur.to_string(),
+            service: service.to_string(),
+        };
+        match index.get(&demandeur.to_lowercase()) {
+            Some(&i) => {
+                doublons += 1;
+                entrees[i] = entree;
+            }
+            None => {
+                index.insert(demandeur.to_lowercase(), entrees.len());
+                entrees.push(entree);
+            }
+        }
+    }
+
+    if entrees.is_empty() {
+        return Err(AppError::EmptyFile);
+    }
+    Ok(AnnuaireParse {
+        entrees,
+        lignes,
+        ignorees,
+        doublons,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_semicolon_with_bom() {
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend_from_slice(
+            "Nom complet;Département\nDupont Jean;Comptabilité\nMartin Léa;RH\n;Achats\nDUPONT JEAN;Finances\n"
+                .as_bytes(),
+        );
+        let parse = parse_annuaire(&bytes).unwrap();
+        assert_eq!(parse.lignes, 4);
+        assert_eq!(parse.ignorees, 1);
+        assert_eq!(parse.doublons, 1);
+        assert_eq!(parse.entrees.len(), 2);
+        assert_eq!(parse.entrees[0].demandeur, "DUPONT JEAN");
+        assert_eq!(parse.entrees[0].service, "Finances");
+    }
+
+    #[test]
+    fn test_parse_comma_and_latin1() {
+        // "Entité" en Windows-1252
+        let mut bytes = b"Login,Entit".to_vec();
+        bytes.push(0xE9);
+        bytes.extend_from_slice(b"\njdupont,DSI\n");
+        let parse = parse_annuaire(&bytes).unwrap();
+        assert_eq!(
+            parse.entrees,
+            vec![EntreeAnnuaire {
+                demandeur: "jdupont".into(),
+                service: "DSI".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_colonnes_exactes_puis_mot_entier() {
+        let parse =
+            parse_annuaire("Prénom;Nom de famille;ENTITÉ\nJean;Dupont;DSI\n".as_bytes()).unwrap();
+        assert_eq!(
+            parse.entrees,
+            vec![EntreeAnnuaire {
+                demandeur: "Dupont".into(),
+                service: "DSI".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_columns() {
+        let err = parse_annuaire(b"Nom;Telephone\nDupont;0102\n").unwrap_err();
+        assert!(matches!(err, AppError::MissingColumns(ref c) if c == &["Service".to_string()]));
+        assert!(matches!(parse_annuaire(b""), Err(AppError::EmptyFile)));
+    }
+}
@@ -1,4 +1,7 @@
 pub mod columns;
 pub mod deserializers;
+pub mod directory;
 pub mod pipeline;
+pub mod suivis;
 pub mod types;
+pub mod vendor;
@@ -8,7 +8,9 @@ use encoding_rs::WINDOWS_1252;
 
 use crate::error::AppError;
 use crate::parser::columns::{validate_columns, ColumnMap};
-use crate::parser::deserializers::{parse_french_datetime, parse_opt_i32, parse_spaced_i64};
+use crate::parser::deserializers::{
+    parse_french_datetime, parse_impact_glpi, parse_niveau_glpi, parse_opt_i32, parse_spaced_i64,
+};
 use crate::parser::types::{GlpiTicketNormalized, GlpiTicketRaw, ParseWarning};
 
 /// Statuts indiquant un ticket encore actif (vivant).
@@ -58,7 +60,7 @@ pub fn parse_file(
 
 /// Detect encoding from raw bytes and convert to UTF-8 string.
 /// Strategy: strip UTF-8 BOM → try UTF-8 → fallback Windows-1252 (superset of Latin-1/CP850).
-fn decode_to_utf8(bytes: &[u8]) -> String {
+pub(crate) fn decode_to_utf8(bytes: &[u8]) -> String {
     // Strip UTF-8 BOM if present
     let data = if bytes.starts_with(&[0xEF, 0xBB, 0xBF]) {
         &bytes[3..]
@@ -121,6 +123,7 @@ fn row_to_raw(col_map: &ColumnMap, row: &[String]) -> GlpiTicketRaw {
         type_ticket: col_map.get_from_slice(row, "Type").map(str::to_string),
         priorite: col_map.get_from_slice(row, "Priorité").map(str::to_string),
         urgence: col_map.get_from_slice(row, "Urgence").map(str::to_string),
+        impact: col_map.get_from_slice(row, "Impact").map(str::to_string),
         demandeur: col_map
             .get_from_slice(row, "Demandeur - Demandeur")
             .map(str::to_string),
@@ -366,6 +369,7 @@ fn record_to_raw(col_map: &ColumnMap, record: &csv::StringRecord) -> GlpiTicketR
         type_ticket: col_map.get(record, "Type").map(str::to_string),
         priorite: col_map.get(record, "Priorité").map(str::to_string),
         urgence: col_map.get(record, "Urgence").map(str::to_string),
+        impact: col_map.get(record, "Impact").map(str::to_string),
         demandeur: col_map
             .get(record, "Demandeur - Demandeur")
             .map(str::to_string),
@@ -501,26 +505,28 @@ fn normalize_ticket(
         .filter(|s| !s.trim().is_empty())
         .map(|s| s.trim().to_string());
 
-    let c_parts: Vec<&str> = categorie.as_deref().unwrap_or("").split(" > ").collect();
-    let categorie_niveau1 = c_parts
-        .first()
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty());
-    let categorie_niveau2 = c_parts
-        .get(1)
+    // Chemin complet (profondeur quelconque), niveaux vides ignorés
+    let categorie_chemin: Vec<String> = categorie
+        .as_deref()
+        .unwrap_or("")
+        .split(" > ")
         .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty());
+        .filter(|s| !s.is_empty())
+        .collect();
+    let categorie_niveau1 = categorie_chemin.first().cloned();
+    let categorie_niveau2 = categorie_chemin.get(1).cloned();
 
     Ok(GlpiTicketNormalized {
         id,
         titre: raw.titre.as_deref().unwrap_or("").trim().to_string(),
         statut,
         type_ticket: raw.type_ticket.as_deref().unwrap_or("").trim().to_string(),
-        priorite: raw.priorite.as_deref().and_then(parse_opt_i32),
+        priorite: raw.priorite.as_deref().and_then(parse_niveau_glpi),
         priorite_label: raw.priorite.as_deref()
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty()),
-        urgence: raw.urgence.as_deref().and_then(parse_opt_i32),
+        urgence: raw.urgence.as_deref().and_then(parse_niveau_glpi),
+        impact: raw.impact.as_deref().and_then(parse_impact_glpi),
         demandeur: raw.demandeur.as_deref().unwrap_or("").trim().to_string(),
         date_ouverture,
         derniere_modification,
@@ -551,6 +557,7 @@ fn normalize_ticket(
         categorie,
         categorie_niveau1,
         categorie_niveau2,
+        categorie_chemin,
         date_resolution,
         est_vivant,
         anciennete_jours,
@@ -644,6 +651,17 @@ mod tests {
         assert_eq!(out.tickets[0].categorie.as_deref(), Some("MaCat"));
     }
 
+    #[test]
+    fn test_categorie_chemin_profond() {
+        let csv = format!(
+            "{HDR}\n1;T;G;Nouveau;T;D;01-01-2026 08:00;Inc;;0;;;;3;;4;;Matériel > Poste > Écran >  Dalle "
+        );
+        let out = parse(&csv);
+        let t = &out.tickets[0];
+        assert_eq!(t.categorie_chemin, vec!["Matériel", "Poste", "Écran", "Dalle"]);
+        assert_eq!(t.categorie_niveau2.as_deref(), Some("Poste"));
+    }
+
     // ── US002 / RG-006 : empty numeric fields → None ─────────────────────────
 
     #[test]
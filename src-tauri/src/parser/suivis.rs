@@ -0,0 +1,46 @@
+//! Découpage du champ « Suivis - Description » en suivis individuels.
+//!
+//! GLPI sépare les suivis d'un ticket par une ligne horizontale (`<hr>`, ou
+//! le marqueur brut `#LBHR#` selon la version) ; un même suivi peut contenir
+//! des retours à la ligne et des `<br>`. Quand le HTML de l'export a été
+//! nettoyé, le séparateur disparaît : chaque ligne est alors un suivi.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+static SEPARATEUR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<hr\s*/?>|#LBHR#").expect("regex séparateur invalide"));
+
+/// Suivis bruts (HTML conservé), du plus ancien au plus récent. Les segments
+/// vides sont conservés : à l'appelant de les filtrer après nettoyage.
+pub fn decouper_suivis(brut: &str) -> Vec<&str> {
+    if SEPARATEUR_REGEX.is_match(brut) {
+        SEPARATEUR_REGEX.split(brut).collect()
+    } else {
+        brut.split('\n').collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separateur_glpi_garde_les_suivis_multilignes() {
+        let suivis = decouper_suivis("Premier<hr>Deuxième\nsur deux lignes<br>fin<HR />Troisième");
+        assert_eq!(
+            suivis,
+            vec!["Premier", "Deuxième\nsur deux lignes<br>fin", "Troisième"]
+        );
+        assert_eq!(decouper_suivis("A#LBHR#B\nC"), vec!["A", "B\nC"]);
+    }
+
+    #[test]
+    fn test_sans_separateur_une_ligne_par_suivi() {
+        assert_eq!(
+            decouper_suivis("Premier\nSecond"),
+            vec!["Premier", "Second"]
+        );
+    }
+}
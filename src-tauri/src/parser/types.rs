@@ -8,6 +8,7 @@ pub struct GlpiTicketRaw {
     pub type_ticket: Option<String>,
     pub priorite: Option<String>,
     pub urgence: Option<String>,
+    pub impact: Option<String>,
     pub demandeur: Option<String>,
     pub date_ouverture: Option<String>,
     pub derniere_modification: Option<String>,
@@ -31,6 +32,7 @@ pub struct GlpiTicketNormalized {
     pub priorite: Option<i32>,
     pub priorite_label: Option<String>,
     pub urgence: Option<i32>,
+    pub impact: Option<i32>,
     pub demandeur: String,
     pub date_ouverture: String,
     pub derniere_modification: Option<String>,
@@ -49,6 +51,8 @@ pub struct GlpiTicketNormalized {
     pub categorie: Option<String>,
     pub categorie_niveau1: Option<String>,
     pub categorie_niveau2: Option<String>,
+    /// Chemin complet de la catégorie, un élément par niveau.
+    pub categorie_chemin: Vec<String>,
     pub date_resolution: Option<String>,
     pub est_vivant: bool,
     pub anciennete_jours: Option<i64>,
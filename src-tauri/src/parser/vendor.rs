@@ -0,0 +1,151 @@
+//! Lecture du champ « Plugins - Intervention fourniseur : Intervention ».
+//!
+//! Le plugin exporte une intervention par ligne (retours à la ligne ou
+//! `<br>`), sous une forme libre : nom du fournisseur, éventuellement suivi
+//! d'une date de début et d'une date de fin (« Orange du 02-01-2025 au
+//! 05-01-2025 », « Dell - 2025-01-02 10:00 »). Les dates sont extraites, le
+//! reste de la ligne donne le fournisseur. Une valeur booléenne (« Oui »,
+//! « 1 ») signale une intervention sans fournisseur précisé.
+
+use std::sync::LazyLock;
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use regex::Regex;
+
+use super::deserializers::parse_french_datetime;
+
+static SEPARATEUR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\r?\n|\r|<br\s*/?>").expect("regex séparateur invalide"));
+
+static DATE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"\b(\d{1,2}[/-]\d{1,2}[/-]\d{4}|\d{4}-\d{2}-\d{2})(?:[ T](\d{1,2}:\d{2})(?::\d{2})?)?\b",
+    )
+    .expect("regex date invalide")
+});
+
+static MOTS_VIDES_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:fournisseur|intervention|prestataire)\s*:|\b(?:du|au|le|depuis|jusqu'au|de|à)\s*$")
+        .expect("regex mots vides invalide")
+});
+
+/// Valeurs signalant l'absence d'intervention.
+const VALEURS_NEGATIVES: [&str; 5] = ["0", "non", "no", "aucune", "aucun"];
+/// Valeurs signalant une intervention sans fournisseur précisé.
+const VALEURS_POSITIVES: [&str; 4] = ["1", "oui", "yes", "x"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterventionFournisseur {
+    /// `None` quand seul un indicateur (« Oui ») est exporté.
+    pub fournisseur: Option<String>,
+    pub date_debut: Option<NaiveDateTime>,
+    pub date_fin: Option<NaiveDateTime>,
+}
+
+fn parse_date(date: &str, heure: Option<&str>) -> Option<NaiveDateTime> {
+    let date = date.replace('/', "-");
+    let texte = match heure {
+        Some(h) => format!("{date} {h}"),
+        None => date.clone(),
+    };
+    parse_french_datetime(&texte).or_else(|| {
+        let jour = NaiveDate::parse_from_str(&date, "%d-%m-%Y").ok()?;
+        let heure = heure
+            .and_then(|h| NaiveTime::parse_from_str(h, "%H:%M").ok())
+            .unwrap_or(NaiveTime::MIN);
+        Some(jour.and_time(heure))
+    })
+}
+
+fn nettoyer_fournisseur(texte: &str) -> Option<String> {
+    let mut nom = texte.trim().to_string();
+    // Retire libellés de tête et prépositions de fin jusqu'à stabilité
+    loop {
+        let nettoye = MOTS_VIDES_REGEX
+            .replace_all(&nom, " ")
+            .trim_matches(|c: char| c.is_whitespace() || "-:|,;()[]".contains(c))
+            .to_string();
+        if nettoye == nom {
+            break;
+        }
+        nom = nettoye;
+    }
+    let nom = nom.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!nom.is_empty()).then_some(nom)
+}
+
+fn parse_ligne(ligne: &str) -> Option<InterventionFournisseur> {
+    let ligne = ligne.trim();
+    if ligne.is_empty() || VALEURS_NEGATIVES.contains(&ligne.to_lowercase().as_str()) {
+        return None;
+    }
+    if VALEURS_POSITIVES.contains(&ligne.to_lowercase().as_str()) {
+        return Some(InterventionFournisseur {
+            fournisseur: None,
+            date_debut: None,
+            date_fin: None,
+        });
+    }
+    let dates: Vec<NaiveDateTime> = DATE_REGEX
+        .captures_iter(ligne)
+        .filter_map(|c| parse_date(&c[1], c.get(2).map(|m| m.as_str())))
+        .collect();
+    let sans_dates = DATE_REGEX.replace_all(ligne, " ");
+    let fournisseur = nettoyer_fournisseur(&sans_dates);
+    if fournisseur.is_none() && dates.is_empty() {
+        return None;
+    }
+    Some(InterventionFournisseur {
+        fournisseur,
+        date_debut: dates.first().copied(),
+        date_fin: dates.get(1).copied(),
+    })
+}
+
+/// Interventions décrites dans le champ brut, dans l'ordre d'apparition.
+pub fn parse_interventions(brut: &str) -> Vec<InterventionFournisseur> {
+    SEPARATEUR_REGEX
+        .split(brut)
+        .filter_map(parse_ligne)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_vendor_with_dates() {
+        let i = parse_interventions("Orange du 02-01-2025 au 05/01/2025 14:30");
+        assert_eq!(i.len(), 1);
+        assert_eq!(i[0].fournisseur.as_deref(), Some("Orange"));
+        assert_eq!(i[0].date_debut, Some(dt("2025-01-02 00:00:00")));
+        assert_eq!(i[0].date_fin, Some(dt("2025-01-05 14:30:00")));
+    }
+
+    #[test]
+    fn test_multiple_lines_and_labels() {
+        let i = parse_interventions(
+            "Fournisseur : Dell - 2025-01-02 10:00<br/>  \nIBM (depuis le 2025-02-01)\r\nNon",
+        );
+        assert_eq!(i.len(), 2);
+        assert_eq!(i[0].fournisseur.as_deref(), Some("Dell"));
+        assert_eq!(i[0].date_debut, Some(dt("2025-01-02 10:00:00")));
+        assert_eq!(i[0].date_fin, None);
+        assert_eq!(i[1].fournisseur.as_deref(), Some("IBM"));
+        assert_eq!(i[1].date_debut, Some(dt("2025-02-01 00:00:00")));
+    }
+
+    #[test]
+    fn test_flags() {
+        assert!(parse_interventions("").is_empty());
+        assert!(parse_interventions(" 0 ").is_empty());
+        let oui = parse_interventions("Oui");
+        assert_eq!(oui.len(), 1);
+        assert_eq!(oui[0].fournisseur, None);
+    }
+}
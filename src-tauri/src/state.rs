@@ -1,8 +1,24 @@
 use rusqlite::Connection;
 use std::sync::Mutex;
 
+use crate::workspace::WorkspaceContext;
+
 pub struct AppState {
     pub db: Mutex<Option<Connection>>,
+    /// Workspace ouvert. Verrou pris avant `db` lors d'un changement de workspace.
+    pub workspace: Mutex<Option<WorkspaceContext>>,
+}
+
+impl AppState {
+    /// Remplace la connexion courante. L'ancienne connexion est fermée hors verrou.
+    pub fn swap_connection(&self, conn: Connection) -> Result<(), String> {
+        let previous = {
+            let mut guard = self.db.lock().map_err(|e| format!("Mutex poisoned: {}", e))?;
+            guard.replace(conn)
+        };
+        drop(previous);
+        Ok(())
+    }
 }
 
 pub trait DbAccess {
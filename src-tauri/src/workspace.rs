@@ -0,0 +1,308 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+/// Registre des espaces de travail, persisté dans `workspaces.json` (app_data_dir).
+const REGISTRY_FILE: &str = "workspaces.json";
+/// Sous-dossier contenant les bases des workspaces créés par l'utilisateur.
+const WORKSPACES_DIR: &str = "workspaces";
+/// Le workspace par défaut réutilise la base historique pour ne rien perdre.
+pub const DEFAULT_WORKSPACE_ID: &str = "default";
+const DEFAULT_WORKSPACE_NAME: &str = "Par défaut";
+const DEFAULT_DB_FILE: &str = "glpi_dashboard.db";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    /// Chemin de la base, relatif à app_data_dir.
+    pub db_file: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceRegistry {
+    pub last_used: String,
+    pub workspaces: Vec<Workspace>,
+}
+
+/// Workspace actuellement ouvert, conservé dans AppState.
+#[derive(Debug, Clone)]
+pub struct WorkspaceContext {
+    pub data_dir: PathBuf,
+    pub active_id: String,
+}
+
+impl Default for WorkspaceRegistry {
+    fn default() -> Self {
+        Self {
+            last_used: DEFAULT_WORKSPACE_ID.to_string(),
+            workspaces: vec![Workspace {
+                id: DEFAULT_WORKSPACE_ID.to_string(),
+                name: DEFAULT_WORKSPACE_NAME.to_string(),
+                db_file: DEFAULT_DB_FILE.to_string(),
+                created_at: now_iso(),
+            }],
+        }
+    }
+}
+
+impl WorkspaceRegistry {
+    /// Charge le registre ; absent → registre par défaut pointant sur la base historique.
+    pub fn load(data_dir: &Path) -> Result<Self, AppError> {
+        let path = data_dir.join(REGISTRY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let raw = std::fs::read_to_string(&path)?;
+        let mut registry: Self = serde_json::from_str(&raw)?;
+        if registry.workspaces.is_empty() {
+            registry = Self::default();
+        }
+        if registry.get(&registry.last_used).is_none() {
+            registry.last_used = registry.workspaces[0].id.clone();
+        }
+        Ok(registry)
+    }
+
+    /// Écrit le registre via un fichier temporaire puis rename (pas de JSON tronqué).
+    pub fn save(&self, data_dir: &Path) -> Result<(), AppError> {
+        std::fs::create_dir_all(data_dir)?;
+        let path = data_dir.join(REGISTRY_FILE);
+        let tmp = data_dir.join(format!("{REGISTRY_FILE}.tmp"));
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Workspace> {
+        self.workspaces.iter().find(|w| w.id == id)
+    }
+
+    /// Workspace à ouvrir au démarrage (dernier utilisé).
+    pub fn last_used(&self) -> &Workspace {
+        self.get(&self.last_used).unwrap_or(&self.workspaces[0])
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<Workspace, AppError> {
+        let name = self.validate_name(name, None)?;
+
+        let base = slugify(&name);
+        let mut id = base.clone();
+        let mut n = 2;
+        while self.get(&id).is_some() {
+            id = format!("{base}-{n}");
+            n += 1;
+        }
+
+        let ws = Workspace {
+            db_file: format!("{WORKSPACES_DIR}/{id}.db"),
+            id,
+            name,
+            created_at: now_iso(),
+        };
+        self.workspaces.push(ws.clone());
+        Ok(ws)
+    }
+
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<Workspace, AppError> {
+        let name = self.validate_name(name, Some(id))?;
+        let ws = self
+            .workspaces
+            .iter_mut()
+            .find(|w| w.id == id)
+            .ok_or_else(|| AppError::Custom(format!("Workspace introuvable: {id}")))?;
+        ws.name = name;
+        Ok(ws.clone())
+    }
+
+    /// Retire un workspace du registre. Le workspace actif, le dernier restant
+    /// et le workspace par défaut (base historique) ne peuvent pas être supprimés.
+    pub fn remove(&mut self, id: &str, active_id: &str) -> Result<Workspace, AppError> {
+        if id == active_id {
+            return Err(AppError::Custom(
+                "Impossible de supprimer le workspace actif".to_string(),
+            ));
+        }
+        let idx = self
+            .workspaces
+            .iter()
+            .position(|w| w.id == id)
+            .ok_or_else(|| AppError::Custom(format!("Workspace introuvable: {id}")))?;
+        if self.workspaces[idx].db_file == DEFAULT_DB_FILE {
+            return Err(AppError::Custom(
+                "Impossible de supprimer le workspace par défaut".to_string(),
+            ));
+        }
+        if self.workspaces.len() == 1 {
+            return Err(AppError::Custom(
+                "Impossible de supprimer le dernier workspace".to_string(),
+            ));
+        }
+        Ok(self.workspaces.remove(idx))
+    }
+
+    fn validate_name(&self, name: &str, exclude_id: Option<&str>) -> Result<String, AppError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Custom("Nom de workspace vide".to_string()));
+        }
+        let lower = name.to_lowercase();
+        let taken = self
+            .workspaces
+            .iter()
+            .any(|w| Some(w.id.as_str()) != exclude_id && w.name.to_lowercase() == lower);
+        if taken {
+            return Err(AppError::Custom(format!(
+                "Un workspace « {name} » existe déjà"
+            )));
+        }
+        Ok(name.to_string())
+    }
+}
+
+/// Chemin absolu de la base d'un workspace.
+pub fn db_path(data_dir: &Path, ws: &Workspace) -> PathBuf {
+    data_dir.join(&ws.db_file)
+}
+
+/// Supprime la base d'un workspace ainsi que ses fichiers WAL/SHM.
+pub fn remove_db_files(path: &Path) -> Result<(), AppError> {
+    for suffix in ["", "-wal", "-shm"] {
+        let mut p = path.as_os_str().to_os_string();
+        p.push(suffix);
+        let p = PathBuf::from(p);
+        if p.exists() {
+            std::fs::remove_file(&p)?;
+        }
+    }
+    Ok(())
+}
+
+/// Identifiant de fichier : minuscules ASCII, accents retirés, tirets.
+pub fn slugify(name: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+
+    let mut slug = String::new();
+    for c in name.nfd().filter(|c| c.is_ascii()) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_matches('-').to_string();
+    if slug.is_empty() {
+        "workspace".to_string()
+    } else {
+        slug
+    }
+}
+
+fn now_iso() -> String {
+    chrono::Local::now()
+        .naive_local()
+        .format("%Y-%m-%dT%H:%M:%S")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("glpi-ws-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_slugify_accents_and_spaces() {
+        assert_eq!(slugify("Équipe Réseau 2026"), "equipe-reseau-2026");
+        assert_eq!(slugify("  --  "), "workspace");
+    }
+
+    #[test]
+    fn test_default_registry_uses_legacy_db() {
+        let reg = WorkspaceRegistry::default();
+        assert_eq!(reg.last_used, DEFAULT_WORKSPACE_ID);
+        assert_eq!(reg.last_used().db_file, "glpi_dashboard.db");
+    }
+
+    #[test]
+    fn test_create_generates_unique_ids() {
+        let mut reg = WorkspaceRegistry::default();
+        let a = reg.create("Support N2").unwrap();
+        let b = reg.create("Support-N2 bis").unwrap();
+        assert_eq!(a.id, "support-n2");
+        assert_eq!(a.db_file, "workspaces/support-n2.db");
+        assert_ne!(a.id, b.id);
+        // Nom en double (insensible à la casse) refusé
+        assert!(reg.create("support n2").is_err());
+        assert!(reg.create("   ").is_err());
+    }
+
+    #[test]
+    fn test_rename_keeps_id() {
+        let mut reg = WorkspaceRegistry::default();
+        let ws = reg.create("Projet A").unwrap();
+        let renamed = reg.rename(&ws.id, "Projet B").unwrap();
+        assert_eq!(renamed.id, ws.id);
+        assert_eq!(renamed.name, "Projet B");
+        // Renommer avec son propre nom est autorisé
+        assert!(reg.rename(&ws.id, "projet b").is_ok());
+        assert!(reg.rename("inconnu", "X").is_err());
+    }
+
+    #[test]
+    fn test_remove_refuses_active_and_last() {
+        let mut reg = WorkspaceRegistry::default();
+        assert!(reg.remove(DEFAULT_WORKSPACE_ID, "autre").is_err());
+        let ws = reg.create("Temp").unwrap();
+        assert!(reg.remove(&ws.id, &ws.id).is_err());
+        let removed = reg.remove(&ws.id, DEFAULT_WORKSPACE_ID).unwrap();
+        assert_eq!(removed.id, ws.id);
+        assert_eq!(reg.workspaces.len(), 1);
+    }
+
+    #[test]
+    fn test_remove_refuses_default_workspace() {
+        let mut reg = WorkspaceRegistry::default();
+        let ws = reg.create("Projet").unwrap();
+        let err = reg.remove(DEFAULT_WORKSPACE_ID, &ws.id).unwrap_err();
+        assert!(err.to_string().contains("par défaut"), "{err}");
+        assert_eq!(reg.workspaces.len(), 2);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = temp_dir("roundtrip");
+        let mut reg = WorkspaceRegistry::default();
+        let ws = reg.create("Client X").unwrap();
+        reg.last_used = ws.id.clone();
+        reg.save(&dir).unwrap();
+
+        let loaded = WorkspaceRegistry::load(&dir).unwrap();
+        assert_eq!(loaded.workspaces.len(), 2);
+        assert_eq!(loaded.last_used().id, ws.id);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_load_falls_back_when_last_used_missing() {
+        let dir = temp_dir("fallback");
+        let mut reg = WorkspaceRegistry::default();
+        reg.last_used = "supprime".to_string();
+        reg.save(&dir).unwrap();
+
+        let loaded = WorkspaceRegistry::load(&dir).unwrap();
+        assert_eq!(loaded.last_used, DEFAULT_WORKSPACE_ID);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
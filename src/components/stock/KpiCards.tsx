@@ -8,7 +8,11 @@ interface KpiCardsProps {
 }
 
 export function KpiCards({ overview, onUnassignedClick }: KpiCardsProps) {
-  const over30 = overview.parAnciennete.find((r) => r.thresholdDays === 30)?.count ?? 0;
+  // Somme des tranches au-dela de 30 j ; carte masquee si aucune tranche ne commence a 30 j
+  const has30 = overview.parAnciennete.some((r) => r.thresholdDays === 30);
+  const over30 = overview.parAnciennete
+    .filter((r) => r.thresholdDays >= 30)
+    .reduce((sum, r) => sum + r.count, 0);
 
   return (
     <div className="grid grid-cols-1 gap-5 sm:grid-cols-2 xl:grid-cols-5">
@@ -39,12 +43,14 @@ export function KpiCards({ overview, onUnassignedClick }: KpiCardsProps) {
         icon={<Clock size={18} className="text-amber-600" />}
         accentColor="#FF8F00"
       />
-      <KpiCard
-        label="Tickets > 30 j"
-        value={over30}
-        icon={<AlertTriangle size={18} className="text-red-600" />}
-        accentColor="#C62828"
-      />
+      {has30 && (
+        <KpiCard
+          label="Tickets > 30 j"
+          value={over30}
+          icon={<AlertTriangle size={18} className="text-red-600" />}
+          accentColor="#C62828"
+        />
+      )}
     </div>
   );
 }
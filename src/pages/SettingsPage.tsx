@@ -300,6 +300,10 @@ function SettingsPage() {
               onChange={(v) => set("seuilInactiviteRelancer", v)}
             />
           </div>
+          <p className="mt-3 text-xs text-slate-400">
+            La classification est calculee a l'import : ces seuils et leurs surcharges
+            par groupe ou technicien s'appliquent au prochain import.
+          </p>
         </SettingsCard>
         </div>
 
@@ -115,6 +115,7 @@ export interface TauxN1ParGroupe {
 
 export interface TauxN1Trend {
   periode: string;
+  n1StrictCount: number;
   n1StrictPct: number;
   n1ElargiPct: number;
   totalResolus: number;